@@ -1,9 +1,43 @@
+use crate::audit::{self, AuditEntry, StateSnapshot};
+use crate::bond::{self, BondInfo, BondSlave};
+use crate::bridge::{self, BridgeInfo, BridgePort, FdbEntry};
+use crate::config::Config;
+use crate::conflicts::{self, Conflict};
+use crate::dhcp::{self, DhcpClient, DhcpLease, DhcpOffer};
+use crate::helper::{self, HelperRequest};
 use crate::models::{
-    DnsConfiguration, InterfaceTableRow, IpConfigMode, NetworkInterface, SortColumn,
+    BondMode, DnsConfiguration, InterfaceAddress, InterfaceStatistics, InterfaceTableRow,
+    InterfaceType, IpConfigMode, IpNetwork, Ipv6Setting, Ipv6Settings, MacAddr, MacFormat,
+    NetworkInterface, Offload, SortColumn, VlanProtocol, WifiSecurity, WireGuardPeerConfig,
 };
+use crate::namespace::{self, Namespace};
 use crate::network;
-use crate::sudo;
+use crate::sudo::{self, PrivilegeStrategy, SudoError};
+use crate::undo::{self, UndoEntry};
+use crate::validation::{self, Ipv4FormCheck, Ipv6FormCheck, VlanFormCheck};
+use crate::wifi::WifiNetwork;
+use crate::wireguard;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv6Addr};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+/// How long sudo keeps credentials cached by default (`timestamp_timeout`)
+const SUDO_TIMESTAMP_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// sudo's default `passwd_tries`
+const MAX_PASSWORD_ATTEMPTS: u8 = 3;
+
+/// Bytes reserved for the password buffer. Input stops here rather than
+/// letting the `String` grow, which would leave an unzeroed copy behind.
+const PASSWORD_CAPACITY: usize = 256;
+
+/// How long to collect DHCP offers; servers answer within a second or two
+const DHCP_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
@@ -12,19 +46,45 @@ pub enum AppMode {
     EditIp,
     EditDns,
     EditIpv6,
+    EditVlan,
+    EditMac,
     Details,
     Help,
     ConfirmDialog,
+    SudoPassword,
     Terminal,
+    History,
+    DhcpOffers,
+    Wifi,
+    WifiPassword,
+    Bonds,
+    CreateBond,
+    Bridges,
+    CreateBridge,
+    EnslaveInterface,
+    AddWireGuardPeer,
+    RemoveWireGuardPeer,
+    WireGuardKeys,
+    Namespaces,
+    Offloads,
+    Statistics,
+    SubnetCalculator,
+    Conflicts,
 }
 
 #[derive(Debug, Clone)]
 pub struct IpEditState {
     pub mode: IpConfigMode,
+    /// IPv4 addresses currently on the interface, primary first per subnet
+    pub addresses: Vec<InterfaceAddress>,
+    pub address_index: usize,
     pub ip_buffer: String,
     pub netmask_buffer: String,
+    pub label_buffer: String,
     pub gateway_buffer: String,
-    pub current_field: usize, // 0=mode, 1=ip, 2=netmask, 3=gateway
+    pub current_field: usize, // 0=mode, 1=addresses, 2=ip, 3=netmask, 4=label, 5=gateway
+    /// Result of the last check; set on the first submit, then kept current while typing
+    pub check: Option<Ipv4FormCheck>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,12 +100,422 @@ pub struct DnsEditState {
 #[derive(Debug, Clone)]
 pub struct Ipv6EditState {
     pub enabled: bool,
+    /// Non link-local IPv6 addresses currently on the interface
+    pub addresses: Vec<InterfaceAddress>,
+    pub address_index: usize,
     pub ip_buffer: String,
     pub prefix_buffer: String,
-    pub current_field: usize, // 0=enabled, 1=ip, 2=prefix
+    pub gateway_buffer: String,
+    /// Edited copy of the interface's sysctls, `None` where unsupported
+    pub settings: Option<Ipv6Settings>,
+    pub dhcpv6: bool,
+    // 0=enabled, 1=addresses, 2=ip, 3=prefix, 4=gateway,
+    // 5=addr_gen_mode, 6=use_tempaddr, 7=accept_ra, 8=dhcpv6
+    pub current_field: usize,
+    pub check: Option<Ipv6FormCheck>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VlanEditState {
+    pub parent: String,
+    /// VLANs already on `parent`, by id
+    pub vlans: Vec<String>,
+    pub vlan_index: usize,
+    pub id_buffer: String,
+    pub name_buffer: String,
+    pub protocol: VlanProtocol,
+    pub mtu_buffer: String,
+    pub current_field: usize, // 0=vlans, 1=id, 2=name, 3=protocol, 4=mtu
+    pub check: Option<VlanFormCheck>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MacEditState {
+    pub interface: String,
+    pub buffer: String,
+    pub error: Option<String>,
+}
+
+/// Everything the IPv6 editor can change in one go, applied in field order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ipv6Changes {
+    pub enable: bool,
+    pub settings: Vec<Ipv6Setting>,
+    pub address: Option<(Ipv6Addr, u8)>,
+    pub gateway: Option<Ipv6Addr>,
+    pub dhcpv6: bool,
+}
+
+impl Ipv6Changes {
+    pub fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.enable {
+            lines.push("Enable IPv6".to_string());
+        }
+        lines.extend(
+            self.settings
+                .iter()
+                .map(|setting| format!("Set {}", setting.describe())),
+        );
+        if let Some((address, prefix)) = self.address {
+            lines.push(format!("Add {}/{}", address, prefix));
+        }
+        if let Some(gateway) = self.gateway {
+            lines.push(format!("Default route via {}", gateway));
+        }
+        if self.dhcpv6 {
+            lines.push("Start DHCPv6 client".to_string());
+        }
+        lines
+    }
+}
+
+/// Masked sudo password entry. Deliberately not `Debug` so the buffer
+/// can never end up in a log line.
+pub struct PasswordPromptState {
+    pub buffer: Zeroizing<String>,
+    pub attempts: u8,
+    pub error: Option<String>,
+}
+
+impl PasswordPromptState {
+    fn new() -> Self {
+        Self {
+            // Reserve up front so typing doesn't reallocate and leave copies behind
+            buffer: Zeroizing::new(String::with_capacity(PASSWORD_CAPACITY)),
+            attempts: 0,
+            error: None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.zeroize();
+        self.attempts = 0;
+        self.error = None;
+    }
+}
+
+/// Networks on the WiFi screen and the passphrase being typed for one of
+/// them. Not `Debug` for the same reason as `PasswordPromptState`.
+pub struct WifiState {
+    pub interface: String,
+    pub networks: Vec<WifiNetwork>,
+    pub selected: usize,
+    pub password: Zeroizing<String>,
+    pub password_error: Option<String>,
+}
+
+impl WifiState {
+    fn new(interface: String, networks: Vec<WifiNetwork>) -> Self {
+        Self {
+            interface,
+            networks,
+            selected: 0,
+            password: Zeroizing::new(String::with_capacity(256)),
+            password_error: None,
+        }
+    }
+
+    pub fn selected_network(&self) -> Option<&WifiNetwork> {
+        self.networks.get(self.selected)
+    }
+}
+
+/// Bonds screen: one row per bond followed by one per slave, plus the
+/// new-bond form
+#[derive(Debug, Clone, Default)]
+pub struct BondState {
+    pub bonds: Vec<BondInfo>,
+    pub selected: usize,
+    pub name_buffer: String,
+    pub mode: BondMode,
+    pub miimon_buffer: String,
+    pub current_field: usize, // 0=name, 1=mode, 2=miimon
+    pub form_error: Option<String>,
+}
+
+impl BondState {
+    /// (bond index, slave index) of every row
+    pub fn rows(&self) -> Vec<(usize, Option<usize>)> {
+        self.bonds
+            .iter()
+            .enumerate()
+            .flat_map(|(i, bond)| {
+                std::iter::once((i, None)).chain((0..bond.slaves.len()).map(move |j| (i, Some(j))))
+            })
+            .collect()
+    }
+
+    pub fn selected_bond(&self) -> Option<&BondInfo> {
+        let (bond, _) = *self.rows().get(self.selected)?;
+        self.bonds.get(bond)
+    }
+
+    pub fn selected_slave(&self) -> Option<(&BondInfo, &BondSlave)> {
+        let (bond, slave) = *self.rows().get(self.selected)?;
+        let bond = self.bonds.get(bond)?;
+        Some((bond, bond.slaves.get(slave?)?))
+    }
+}
+
+/// Bridges screen: one row per bridge followed by one per port, or the
+/// forwarding database of the selected bridge
+#[derive(Debug, Clone, Default)]
+pub struct BridgeState {
+    pub bridges: Vec<BridgeInfo>,
+    pub selected: usize,
+    /// Shown instead of the ports while set
+    pub fdb: Option<Vec<FdbEntry>>,
+    pub fdb_scroll: usize,
+    pub name_buffer: String,
+    pub stp: bool,
+    pub current_field: usize, // 0=name, 1=stp
+    pub form_error: Option<String>,
+}
+
+impl BridgeState {
+    /// (bridge index, port index) of every row
+    pub fn rows(&self) -> Vec<(usize, Option<usize>)> {
+        self.bridges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, bridge)| {
+                std::iter::once((i, None)).chain((0..bridge.ports.len()).map(move |j| (i, Some(j))))
+            })
+            .collect()
+    }
+
+    pub fn selected_bridge(&self) -> Option<&BridgeInfo> {
+        let (bridge, _) = *self.rows().get(self.selected)?;
+        self.bridges.get(bridge)
+    }
+
+    pub fn selected_port(&self) -> Option<(&BridgeInfo, &BridgePort)> {
+        let (bridge, port) = *self.rows().get(self.selected)?;
+        let bridge = self.bridges.get(bridge)?;
+        Some((bridge, bridge.ports.get(port?)?))
+    }
+}
+
+/// Peer forms and generated keys of the WireGuard interface on the details
+/// screen. Not `Debug`, as it may hold a private key.
+#[derive(Default)]
+pub struct WireGuardState {
+    pub interface: String,
+    pub public_key_buffer: String,
+    pub allowed_ips_buffer: String,
+    pub endpoint_buffer: String,
+    pub keepalive_buffer: String,
+    pub current_field: usize, // 0=public key, 1=allowed IPs, 2=endpoint, 3=keepalive
+    pub form_error: Option<String>,
+    /// Peer picked for removal
+    pub peer_index: usize,
+    /// (private, public); dropped, and so wiped, when the dialog closes
+    pub keypair: Option<(Zeroizing<String>, String)>,
+}
+
+impl WireGuardState {
+    fn new(interface: String) -> Self {
+        Self {
+            interface,
+            ..Default::default()
+        }
+    }
+}
+
+/// Interfaces that can be added to `master`, picked from a list
+#[derive(Debug, Clone, Default)]
+pub struct PortPicker {
+    pub master: String,
+    pub candidates: Vec<String>,
+    pub index: usize,
+}
+
+/// Offloads of the details screen's interface, one of them picked
+#[derive(Debug, Clone, Default)]
+pub struct OffloadPicker {
+    pub interface: String,
+    pub index: usize,
+}
+
+/// Statistics page: every counter of one interface and how far each moved
+/// since the previous read
+#[derive(Debug, Clone)]
+pub struct StatisticsView {
+    pub interface: String,
+    pub current: InterfaceStatistics,
+    pub previous: Option<InterfaceStatistics>,
+    /// Time between the previous read and this one
+    pub interval: Option<Duration>,
+    read_at: Instant,
+    pub scroll: usize,
+    /// Leave out counters that are still zero
+    pub hide_zero: bool,
+}
+
+/// Subnet calculator, pre-filled from an interface address
+#[derive(Debug, Clone)]
+pub struct SubnetCalculator {
+    pub network_buffer: String,
+    /// Prefix length to split the subnet into
+    pub split_buffer: String,
+    /// Prefixes to summarize, separated by commas or spaces
+    pub summarize_buffer: String,
+    pub current_field: usize, // 0=network, 1=split, 2=summarize
+    pub scroll: usize,
+    /// Screen to go back to
+    return_to: AppMode,
+}
+
+impl SubnetCalculator {
+    pub fn network(&self) -> Result<IpNetwork, String> {
+        if self.network_buffer.trim().is_empty() {
+            return Err("Enter an address and prefix, e.g. 192.168.1.0/24".to_string());
+        }
+        self.network_buffer.parse()
+    }
+
+    /// `None` while the split field is empty
+    pub fn split_prefix(&self, network: &IpNetwork) -> Option<Result<u8, String>> {
+        let input = self.split_buffer.trim().trim_start_matches('/');
+        if input.is_empty() {
+            return None;
+        }
+        Some(
+            input
+                .parse::<u8>()
+                .ok()
+                .filter(|&prefix| prefix > network.prefix() && prefix <= network.max_prefix())
+                .ok_or_else(|| {
+                    format!(
+                        "split prefix must be between /{} and /{}",
+                        network.prefix() + 1,
+                        network.max_prefix()
+                    )
+                }),
+        )
+    }
+
+    /// Number of subnets the split makes, zero while it can't be done
+    pub fn split_count(&self) -> u128 {
+        let Ok(network) = self.network() else {
+            return 0;
+        };
+        match self.split_prefix(&network) {
+            Some(Ok(prefix)) => network.subnet_count(prefix).unwrap_or(u128::MAX),
+            _ => 0,
+        }
+    }
+
+    pub fn summarize_input(&self) -> Result<Vec<IpNetwork>, String> {
+        self.summarize_buffer
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+/// One counter of the statistics page
+#[derive(Debug, Clone)]
+pub struct CounterRow {
+    pub name: String,
+    pub value: u64,
+    /// `None` on the first read, and for counters that went backwards
+    /// because the driver reset them
+    pub delta: Option<u64>,
+}
+
+impl StatisticsView {
+    pub fn new(interface: String, current: InterfaceStatistics) -> Self {
+        Self {
+            interface,
+            current,
+            previous: None,
+            interval: None,
+            read_at: Instant::now(),
+            scroll: 0,
+            hide_zero: false,
+        }
+    }
+
+    /// Take a new read, keeping the last one to compare against
+    fn update(&mut self, current: InterfaceStatistics) {
+        self.previous = Some(std::mem::replace(&mut self.current, current));
+        self.interval = Some(self.read_at.elapsed());
+        self.read_at = Instant::now();
+    }
+
+    /// The kernel's counters
+    pub fn link_rows(&self) -> Vec<CounterRow> {
+        let previous = self.previous.as_ref().map(|p| p.counters.named());
+        self.rows(
+            self.current
+                .counters
+                .named()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value)),
+            |name| {
+                previous
+                    .as_ref()?
+                    .iter()
+                    .find(|(other, _)| *other == name)
+                    .map(|(_, value)| *value)
+            },
+        )
+    }
+
+    /// The driver's counters, as `ethtool -S` lists them
+    pub fn driver_rows(&self) -> Vec<CounterRow> {
+        self.rows(self.current.driver.iter().cloned(), |name| {
+            self.previous
+                .as_ref()?
+                .driver
+                .iter()
+                .find(|(other, _)| other == name)
+                .map(|(_, value)| *value)
+        })
+    }
+
+    fn rows(
+        &self,
+        counters: impl Iterator<Item = (String, u64)>,
+        previous: impl Fn(&str) -> Option<u64>,
+    ) -> Vec<CounterRow> {
+        counters
+            .filter(|(_, value)| !self.hide_zero || *value != 0)
+            .map(|(name, value)| CounterRow {
+                delta: previous(&name).and_then(|before| value.checked_sub(before)),
+                name,
+                value,
+            })
+            .collect()
+    }
+}
+
+/// Compare two optional sort keys in the direction `ordered` gives,
+/// keeping the rows without a value at the end either way
+fn nones_last<T: Ord>(
+    a: &Option<T>,
+    b: &Option<T>,
+    ordered: impl Fn(Ordering) -> Ordering,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => ordered(a.cmp(b)),
+        _ => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// Network namespaces to switch to
+#[derive(Debug, Clone, Default)]
+pub struct NamespacePicker {
+    pub namespaces: Vec<Namespace>,
+    /// 0 is the namespace we started in, then one past the index in `namespaces`
+    pub index: usize,
 }
 
 pub struct App {
+    pub privilege: PrivilegeStrategy,
     pub interfaces: Vec<NetworkInterface>,
     pub dns_config: DnsConfiguration,
     pub table_rows: Vec<InterfaceTableRow>,
@@ -64,37 +534,289 @@ pub struct App {
     pub ip_edit_state: IpEditState,
     pub dns_edit_state: DnsEditState,
     pub ipv6_edit_state: Ipv6EditState,
+    pub vlan_edit_state: VlanEditState,
+    pub mac_edit_state: MacEditState,
 
     // Confirmation dialog
     pub confirm_message: String,
     pub confirm_action: Option<ConfirmAction>,
 
+    // Sudo authentication
+    pub password_state: PasswordPromptState,
+    pub sudo_authenticated_at: Option<Instant>,
+
     // Terminal
     pub terminal_command: String,
     pub terminal_output: Vec<String>,
     pub terminal_scroll: usize,
     pub terminal_needs_clear: bool,
+
+    // Audit log
+    pub audit_log_path: PathBuf,
+    pub history: Vec<AuditEntry>,
+    pub history_index: usize,
+
+    // Actions executed this session, most recent last
+    pub undo_stack: Vec<UndoEntry>,
+
+    // Interfaces whose extra addresses are listed in the table
+    pub expanded_rows: HashSet<String>,
+
+    // DHCP lease of the interface shown on the details screen
+    pub details_lease: Option<(DhcpClient, DhcpLease)>,
+
+    // Result of the last DHCP probe: (interface, offers)
+    pub dhcp_offers: Option<(String, Vec<DhcpOffer>)>,
+
+    // WiFi screen, while it is open
+    pub wifi: Option<WifiState>,
+
+    // Bonds screen, while it is open
+    pub bonds: Option<BondState>,
+
+    // Bridges screen, while it is open
+    pub bridges: Option<BridgeState>,
+
+    // Interfaces offered for a bond or bridge
+    pub port_picker: Option<PortPicker>,
+
+    // WireGuard forms, while the details screen shows a WireGuard interface
+    pub wireguard: Option<WireGuardState>,
+
+    // Namespace selector, while it is open
+    pub namespace_picker: Option<NamespacePicker>,
+
+    // Offload list, while it is open
+    pub offload_picker: Option<OffloadPicker>,
+
+    // Statistics page, while it is open
+    pub statistics: Option<StatisticsView>,
+
+    // Whether the table has a Speed column
+    pub show_speed: bool,
+
+    // How the MAC column is written
+    pub mac_format: MacFormat,
+
+    // Subnet calculator, while it is open
+    pub subnet_calculator: Option<SubnetCalculator>,
+
+    // Address and route conflicts found at the last refresh
+    pub conflicts: Vec<Conflict>,
+    pub conflicts_scroll: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConfirmAction {
     SetDhcp(String),
-    SetStaticIp(String, String, String, Option<String>),
+    RenewDhcp(String, DhcpClient),
+    ReleaseDhcp(String, DhcpClient),
+    /// Not a change; goes through here to get privileges
+    ProbeDhcp(String),
+    /// Not a change either
+    ScanWifi(String),
+    /// (interface, SSID, security); the password stays in `WifiState`
+    ConnectWifi(String, String, WifiSecurity),
+    DisconnectWifi(String),
+    ForgetWifi(String, String),
+    /// (interface, ip, netmask, gateway, label)
+    SetStaticIp(
+        String,
+        String,
+        String,
+        Option<String>,
+        #[serde(default)] Option<String>,
+    ),
+    RemoveAddress(String, IpAddr, u8),
+    MakePrimary(String, IpAddr, u8),
     SetDns(Vec<String>, Vec<String>),
     ToggleInterface(String, bool),
+    SetMac(String, MacAddr),
     DisableIpv6(String),
     EnableIpv6(String),
     SetStaticIpv6(String, String, u8),
+    ConfigureIpv6(String, Ipv6Changes),
+    FlushDnsCache,
+    /// (parent, name, id, protocol, mtu)
+    CreateVlan(String, String, u16, VlanProtocol, Option<u32>),
+    DeleteVlan(String),
+    /// (name, mode, miimon)
+    CreateBond(String, BondMode, Option<u32>),
+    DeleteBond(String),
+    /// (bond, interface)
+    EnslaveInterface(String, String),
+    ReleaseInterface(String, String),
+    SetActiveSlave(String, String),
+    /// (name, stp)
+    CreateBridge(String, bool),
+    DeleteBridge(String),
+    SetBridgeStp(String, bool),
+    /// (interface, kernel feature, enabled)
+    SetOffload(String, String, bool),
+    /// Not a change; reads peers through the helper when we aren't root
+    ReadWireGuard(String),
+    AddWireGuardPeer(String, WireGuardPeerConfig),
+    /// (interface, public key)
+    RemoveWireGuardPeer(String, String),
+    /// Not a change; reading another namespace takes the helper when we aren't root
+    SwitchNamespace(Option<Namespace>),
+    /// Restore steps for an earlier action: (interface, undone summary, steps)
+    Undo(Option<String>, String, Vec<HelperRequest>),
+}
+
+impl ConfirmAction {
+    /// The interface this action changes, if it targets one
+    pub fn interface(&self) -> Option<&str> {
+        match self {
+            ConfirmAction::SetDhcp(iface)
+            | ConfirmAction::RenewDhcp(iface, _)
+            | ConfirmAction::ReleaseDhcp(iface, _)
+            | ConfirmAction::ProbeDhcp(iface)
+            | ConfirmAction::ScanWifi(iface)
+            | ConfirmAction::ConnectWifi(iface, ..)
+            | ConfirmAction::DisconnectWifi(iface)
+            | ConfirmAction::ForgetWifi(iface, _)
+            | ConfirmAction::CreateVlan(_, iface, ..)
+            | ConfirmAction::DeleteVlan(iface)
+            | ConfirmAction::CreateBond(iface, ..)
+            | ConfirmAction::DeleteBond(iface)
+            | ConfirmAction::EnslaveInterface(_, iface)
+            | ConfirmAction::ReleaseInterface(_, iface)
+            | ConfirmAction::SetActiveSlave(iface, _)
+            | ConfirmAction::CreateBridge(iface, _)
+            | ConfirmAction::DeleteBridge(iface)
+            | ConfirmAction::SetBridgeStp(iface, _)
+            | ConfirmAction::SetOffload(iface, ..)
+            | ConfirmAction::ReadWireGuard(iface)
+            | ConfirmAction::AddWireGuardPeer(iface, _)
+            | ConfirmAction::RemoveWireGuardPeer(iface, _)
+            | ConfirmAction::SetStaticIp(iface, ..)
+            | ConfirmAction::RemoveAddress(iface, ..)
+            | ConfirmAction::MakePrimary(iface, ..)
+            | ConfirmAction::ToggleInterface(iface, _)
+            | ConfirmAction::SetMac(iface, _)
+            | ConfirmAction::DisableIpv6(iface)
+            | ConfirmAction::EnableIpv6(iface)
+            | ConfirmAction::SetStaticIpv6(iface, ..)
+            | ConfirmAction::ConfigureIpv6(iface, _) => Some(iface),
+            ConfirmAction::Undo(iface, ..) => iface.as_deref(),
+            ConfirmAction::SetDns(..)
+            | ConfirmAction::FlushDnsCache
+            | ConfirmAction::SwitchNamespace(_) => None,
+        }
+    }
+
+    /// WiFi actions change daemon state that undo snapshots don't cover
+    pub fn is_wifi(&self) -> bool {
+        matches!(
+            self,
+            ConfirmAction::ScanWifi(_)
+                | ConfirmAction::ConnectWifi(..)
+                | ConfirmAction::DisconnectWifi(_)
+                | ConfirmAction::ForgetWifi(..)
+        )
+    }
+
+    /// One line description for the history screen
+    pub fn summary(&self) -> String {
+        match self {
+            ConfirmAction::SetDhcp(_) => "Set DHCP".to_string(),
+            ConfirmAction::RenewDhcp(_, client) => {
+                format!("Renew DHCP lease ({})", client.as_str())
+            }
+            ConfirmAction::ReleaseDhcp(_, client) => {
+                format!("Release DHCP lease ({})", client.as_str())
+            }
+            ConfirmAction::ProbeDhcp(_) => "Probe for DHCP servers".to_string(),
+            ConfirmAction::ScanWifi(_) => "Scan for WiFi networks".to_string(),
+            ConfirmAction::ConnectWifi(_, ssid, _) => format!("Connect to WiFi '{}'", ssid),
+            ConfirmAction::DisconnectWifi(_) => "Disconnect from WiFi".to_string(),
+            ConfirmAction::ForgetWifi(_, ssid) => format!("Forget WiFi '{}'", ssid),
+            ConfirmAction::CreateVlan(parent, _, id, protocol, _) => {
+                format!("Create {} VLAN {} on {}", protocol.as_str(), id, parent)
+            }
+            ConfirmAction::DeleteVlan(_) => "Delete VLAN".to_string(),
+            ConfirmAction::CreateBond(_, mode, _) => format!("Create {} bond", mode.as_str()),
+            ConfirmAction::DeleteBond(_) => "Delete bond".to_string(),
+            ConfirmAction::CreateBridge(..) => "Create bridge".to_string(),
+            ConfirmAction::DeleteBridge(_) => "Delete bridge".to_string(),
+            ConfirmAction::SetBridgeStp(_, enabled) => {
+                format!("{} STP", if *enabled { "Enable" } else { "Disable" })
+            }
+            ConfirmAction::SetOffload(_, feature, enabled) => {
+                format!("Turn {} {}", feature, if *enabled { "on" } else { "off" })
+            }
+            ConfirmAction::ReadWireGuard(_) => "Read WireGuard configuration".to_string(),
+            ConfirmAction::SwitchNamespace(choice) => format!(
+                "Switch to network namespace {}",
+                namespace::label_of(choice.as_ref())
+            ),
+            ConfirmAction::AddWireGuardPeer(_, peer) => {
+                format!(
+                    "Add WireGuard peer {}",
+                    wireguard::short_key(&peer.public_key)
+                )
+            }
+            ConfirmAction::RemoveWireGuardPeer(_, key) => {
+                format!("Remove WireGuard peer {}", wireguard::short_key(key))
+            }
+            ConfirmAction::EnslaveInterface(master, _) => format!("Add to {}", master),
+            ConfirmAction::ReleaseInterface(master, _) => format!("Release from {}", master),
+            ConfirmAction::SetActiveSlave(_, slave) => format!("Fail over to {}", slave),
+            ConfirmAction::SetStaticIp(_, ip, netmask, gateway, label) => format!(
+                "Add IP {}/{}{} gw {}",
+                ip,
+                netmask,
+                label
+                    .as_deref()
+                    .map(|label| format!(" as {}", label))
+                    .unwrap_or_default(),
+                gateway.as_deref().unwrap_or("none")
+            ),
+            ConfirmAction::RemoveAddress(_, ip, prefix) => format!("Remove IP {}/{}", ip, prefix),
+            ConfirmAction::MakePrimary(_, ip, prefix) => {
+                format!("Make {}/{} primary", ip, prefix)
+            }
+            ConfirmAction::SetDns(servers, domains) => {
+                format!(
+                    "Set DNS [{}] search [{}]",
+                    servers.join(", "),
+                    domains.join(", ")
+                )
+            }
+            ConfirmAction::ToggleInterface(_, enabled) => {
+                format!("Set link {}", if *enabled { "up" } else { "down" })
+            }
+            ConfirmAction::SetMac(_, mac) => format!("Set MAC address {}", mac),
+            ConfirmAction::DisableIpv6(_) => "Disable IPv6".to_string(),
+            ConfirmAction::EnableIpv6(_) => "Enable IPv6".to_string(),
+            ConfirmAction::SetStaticIpv6(_, ip, prefix) => {
+                format!("Add IPv6 {}/{}", ip, prefix)
+            }
+            ConfirmAction::ConfigureIpv6(_, changes) => {
+                format!("IPv6: {}", changes.describe().join(", "))
+            }
+            ConfirmAction::FlushDnsCache => "Flush DNS cache".to_string(),
+            ConfirmAction::Undo(_, undone, _) => format!("Undo: {}", undone),
+        }
+    }
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: Config) -> Result<Self> {
+        let privilege = sudo::strategy();
+        let audit_log_path = config
+            .audit_log
+            .clone()
+            .unwrap_or_else(audit::default_log_path);
         let interfaces = network::get_network_interfaces()?;
         let dns_config = network::get_dns_configuration()?;
-        let table_rows = Self::create_table_rows(&interfaces, &dns_config);
+        let table_rows = Self::create_table_rows(&interfaces, &dns_config, config.mac_format);
+        let conflicts = find_conflicts(&interfaces);
         let filtered_rows: Vec<usize> = (0..table_rows.len()).collect();
 
         Ok(Self {
+            privilege,
             interfaces,
             dns_config,
             table_rows,
@@ -107,14 +829,17 @@ impl App {
             sort_ascending: true,
             page_size: 20,
             should_quit: false,
-            status_message: None,
 
             ip_edit_state: IpEditState {
                 mode: IpConfigMode::DHCP,
+                addresses: Vec::new(),
+                address_index: 0,
                 ip_buffer: String::new(),
                 netmask_buffer: String::new(),
+                label_buffer: String::new(),
                 gateway_buffer: String::new(),
                 current_field: 0,
+                check: None,
             },
 
             dns_edit_state: DnsEditState {
@@ -128,40 +853,133 @@ impl App {
 
             ipv6_edit_state: Ipv6EditState {
                 enabled: true,
+                addresses: Vec::new(),
+                address_index: 0,
                 ip_buffer: String::new(),
                 prefix_buffer: String::from("64"),
+                gateway_buffer: String::new(),
+                settings: None,
+                dhcpv6: false,
                 current_field: 0,
+                check: None,
             },
+            vlan_edit_state: VlanEditState::default(),
+            mac_edit_state: MacEditState::default(),
 
             confirm_message: String::new(),
             confirm_action: None,
 
+            password_state: PasswordPromptState::new(),
+            sudo_authenticated_at: None,
+
             terminal_command: String::new(),
             terminal_output: Vec::new(),
             terminal_scroll: 0,
             terminal_needs_clear: false,
+
+            audit_log_path,
+            history: Vec::new(),
+            history_index: 0,
+
+            undo_stack: Vec::new(),
+            expanded_rows: HashSet::new(),
+            details_lease: None,
+            dhcp_offers: None,
+            wifi: None,
+            bonds: None,
+            bridges: None,
+            port_picker: None,
+            wireguard: None,
+            namespace_picker: None,
+            offload_picker: None,
+            statistics: None,
+            show_speed: config.speed_column,
+            mac_format: config.mac_format,
+            subnet_calculator: None,
+            status_message: conflicts_warning(&conflicts),
+            conflicts,
+            conflicts_scroll: 0,
         })
     }
 
     fn create_table_rows(
         interfaces: &[NetworkInterface],
         dns_config: &DnsConfiguration,
+        mac_format: MacFormat,
     ) -> Vec<InterfaceTableRow> {
         interfaces
             .iter()
-            .map(|iface| InterfaceTableRow::from_interface(iface, dns_config))
+            .map(|iface| InterfaceTableRow::from_interface(iface, dns_config, mac_format))
             .collect()
     }
 
     pub fn refresh_data(&mut self) -> Result<()> {
         self.interfaces = network::get_network_interfaces()?;
         self.dns_config = network::get_dns_configuration()?;
-        self.table_rows = Self::create_table_rows(&self.interfaces, &self.dns_config);
+        self.table_rows =
+            Self::create_table_rows(&self.interfaces, &self.dns_config, self.mac_format);
         self.apply_filter();
         self.apply_sort();
+
+        // A change that causes a conflict is called out next to its result
+        let conflicts = find_conflicts(&self.interfaces);
+        let new: Vec<Conflict> = conflicts
+            .iter()
+            .filter(|conflict| !self.conflicts.contains(conflict))
+            .cloned()
+            .collect();
+        if let Some(warning) = conflicts_warning(&new) {
+            self.append_status(warning);
+        }
+        self.conflicts = conflicts;
         Ok(())
     }
 
+    /// Refresh where a failure mustn't end the TUI. Reads in a namespace go
+    /// through the helper, so an expired sudo timestamp asks for the password
+    /// and re-enters the namespace, one that was deleted falls back to the
+    /// host and anything else is added to the status line. Returns whether
+    /// the data is current.
+    pub fn refresh_or_report(&mut self) -> bool {
+        let Err(e) = self.refresh_data() else {
+            return true;
+        };
+
+        let selected = namespace::selected();
+        if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) {
+            self.sudo_authenticated_at = None;
+            self.confirm_action = Some(ConfirmAction::SwitchNamespace(selected));
+            self.start_password_prompt();
+        } else if let Some(gone) = selected.filter(|selected| !selected.path.exists()) {
+            namespace::select(None);
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+            self.expanded_rows.clear();
+            self.append_status(format!(
+                "Network namespace {} is gone, showing the host",
+                gone.label()
+            ));
+            if let Err(e) = self.refresh_data() {
+                self.append_status(format!("Failed to refresh: {:#}", e));
+            }
+        } else {
+            self.append_status(format!("Failed to refresh: {:#}", e));
+        }
+        false
+    }
+
+    pub fn open_conflicts(&mut self) {
+        self.conflicts_scroll = 0;
+        self.mode = AppMode::Conflicts;
+    }
+
+    pub fn scroll_conflicts(&mut self, lines: isize) {
+        self.conflicts_scroll = self
+            .conflicts_scroll
+            .saturating_add_signed(lines)
+            .min(self.conflicts.len().saturating_sub(1));
+    }
+
     pub fn apply_filter(&mut self) {
         if self.search_query.is_empty() {
             self.filtered_rows = (0..self.table_rows.len()).collect();
@@ -174,6 +992,10 @@ impl App {
                 .filter(|(_, row)| {
                     row.name.to_lowercase().contains(&query)
                         || row.ip_address.to_lowercase().contains(&query)
+                        || row
+                            .extra_addresses
+                            .iter()
+                            .any(|addr| addr.to_lowercase().contains(&query))
                         || row.mac_address.to_lowercase().contains(&query)
                         || row.interface_type.to_lowercase().contains(&query)
                 })
@@ -186,28 +1008,56 @@ impl App {
         }
     }
 
+    /// Sort by the next column shown, wrapping around
+    pub fn next_sort_column(&mut self) {
+        self.sort_column = self.sort_column.next();
+        if self.sort_column == SortColumn::Speed && !self.show_speed {
+            self.sort_column = self.sort_column.next();
+        }
+        self.apply_sort();
+    }
+
+    pub fn previous_sort_column(&mut self) {
+        self.sort_column = self.sort_column.prev();
+        if self.sort_column == SortColumn::Speed && !self.show_speed {
+            self.sort_column = self.sort_column.prev();
+        }
+        self.apply_sort();
+    }
+
+    pub fn toggle_speed_column(&mut self) {
+        self.show_speed = !self.show_speed;
+        if !self.show_speed && self.sort_column == SortColumn::Speed {
+            self.sort_column = SortColumn::Interface;
+            self.apply_sort();
+        }
+        self.set_status(format!(
+            "Speed column {}",
+            if self.show_speed { "shown" } else { "hidden" }
+        ));
+    }
+
     pub fn apply_sort(&mut self) {
         let column = self.sort_column;
         let ascending = self.sort_ascending;
 
+        let ordered = |cmp: Ordering| if ascending { cmp } else { cmp.reverse() };
+
         self.filtered_rows.sort_by(|&a, &b| {
             let row_a = &self.table_rows[a];
             let row_b = &self.table_rows[b];
 
-            let cmp = match column {
-                SortColumn::Interface => row_a.name.cmp(&row_b.name),
-                SortColumn::Type => row_a.interface_type.cmp(&row_b.interface_type),
-                SortColumn::IpAddress => row_a.ip_address.cmp(&row_b.ip_address),
-                SortColumn::MacAddress => row_a.mac_address.cmp(&row_b.mac_address),
-                SortColumn::SubnetMask => row_a.subnet_mask.cmp(&row_b.subnet_mask),
-                SortColumn::DnsServers => row_a.dns_servers.cmp(&row_b.dns_servers),
-                SortColumn::Status => row_a.status.cmp(&row_b.status),
-            };
-
-            if ascending {
-                cmp
-            } else {
-                cmp.reverse()
+            match column {
+                SortColumn::Interface => ordered(row_a.name.cmp(&row_b.name)),
+                SortColumn::Type => ordered(row_a.interface_type.cmp(&row_b.interface_type)),
+                // Numerically, with rows lacking the value last
+                SortColumn::IpAddress => nones_last(&row_a.ip, &row_b.ip, ordered),
+                SortColumn::MacAddress => nones_last(&row_a.mac, &row_b.mac, ordered),
+                SortColumn::SubnetMask => nones_last(&row_a.netmask, &row_b.netmask, ordered),
+                SortColumn::DnsServers => ordered(row_a.dns_servers.cmp(&row_b.dns_servers)),
+                SortColumn::Status => ordered(row_a.status.cmp(&row_b.status)),
+                // Links without a known speed go last
+                SortColumn::Speed => nones_last(&row_a.speed_mbps, &row_b.speed_mbps, ordered),
             }
         });
     }
@@ -300,29 +1150,39 @@ impl App {
         self.status_message = None;
     }
 
+    /// Add `note` after the current message rather than replacing it
+    fn append_status(&mut self, note: String) {
+        self.status_message = Some(match self.status_message.take() {
+            Some(message) => format!("{} | {}", message, note),
+            None => note,
+        });
+    }
+
     // IP Edit functions
     pub fn start_edit_ip(&mut self) {
         if let Some(iface) = self.get_selected_interface() {
-            let ip_buffer = iface
+            let addresses: Vec<InterfaceAddress> = iface
                 .ip_addresses
                 .iter()
-                .find(|addr| !addr.is_ipv6())
-                .map(|addr| addr.ip.to_string())
-                .unwrap_or_default();
+                .filter(|addr| !addr.is_ipv6())
+                .cloned()
+                .collect();
 
-            let netmask_buffer = iface
-                .ip_addresses
-                .iter()
-                .find(|addr| !addr.is_ipv6())
-                .and_then(|addr| addr.netmask.as_ref())
+            let netmask_buffer = addresses
+                .first()
+                .and_then(|addr| addr.netmask())
                 .map(|mask| mask.to_string())
                 .unwrap_or_else(|| "255.255.255.0".to_string());
 
             self.ip_edit_state.mode = IpConfigMode::DHCP;
-            self.ip_edit_state.ip_buffer = ip_buffer;
+            self.ip_edit_state.addresses = addresses;
+            self.ip_edit_state.address_index = 0;
+            self.ip_edit_state.ip_buffer.clear();
             self.ip_edit_state.netmask_buffer = netmask_buffer;
+            self.ip_edit_state.label_buffer.clear();
             self.ip_edit_state.gateway_buffer.clear();
             self.ip_edit_state.current_field = 0;
+            self.ip_edit_state.check = None;
             self.mode = AppMode::EditIp;
         }
     }
@@ -349,23 +1209,141 @@ impl App {
 
     pub fn start_edit_ipv6(&mut self) {
         if let Some(iface) = self.get_selected_interface() {
-            let enabled = iface.ipv6_enabled;
-            let ip_buffer = iface
+            let addresses: Vec<InterfaceAddress> = iface
                 .ip_addresses
                 .iter()
-                .find(|addr| addr.is_ipv6())
-                .map(|addr| addr.ip.to_string())
-                .unwrap_or_default();
+                .filter(|addr| addr.is_ipv6() && !addr.is_link_local())
+                .cloned()
+                .collect();
+
+            let (enabled, settings) = (iface.ipv6_enabled, iface.ipv6_settings);
 
             self.ipv6_edit_state.enabled = enabled;
-            self.ipv6_edit_state.ip_buffer = ip_buffer;
+            self.ipv6_edit_state.settings = settings;
+            self.ipv6_edit_state.addresses = addresses;
+            self.ipv6_edit_state.address_index = 0;
+            self.ipv6_edit_state.ip_buffer.clear();
             self.ipv6_edit_state.prefix_buffer = String::from("64");
+            self.ipv6_edit_state.gateway_buffer.clear();
+            self.ipv6_edit_state.dhcpv6 = false;
             self.ipv6_edit_state.current_field = 0;
+            self.ipv6_edit_state.check = None;
             self.mode = AppMode::EditIpv6;
         }
     }
 
+    /// Opens on the parent when a VLAN is selected, with that VLAN highlighted
+    pub fn start_edit_vlan(&mut self) {
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        let (parent, selected) = match iface.vlan {
+            Some(ref vlan) => (vlan.parent.clone(), Some(iface.name.clone())),
+            None => (iface.name.clone(), None),
+        };
+
+        let mut vlans: Vec<(u16, String)> = self
+            .interfaces
+            .iter()
+            .filter_map(|other| {
+                let vlan = other.vlan.as_ref().filter(|vlan| vlan.parent == parent)?;
+                Some((vlan.id, other.name.clone()))
+            })
+            .collect();
+        vlans.sort();
+
+        let vlans: Vec<String> = vlans.into_iter().map(|(_, name)| name).collect();
+        self.vlan_edit_state = VlanEditState {
+            vlan_index: selected
+                .and_then(|name| vlans.iter().position(|vlan| *vlan == name))
+                .unwrap_or(0),
+            current_field: if vlans.is_empty() { 1 } else { 0 },
+            parent,
+            vlans,
+            ..Default::default()
+        };
+        self.mode = AppMode::EditVlan;
+    }
+
+    fn check_vlan_form(&self) -> Option<VlanFormCheck> {
+        let state = &self.vlan_edit_state;
+        let parent = self
+            .interfaces
+            .iter()
+            .find(|iface| iface.name == state.parent)?;
+        Some(validation::check_vlan_form(
+            &state.id_buffer,
+            &state.name_buffer,
+            state.protocol,
+            &state.mtu_buffer,
+            parent,
+            &self.interfaces,
+        ))
+    }
+
+    pub fn revalidate_vlan_form(&mut self) {
+        if self.vlan_edit_state.check.is_some() {
+            self.vlan_edit_state.check = self.check_vlan_form();
+        }
+    }
+
+    pub fn prepare_create_vlan(&mut self) {
+        let Some(check) = self.check_vlan_form() else {
+            self.set_status(format!("{} no longer exists", self.vlan_edit_state.parent));
+            return;
+        };
+        let valid = check.is_valid();
+        let (id, name, mtu) = (check.id, check.name.clone(), check.mtu);
+        self.vlan_edit_state.check = Some(check);
+
+        let (true, Some(id), Some(name)) = (valid, id, name) else {
+            self.set_status("Fix the highlighted fields first".to_string());
+            return;
+        };
+
+        let state = &self.vlan_edit_state;
+        self.confirm_message = format!(
+            "Create {} VLAN {} as '{}' on '{}'{}?",
+            state.protocol.as_str(),
+            id,
+            name,
+            state.parent,
+            mtu.map(|mtu| format!(" with MTU {}", mtu))
+                .unwrap_or_default()
+        );
+        self.confirm_action = Some(ConfirmAction::CreateVlan(
+            state.parent.clone(),
+            name,
+            id,
+            state.protocol,
+            mtu,
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_delete_vlan(&mut self) {
+        let state = &self.vlan_edit_state;
+        let Some(name) = state.vlans.get(state.vlan_index).cloned() else {
+            return;
+        };
+        let has_addresses = self
+            .interfaces
+            .iter()
+            .any(|iface| iface.name == name && !iface.ip_addresses.is_empty());
+
+        self.confirm_message = format!("Delete VLAN '{}' from '{}'?", name, state.parent);
+        if has_addresses {
+            self.confirm_message
+                .push_str("\nIts addresses and routes go with it.");
+        }
+        self.confirm_action = Some(ConfirmAction::DeleteVlan(name));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
     pub fn show_details(&mut self) {
+        self.details_lease = self
+            .get_selected_interface()
+            .and_then(|iface| dhcp::find_lease(&iface.name));
         self.mode = AppMode::Details;
     }
 
@@ -396,46 +1374,197 @@ impl App {
         }
     }
 
+    pub fn prepare_renew_dhcp(&mut self) {
+        self.prepare_lease_action(true);
+    }
+
+    pub fn prepare_release_dhcp(&mut self) {
+        self.prepare_lease_action(false);
+    }
+
+    /// Renew or release needs the client that holds the lease, so it only
+    /// works once a lease has been found
+    fn prepare_lease_action(&mut self, renew: bool) {
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        let name = iface.name.clone();
+        let Some((client, lease)) = &self.details_lease else {
+            self.set_status(format!("No DHCP lease found for {}", name));
+            return;
+        };
+
+        let address = lease
+            .address
+            .map(|address| address.to_string())
+            .unwrap_or_else(|| "the lease".to_string());
+        if renew {
+            self.confirm_message = format!(
+                "Renew {} on '{}' through {}?",
+                address,
+                name,
+                client.as_str()
+            );
+            self.confirm_action = Some(ConfirmAction::RenewDhcp(name, *client));
+        } else {
+            self.confirm_message = format!(
+                "Release {} on '{}' through {}?\nThe interface will lose this address.",
+                address,
+                name,
+                client.as_str()
+            );
+            self.confirm_action = Some(ConfirmAction::ReleaseDhcp(name, *client));
+        }
+        self.mode = AppMode::ConfirmDialog;
+    }
+
     pub fn prepare_static_ip_config(&mut self) {
         if let Some(iface) = self.get_selected_interface() {
             let name = iface.name.clone();
-            let ip = self.ip_edit_state.ip_buffer.clone();
-            let netmask = self.ip_edit_state.netmask_buffer.clone();
-            let gateway = if self.ip_edit_state.gateway_buffer.is_empty() {
-                None
-            } else {
-                Some(self.ip_edit_state.gateway_buffer.clone())
+            let state = &self.ip_edit_state;
+            let gateway =
+                Some(state.gateway_buffer.trim().to_string()).filter(|gateway| !gateway.is_empty());
+            let label =
+                Some(state.label_buffer.trim().to_string()).filter(|label| !label.is_empty());
+
+            let check = validation::check_ipv4_form(
+                &state.ip_buffer,
+                &state.netmask_buffer,
+                &state.label_buffer,
+                gateway.as_deref().unwrap_or(""),
+                &name,
+                &self.interfaces,
+            );
+            let valid = check.is_valid();
+            let warnings = check.warnings.clone();
+            let prefix = check.prefix;
+            self.ip_edit_state.check = Some(check);
+
+            // Stay in the form so the inline errors can be fixed
+            let netmask = prefix.and_then(validation::prefix_to_netmask);
+            let (true, Some(prefix), Some(netmask)) = (valid, prefix, netmask) else {
+                self.set_status("Fix the highlighted fields first".to_string());
+                return;
             };
 
+            // CIDR input is stored with the netmask it stands for
+            let (ip, _) = validation::split_cidr(&self.ip_edit_state.ip_buffer);
+            let ip = ip.to_string();
+            let netmask = netmask.to_string();
+
             self.confirm_message = format!(
-                "Set static IP on '{}'?\nIP: {}\nNetmask: {}\nGateway: {}",
+                "Add static IP on '{}'?\nIP: {}/{}\nNetmask: {}\nLabel: {}\nGateway: {}",
                 name,
                 ip,
+                prefix,
                 netmask,
+                label.as_deref().unwrap_or("None"),
                 gateway.as_deref().unwrap_or("None")
             );
+            for warning in warnings {
+                self.confirm_message
+                    .push_str(&format!("\nWarning: {}", warning));
+            }
 
-            self.confirm_action = Some(ConfirmAction::SetStaticIp(name, ip, netmask, gateway));
+            self.confirm_action = Some(ConfirmAction::SetStaticIp(
+                name, ip, netmask, gateway, label,
+            ));
 
             self.mode = AppMode::ConfirmDialog;
         }
     }
 
-    pub fn prepare_dns_config(&mut self) {
-        let servers: Vec<String> = self
-            .dns_edit_state
-            .dns_servers
-            .iter()
-            .filter(|s| !s.is_empty())
-            .cloned()
-            .collect();
+    /// Re-run the IPv4 form check after an edit, once the form has been submitted
+    pub fn revalidate_ip_form(&mut self) {
+        if self.ip_edit_state.check.is_none() {
+            return;
+        }
+        if let Some(iface) = self.get_selected_interface() {
+            let state = &self.ip_edit_state;
+            let check = validation::check_ipv4_form(
+                &state.ip_buffer,
+                &state.netmask_buffer,
+                &state.label_buffer,
+                &state.gateway_buffer,
+                &iface.name,
+                &self.interfaces,
+            );
+            self.ip_edit_state.check = Some(check);
+        }
+    }
 
-        let domains = self.dns_edit_state.search_domains.clone();
+    fn selected_edit_address(&self) -> Option<(String, IpAddr, u8)> {
+        let iface = self.get_selected_interface()?;
+        let addr = self
+            .ip_edit_state
+            .addresses
+            .get(self.ip_edit_state.address_index)?;
+        Some((iface.name.clone(), addr.ip, addr.prefix?))
+    }
 
-        self.confirm_message = format!(
-            "Update DNS configuration?\nServers: {}\nSearch domains: {}",
-            servers.join(", "),
-            if domains.is_empty() {
+    /// Confirm removing the address selected in the IP editor
+    pub fn prepare_remove_address(&mut self) {
+        if let Some((name, ip, prefix)) = self.selected_edit_address() {
+            self.confirm_message = format!(
+                "Remove {}/{} from '{}'?\nOther addresses stay in place.",
+                ip, prefix, name
+            );
+            self.confirm_action = Some(ConfirmAction::RemoveAddress(name, ip, prefix));
+            self.mode = AppMode::ConfirmDialog;
+        }
+    }
+
+    /// Confirm promoting the address selected in the IP editor to primary
+    pub fn prepare_make_primary(&mut self) {
+        let Some((name, ip, prefix)) = self.selected_edit_address() else {
+            return;
+        };
+
+        let is_secondary = self
+            .ip_edit_state
+            .addresses
+            .get(self.ip_edit_state.address_index)
+            .is_some_and(|addr| addr.secondary);
+        if !is_secondary {
+            self.set_status(format!("{} is already a primary address", ip));
+            return;
+        }
+
+        self.confirm_message = format!(
+            "Make {}/{} the primary address on '{}'?\nAddresses in this subnet are briefly removed and re-added.",
+            ip, prefix, name
+        );
+        self.confirm_action = Some(ConfirmAction::MakePrimary(name, ip, prefix));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    /// Show or hide the extra addresses under the selected table row
+    pub fn toggle_expanded(&mut self) {
+        if let Some(row) = self.get_selected_row() {
+            let name = row.name.clone();
+            if row.extra_addresses.is_empty() {
+                self.set_status(format!("{} has a single address", name));
+            } else if !self.expanded_rows.remove(&name) {
+                self.expanded_rows.insert(name);
+            }
+        }
+    }
+
+    pub fn prepare_dns_config(&mut self) {
+        let servers: Vec<String> = self
+            .dns_edit_state
+            .dns_servers
+            .iter()
+            .filter(|s| !s.is_empty())
+            .cloned()
+            .collect();
+
+        let domains = self.dns_edit_state.search_domains.clone();
+
+        self.confirm_message = format!(
+            "Update DNS configuration?\nServers: {}\nSearch domains: {}",
+            servers.join(", "),
+            if domains.is_empty() {
                 "None".to_string()
             } else {
                 domains.join(", ")
@@ -447,83 +1576,680 @@ impl App {
     }
 
     pub fn prepare_ipv6_config(&mut self) {
-        if let Some(iface) = self.get_selected_interface() {
-            let name = iface.name.clone();
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        let name = iface.name.clone();
+        let currently_enabled = iface.ipv6_enabled;
+        let current_settings = iface.ipv6_settings;
+        let state = &self.ipv6_edit_state;
+
+        if !state.enabled {
+            self.confirm_message = format!("Disable IPv6 on '{}'?", name);
+            self.confirm_action = Some(ConfirmAction::DisableIpv6(name));
+            self.mode = AppMode::ConfirmDialog;
+            return;
+        }
+
+        let check = validation::check_ipv6_form(
+            &state.ip_buffer,
+            &state.prefix_buffer,
+            &state.gateway_buffer,
+            &name,
+            &self.interfaces,
+        );
+        let valid = check.is_valid();
+        let warnings = check.warnings.clone();
+
+        let changes = Ipv6Changes {
+            enable: !currently_enabled,
+            settings: match (state.settings, current_settings) {
+                (Some(edited), Some(current)) => edited.changes_from(&current),
+                _ => Vec::new(),
+            },
+            address: check.address,
+            gateway: check.gateway,
+            dhcpv6: state.dhcpv6,
+        };
+        self.ipv6_edit_state.check = Some(check);
+
+        // Stay in the form so the inline errors can be fixed
+        if !valid {
+            self.set_status("Fix the highlighted fields first".to_string());
+            return;
+        }
 
-            if !self.ipv6_edit_state.enabled {
-                self.confirm_message = format!("Disable IPv6 on '{}'?", name);
-                self.confirm_action = Some(ConfirmAction::DisableIpv6(name));
-            } else if !self.ipv6_edit_state.ip_buffer.is_empty() {
-                let prefix: u8 = self.ipv6_edit_state.prefix_buffer.parse().unwrap_or(64);
-                let ip_buffer = self.ipv6_edit_state.ip_buffer.clone();
-                self.confirm_message = format!(
+        let only_enable = Ipv6Changes {
+            enable: true,
+            ..Default::default()
+        };
+        let only_address = Ipv6Changes {
+            address: changes.address,
+            ..Default::default()
+        };
+        let (message, action) = if changes == Ipv6Changes::default() {
+            self.set_status("No IPv6 changes to apply".to_string());
+            return;
+        } else if changes == only_enable {
+            (
+                format!("Enable IPv6 on '{}'?", name),
+                ConfirmAction::EnableIpv6(name),
+            )
+        } else if let Some((address, prefix)) = changes.address.filter(|_| changes == only_address)
+        {
+            (
+                format!(
                     "Set static IPv6 on '{}'?\nAddress: {}/{}",
-                    name, ip_buffer, prefix
-                );
-                self.confirm_action = Some(ConfirmAction::SetStaticIpv6(name, ip_buffer, prefix));
-            } else {
-                self.confirm_message = format!("Enable IPv6 on '{}'?", name);
-                self.confirm_action = Some(ConfirmAction::EnableIpv6(name));
+                    name, address, prefix
+                ),
+                ConfirmAction::SetStaticIpv6(name, address.to_string(), prefix),
+            )
+        } else {
+            let mut message = format!("Update IPv6 on '{}'?", name);
+            for line in changes.describe() {
+                message.push_str(&format!("\n{}", line));
+            }
+            if changes
+                .settings
+                .iter()
+                .any(|setting| matches!(setting, Ipv6Setting::AddrGenMode(_)))
+            {
+                message.push_str("\nThe address generation mode applies once the link is toggled.");
             }
+            (message, ConfirmAction::ConfigureIpv6(name, changes))
+        };
 
-            self.mode = AppMode::ConfirmDialog;
+        self.confirm_message = message;
+        for warning in warnings {
+            self.confirm_message
+                .push_str(&format!("\nWarning: {}", warning));
+        }
+        self.confirm_action = Some(action);
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    /// Re-run the IPv6 form check after an edit, once the form has been submitted
+    pub fn revalidate_ipv6_form(&mut self) {
+        if self.ipv6_edit_state.check.is_none() {
+            return;
+        }
+        if let Some(iface) = self.get_selected_interface() {
+            let state = &self.ipv6_edit_state;
+            let check = validation::check_ipv6_form(
+                &state.ip_buffer,
+                &state.prefix_buffer,
+                &state.gateway_buffer,
+                &iface.name,
+                &self.interfaces,
+            );
+            self.ipv6_edit_state.check = Some(check);
+        }
+    }
+
+    /// Confirm removing the address selected in the IPv6 editor
+    pub fn prepare_remove_ipv6_address(&mut self) {
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        let state = &self.ipv6_edit_state;
+        let Some(addr) = state.addresses.get(state.address_index) else {
+            return;
+        };
+        let (name, ip, prefix) = (iface.name.clone(), addr.ip, addr.prefix.unwrap_or(64));
+
+        self.confirm_message = format!("Remove {}/{} from '{}'?", ip, prefix, name);
+        if addr
+            .ipv6_info
+            .as_ref()
+            .is_some_and(|info| info.is_dynamic())
+        {
+            self.confirm_message
+                .push_str("\nThis address is autoconfigured and may come back.");
         }
+        self.confirm_action = Some(ConfirmAction::RemoveAddress(name, ip, prefix));
+        self.mode = AppMode::ConfirmDialog;
     }
 
     pub fn execute_confirmed_action(&mut self) -> Result<()> {
+        if self.confirm_action.is_some() && !self.sudo_credentials_cached() {
+            self.start_password_prompt();
+            return Ok(());
+        }
+
+        if let Some(ConfirmAction::ProbeDhcp(interface)) = &self.confirm_action {
+            let interface = interface.clone();
+            self.confirm_action = None;
+            return self.run_dhcp_probe(interface);
+        }
+
+        if let Some(ConfirmAction::ScanWifi(interface)) = &self.confirm_action {
+            let interface = interface.clone();
+            self.confirm_action = None;
+            return self.run_wifi_scan(interface);
+        }
+
+        if let Some(ConfirmAction::ReadWireGuard(interface)) = &self.confirm_action {
+            let interface = interface.clone();
+            self.confirm_action = None;
+            return self.run_wireguard_read(interface);
+        }
+
+        if let Some(ConfirmAction::SwitchNamespace(choice)) = &self.confirm_action {
+            let choice = choice.clone();
+            self.confirm_action = None;
+            return self.switch_namespace(choice);
+        }
+
         if let Some(action) = self.confirm_action.take() {
-            let result = match action {
-                ConfirmAction::SetDhcp(iface) => {
-                    sudo::set_dhcp(&iface)?;
-                    format!("DHCP enabled on {}", iface)
+            // Best effort: the action was confirmed, so it runs either way,
+            // but without the earlier state there is nothing to undo it to
+            let before = StateSnapshot::capture(action.interface()).ok();
+            helper::take_command_log();
+
+            let outcome = self.run_action(&action);
+            let commands = helper::take_command_log();
+
+            if let Err(ref e) = outcome {
+                if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) {
+                    // The sudo timestamp expired under us; ask again and retry
+                    self.sudo_authenticated_at = None;
+                    self.confirm_action = Some(action);
+                    self.start_password_prompt();
+                    return Ok(());
+                }
+            }
+
+            if let Some(wifi) = &mut self.wifi {
+                wifi.password.zeroize();
+            }
+            let after = StateSnapshot::capture(action.interface()).ok();
+
+            // A failed action is only worth undoing if it got part of the way
+            let changed = outcome.is_ok()
+                || before
+                    .as_ref()
+                    .zip(after.as_ref())
+                    .is_some_and(|(before, after)| {
+                        !undo::plan(before, after).is_ok_and(|steps| steps.is_empty())
+                    });
+
+            // Undoing walks back down the stack; everything else can be undone later
+            let mut undo_note = None;
+            if matches!(action, ConfirmAction::Undo(..)) {
+                if outcome.is_ok() {
+                    self.undo_stack.pop();
                 }
-                ConfirmAction::SetStaticIp(iface, ip, netmask, gateway) => {
-                    sudo::set_static_ip(&iface, &ip, &netmask, gateway.as_deref())?;
-                    format!("Static IP set on {}", iface)
+            } else if changed
+                && ((action.interface().is_some() && !action.is_wifi())
+                    || matches!(action, ConfirmAction::SetDns(..)))
+            {
+                match &before {
+                    Some(before) => self.undo_stack.push(UndoEntry {
+                        action: action.clone(),
+                        before: before.clone(),
+                        namespace: namespace::selected(),
+                    }),
+                    None => undo_note = Some(" (can't be undone: the earlier state wasn't read)"),
                 }
-                ConfirmAction::SetDns(servers, domains) => {
-                    let server_refs: Vec<&str> = servers.iter().map(|s| s.as_str()).collect();
-                    sudo::set_dns_servers("", &server_refs)?;
+            }
 
-                    if !domains.is_empty() {
-                        let domain_refs: Vec<&str> = domains.iter().map(|s| s.as_str()).collect();
-                        sudo::set_search_domains("", &domain_refs)?;
-                    }
-                    "DNS configuration updated".to_string()
+            let entry = AuditEntry::new(
+                action,
+                before,
+                after,
+                commands,
+                outcome.as_ref().err().map(|e| format!("{:#}", e)),
+            );
+            let audit_result = audit::append(&self.audit_log_path, &entry);
+
+            let mut message = outcome?;
+            if let Some(note) = undo_note {
+                message.push_str(note);
+            }
+            if let Err(e) = audit_result {
+                message = format!("{} (audit log write failed: {})", message, e);
+            }
+
+            self.set_status(message);
+            self.mode = AppMode::Normal;
+            self.refresh_or_report();
+            if self.mode == AppMode::SudoPassword {
+                return Ok(());
+            }
+
+            // Back to the WiFi screen, with the list showing the new connection
+            if let Some(interface) = self.wifi.as_ref().map(|wifi| wifi.interface.clone()) {
+                self.mode = AppMode::Wifi;
+                if let Err(e) = self.run_wifi_scan(interface) {
+                    self.set_status(format!("WiFi scan failed: {}", e));
                 }
-                ConfirmAction::ToggleInterface(iface, enabled) => {
-                    sudo::set_interface_status(&iface, enabled)?;
-                    format!(
-                        "Interface {} {}",
-                        iface,
-                        if enabled { "enabled" } else { "disabled" }
-                    )
+            }
+            self.reload_bonds();
+            self.reload_bridges();
+            self.mode = self.screen_mode();
+        }
+
+        Ok(())
+    }
+
+    /// The probe changes nothing, so it skips the audit log and undo stack
+    fn run_dhcp_probe(&mut self, interface: String) -> Result<()> {
+        match sudo::probe_dhcp(&interface, DHCP_PROBE_TIMEOUT) {
+            Ok(offers) => {
+                self.dhcp_offers = Some((interface, offers));
+                self.mode = AppMode::DhcpOffers;
+                Ok(())
+            }
+            Err(e) if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) => {
+                self.sudo_authenticated_at = None;
+                self.confirm_action = Some(ConfirmAction::ProbeDhcp(interface));
+                self.start_password_prompt();
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Keeps the selection on the same network when rescanning
+    fn run_wifi_scan(&mut self, interface: String) -> Result<()> {
+        match sudo::scan_wifi(&interface) {
+            Ok(networks) => {
+                let previous = self
+                    .wifi
+                    .as_ref()
+                    .filter(|wifi| wifi.interface == interface)
+                    .and_then(|wifi| wifi.selected_network())
+                    .map(|network| network.bssid.clone());
+                let mut state = WifiState::new(interface, networks);
+                state.selected = previous
+                    .and_then(|bssid| state.networks.iter().position(|n| n.bssid == bssid))
+                    .unwrap_or(0);
+                self.wifi = Some(state);
+                self.mode = AppMode::Wifi;
+                Ok(())
+            }
+            Err(e) if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) => {
+                self.sudo_authenticated_at = None;
+                self.confirm_action = Some(ConfirmAction::ScanWifi(interface));
+                self.start_password_prompt();
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Starting the helper is all it takes: from then on every refresh reads
+    /// the peers through it
+    fn run_wireguard_read(&mut self, interface: String) -> Result<()> {
+        match sudo::get_wireguard(&interface) {
+            Ok(info) => {
+                if !self.refresh_or_report() {
+                    return Ok(());
+                }
+                self.mode = AppMode::Details;
+                self.set_status(format!(
+                    "{} peer(s) on {}; press a or x again",
+                    info.peers.len(),
+                    interface
+                ));
+                Ok(())
+            }
+            Err(e) if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) => {
+                self.sudo_authenticated_at = None;
+                self.confirm_action = Some(ConfirmAction::ReadWireGuard(interface));
+                self.start_password_prompt();
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn run_action(&self, action: &ConfirmAction) -> Result<String> {
+        let result = match action {
+            ConfirmAction::SetDhcp(iface) => {
+                sudo::set_dhcp(iface)?;
+                format!("DHCP enabled on {}", iface)
+            }
+            ConfirmAction::RenewDhcp(iface, client) => {
+                sudo::renew_dhcp(iface, *client)?;
+                format!("DHCP lease renewed on {}", iface)
+            }
+            ConfirmAction::ReleaseDhcp(iface, client) => {
+                sudo::release_dhcp(iface, *client)?;
+                format!("DHCP lease released on {}", iface)
+            }
+            ConfirmAction::ProbeDhcp(iface) => {
+                let offers = sudo::probe_dhcp(iface, DHCP_PROBE_TIMEOUT)?;
+                format!("{} DHCP offers on {}", offers.len(), iface)
+            }
+            ConfirmAction::ScanWifi(iface) => {
+                let networks = sudo::scan_wifi(iface)?;
+                format!("{} WiFi networks found on {}", networks.len(), iface)
+            }
+            ConfirmAction::ConnectWifi(iface, ssid, security) => {
+                let password = self
+                    .wifi
+                    .as_ref()
+                    .map(|wifi| wifi.password.as_str())
+                    .filter(|password| !password.is_empty());
+                sudo::connect_wifi(iface, ssid, *security, password)?;
+                format!("{} connected to '{}'", iface, ssid)
+            }
+            ConfirmAction::DisconnectWifi(iface) => {
+                sudo::disconnect_wifi(iface)?;
+                format!("{} disconnected", iface)
+            }
+            ConfirmAction::ForgetWifi(iface, ssid) => {
+                sudo::forget_wifi(iface, ssid)?;
+                format!("Forgot WiFi network '{}'", ssid)
+            }
+            ConfirmAction::SetStaticIp(iface, ip, netmask, gateway, label) => {
+                sudo::set_static_ip(iface, ip, netmask, gateway.as_deref(), label.as_deref())?;
+                format!("Static IP {} added to {}", ip, iface)
+            }
+            ConfirmAction::RemoveAddress(iface, ip, prefix) => {
+                sudo::remove_address(iface, *ip, *prefix)?;
+                format!("Removed {}/{} from {}", ip, prefix, iface)
+            }
+            ConfirmAction::MakePrimary(iface, ip, prefix) => {
+                sudo::make_primary(iface, *ip, *prefix)?;
+                format!("{} is now the primary address on {}", ip, iface)
+            }
+            ConfirmAction::SetDns(servers, domains) => {
+                let server_refs: Vec<&str> = servers.iter().map(|s| s.as_str()).collect();
+                let domain_refs: Vec<&str> = domains.iter().map(|s| s.as_str()).collect();
+                sudo::set_dns("", &server_refs, &domain_refs)?;
+                "DNS configuration updated".to_string()
+            }
+            ConfirmAction::ToggleInterface(iface, enabled) => {
+                sudo::set_interface_status(iface, *enabled)?;
+                format!(
+                    "Interface {} {}",
+                    iface,
+                    if *enabled { "enabled" } else { "disabled" }
+                )
+            }
+            ConfirmAction::SetMac(iface, mac) => {
+                sudo::set_mac(iface, *mac)?;
+                format!("MAC address of {} set to {}", iface, mac)
+            }
+            ConfirmAction::DisableIpv6(iface) => {
+                sudo::disable_ipv6(iface)?;
+                format!("IPv6 disabled on {}", iface)
+            }
+            ConfirmAction::EnableIpv6(iface) => {
+                sudo::enable_ipv6(iface)?;
+                format!("IPv6 enabled on {}", iface)
+            }
+            ConfirmAction::SetStaticIpv6(iface, ip, prefix) => {
+                sudo::set_static_ipv6(iface, ip, *prefix)?;
+                format!("Static IPv6 set on {}", iface)
+            }
+            ConfirmAction::ConfigureIpv6(iface, changes) => {
+                if changes.enable {
+                    sudo::enable_ipv6(iface)?;
                 }
-                ConfirmAction::DisableIpv6(iface) => {
-                    sudo::disable_ipv6(&iface)?;
-                    format!("IPv6 disabled on {}", iface)
+                for setting in &changes.settings {
+                    sudo::set_ipv6_setting(iface, *setting)?;
                 }
-                ConfirmAction::EnableIpv6(iface) => {
-                    sudo::enable_ipv6(&iface)?;
-                    format!("IPv6 enabled on {}", iface)
+                if let Some((address, prefix)) = changes.address {
+                    sudo::set_static_ipv6(iface, &address.to_string(), prefix)?;
                 }
-                ConfirmAction::SetStaticIpv6(iface, ip, prefix) => {
-                    sudo::set_static_ipv6(&iface, &ip, prefix)?;
-                    format!("Static IPv6 set on {}", iface)
+                if let Some(gateway) = changes.gateway {
+                    sudo::set_ipv6_gateway(iface, gateway)?;
                 }
-            };
+                if changes.dhcpv6 {
+                    sudo::start_dhcpv6(iface)?;
+                }
+                format!("IPv6 configuration updated on {}", iface)
+            }
+            ConfirmAction::CreateVlan(parent, name, id, protocol, mtu) => {
+                sudo::create_vlan(parent, name, *id, *protocol, *mtu)?;
+                format!("VLAN {} created as {}", id, name)
+            }
+            ConfirmAction::DeleteVlan(iface) => {
+                sudo::delete_vlan(iface)?;
+                format!("VLAN {} deleted", iface)
+            }
+            ConfirmAction::CreateBond(name, mode, miimon) => {
+                sudo::create_bond(name, *mode, *miimon)?;
+                format!("Bond {} created", name)
+            }
+            ConfirmAction::DeleteBond(name) => {
+                sudo::delete_bond(name)?;
+                format!("Bond {} deleted", name)
+            }
+            ConfirmAction::EnslaveInterface(master, iface) => {
+                sudo::enslave_interface(master, iface)?;
+                format!("{} added to {}", iface, master)
+            }
+            ConfirmAction::ReleaseInterface(master, iface) => {
+                sudo::release_interface(master, iface)?;
+                format!("{} released from {}", iface, master)
+            }
+            ConfirmAction::CreateBridge(name, stp) => {
+                sudo::create_bridge(name, *stp)?;
+                format!("Bridge {} created", name)
+            }
+            ConfirmAction::DeleteBridge(name) => {
+                sudo::delete_bridge(name)?;
+                format!("Bridge {} deleted", name)
+            }
+            ConfirmAction::SetBridgeStp(bridge, enabled) => {
+                sudo::set_bridge_stp(bridge, *enabled)?;
+                format!(
+                    "STP {} on {}",
+                    if *enabled { "enabled" } else { "disabled" },
+                    bridge
+                )
+            }
+            ConfirmAction::SetOffload(iface, feature, enabled) => {
+                sudo::set_offload(iface, feature, *enabled)?;
+                format!(
+                    "{} turned {} on {}",
+                    feature,
+                    if *enabled { "on" } else { "off" },
+                    iface
+                )
+            }
+            ConfirmAction::ReadWireGuard(iface) => {
+                let info = sudo::get_wireguard(iface)?;
+                format!("{} peer(s) on {}", info.peers.len(), iface)
+            }
+            ConfirmAction::SwitchNamespace(choice) => {
+                format!("Switched to {}", namespace::label_of(choice.as_ref()))
+            }
+            ConfirmAction::AddWireGuardPeer(iface, peer) => {
+                sudo::add_wireguard_peer(iface, peer)?;
+                format!(
+                    "Peer {} added to {}",
+                    wireguard::short_key(&peer.public_key),
+                    iface
+                )
+            }
+            ConfirmAction::RemoveWireGuardPeer(iface, key) => {
+                sudo::remove_wireguard_peer(iface, key)?;
+                format!("Peer {} removed from {}", wireguard::short_key(key), iface)
+            }
+            ConfirmAction::SetActiveSlave(bond, iface) => {
+                sudo::set_active_slave(bond, iface)?;
+                format!("{} failed over to {}", bond, iface)
+            }
+            ConfirmAction::FlushDnsCache => {
+                sudo::flush_dns_cache()?;
+                "DNS cache flushed successfully".to_string()
+            }
+            ConfirmAction::Undo(_, undone, steps) => {
+                for step in steps {
+                    helper::call(step.clone())?;
+                }
+                format!("Undone: {}", undone)
+            }
+        };
 
-            self.set_status(result);
-            self.mode = AppMode::Normal;
-            self.refresh_data()?;
+        Ok(result)
+    }
+
+    pub fn prepare_undo(&mut self) -> Result<()> {
+        let Some(entry) = self.undo_stack.last() else {
+            self.set_status("Nothing to undo".to_string());
+            return Ok(());
+        };
+
+        let interface = entry.action.interface().map(|name| name.to_string());
+        let undone = entry.action.summary();
+        if entry.namespace != namespace::selected() {
+            self.set_status(format!(
+                "Switch to network namespace {} to undo '{}'",
+                namespace::label_of(entry.namespace.as_ref()),
+                undone
+            ));
+            return Ok(());
+        }
+        // Kept on the stack, so it can be tried again once the state reads
+        let current = match StateSnapshot::capture(interface.as_deref()) {
+            Ok(current) => current,
+            Err(e) => {
+                self.set_status(format!(
+                    "Can't read the current state to undo '{}': {:#}",
+                    undone, e
+                ));
+                return Ok(());
+            }
+        };
+        let steps = match undo::plan(&entry.before, &current) {
+            Ok(steps) => steps,
+            Err(reason) => {
+                // Kept, so the record of what was there isn't lost
+                self.set_status(format!(
+                    "'{}' can't be undone automatically: {}",
+                    undone, reason
+                ));
+                return Ok(());
+            }
+        };
+
+        if steps.is_empty() {
+            self.undo_stack.pop();
+            self.set_status(format!(
+                "Nothing to undo for '{}': state already matches",
+                undone
+            ));
+            return Ok(());
+        }
+
+        let mut message = format!("Undo '{}'?\n", undone);
+        for step in &steps {
+            message.push_str(&format!("\n{}", step.describe()));
+        }
+        if self.undo_stack.len() > 1 {
+            message.push_str(&format!(
+                "\n\n{} earlier change(s) can be undone after this",
+                self.undo_stack.len() - 1
+            ));
         }
 
+        self.confirm_message = message;
+        self.confirm_action = Some(ConfirmAction::Undo(interface, undone, steps));
+        self.mode = AppMode::ConfirmDialog;
         Ok(())
     }
 
     pub fn cancel_confirm(&mut self) {
         self.confirm_action = None;
-        self.mode = AppMode::Normal;
+        self.password_state.reset();
+        if let Some(wifi) = &mut self.wifi {
+            wifi.password.zeroize();
+        }
+        self.mode = self.screen_mode();
+    }
+
+    /// The WiFi, bonds or bridges screen when one is open, the offloads or
+    /// WireGuard forms of the details screen, otherwise the interface list
+    pub fn screen_mode(&self) -> AppMode {
+        if self.wifi.is_some() {
+            AppMode::Wifi
+        } else if self.bonds.is_some() {
+            AppMode::Bonds
+        } else if self.bridges.is_some() {
+            AppMode::Bridges
+        } else if self.offload_picker.is_some() {
+            AppMode::Offloads
+        } else if self.wireguard.is_some() {
+            AppMode::Details
+        } else {
+            AppMode::Normal
+        }
+    }
+
+    // Sudo password functions
+    fn sudo_credentials_cached(&mut self) -> bool {
+        if self
+            .sudo_authenticated_at
+            .is_some_and(|at| at.elapsed() < SUDO_TIMESTAMP_TIMEOUT)
+        {
+            return true;
+        }
+
+        // `sudo -n -v` also succeeds for NOPASSWD setups and refreshes the timestamp;
+        // strategies without a password prompt always report true
+        if sudo::has_cached_credentials() {
+            self.sudo_authenticated_at = Some(Instant::now());
+            return true;
+        }
+
+        false
+    }
+
+    fn start_password_prompt(&mut self) {
+        self.password_state.reset();
+        self.mode = AppMode::SudoPassword;
+    }
+
+    pub fn add_password_char(&mut self, c: char) {
+        let buffer = &mut self.password_state.buffer;
+        if buffer.len() + c.len_utf8() > buffer.capacity() {
+            return;
+        }
+        buffer.push(c);
+    }
+
+    pub fn remove_password_char(&mut self) {
+        self.password_state.buffer.pop();
+    }
+
+    pub fn clear_password(&mut self) {
+        self.password_state.buffer.zeroize();
+    }
+
+    pub fn submit_password(&mut self) -> Result<()> {
+        let result = sudo::authenticate(&self.password_state.buffer);
+        self.password_state.buffer.zeroize();
+
+        match result {
+            Ok(()) => {
+                self.sudo_authenticated_at = Some(Instant::now());
+                self.password_state.reset();
+                self.execute_confirmed_action()
+            }
+            Err(e) if matches!(e.downcast_ref(), Some(SudoError::IncorrectPassword)) => {
+                self.password_state.attempts += 1;
+                if self.password_state.attempts >= MAX_PASSWORD_ATTEMPTS {
+                    self.cancel_confirm();
+                    self.set_status(format!(
+                        "sudo: {} incorrect password attempts",
+                        MAX_PASSWORD_ATTEMPTS
+                    ));
+                } else {
+                    self.password_state.error = Some(format!(
+                        "Sorry, try again ({}/{})",
+                        self.password_state.attempts, MAX_PASSWORD_ATTEMPTS
+                    ));
+                }
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
     }
 
     pub fn toggle_interface(&mut self) -> Result<()> {
@@ -541,6 +2267,87 @@ impl App {
         Ok(())
     }
 
+    pub fn start_edit_mac(&mut self) {
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        if iface.mac_address.is_none() {
+            self.set_status(format!("{} has no MAC address", iface.name));
+            return;
+        }
+
+        self.mac_edit_state = MacEditState {
+            interface: iface.name.clone(),
+            buffer: iface
+                .mac_address
+                .map(|mac| mac.to_string())
+                .unwrap_or_default(),
+            error: None,
+        };
+        self.mode = AppMode::EditMac;
+    }
+
+    pub fn randomize_mac(&mut self) {
+        self.mac_edit_state.buffer = MacAddr::random_local().to_string();
+        self.mac_edit_state.error = None;
+    }
+
+    /// Back to the address the hardware came with
+    pub fn fill_permanent_mac(&mut self) {
+        let state = &mut self.mac_edit_state;
+        match self
+            .interfaces
+            .iter()
+            .find(|iface| iface.name == state.interface)
+            .and_then(|iface| iface.permanent_mac)
+        {
+            Some(permanent) => {
+                state.buffer = permanent.to_string();
+                state.error = None;
+            }
+            None => state.error = Some("The driver reports no permanent address".to_string()),
+        }
+    }
+
+    pub fn prepare_set_mac(&mut self) {
+        let state = &mut self.mac_edit_state;
+        let Some(iface) = self
+            .interfaces
+            .iter()
+            .find(|iface| iface.name == state.interface)
+        else {
+            self.mode = AppMode::Normal;
+            return;
+        };
+        let (mac, warning) = match validation::check_mac(&state.buffer, iface, &self.interfaces) {
+            Ok(checked) => checked,
+            Err(e) => {
+                state.error = Some(e);
+                return;
+            }
+        };
+        state.error = None;
+
+        self.confirm_message = format!(
+            "Change the MAC address of {} from {} to {}?",
+            iface.name,
+            iface
+                .mac_address
+                .map(|mac| mac.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+            mac
+        );
+        if iface.is_up {
+            self.confirm_message
+                .push_str("\nThe link goes down for a moment; DHCP may hand out a new lease.");
+        }
+        if let Some(warning) = warning {
+            self.confirm_message.push_str(&format!("\n⚠ {}", warning));
+        }
+        self.confirm_action = Some(ConfirmAction::SetMac(iface.name.clone(), mac));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
     // Terminal functions
     pub fn open_terminal(&mut self) {
         self.mode = AppMode::Terminal;
@@ -633,9 +2440,1374 @@ impl App {
         self.terminal_needs_clear = true;
     }
 
-    pub fn flush_dns_cache(&mut self) -> Result<()> {
-        sudo::flush_dns_cache()?;
-        self.set_status("DNS cache flushed successfully".to_string());
+    // History functions
+    pub fn open_history(&mut self) -> Result<()> {
+        self.history = audit::load(&self.audit_log_path)?;
+        self.history_index = 0;
+        self.mode = AppMode::History;
+        Ok(())
+    }
+
+    pub fn next_history_entry(&mut self) {
+        if self.history_index + 1 < self.history.len() {
+            self.history_index += 1;
+        }
+    }
+
+    pub fn previous_history_entry(&mut self) {
+        self.history_index = self.history_index.saturating_sub(1);
+    }
+
+    /// List the namespaces to switch to, preselecting the current one
+    pub fn open_namespaces(&mut self) -> Result<()> {
+        // Only root sees into the processes of other users
+        let namespaces = if !namespace::can_enter() && helper::is_running() {
+            sudo::list_namespaces()?
+        } else {
+            namespace::list()?
+        };
+        let index = namespace::selected()
+            .and_then(|current| namespaces.iter().position(|ns| ns.path == current.path))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+
+        self.namespace_picker = Some(NamespacePicker { namespaces, index });
+        self.mode = AppMode::Namespaces;
+        Ok(())
+    }
+
+    pub fn close_namespace_picker(&mut self) {
+        self.namespace_picker = None;
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn next_namespace(&mut self) {
+        if let Some(picker) = &mut self.namespace_picker {
+            if picker.index < picker.namespaces.len() {
+                picker.index += 1;
+            }
+        }
+    }
+
+    pub fn previous_namespace(&mut self) {
+        if let Some(picker) = &mut self.namespace_picker {
+            picker.index = picker.index.saturating_sub(1);
+        }
+    }
+
+    pub fn select_namespace(&mut self) -> Result<()> {
+        let Some(picker) = self.namespace_picker.take() else {
+            return Ok(());
+        };
+        let choice = picker
+            .index
+            .checked_sub(1)
+            .and_then(|index| picker.namespaces.get(index).cloned());
+        self.mode = AppMode::Normal;
+        self.switch_namespace(choice)
+    }
+
+    /// Show and change another namespace from now on; `None` is our own
+    fn switch_namespace(&mut self, choice: Option<Namespace>) -> Result<()> {
+        let previous = namespace::selected();
+        namespace::select(choice.clone());
+
+        match self.refresh_data() {
+            Ok(()) => {
+                self.selected_index = 0;
+                self.scroll_offset = 0;
+                self.expanded_rows.clear();
+                self.mode = AppMode::Normal;
+                self.set_status(format!(
+                    "Showing network namespace {}",
+                    namespace::label_of(choice.as_ref())
+                ));
+                Ok(())
+            }
+            Err(e) => {
+                namespace::select(previous);
+                let _ = self.refresh_data();
+                if matches!(e.downcast_ref(), Some(SudoError::PasswordRequired)) {
+                    self.sudo_authenticated_at = None;
+                    self.confirm_action = Some(ConfirmAction::SwitchNamespace(choice));
+                    self.start_password_prompt();
+                    return Ok(());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Bonds and bridges are read from sysfs and procfs, which show the
+    /// namespace we started in whichever one a thread has entered
+    fn in_own_namespace(&mut self, screen: &str) -> bool {
+        let Some(selected) = namespace::selected() else {
+            return true;
+        };
+        self.set_status(format!(
+            "The {} screen only works in the host namespace, not {}",
+            screen,
+            selected.label()
+        ));
+        false
+    }
+
+    pub fn probe_dhcp(&mut self) -> Result<()> {
+        if let Some(iface) = self.get_selected_interface() {
+            self.confirm_action = Some(ConfirmAction::ProbeDhcp(iface.name.clone()));
+            self.execute_confirmed_action()?;
+        }
         Ok(())
     }
+
+    /// Scanning needs the helper, so the screen opens once the first scan is in
+    pub fn open_wifi(&mut self) -> Result<()> {
+        let Some(iface) = self.get_selected_interface() else {
+            return Ok(());
+        };
+        if iface.interface_type != InterfaceType::WiFi {
+            let message = format!("{} is not a WiFi interface", iface.name);
+            self.set_status(message);
+            return Ok(());
+        }
+
+        let name = iface.name.clone();
+        self.wifi = None;
+        self.confirm_action = Some(ConfirmAction::ScanWifi(name));
+        self.execute_confirmed_action()
+    }
+
+    pub fn rescan_wifi(&mut self) -> Result<()> {
+        if let Some(wifi) = &self.wifi {
+            self.confirm_action = Some(ConfirmAction::ScanWifi(wifi.interface.clone()));
+            self.execute_confirmed_action()?;
+        }
+        Ok(())
+    }
+
+    pub fn close_wifi(&mut self) {
+        self.wifi = None;
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn next_wifi_network(&mut self) {
+        if let Some(wifi) = &mut self.wifi {
+            if wifi.selected + 1 < wifi.networks.len() {
+                wifi.selected += 1;
+            }
+        }
+    }
+
+    pub fn previous_wifi_network(&mut self) {
+        if let Some(wifi) = &mut self.wifi {
+            wifi.selected = wifi.selected.saturating_sub(1);
+        }
+    }
+
+    /// Saved and open networks connect straight away; others ask for a
+    /// password first
+    pub fn prepare_wifi_connect(&mut self) -> Result<()> {
+        let Some(wifi) = &mut self.wifi else {
+            return Ok(());
+        };
+        let Some(network) = wifi.selected_network() else {
+            return Ok(());
+        };
+
+        let message = if network.ssid.is_empty() {
+            Some("Hidden networks can't be joined from here".to_string())
+        } else if network.in_use {
+            Some(format!("Already connected to '{}'", network.ssid))
+        } else if !network.saved
+            && (network.security.is_enterprise() || network.security == WifiSecurity::Wep)
+        {
+            Some(format!(
+                "{} networks are not supported",
+                network.security.as_str()
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            self.set_status(message);
+            return Ok(());
+        }
+
+        let action = ConfirmAction::ConnectWifi(
+            wifi.interface.clone(),
+            network.ssid.clone(),
+            network.security,
+        );
+        if network.security.needs_password() && !network.saved {
+            wifi.password.zeroize();
+            wifi.password_error = None;
+            self.confirm_action = Some(action);
+            self.mode = AppMode::WifiPassword;
+            return Ok(());
+        }
+
+        self.confirm_action = Some(action);
+        self.execute_confirmed_action()
+    }
+
+    pub fn add_wifi_password_char(&mut self, c: char) {
+        if let Some(wifi) = &mut self.wifi {
+            wifi.password.push(c);
+        }
+    }
+
+    pub fn remove_wifi_password_char(&mut self) {
+        if let Some(wifi) = &mut self.wifi {
+            wifi.password.pop();
+        }
+    }
+
+    pub fn clear_wifi_password(&mut self) {
+        if let Some(wifi) = &mut self.wifi {
+            wifi.password.zeroize();
+        }
+    }
+
+    /// Check the password locally so a typo doesn't cost a round trip
+    pub fn submit_wifi_password(&mut self) -> Result<()> {
+        let (Some(wifi), Some(ConfirmAction::ConnectWifi(_, _, security))) =
+            (&mut self.wifi, &self.confirm_action)
+        else {
+            return Ok(());
+        };
+
+        if let Err(e) = validation::check_wifi_password(&wifi.password, *security) {
+            wifi.password_error = Some(e);
+            return Ok(());
+        }
+        wifi.password_error = None;
+        self.execute_confirmed_action()
+    }
+
+    pub fn prepare_wifi_disconnect(&mut self) {
+        let Some(wifi) = &self.wifi else {
+            return;
+        };
+        let Some(network) = wifi.networks.iter().find(|network| network.in_use) else {
+            self.set_status(format!("{} is not connected", wifi.interface));
+            return;
+        };
+
+        self.confirm_message = format!("Disconnect {} from '{}'?", wifi.interface, network.ssid);
+        self.confirm_action = Some(ConfirmAction::DisconnectWifi(wifi.interface.clone()));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_wifi_forget(&mut self) {
+        let Some(network) = self.wifi.as_ref().and_then(|wifi| wifi.selected_network()) else {
+            return;
+        };
+        if !network.saved {
+            let message = format!("'{}' is not a saved network", network.ssid);
+            self.set_status(message);
+            return;
+        }
+
+        let interface = self.wifi.as_ref().map(|wifi| wifi.interface.clone());
+        let ssid = network.ssid.clone();
+        self.confirm_message = format!(
+            "Forget '{}'?\nThe saved password will be deleted{}.",
+            ssid,
+            if network.in_use {
+                " and the interface disconnected"
+            } else {
+                ""
+            }
+        );
+        self.confirm_action = Some(ConfirmAction::ForgetWifi(
+            interface.unwrap_or_default(),
+            ssid,
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn open_bonds(&mut self) -> Result<()> {
+        if !self.in_own_namespace("bonds") {
+            return Ok(());
+        }
+        if !self.refresh_or_report() {
+            return Ok(());
+        }
+        self.bonds = Some(BondState {
+            bonds: bond::get_bonds(&self.interfaces),
+            ..Default::default()
+        });
+        self.mode = AppMode::Bonds;
+        Ok(())
+    }
+
+    /// Re-read the bonds, keeping the selection on the same row
+    pub fn reload_bonds(&mut self) {
+        let Some(state) = &mut self.bonds else {
+            return;
+        };
+        let previous = state.rows().get(state.selected).map(|&(i, slave)| {
+            let bond = &state.bonds[i];
+            (
+                bond.name.clone(),
+                slave.map(|j| bond.slaves[j].name.clone()),
+            )
+        });
+
+        state.bonds = bond::get_bonds(&self.interfaces);
+        let rows = state.rows();
+        state.selected = previous
+            .and_then(|(bond, slave)| {
+                rows.iter().position(|&(i, j)| {
+                    state.bonds[i].name == bond
+                        && j.map(|j| state.bonds[i].slaves[j].name.clone()) == slave
+                })
+            })
+            .unwrap_or(0)
+            .min(rows.len().saturating_sub(1));
+    }
+
+    pub fn close_bonds(&mut self) {
+        self.bonds = None;
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn next_bond_row(&mut self) {
+        if let Some(state) = &mut self.bonds {
+            if state.selected + 1 < state.rows().len() {
+                state.selected += 1;
+            }
+        }
+    }
+
+    pub fn previous_bond_row(&mut self) {
+        if let Some(state) = &mut self.bonds {
+            state.selected = state.selected.saturating_sub(1);
+        }
+    }
+
+    pub fn start_create_bond(&mut self) {
+        if let Some(state) = &mut self.bonds {
+            state.name_buffer.clear();
+            state.mode = BondMode::default();
+            state.miimon_buffer = "100".to_string();
+            state.current_field = 0;
+            state.form_error = None;
+            self.mode = AppMode::CreateBond;
+        }
+    }
+
+    pub fn prepare_create_bond(&mut self) {
+        let Some(state) = &mut self.bonds else {
+            return;
+        };
+        let (name, miimon) = match validation::check_bond_form(
+            &state.name_buffer,
+            &state.miimon_buffer,
+            &self.interfaces,
+        ) {
+            Ok(form) => form,
+            Err(e) => {
+                state.form_error = Some(e);
+                return;
+            }
+        };
+        state.form_error = None;
+
+        self.confirm_message = format!(
+            "Create {} bond '{}'{}?",
+            state.mode.as_str(),
+            name,
+            miimon
+                .map(|miimon| format!(" with {} ms link monitoring", miimon))
+                .unwrap_or_default()
+        );
+        self.confirm_action = Some(ConfirmAction::CreateBond(name, state.mode, miimon));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    /// Offer the interfaces that could join `master`: not loopback, not in
+    /// a bond or bridge already and not a bridge themselves. Bonds can be
+    /// bridge ports but not bond slaves.
+    fn start_add_port(&mut self, master: String) {
+        let is_bridge = self
+            .interfaces
+            .iter()
+            .any(|iface| iface.name == master && iface.interface_type == InterfaceType::Bridge);
+        let candidates: Vec<String> = self
+            .interfaces
+            .iter()
+            .filter(|iface| {
+                iface.name != master
+                    && !iface.is_loopback
+                    && iface.master.is_none()
+                    && match iface.interface_type {
+                        InterfaceType::Bridge => false,
+                        InterfaceType::Bond | InterfaceType::Team => is_bridge,
+                        _ => true,
+                    }
+            })
+            .map(|iface| iface.name.clone())
+            .collect();
+
+        if candidates.is_empty() {
+            self.set_status(format!("No interfaces can be added to {}", master));
+            return;
+        }
+        self.port_picker = Some(PortPicker {
+            master,
+            candidates,
+            index: 0,
+        });
+        self.mode = AppMode::EnslaveInterface;
+    }
+
+    pub fn start_enslave(&mut self) {
+        let Some(bond) = self.bonds.as_ref().and_then(|s| s.selected_bond()) else {
+            return;
+        };
+        self.start_add_port(bond.name.clone());
+    }
+
+    pub fn close_port_picker(&mut self) {
+        self.port_picker = None;
+        self.mode = self.screen_mode();
+    }
+
+    pub fn next_port_candidate(&mut self) {
+        if let Some(picker) = &mut self.port_picker {
+            if picker.index + 1 < picker.candidates.len() {
+                picker.index += 1;
+            }
+        }
+    }
+
+    pub fn previous_port_candidate(&mut self) {
+        if let Some(picker) = &mut self.port_picker {
+            picker.index = picker.index.saturating_sub(1);
+        }
+    }
+
+    pub fn prepare_enslave(&mut self) {
+        let Some(picker) = self.port_picker.take() else {
+            return;
+        };
+        let Some(iface) = picker.candidates.get(picker.index) else {
+            return;
+        };
+        let master = self.interfaces.iter().find(|i| i.name == picker.master);
+        let has_addresses = self
+            .interfaces
+            .iter()
+            .any(|other| other.name == *iface && !other.ip_addresses.is_empty());
+
+        self.confirm_message = format!("Add '{}' to {}?", iface, picker.master);
+        if master.is_some_and(|master| master.interface_type == InterfaceType::Bridge) {
+            if has_addresses {
+                self.confirm_message.push_str(&format!(
+                    "\nAddresses on {} stop working; configure the bridge instead.",
+                    iface
+                ));
+            }
+            if let Some(tool) = bridge::managed_by(&picker.master) {
+                self.confirm_message.push_str(&format!(
+                    "\n{} manages {} and may undo this.",
+                    tool, picker.master
+                ));
+            }
+        } else {
+            self.confirm_message
+                .push_str(&format!("\n{} is taken down while it joins.", iface));
+            if has_addresses {
+                self.confirm_message
+                    .push_str(" Its own addresses stop working; configure the bond instead.");
+            }
+        }
+        self.confirm_action = Some(ConfirmAction::EnslaveInterface(
+            picker.master.clone(),
+            iface.clone(),
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_release(&mut self) {
+        let Some((bond, slave)) = self.bonds.as_ref().and_then(|s| s.selected_slave()) else {
+            self.set_status("Select a slave to release".to_string());
+            return;
+        };
+
+        self.confirm_message = format!("Release '{}' from {}?", slave.name, bond.name);
+        if bond.is_active(slave) && bond.slaves.iter().filter(|s| s.is_up()).count() <= 1 {
+            self.confirm_message
+                .push_str(&format!("\n{} has no other working slave.", bond.name));
+        }
+        self.confirm_action = Some(ConfirmAction::ReleaseInterface(
+            bond.name.clone(),
+            slave.name.clone(),
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_failover(&mut self) {
+        let Some((bond, slave)) = self.bonds.as_ref().and_then(|s| s.selected_slave()) else {
+            self.set_status("Select the slave to fail over to".to_string());
+            return;
+        };
+        let message = if !bond.mode.is_some_and(|mode| mode.has_active_slave()) {
+            Some(format!("{} has no single active slave", bond.mode_name()))
+        } else if bond.active_slave.as_deref() == Some(slave.name.as_str()) {
+            Some(format!("{} is already the active slave", slave.name))
+        } else if !slave.is_up() {
+            Some(format!("{} has no link", slave.name))
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            self.set_status(message);
+            return;
+        }
+
+        self.confirm_message = format!(
+            "Fail {} over from {} to '{}'?",
+            bond.name,
+            bond.active_slave.as_deref().unwrap_or("none"),
+            slave.name
+        );
+        self.confirm_action = Some(ConfirmAction::SetActiveSlave(
+            bond.name.clone(),
+            slave.name.clone(),
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_delete_bond(&mut self) {
+        let Some(bond) = self.bonds.as_ref().and_then(|s| s.selected_bond()) else {
+            return;
+        };
+        if bond.team {
+            self.set_status(format!("{} is managed by teamd", bond.name));
+            return;
+        }
+
+        self.confirm_message = format!("Delete bond '{}'?", bond.name);
+        if !bond.slaves.is_empty() {
+            self.confirm_message.push_str(&format!(
+                "\nIts slaves are released: {}",
+                bond.slaves
+                    .iter()
+                    .map(|slave| slave.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        self.confirm_action = Some(ConfirmAction::DeleteBond(bond.name.clone()));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn open_bridges(&mut self) -> Result<()> {
+        if !self.in_own_namespace("bridges") {
+            return Ok(());
+        }
+        if !self.refresh_or_report() {
+            return Ok(());
+        }
+        self.bridges = Some(BridgeState {
+            bridges: bridge::get_bridges(&self.interfaces),
+            ..Default::default()
+        });
+        self.mode = AppMode::Bridges;
+        Ok(())
+    }
+
+    /// Re-read the bridges, keeping the selection on the same row and the
+    /// forwarding database open
+    pub fn reload_bridges(&mut self) {
+        let Some(state) = &mut self.bridges else {
+            return;
+        };
+        let previous = state.rows().get(state.selected).map(|&(i, port)| {
+            let bridge = &state.bridges[i];
+            (
+                bridge.name.clone(),
+                port.map(|j| bridge.ports[j].name.clone()),
+            )
+        });
+
+        state.bridges = bridge::get_bridges(&self.interfaces);
+        let rows = state.rows();
+        state.selected = previous
+            .and_then(|(bridge, port)| {
+                rows.iter().position(|&(i, j)| {
+                    state.bridges[i].name == bridge
+                        && j.map(|j| state.bridges[i].ports[j].name.clone()) == port
+                })
+            })
+            .unwrap_or(0)
+            .min(rows.len().saturating_sub(1));
+
+        if state.fdb.is_some() {
+            state.fdb = state
+                .selected_bridge()
+                .and_then(|bridge| bridge::get_fdb(&bridge.name).ok());
+        }
+    }
+
+    pub fn close_bridges(&mut self) {
+        self.bridges = None;
+        self.mode = AppMode::Normal;
+    }
+
+    pub fn next_bridge_row(&mut self) {
+        if let Some(state) = &mut self.bridges {
+            match state.fdb {
+                Some(ref fdb) if state.fdb_scroll + 1 < fdb.len() => state.fdb_scroll += 1,
+                Some(_) => {}
+                None if state.selected + 1 < state.rows().len() => state.selected += 1,
+                None => {}
+            }
+        }
+    }
+
+    pub fn previous_bridge_row(&mut self) {
+        if let Some(state) = &mut self.bridges {
+            if state.fdb.is_some() {
+                state.fdb_scroll = state.fdb_scroll.saturating_sub(1);
+            } else {
+                state.selected = state.selected.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Switch between the ports and the forwarding database of the
+    /// selected bridge
+    pub fn toggle_fdb(&mut self) -> Result<()> {
+        let Some(state) = &mut self.bridges else {
+            return Ok(());
+        };
+        if state.fdb.take().is_some() {
+            return Ok(());
+        }
+        if let Some(bridge) = state.selected_bridge() {
+            state.fdb = Some(bridge::get_fdb(&bridge.name)?);
+            state.fdb_scroll = 0;
+        }
+        Ok(())
+    }
+
+    pub fn start_create_bridge(&mut self) {
+        if let Some(state) = &mut self.bridges {
+            state.name_buffer.clear();
+            state.stp = false;
+            state.current_field = 0;
+            state.form_error = None;
+            self.mode = AppMode::CreateBridge;
+        }
+    }
+
+    pub fn prepare_create_bridge(&mut self) {
+        let Some(state) = &mut self.bridges else {
+            return;
+        };
+        let name = match validation::check_new_link_name(&state.name_buffer, "br", &self.interfaces)
+        {
+            Ok(name) => name,
+            Err(e) => {
+                state.form_error = Some(e);
+                return;
+            }
+        };
+        state.form_error = None;
+
+        self.confirm_message = format!(
+            "Create bridge '{}' with STP {}?",
+            name,
+            if state.stp { "on" } else { "off" }
+        );
+        self.confirm_action = Some(ConfirmAction::CreateBridge(name, state.stp));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn start_add_bridge_port(&mut self) {
+        let Some(bridge) = self.bridges.as_ref().and_then(|s| s.selected_bridge()) else {
+            return;
+        };
+        self.start_add_port(bridge.name.clone());
+    }
+
+    pub fn prepare_remove_bridge_port(&mut self) {
+        let Some((bridge, port)) = self.bridges.as_ref().and_then(|s| s.selected_port()) else {
+            self.set_status("Select a port to remove".to_string());
+            return;
+        };
+
+        self.confirm_message = format!("Remove port '{}' from {}?", port.name, bridge.name);
+        if let Some(tool) = bridge.managed_by {
+            self.confirm_message.push_str(&format!(
+                "\n{} manages {} and may undo this.",
+                tool, bridge.name
+            ));
+        }
+        self.confirm_action = Some(ConfirmAction::ReleaseInterface(
+            bridge.name.clone(),
+            port.name.clone(),
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_toggle_stp(&mut self) {
+        let Some(bridge) = self.bridges.as_ref().and_then(|s| s.selected_bridge()) else {
+            return;
+        };
+
+        let enable = !bridge.stp;
+        self.confirm_message = format!(
+            "{} STP on {}?",
+            if enable { "Enable" } else { "Disable" },
+            bridge.name
+        );
+        if enable {
+            self.confirm_message
+                .push_str("\nPorts stop forwarding for the listening and learning delays.");
+        }
+        if let Some(tool) = bridge.managed_by {
+            self.confirm_message.push_str(&format!(
+                "\n{} manages {} and may undo this.",
+                tool, bridge.name
+            ));
+        }
+        self.confirm_action = Some(ConfirmAction::SetBridgeStp(bridge.name.clone(), enable));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn prepare_delete_bridge(&mut self) {
+        let Some(bridge) = self.bridges.as_ref().and_then(|s| s.selected_bridge()) else {
+            return;
+        };
+
+        self.confirm_message = format!("Delete bridge '{}'?", bridge.name);
+        if !bridge.ports.is_empty() {
+            self.confirm_message.push_str(&format!(
+                "\nIts ports are released: {}",
+                bridge
+                    .ports
+                    .iter()
+                    .map(|port| port.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if let Some(tool) = bridge.managed_by {
+            self.confirm_message.push_str(&format!(
+                "\n{} created {} and will fail until it is recreated.",
+                tool, bridge.name
+            ));
+        }
+        self.confirm_action = Some(ConfirmAction::DeleteBridge(bridge.name.clone()));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn flush_dns_cache(&mut self) -> Result<()> {
+        self.confirm_action = Some(ConfirmAction::FlushDnsCache);
+        self.execute_confirmed_action()
+    }
+
+    /// The details screen's interface, if it is a WireGuard one
+    fn selected_wireguard(&mut self) -> Option<&NetworkInterface> {
+        let iface = self.get_selected_interface()?;
+        if iface.interface_type != InterfaceType::WireGuard {
+            let message = format!("{} is not a WireGuard interface", iface.name);
+            self.set_status(message);
+            return None;
+        }
+        self.get_selected_interface()
+    }
+
+    /// Peers are only known once read; without root that takes the helper
+    fn wireguard_peers_known(&mut self) -> Result<bool> {
+        let Some(iface) = self.selected_wireguard() else {
+            return Ok(false);
+        };
+        if iface.wireguard.is_some() {
+            return Ok(true);
+        }
+        self.confirm_action = Some(ConfirmAction::ReadWireGuard(iface.name.clone()));
+        self.execute_confirmed_action()?;
+        Ok(false)
+    }
+
+    fn wireguard_state(&mut self, interface: &str) -> &mut WireGuardState {
+        if self
+            .wireguard
+            .as_ref()
+            .is_none_or(|state| state.interface != interface)
+        {
+            self.wireguard = Some(WireGuardState::new(interface.to_string()));
+        }
+        self.wireguard.get_or_insert_with(Default::default)
+    }
+
+    pub fn start_add_wireguard_peer(&mut self) -> Result<()> {
+        if !self.wireguard_peers_known()? {
+            return Ok(());
+        }
+        let Some(name) = self
+            .get_selected_interface()
+            .map(|iface| iface.name.clone())
+        else {
+            return Ok(());
+        };
+
+        let state = self.wireguard_state(&name);
+        state.public_key_buffer.clear();
+        state.allowed_ips_buffer.clear();
+        state.endpoint_buffer.clear();
+        state.keepalive_buffer.clear();
+        state.current_field = 0;
+        state.form_error = None;
+        self.mode = AppMode::AddWireGuardPeer;
+        Ok(())
+    }
+
+    pub fn next_wireguard_field(&mut self) {
+        if let Some(state) = &mut self.wireguard {
+            state.current_field = (state.current_field + 1) % 4;
+        }
+    }
+
+    pub fn previous_wireguard_field(&mut self) {
+        if let Some(state) = &mut self.wireguard {
+            state.current_field = (state.current_field + 3) % 4;
+        }
+    }
+
+    pub fn wireguard_buffer(&mut self) -> Option<&mut String> {
+        let state = self.wireguard.as_mut()?;
+        state.form_error = None;
+        Some(match state.current_field {
+            0 => &mut state.public_key_buffer,
+            1 => &mut state.allowed_ips_buffer,
+            2 => &mut state.endpoint_buffer,
+            _ => &mut state.keepalive_buffer,
+        })
+    }
+
+    pub fn prepare_add_wireguard_peer(&mut self) {
+        let Some(state) = &self.wireguard else {
+            return;
+        };
+        let Some(device) = self
+            .interfaces
+            .iter()
+            .find(|iface| iface.name == state.interface)
+            .and_then(|iface| iface.wireguard.as_ref())
+        else {
+            return;
+        };
+
+        let peer = match validation::check_wireguard_peer_form(
+            &state.public_key_buffer,
+            &state.allowed_ips_buffer,
+            &state.endpoint_buffer,
+            &state.keepalive_buffer,
+            device,
+        ) {
+            Ok(peer) => peer,
+            Err(e) => {
+                if let Some(state) = &mut self.wireguard {
+                    state.form_error = Some(e);
+                }
+                return;
+            }
+        };
+
+        let mut message = format!("Add peer {} to '{}'?", peer.public_key, state.interface);
+        if peer.allowed_ips.is_empty() {
+            message.push_str("\nNo allowed IPs: no traffic will be routed to it.");
+        } else {
+            let allowed: Vec<String> = peer.allowed_ips.iter().map(|a| a.to_string()).collect();
+            message.push_str(&format!("\nAllowed IPs: {}", allowed.join(", ")));
+        }
+        if let Some(endpoint) = peer.endpoint {
+            message.push_str(&format!("\nEndpoint: {}", endpoint));
+        }
+        if let Some(keepalive) = peer.persistent_keepalive {
+            message.push_str(&format!("\nKeepalive every {}s", keepalive));
+        }
+        // Another peer's allowed IPs move over to the new one
+        let taken: Vec<String> = device
+            .peers
+            .iter()
+            .flat_map(|other| &other.allowed_ips)
+            .filter(|allowed| peer.allowed_ips.contains(allowed))
+            .map(|allowed| allowed.to_string())
+            .collect();
+        if !taken.is_empty() {
+            message.push_str(&format!(
+                "\n{} will be taken from the peer that routes it now.",
+                taken.join(", ")
+            ));
+        }
+
+        self.confirm_message = message;
+        self.confirm_action = Some(ConfirmAction::AddWireGuardPeer(
+            state.interface.clone(),
+            peer,
+        ));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    pub fn start_remove_wireguard_peer(&mut self) -> Result<()> {
+        if !self.wireguard_peers_known()? {
+            return Ok(());
+        }
+        let Some(iface) = self.get_selected_interface() else {
+            return Ok(());
+        };
+        let name = iface.name.clone();
+        if iface
+            .wireguard
+            .as_ref()
+            .is_some_and(|wg| wg.peers.is_empty())
+        {
+            self.set_status(format!("{} has no peers", name));
+            return Ok(());
+        }
+
+        self.wireguard_state(&name).peer_index = 0;
+        self.mode = AppMode::RemoveWireGuardPeer;
+        Ok(())
+    }
+
+    /// Peers of the interface the WireGuard forms are for
+    pub fn wireguard_peers(&self) -> &[crate::models::WireGuardPeer] {
+        self.wireguard
+            .as_ref()
+            .and_then(|state| self.interfaces.iter().find(|i| i.name == state.interface))
+            .and_then(|iface| iface.wireguard.as_ref())
+            .map(|wg| wg.peers.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn next_wireguard_peer(&mut self) {
+        let count = self.wireguard_peers().len();
+        if let Some(state) = &mut self.wireguard {
+            if count > 0 {
+                state.peer_index = (state.peer_index + 1) % count;
+            }
+        }
+    }
+
+    pub fn previous_wireguard_peer(&mut self) {
+        let count = self.wireguard_peers().len();
+        if let Some(state) = &mut self.wireguard {
+            if count > 0 {
+                state.peer_index = (state.peer_index + count - 1) % count;
+            }
+        }
+    }
+
+    pub fn prepare_remove_wireguard_peer(&mut self) {
+        let Some(state) = &self.wireguard else {
+            return;
+        };
+        let Some(peer) = self.wireguard_peers().get(state.peer_index) else {
+            return;
+        };
+
+        let mut message = format!(
+            "Remove peer {} from '{}'?",
+            peer.public_key, state.interface
+        );
+        if !peer.allowed_ips.is_empty() {
+            let allowed: Vec<String> = peer.allowed_ips.iter().map(|a| a.to_string()).collect();
+            message.push_str(&format!(
+                "\nTraffic to {} will no longer be routed to it.",
+                allowed.join(", ")
+            ));
+        }
+        if peer.has_preshared_key {
+            message.push_str("\nIts preshared key can't be restored by undo.");
+        }
+        let action =
+            ConfirmAction::RemoveWireGuardPeer(state.interface.clone(), peer.public_key.clone());
+
+        self.confirm_message = message;
+        self.confirm_action = Some(action);
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    /// Nothing is installed; the keys are only shown to be copied
+    pub fn generate_wireguard_keys(&mut self) {
+        let Some(name) = self.selected_wireguard().map(|iface| iface.name.clone()) else {
+            return;
+        };
+        self.wireguard_state(&name).keypair = Some(wireguard::generate_keypair());
+        self.mode = AppMode::WireGuardKeys;
+    }
+
+    pub fn copy_wireguard_public_key(&mut self) {
+        let Some((_, public)) = self.wireguard.as_ref().and_then(|s| s.keypair.as_ref()) else {
+            return;
+        };
+        let result = self.copy_to_clipboard(public);
+        match result {
+            Ok(()) => self.set_status("Copied public key to clipboard".to_string()),
+            Err(e) => self.set_status(format!("Failed to copy: {}", e)),
+        }
+    }
+
+    /// Back to the details screen from a WireGuard form or the keys dialog
+    pub fn close_wireguard_dialog(&mut self) {
+        if let Some(state) = &mut self.wireguard {
+            state.keypair = None;
+            state.form_error = None;
+        }
+        self.mode = AppMode::Details;
+    }
+
+    pub fn close_details(&mut self) {
+        self.wireguard = None;
+        self.mode = AppMode::Normal;
+    }
+
+    /// List the offloads of the details screen's interface
+    pub fn open_offloads(&mut self) {
+        let Some(iface) = self.get_selected_interface() else {
+            return;
+        };
+        let name = iface.name.clone();
+        if iface
+            .ethtool
+            .as_ref()
+            .is_none_or(|ethtool| ethtool.offloads.is_empty())
+        {
+            self.set_status(format!("{} reports no offloads", name));
+            return;
+        }
+
+        self.offload_picker = Some(OffloadPicker {
+            interface: name,
+            index: 0,
+        });
+        self.mode = AppMode::Offloads;
+    }
+
+    pub fn close_offloads(&mut self) {
+        self.offload_picker = None;
+        self.mode = AppMode::Details;
+    }
+
+    /// Offloads of the interface the list is open for, as last read
+    pub fn offloads(&self) -> &[Offload] {
+        self.offload_picker
+            .as_ref()
+            .and_then(|picker| {
+                self.interfaces
+                    .iter()
+                    .find(|iface| iface.name == picker.interface)
+            })
+            .and_then(|iface| iface.ethtool.as_ref())
+            .map(|ethtool| ethtool.offloads.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn next_offload(&mut self) {
+        let count = self.offloads().len();
+        if let Some(picker) = &mut self.offload_picker {
+            if picker.index + 1 < count {
+                picker.index += 1;
+            }
+        }
+    }
+
+    pub fn previous_offload(&mut self) {
+        if let Some(picker) = &mut self.offload_picker {
+            picker.index = picker.index.saturating_sub(1);
+        }
+    }
+
+    pub fn prepare_toggle_offload(&mut self) {
+        let Some(picker) = &self.offload_picker else {
+            return;
+        };
+        let interface = picker.interface.clone();
+        let Some(offload) = self.offloads().get(picker.index).cloned() else {
+            return;
+        };
+        if offload.fixed {
+            self.set_status(format!(
+                "The {} driver doesn't let {} be changed",
+                interface, offload.name
+            ));
+            return;
+        }
+
+        let enable = !offload.enabled;
+        self.confirm_message = format!(
+            "Turn {} ({}) {} on {}?",
+            offload.label(),
+            offload.name,
+            if enable { "on" } else { "off" },
+            interface
+        );
+        if !enable {
+            self.confirm_message
+                .push_str("\nOffloads that depend on it are turned off with it.");
+        }
+        self.confirm_action = Some(ConfirmAction::SetOffload(interface, offload.name, enable));
+        self.mode = AppMode::ConfirmDialog;
+    }
+
+    /// Read every counter of the details screen's interface
+    pub fn open_statistics(&mut self) -> Result<()> {
+        let Some(iface) = self.get_selected_interface() else {
+            return Ok(());
+        };
+        let interface = iface.name.clone();
+        let current = network::get_statistics(&interface)?;
+
+        self.statistics = Some(StatisticsView::new(interface, current));
+        self.mode = AppMode::Statistics;
+        Ok(())
+    }
+
+    /// Read the counters again; what they were becomes the baseline for
+    /// the deltas
+    pub fn refresh_statistics(&mut self) -> Result<()> {
+        let Some(view) = &mut self.statistics else {
+            return Ok(());
+        };
+        view.update(network::get_statistics(&view.interface)?);
+        Ok(())
+    }
+
+    pub fn close_statistics(&mut self) {
+        self.statistics = None;
+        self.mode = AppMode::Details;
+    }
+
+    pub fn scroll_statistics(&mut self, lines: isize) {
+        if let Some(view) = &mut self.statistics {
+            let rows = view.link_rows().len() + view.driver_rows().len();
+            view.scroll = view
+                .scroll
+                .saturating_add_signed(lines)
+                .min(rows.saturating_sub(1));
+        }
+    }
+
+    pub fn toggle_zero_counters(&mut self) {
+        if let Some(view) = &mut self.statistics {
+            view.hide_zero = !view.hide_zero;
+            view.scroll = 0;
+        }
+    }
+
+    /// Open the calculator on the selected interface's first routable
+    /// address, or empty when it has none
+    pub fn open_subnet_calculator(&mut self) {
+        let network = self.get_selected_interface().and_then(|iface| {
+            let mut addresses = iface
+                .ip_addresses
+                .iter()
+                .filter(|addr| !addr.is_link_local());
+            addresses.next()?.network()
+        });
+
+        self.subnet_calculator = Some(SubnetCalculator {
+            network_buffer: network
+                .map(|network| network.to_string())
+                .unwrap_or_default(),
+            split_buffer: String::new(),
+            summarize_buffer: String::new(),
+            current_field: 0,
+            scroll: 0,
+            return_to: self.mode,
+        });
+        self.mode = AppMode::SubnetCalculator;
+    }
+
+    pub fn close_subnet_calculator(&mut self) {
+        if let Some(calculator) = self.subnet_calculator.take() {
+            self.mode = calculator.return_to;
+        }
+    }
+
+    pub fn next_calculator_field(&mut self) {
+        if let Some(calculator) = &mut self.subnet_calculator {
+            calculator.current_field = (calculator.current_field + 1) % 3;
+        }
+    }
+
+    pub fn previous_calculator_field(&mut self) {
+        if let Some(calculator) = &mut self.subnet_calculator {
+            calculator.current_field = (calculator.current_field + 2) % 3;
+        }
+    }
+
+    /// The field being typed into; the results scroll back to the top
+    pub fn calculator_buffer(&mut self) -> Option<&mut String> {
+        let calculator = self.subnet_calculator.as_mut()?;
+        calculator.scroll = 0;
+        Some(match calculator.current_field {
+            0 => &mut calculator.network_buffer,
+            1 => &mut calculator.split_buffer,
+            _ => &mut calculator.summarize_buffer,
+        })
+    }
+
+    pub fn scroll_calculator(&mut self, lines: isize) {
+        if let Some(calculator) = &mut self.subnet_calculator {
+            let last = calculator.split_count().saturating_sub(1);
+            calculator.scroll = calculator
+                .scroll
+                .saturating_add_signed(lines)
+                .min(last.try_into().unwrap_or(usize::MAX));
+        }
+    }
+}
+
+/// Routes can't always be read (macOS only shows the default ones), in
+/// which case only the addresses are compared
+fn find_conflicts(interfaces: &[NetworkInterface]) -> Vec<Conflict> {
+    let routes = network::get_routes().unwrap_or_default();
+    conflicts::detect(interfaces, &routes)
+}
+
+fn conflicts_warning(conflicts: &[Conflict]) -> Option<String> {
+    let first = conflicts.first()?;
+    Some(match conflicts.len() {
+        1 => format!("⚠ {} (O: report)", first.summary),
+        count => format!("⚠ {} (+{} more, O: report)", first.summary, count - 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::LinkCounters;
+
+    fn read(rx_bytes: u64, rx_errors: u64, driver: &[(&str, u64)]) -> InterfaceStatistics {
+        InterfaceStatistics {
+            counters: LinkCounters {
+                rx_bytes,
+                rx_errors,
+                ..Default::default()
+            },
+            driver: driver
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+        }
+    }
+
+    fn delta(rows: &[CounterRow], name: &str) -> Option<u64> {
+        rows.iter().find(|row| row.name == name).unwrap().delta
+    }
+
+    #[test]
+    fn missing_values_sort_last_both_ways() {
+        let mut speeds = vec![Some(100), None, Some(10_000), Some(1000), None];
+        let sorted = |speeds: &mut Vec<Option<u32>>, ascending: bool| {
+            let ordered = |cmp: Ordering| if ascending { cmp } else { cmp.reverse() };
+            speeds.sort_by(|a, b| nones_last(a, b, ordered));
+            speeds.clone()
+        };
+
+        assert_eq!(
+            sorted(&mut speeds, true),
+            [Some(100), Some(1000), Some(10_000), None, None]
+        );
+        assert_eq!(
+            sorted(&mut speeds, false),
+            [Some(10_000), Some(1000), Some(100), None, None]
+        );
+    }
+
+    #[test]
+    fn first_read_has_no_deltas() {
+        let view = StatisticsView::new(
+            "eth0".to_string(),
+            read(1000, 2, &[("rx_queue_0_packets", 5)]),
+        );
+
+        assert_eq!(view.interval, None);
+        assert!(view.link_rows().iter().all(|row| row.delta.is_none()));
+        assert!(view.driver_rows().iter().all(|row| row.delta.is_none()));
+    }
+
+    #[test]
+    fn deltas_between_reads() {
+        let mut view = StatisticsView::new(
+            "eth0".to_string(),
+            read(1000, 2, &[("rx_queue_0_packets", 5), ("rx_csum_errors", 1)]),
+        );
+        view.update(read(
+            4096,
+            2,
+            &[("rx_queue_0_packets", 9), ("rx_csum_errors", 1)],
+        ));
+
+        assert!(view.interval.is_some());
+        let link = view.link_rows();
+        assert_eq!(link.len(), LinkCounters::default().named().len());
+        assert_eq!(delta(&link, "rx_bytes"), Some(3096));
+        assert_eq!(delta(&link, "rx_errors"), Some(0));
+        assert_eq!(delta(&link, "tx_bytes"), Some(0));
+
+        let driver = view.driver_rows();
+        assert_eq!(delta(&driver, "rx_queue_0_packets"), Some(4));
+        assert_eq!(delta(&driver, "rx_csum_errors"), Some(0));
+
+        // Always against the read just before
+        view.update(read(
+            5000,
+            3,
+            &[("rx_queue_0_packets", 9), ("rx_csum_errors", 1)],
+        ));
+        assert_eq!(delta(&view.link_rows(), "rx_bytes"), Some(904));
+        assert_eq!(delta(&view.link_rows(), "rx_errors"), Some(1));
+    }
+
+    #[test]
+    fn counters_going_backwards_have_no_delta() {
+        // A driver reset, and a 32-bit driver counter wrapping around
+        let mut view = StatisticsView::new(
+            "eth0".to_string(),
+            read(1_000_000, 0, &[("tx_packets_phy", u32::MAX as u64 - 10)]),
+        );
+        view.update(read(1200, 0, &[("tx_packets_phy", 20)]));
+
+        assert_eq!(delta(&view.link_rows(), "rx_bytes"), None);
+        assert_eq!(delta(&view.driver_rows(), "tx_packets_phy"), None);
+
+        // Counting on from there
+        view.update(read(1500, 0, &[("tx_packets_phy", 25)]));
+        assert_eq!(delta(&view.link_rows(), "rx_bytes"), Some(300));
+        assert_eq!(delta(&view.driver_rows(), "tx_packets_phy"), Some(5));
+    }
+
+    #[test]
+    fn driver_counters_come_and_go() {
+        let mut view = StatisticsView::new("eth0".to_string(), read(0, 0, &[("old", 7)]));
+        view.update(read(0, 0, &[("new", 3)]));
+
+        let driver = view.driver_rows();
+        assert_eq!(driver.len(), 1);
+        assert_eq!(delta(&driver, "new"), None);
+    }
+
+    #[test]
+    fn zero_counters_can_be_hidden() {
+        let mut view = StatisticsView::new("eth0".to_string(), read(10, 0, &[("a", 0), ("b", 1)]));
+        view.hide_zero = true;
+
+        let names: Vec<_> = view
+            .link_rows()
+            .into_iter()
+            .chain(view.driver_rows())
+            .map(|row| row.name)
+            .collect();
+        assert_eq!(names, ["rx_bytes", "b"]);
+    }
 }
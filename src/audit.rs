@@ -0,0 +1,380 @@
+use crate::app::ConfirmAction;
+use crate::bond;
+use crate::bridge;
+use crate::helper::CommandRecord;
+use crate::models::{BondMode, DnsConfiguration, InterfaceType, NetworkInterface};
+use crate::namespace;
+use crate::network;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// One executed configuration change, stored as a line of JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user: String,
+    pub host: String,
+    pub interface: Option<String>,
+    /// Network namespace the action ran in, when not our own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    pub action: ConfirmAction,
+    /// `None` when the state couldn't be read before the action ran
+    pub before: Option<StateSnapshot>,
+    pub after: Option<StateSnapshot>,
+    pub commands: Vec<CommandRecord>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Configuration relevant to an action, captured around its execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub interface: Option<NetworkInterface>,
+    /// Default gateways through `interface`
+    #[serde(default)]
+    pub gateways: Vec<IpAddr>,
+    pub dns: DnsConfiguration,
+    /// Set when `interface` is a bond run by the bonding driver, in our own
+    /// namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bond: Option<BondSnapshot>,
+    /// Set when `interface` is a bridge, in our own namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridge: Option<BridgeSnapshot>,
+}
+
+/// What it takes to create a deleted bond again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BondSnapshot {
+    pub mode: BondMode,
+    pub miimon: Option<u32>,
+    pub slaves: Vec<String>,
+}
+
+/// What it takes to create a deleted bridge again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeSnapshot {
+    pub stp: bool,
+    pub ports: Vec<String>,
+}
+
+impl StateSnapshot {
+    /// Read the live state of `interface` (if any) and the DNS configuration
+    pub fn capture(interface: Option<&str>) -> Result<Self> {
+        let mut snapshot = Self {
+            interface: None,
+            gateways: Vec::new(),
+            dns: network::get_dns_configuration()?,
+            bond: None,
+            bridge: None,
+        };
+        let Some(name) = interface else {
+            return Ok(snapshot);
+        };
+
+        let interfaces = network::get_network_interfaces()?;
+        snapshot.interface = interfaces.iter().find(|iface| iface.name == name).cloned();
+        snapshot.gateways = network::get_default_gateways(name)?;
+
+        // Only read the bonding and bridge state for the kinds that have it.
+        // Both come from procfs and sysfs, which show our own namespace
+        // whichever one is selected, so they're left out there.
+        let own_namespace = namespace::selected().is_none() && namespace::entered().is_none();
+        match snapshot
+            .interface
+            .as_ref()
+            .map(|iface| iface.interface_type)
+            .filter(|_| own_namespace)
+        {
+            Some(InterfaceType::Bond) => {
+                snapshot.bond = bond::get_bonds(&interfaces)
+                    .into_iter()
+                    .find(|bond| bond.name == name)
+                    .and_then(|bond| {
+                        Some(BondSnapshot {
+                            mode: bond.mode?,
+                            miimon: bond.miimon,
+                            slaves: bond.slaves.into_iter().map(|slave| slave.name).collect(),
+                        })
+                    });
+            }
+            Some(InterfaceType::Bridge) => {
+                snapshot.bridge = bridge::get_bridges(&interfaces)
+                    .into_iter()
+                    .find(|bridge| bridge.name == name)
+                    .map(|bridge| BridgeSnapshot {
+                        stp: bridge.stp,
+                        ports: bridge.ports.into_iter().map(|port| port.name).collect(),
+                    });
+            }
+            _ => {}
+        }
+
+        Ok(snapshot)
+    }
+}
+
+impl AuditEntry {
+    pub fn new(
+        action: ConfirmAction,
+        before: Option<StateSnapshot>,
+        after: Option<StateSnapshot>,
+        commands: Vec<CommandRecord>,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            user: current_user(),
+            host: hostname(),
+            interface: action.interface().map(|name| name.to_string()),
+            namespace: namespace::selected().map(|selected| selected.label()),
+            action,
+            before,
+            after,
+            commands,
+            success: error.is_none(),
+            error,
+        }
+    }
+}
+
+/// Default log location, `~/.local/state/netutil/audit.jsonl`
+pub fn default_log_path() -> PathBuf {
+    let base = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })
+        .unwrap_or_else(std::env::temp_dir);
+
+    base.join("netutil").join("audit.jsonl")
+}
+
+/// Append an entry to the log, creating the file and its directory as needed
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+
+    // A single write keeps concurrent appends from interleaving
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+    file.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+/// Read all entries, newest first. Lines that don't parse are skipped.
+pub fn load(path: &Path) -> Result<Vec<AuditEntry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open audit log {}", path.display()))
+        }
+    };
+
+    let mut entries: Vec<AuditEntry> = BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+
+    entries.reverse();
+    Ok(entries)
+}
+
+fn current_user() -> String {
+    nix::unistd::User::from_uid(nix::unistd::getuid())
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| format!("uid {}", nix::unistd::getuid()))
+}
+
+fn hostname() -> String {
+    nix::unistd::gethostname()
+        .ok()
+        .and_then(|name| name.into_string().ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InterfaceAddress, MacAddr};
+    use serde_json::json;
+
+    fn snapshot(address: &str) -> StateSnapshot {
+        let mut iface = network::new_interface("eth0", false);
+        iface.mac_address = Some(MacAddr([0x02, 0, 0, 0, 0, 0x01]));
+        iface.ip_addresses.push(InterfaceAddress {
+            ip: address.parse().unwrap(),
+            prefix: Some(24),
+            broadcast: None,
+            label: None,
+            secondary: false,
+            ipv6_info: None,
+        });
+        StateSnapshot {
+            interface: Some(iface),
+            gateways: vec!["192.0.2.1".parse().unwrap()],
+            dns: DnsConfiguration {
+                nameservers: vec!["192.0.2.53".parse().unwrap()],
+                search_domains: vec!["example.com".to_string()],
+            },
+            bond: None,
+            bridge: None,
+        }
+    }
+
+    fn entry(up: bool, error: Option<&str>) -> AuditEntry {
+        AuditEntry::new(
+            ConfirmAction::ToggleInterface("eth0".to_string(), up),
+            Some(snapshot("192.0.2.10")),
+            Some(snapshot("192.0.2.11")),
+            vec![CommandRecord {
+                program: "ip".to_string(),
+                args: vec!["link".to_string(), "set".to_string(), "eth0".to_string()],
+                exit_status: Some(0),
+                stderr: String::new(),
+            }],
+            error.map(|error| error.to_string()),
+        )
+    }
+
+    fn log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "netutil-audit-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("nested").join("audit.jsonl")
+    }
+
+    #[test]
+    fn entry_format() {
+        let value = serde_json::to_value(entry(false, Some("ip: exit status 2"))).unwrap();
+
+        assert_eq!(value["interface"], "eth0");
+        assert_eq!(value["action"], json!({"ToggleInterface": ["eth0", false]}));
+        assert_eq!(value["success"], false);
+        assert_eq!(value["error"], "ip: exit status 2");
+        assert_eq!(value["commands"][0]["program"], "ip");
+        assert_eq!(value["commands"][0]["exit_status"], 0);
+        assert_eq!(value["before"]["gateways"], json!(["192.0.2.1"]));
+        assert_eq!(
+            value["before"]["interface"]["ip_addresses"][0]["prefix"],
+            24
+        );
+        assert_eq!(
+            value["before"]["interface"]["mac_address"],
+            "02:00:00:00:00:01"
+        );
+        assert_eq!(
+            value["after"]["interface"]["ip_addresses"][0]["ip"],
+            "192.0.2.11"
+        );
+        // Unset optional parts are left out rather than written as null
+        assert!(value.get("namespace").is_none());
+        assert!(value["before"].get("bond").is_none());
+        assert!(value["before"].get("bridge").is_none());
+        assert!(DateTime::parse_from_rfc3339(value["timestamp"].as_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn entry_round_trip() {
+        let entry = entry(true, None);
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(!line.contains('\n'));
+
+        let read: AuditEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&entry).unwrap()
+        );
+        assert!(read.success);
+        assert_eq!(read.timestamp, entry.timestamp);
+    }
+
+    #[test]
+    fn append_and_load() {
+        let path = log_path("append");
+        assert!(load(&path).unwrap().is_empty());
+
+        append(&path, &entry(false, None)).unwrap();
+        append(&path, &entry(true, Some("failed"))).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.ends_with('\n'));
+
+        // Newest first
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(
+            entries[0].action,
+            ConfirmAction::ToggleInterface(_, true)
+        ));
+        assert_eq!(entries[0].error.as_deref(), Some("failed"));
+        assert!(matches!(
+            entries[1].action,
+            ConfirmAction::ToggleInterface(_, false)
+        ));
+        assert!(entries[1].success);
+
+        std::fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_skips_lines_that_dont_parse() {
+        let path = log_path("skip");
+        append(&path, &entry(false, None)).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"not json\n{\"timestamp\":\"half a line\n\n")
+            .unwrap();
+        append(&path, &entry(true, None)).unwrap();
+
+        let entries = load(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(
+            entries[0].action,
+            ConfirmAction::ToggleInterface(_, true)
+        ));
+
+        std::fs::remove_dir_all(path.parent().unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn reads_older_entries() {
+        // Before the prefix length and optional snapshots: a netmask, "N/A"
+        // for a missing MAC and no gateways
+        let line = r#"{"timestamp":"2026-01-02T03:04:05Z","user":"root","host":"box","interface":"eth0","action":{"SetDhcp":"eth0"},"before":{"interface":{"name":"eth0","interface_type":"Ethernet","ip_addresses":[{"ip":"10.0.0.5","netmask":"255.255.255.0","broadcast":null}],"mac_address":"N/A","is_up":true,"is_loopback":false,"mtu":1500,"ipv6_enabled":true,"ssid":null},"dns":{"nameservers":[],"search_domains":[]}},"after":null,"commands":[],"success":true,"error":null}"#;
+
+        let entry: AuditEntry = serde_json::from_str(line).unwrap();
+        let before = entry.before.unwrap();
+        let iface = before.interface.unwrap();
+        assert_eq!(iface.ip_addresses[0].prefix, Some(24));
+        assert_eq!(iface.mac_address, None);
+        assert!(before.gateways.is_empty());
+        assert!(entry.after.is_none());
+        assert!(matches!(entry.action, ConfirmAction::SetDhcp(ref name) if name == "eth0"));
+    }
+}
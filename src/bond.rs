@@ -0,0 +1,429 @@
+//! Bond and team devices with the state of their slaves.
+//!
+//! On Linux the bonding driver reports everything in `/proc/net/bonding/<bond>`;
+//! the mode comes from sysfs, which has the kernel's short name for it. Team
+//! devices are run by teamd, so only their ports and link state are known.
+
+use crate::models::{BondMode, InterfaceType, NetworkInterface};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BondInfo {
+    pub name: String,
+    /// Driven by teamd rather than the bonding driver
+    pub team: bool,
+    pub mode: Option<BondMode>,
+    pub mii_status: Option<String>,
+    /// Link monitoring interval in ms; 0 turns it off
+    pub miimon: Option<u32>,
+    pub active_slave: Option<String>,
+    /// Only in 802.3ad mode
+    pub lacp: Option<LacpInfo>,
+    pub slaves: Vec<BondSlave>,
+}
+
+impl BondInfo {
+    pub fn mode_name(&self) -> &'static str {
+        match self.mode {
+            Some(mode) => mode.as_str(),
+            None if self.team => "team",
+            None => "unknown",
+        }
+    }
+
+    /// Whether `slave` carries traffic: the active slave in failover modes,
+    /// a member of the active aggregator in 802.3ad and any slave otherwise
+    pub fn is_active(&self, slave: &BondSlave) -> bool {
+        match (self.mode, &self.lacp) {
+            (Some(mode), _) if mode.has_active_slave() => {
+                self.active_slave.as_deref() == Some(&slave.name)
+            }
+            (Some(BondMode::Ieee8023ad), Some(lacp)) => {
+                lacp.aggregator_id.is_some() && slave.aggregator_id == lacp.aggregator_id
+            }
+            _ => slave.is_up(),
+        }
+    }
+}
+
+/// 802.3ad state of the active aggregator
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LacpInfo {
+    /// `slow` (every 30s) or `fast` (every second)
+    pub rate: Option<String>,
+    pub aggregator_id: Option<u32>,
+    pub ports: Option<u32>,
+    pub partner_mac: Option<String>,
+}
+
+impl LacpInfo {
+    /// The switch answered; without a partner the MAC stays all zeros
+    pub fn has_partner(&self) -> bool {
+        self.partner_mac
+            .as_deref()
+            .is_some_and(|mac| mac.chars().any(|c| c.is_ascii_hexdigit() && c != '0'))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BondSlave {
+    pub name: String,
+    /// `up` or `down`, as the bond's link monitoring sees it
+    pub mii_status: Option<String>,
+    pub speed: Option<String>,
+    pub duplex: Option<String>,
+    pub link_failures: u32,
+    pub permanent_mac: Option<String>,
+    pub aggregator_id: Option<u32>,
+}
+
+impl BondSlave {
+    pub fn is_up(&self) -> bool {
+        self.mii_status.as_deref() == Some("up")
+    }
+}
+
+/// Every bond and team among `interfaces`. Bonds whose driver status can't
+/// be read are listed with what the interface list knows.
+pub fn get_bonds(interfaces: &[NetworkInterface]) -> Vec<BondInfo> {
+    interfaces
+        .iter()
+        .filter_map(|iface| {
+            let mut bond = match iface.interface_type {
+                InterfaceType::Bond => {
+                    read_bond(&iface.name).unwrap_or_else(|| from_ports(iface, interfaces))
+                }
+                InterfaceType::Team => BondInfo {
+                    team: true,
+                    ..from_ports(iface, interfaces)
+                },
+                _ => return None,
+            };
+            for slave in bond.slaves.iter_mut().filter(|s| s.mii_status.is_none()) {
+                slave.mii_status = interfaces
+                    .iter()
+                    .find(|iface| iface.name == slave.name)
+                    .map(|iface| if iface.is_up { "up" } else { "down" }.to_string());
+            }
+            Some(bond)
+        })
+        .collect()
+}
+
+/// What the interface list alone says: the ports and whether they are up
+fn from_ports(bond: &NetworkInterface, interfaces: &[NetworkInterface]) -> BondInfo {
+    BondInfo {
+        name: bond.name.clone(),
+        mii_status: Some(if bond.is_up { "up" } else { "down" }.to_string()),
+        slaves: interfaces
+            .iter()
+            .filter(|iface| iface.master.as_deref() == Some(bond.name.as_str()))
+            .map(|iface| BondSlave {
+                name: iface.name.clone(),
+                // Slaves take on the bond's address
+                permanent_mac: iface
+                    .permanent_mac
+                    .or(iface.mac_address)
+                    .map(|mac| mac.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_bond(name: &str) -> Option<BondInfo> {
+    let text = std::fs::read_to_string(format!("/proc/net/bonding/{}", name)).ok()?;
+    let mut bond = parse_proc_bonding(name, &text);
+
+    // "active-backup 1"
+    let sysfs_mode = std::fs::read_to_string(format!("/sys/class/net/{}/bonding/mode", name))
+        .ok()
+        .and_then(|mode| BondMode::from_name(mode.split_whitespace().next()?));
+    if sysfs_mode.is_some() {
+        bond.mode = sysfs_mode;
+    }
+
+    Some(bond)
+}
+
+/// Parse the bonding driver's status file: bond settings first, then one
+/// block per slave starting at `Slave Interface:`
+#[cfg(target_os = "linux")]
+fn parse_proc_bonding(name: &str, text: &str) -> BondInfo {
+    let mut bond = BondInfo {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let mut lacp = LacpInfo::default();
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+
+        if key == "Slave Interface" {
+            bond.slaves.push(BondSlave {
+                name: value.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        match bond.slaves.last_mut() {
+            Some(slave) => match key {
+                "MII Status" => slave.mii_status = Some(value.to_string()),
+                "Speed" => slave.speed = Some(value.to_string()),
+                "Duplex" => slave.duplex = Some(value.to_string()),
+                "Link Failure Count" => slave.link_failures = value.parse().unwrap_or(0),
+                "Permanent HW addr" => slave.permanent_mac = Some(value.to_string()),
+                "Aggregator ID" => slave.aggregator_id = value.parse().ok(),
+                _ => {}
+            },
+            None => match key {
+                "Bonding Mode" => bond.mode = mode_from_description(value),
+                "MII Status" => bond.mii_status = Some(value.to_string()),
+                "MII Polling Interval (ms)" => bond.miimon = value.parse().ok(),
+                "Currently Active Slave" if value != "None" => {
+                    bond.active_slave = Some(value.to_string())
+                }
+                "LACP rate" => lacp.rate = Some(value.to_string()),
+                "Aggregator ID" => lacp.aggregator_id = value.parse().ok(),
+                "Number of ports" => lacp.ports = value.parse().ok(),
+                "Partner Mac Address" => lacp.partner_mac = Some(value.to_string()),
+                _ => {}
+            },
+        }
+    }
+
+    if bond.mode == Some(BondMode::Ieee8023ad) {
+        bond.lacp = Some(lacp);
+    }
+    bond
+}
+
+/// The long names in `Bonding Mode:`, e.g. "fault-tolerance (active-backup)"
+#[cfg(target_os = "linux")]
+fn mode_from_description(description: &str) -> Option<BondMode> {
+    Some(match description {
+        d if d.contains("round-robin") => BondMode::BalanceRr,
+        d if d.contains("active-backup") => BondMode::ActiveBackup,
+        d if d.contains("(xor)") => BondMode::BalanceXor,
+        d if d.contains("broadcast") => BondMode::Broadcast,
+        d if d.contains("802.3ad") => BondMode::Ieee8023ad,
+        d if d.starts_with("transmit load balancing") => BondMode::BalanceTlb,
+        d if d.starts_with("adaptive load balancing") => BondMode::BalanceAlb,
+        _ => return None,
+    })
+}
+
+/// macOS bonds are always 802.3ad; `ifconfig` lists their members
+#[cfg(target_os = "macos")]
+fn read_bond(name: &str) -> Option<BondInfo> {
+    let output = std::process::Command::new("ifconfig")
+        .arg(name)
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+
+    let mut bond = BondInfo {
+        name: name.to_string(),
+        mode: Some(BondMode::Ieee8023ad),
+        ..Default::default()
+    };
+    for line in text.lines().map(str::trim) {
+        if let Some(members) = line.strip_prefix("bond interfaces:") {
+            bond.slaves = members
+                .split_whitespace()
+                .map(|member| BondSlave {
+                    name: member.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+        } else if let Some(status) = line.strip_prefix("status:") {
+            bond.mii_status = Some(
+                if status.trim() == "active" {
+                    "up"
+                } else {
+                    "down"
+                }
+                .to_string(),
+            );
+        }
+    }
+    Some(bond)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/bonding")
+            .join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn slave(bond: &BondInfo, name: &str) -> BondSlave {
+        bond.slaves
+            .iter()
+            .find(|slave| slave.name == name)
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn active_backup() {
+        let bond = parse_proc_bonding("bond0", &fixture("active-backup"));
+
+        assert_eq!(bond.name, "bond0");
+        assert_eq!(bond.mode, Some(BondMode::ActiveBackup));
+        assert_eq!(bond.mii_status.as_deref(), Some("up"));
+        assert_eq!(bond.miimon, Some(100));
+        assert_eq!(bond.active_slave.as_deref(), Some("eth1"));
+        assert_eq!(bond.lacp, None);
+        assert_eq!(
+            bond.slaves,
+            [
+                BondSlave {
+                    name: "eth0".to_string(),
+                    mii_status: Some("down".to_string()),
+                    speed: Some("Unknown".to_string()),
+                    duplex: Some("Unknown".to_string()),
+                    link_failures: 3,
+                    permanent_mac: Some("52:54:00:12:34:01".to_string()),
+                    aggregator_id: None,
+                },
+                BondSlave {
+                    name: "eth1".to_string(),
+                    mii_status: Some("up".to_string()),
+                    speed: Some("1000 Mbps".to_string()),
+                    duplex: Some("full".to_string()),
+                    link_failures: 0,
+                    permanent_mac: Some("52:54:00:12:34:02".to_string()),
+                    aggregator_id: None,
+                },
+            ]
+        );
+
+        assert!(!bond.is_active(&slave(&bond, "eth0")));
+        assert!(bond.is_active(&slave(&bond, "eth1")));
+    }
+
+    #[test]
+    fn active_backup_without_active_slave() {
+        let text = fixture("active-backup").replace(
+            "Currently Active Slave: eth1",
+            "Currently Active Slave: None",
+        );
+        let bond = parse_proc_bonding("bond0", &text);
+
+        assert_eq!(bond.active_slave, None);
+        assert!(bond.slaves.iter().all(|slave| !bond.is_active(slave)));
+    }
+
+    #[test]
+    fn ieee8023ad() {
+        let bond = parse_proc_bonding("bond1", &fixture("802.3ad"));
+
+        assert_eq!(bond.mode, Some(BondMode::Ieee8023ad));
+        assert_eq!(bond.active_slave, None);
+        assert_eq!(
+            bond.lacp,
+            Some(LacpInfo {
+                rate: Some("fast".to_string()),
+                aggregator_id: Some(2),
+                ports: Some(2),
+                partner_mac: Some("00:11:22:33:44:55".to_string()),
+            })
+        );
+        assert!(bond.lacp.as_ref().unwrap().has_partner());
+
+        let names: Vec<_> = bond
+            .slaves
+            .iter()
+            .map(|slave| slave.name.as_str())
+            .collect();
+        assert_eq!(names, ["enp1s0", "enp2s0", "enp3s0"]);
+
+        // The per-slave LACPDU details don't leak into the slave or the bond
+        let first = slave(&bond, "enp1s0");
+        assert_eq!(first.aggregator_id, Some(2));
+        assert_eq!(first.link_failures, 1);
+        assert_eq!(first.speed.as_deref(), Some("10000 Mbps"));
+        assert_eq!(first.permanent_mac.as_deref(), Some("52:54:00:aa:bb:01"));
+
+        let stray = slave(&bond, "enp3s0");
+        assert_eq!(stray.aggregator_id, Some(3));
+        assert!(!stray.is_up());
+
+        assert!(bond.is_active(&first));
+        assert!(bond.is_active(&slave(&bond, "enp2s0")));
+        assert!(!bond.is_active(&stray));
+    }
+
+    #[test]
+    fn ieee8023ad_without_partner() {
+        let text = fixture("802.3ad").replace(
+            "\tPartner Mac Address: 00:11:22:33:44:55",
+            "\tPartner Mac Address: 00:00:00:00:00:00",
+        );
+        let bond = parse_proc_bonding("bond1", &text);
+
+        assert!(!bond.lacp.unwrap().has_partner());
+    }
+
+    #[test]
+    fn truncated() {
+        let text = fixture("active-backup");
+        let cut =
+            text.find("Slave Interface: eth1").unwrap() + "Slave Interface: eth1\nMII Sta".len();
+        let bond = parse_proc_bonding("bond0", &text[..cut]);
+
+        assert_eq!(bond.mode, Some(BondMode::ActiveBackup));
+        assert_eq!(bond.slaves.len(), 2);
+        assert!(slave(&bond, "eth0").mii_status.is_some());
+        assert_eq!(slave(&bond, "eth1").mii_status, None);
+        assert!(!slave(&bond, "eth1").is_up());
+
+        // Cut inside the mode line
+        let bond = parse_proc_bonding("bond0", &text[..text.find("Bonding Mode").unwrap() + 10]);
+        assert_eq!(bond.mode, None);
+        assert!(bond.slaves.is_empty());
+
+        assert_eq!(
+            parse_proc_bonding("bond0", ""),
+            BondInfo {
+                name: "bond0".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn mode_descriptions() {
+        for (description, mode) in [
+            ("load balancing (round-robin)", BondMode::BalanceRr),
+            ("fault-tolerance (active-backup)", BondMode::ActiveBackup),
+            ("load balancing (xor)", BondMode::BalanceXor),
+            ("fault-tolerance (broadcast)", BondMode::Broadcast),
+            (
+                "IEEE 802.3ad Dynamic link aggregation",
+                BondMode::Ieee8023ad,
+            ),
+            ("transmit load balancing", BondMode::BalanceTlb),
+            ("adaptive load balancing", BondMode::BalanceAlb),
+        ] {
+            assert_eq!(
+                mode_from_description(description),
+                Some(mode),
+                "{}",
+                description
+            );
+        }
+        assert_eq!(mode_from_description("something new"), None);
+    }
+}
@@ -0,0 +1,412 @@
+//! Bridges, their ports and forwarding databases.
+//!
+//! On Linux the bridge and port settings come from sysfs, VLAN membership
+//! and the forwarding database from iproute2's `bridge -j`. macOS lists
+//! everything in `ifconfig bridgeN`.
+
+use crate::models::{InterfaceType, NetworkInterface};
+use anyhow::Result;
+use std::process::Command;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BridgeInfo {
+    pub name: String,
+    pub up: bool,
+    pub stp: bool,
+    /// priority.MAC, e.g. `8000.525400123456`
+    pub bridge_id: Option<String>,
+    pub root_id: Option<String>,
+    /// Seconds a learned address is kept without traffic from it
+    pub ageing_time: Option<u32>,
+    /// `None` where the kernel has no VLAN filtering support
+    pub vlan_filtering: Option<bool>,
+    pub default_pvid: Option<u16>,
+    /// Tool that created the bridge and may undo changes to it
+    pub managed_by: Option<&'static str>,
+    pub ports: Vec<BridgePort>,
+}
+
+impl BridgeInfo {
+    pub fn is_root(&self) -> bool {
+        self.bridge_id.is_some() && self.bridge_id == self.root_id
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BridgePort {
+    pub name: String,
+    /// STP port state: disabled, listening, learning, forwarding or blocking
+    pub state: Option<String>,
+    /// STP role; only while STP runs
+    pub role: Option<&'static str>,
+    pub path_cost: Option<u32>,
+    pub vlans: Vec<PortVlan>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortVlan {
+    pub id: u16,
+    /// Untagged frames arriving on the port are put into this VLAN
+    pub pvid: bool,
+    /// Sent without a tag
+    pub untagged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdbKind {
+    /// Address of a port of this host
+    Local,
+    Static,
+    /// Learned from traffic
+    Dynamic,
+}
+
+impl FdbKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FdbKind::Local => "local",
+            FdbKind::Static => "static",
+            FdbKind::Dynamic => "learned",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdbEntry {
+    pub mac: String,
+    pub port: String,
+    pub vlan: Option<u16>,
+    pub kind: FdbKind,
+    /// Seconds since traffic from the address was last seen
+    pub updated: Option<u32>,
+}
+
+/// Docker and libvirt create bridges for their networks and put them back
+/// the way they want them
+pub fn managed_by(name: &str) -> Option<&'static str> {
+    let docker_network = name
+        .strip_prefix("br-")
+        .is_some_and(|id| id.len() == 12 && id.chars().all(|c| c.is_ascii_hexdigit()));
+    if name == "docker0" || docker_network {
+        return Some("Docker");
+    }
+
+    let libvirt_default = name
+        .strip_prefix("virbr")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    if libvirt_default || libvirt_bridges().iter().any(|bridge| bridge == name) {
+        return Some("libvirt");
+    }
+
+    None
+}
+
+/// Bridges named in libvirt's network definitions, e.g. `<bridge name='virbr1'/>`
+fn libvirt_bridges() -> Vec<String> {
+    let mut bridges = Vec::new();
+    for dir in ["/etc/libvirt/qemu/networks", "/run/libvirt/network"] {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(xml) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            bridges.extend(xml.split("<bridge ").skip(1).filter_map(|element| {
+                let name = element.split_once("name=")?.1;
+                let quote = name.chars().next()?;
+                let name = name.get(1..)?;
+                Some(name[..name.find(quote)?].to_string())
+            }));
+        }
+    }
+    bridges
+}
+
+/// Every bridge among `interfaces`
+pub fn get_bridges(interfaces: &[NetworkInterface]) -> Vec<BridgeInfo> {
+    let vlans = port_vlans();
+    interfaces
+        .iter()
+        .filter(|iface| iface.interface_type == InterfaceType::Bridge)
+        .map(|iface| {
+            let mut bridge = read_bridge(&iface.name, interfaces);
+            bridge.up = iface.is_up;
+            bridge.managed_by = managed_by(&iface.name);
+            for port in bridge.ports.iter_mut() {
+                port.vlans = vlans
+                    .iter()
+                    .filter(|(name, _)| *name == port.name)
+                    .map(|(_, vlan)| *vlan)
+                    .collect();
+            }
+            bridge
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn read_bridge(name: &str, interfaces: &[NetworkInterface]) -> BridgeInfo {
+    let sysfs = |file: &str| {
+        std::fs::read_to_string(format!("/sys/class/net/{}/bridge/{}", name, file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let number = |file: &str| sysfs(file).and_then(|value| value.parse::<u32>().ok());
+
+    let mut bridge = BridgeInfo {
+        name: name.to_string(),
+        stp: number("stp_state").is_some_and(|state| state != 0),
+        bridge_id: sysfs("bridge_id"),
+        root_id: sysfs("root_id"),
+        // In centiseconds
+        ageing_time: number("ageing_time").map(|time| time / 100),
+        vlan_filtering: number("vlan_filtering").map(|filtering| filtering != 0),
+        default_pvid: number("default_pvid").and_then(|pvid| u16::try_from(pvid).ok()),
+        ..Default::default()
+    };
+    let root_port = number("root_port").unwrap_or(0);
+
+    for iface in interfaces
+        .iter()
+        .filter(|iface| iface.master.as_deref() == Some(name))
+    {
+        let port_file = |file: &str| {
+            std::fs::read_to_string(format!("/sys/class/net/{}/brport/{}", iface.name, file))
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+        let state = port_file("state").and_then(|state| state.parse::<u8>().ok());
+        let port_no = port_file("port_no")
+            .and_then(|port_no| u32::from_str_radix(port_no.trim_start_matches("0x"), 16).ok());
+
+        let role = match state {
+            _ if !bridge.stp => None,
+            Some(0) => Some("disabled"),
+            _ if root_port != 0 && port_no == Some(root_port) => Some("root"),
+            Some(4) => Some("alternate"),
+            _ if port_file("designated_bridge") == bridge.bridge_id => Some("designated"),
+            _ => Some("alternate"),
+        };
+
+        bridge.ports.push(BridgePort {
+            name: iface.name.clone(),
+            state: state.map(|state| port_state_name(state).to_string()),
+            role,
+            path_cost: port_file("path_cost").and_then(|cost| cost.parse().ok()),
+            vlans: Vec::new(),
+        });
+    }
+
+    bridge
+}
+
+/// `BR_STATE_*`
+#[cfg(target_os = "linux")]
+fn port_state_name(state: u8) -> &'static str {
+    match state {
+        0 => "disabled",
+        1 => "listening",
+        2 => "learning",
+        3 => "forwarding",
+        4 => "blocking",
+        _ => "unknown",
+    }
+}
+
+/// VLAN membership of every port, from `bridge -j vlan show`. Empty unless
+/// a bridge filters VLANs.
+#[cfg(target_os = "linux")]
+fn port_vlans() -> Vec<(String, PortVlan)> {
+    #[derive(serde::Deserialize)]
+    struct Port {
+        ifname: String,
+        #[serde(default)]
+        vlans: Vec<Vlan>,
+    }
+    #[derive(serde::Deserialize)]
+    struct Vlan {
+        vlan: u16,
+        #[serde(default)]
+        flags: Vec<String>,
+    }
+
+    let Ok(output) = Command::new("bridge").args(["-j", "vlan", "show"]).output() else {
+        return Vec::new();
+    };
+    let ports: Vec<Port> = serde_json::from_slice(&output.stdout).unwrap_or_default();
+    ports
+        .into_iter()
+        .flat_map(|port| {
+            port.vlans.into_iter().map(move |vlan| {
+                let flag = |name: &str| vlan.flags.iter().any(|flag| flag == name);
+                (
+                    port.ifname.clone(),
+                    PortVlan {
+                        id: vlan.vlan,
+                        pvid: flag("PVID"),
+                        untagged: flag("Egress Untagged"),
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
+/// Forwarding database of `bridge`; the ports' own hardware entries
+/// (`self`) are left out
+#[cfg(target_os = "linux")]
+pub fn get_fdb(bridge: &str) -> Result<Vec<FdbEntry>> {
+    use crate::namespace;
+    use anyhow::Context;
+
+    #[derive(serde::Deserialize)]
+    struct Entry {
+        mac: String,
+        ifname: String,
+        vlan: Option<u16>,
+        #[serde(default)]
+        flags: Vec<String>,
+        master: Option<String>,
+        #[serde(default)]
+        state: String,
+        updated: Option<u32>,
+    }
+
+    // `bridge` runs in our own namespace, not the selected one
+    if namespace::selected().is_some() || namespace::entered().is_some() {
+        anyhow::bail!("The forwarding database can only be read in the host namespace");
+    }
+
+    let output = Command::new("bridge")
+        .args(["-j", "-s", "fdb", "show", "br", bridge])
+        .output()
+        .context("Failed to run bridge")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "bridge fdb show failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let entries: Vec<Entry> =
+        serde_json::from_slice(&output.stdout).context("Unexpected bridge fdb output")?;
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| !entry.flags.iter().any(|flag| flag == "self"))
+        .filter(|entry| entry.master.as_deref().unwrap_or(bridge) == bridge)
+        .map(|entry| FdbEntry {
+            kind: match entry.state.as_str() {
+                "permanent" => FdbKind::Local,
+                "static" => FdbKind::Static,
+                _ => FdbKind::Dynamic,
+            },
+            mac: entry.mac,
+            port: entry.ifname,
+            vlan: entry.vlan,
+            updated: entry.updated,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+fn ifconfig(name: &str) -> String {
+    Command::new("ifconfig")
+        .arg(name)
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default()
+}
+
+/// `member: en1 flags=3<LEARNING,DISCOVER>` lines, each followed by the
+/// member's port, cost, role and state when STP is on
+#[cfg(target_os = "macos")]
+fn read_bridge(name: &str, _interfaces: &[NetworkInterface]) -> BridgeInfo {
+    let mut bridge = BridgeInfo {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    for line in ifconfig(name).lines().map(str::trim) {
+        if let Some(member) = line.strip_prefix("member:") {
+            let Some(member) = member.split_whitespace().next() else {
+                continue;
+            };
+            bridge.stp |= line.contains("STP");
+            bridge.ports.push(BridgePort {
+                name: member.to_string(),
+                ..Default::default()
+            });
+        } else if let Some(seconds) = line
+            .strip_prefix("Address cache (max cache:")
+            .and_then(|rest| rest.split("timeout:").nth(1))
+        {
+            bridge.ageing_time = seconds.trim_end_matches(')').trim().parse().ok();
+        } else if let Some(port) = bridge.ports.last_mut() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let after = |key: &str| {
+                words
+                    .iter()
+                    .position(|word| *word == key)
+                    .and_then(|i| words.get(i + 1))
+            };
+            if let Some(state) = after("state") {
+                port.state = Some(state.to_string());
+            }
+            let role = after("role").and_then(|role| match *role {
+                "root" => Some("root"),
+                "designated" => Some("designated"),
+                "alternate" => Some("alternate"),
+                "backup" => Some("backup"),
+                "disabled" => Some("disabled"),
+                _ => None,
+            });
+            if role.is_some() {
+                port.role = role;
+            }
+            if let Some(cost) = after("cost") {
+                port.path_cost = cost.parse().ok();
+            }
+        }
+    }
+
+    bridge
+}
+
+#[cfg(target_os = "macos")]
+fn port_vlans() -> Vec<(String, PortVlan)> {
+    Vec::new()
+}
+
+/// The `Address cache:` section: `aa:bb:cc:dd:ee:ff Vlan1 en1 1190 flags=0<>`,
+/// where the number is the seconds left before the entry expires
+#[cfg(target_os = "macos")]
+pub fn get_fdb(bridge: &str) -> Result<Vec<FdbEntry>> {
+    let text = ifconfig(bridge);
+    let mut entries = Vec::new();
+    let mut in_cache = false;
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with("Address cache") {
+            in_cache = true;
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if !in_cache || words.len() < 4 || words[0].matches(':').count() != 5 {
+            continue;
+        }
+        entries.push(FdbEntry {
+            mac: words[0].to_string(),
+            vlan: words[1].strip_prefix("Vlan").and_then(|id| id.parse().ok()),
+            port: words[2].to_string(),
+            kind: if line.contains("STATIC") {
+                FdbKind::Static
+            } else {
+                FdbKind::Dynamic
+            },
+            updated: None,
+        });
+    }
+
+    Ok(entries)
+}
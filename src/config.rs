@@ -0,0 +1,49 @@
+use crate::models::MacFormat;
+use crate::sudo::PrivilegeStrategy;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// User configuration read from `~/.config/netutil/config.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Force a privilege strategy instead of detecting one at startup
+    pub privilege: Option<PrivilegeStrategy>,
+    /// Where to append the audit log instead of `~/.local/state/netutil/audit.jsonl`
+    pub audit_log: Option<PathBuf>,
+    /// Start with the Speed column shown
+    pub speed_column: bool,
+    /// `colon`, `dash` or `cisco`
+    pub mac_format: MacFormat,
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults when it doesn't exist
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_path() else {
+            return Ok(Self::default());
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid config file {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read config file {}", path.display()))
+            }
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("NETUTIL_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(base.join("netutil").join("config.json"))
+}
@@ -0,0 +1,522 @@
+use crate::bridge;
+use crate::models::{InterfaceType, IpNetwork, NetworkInterface, RouteEntry};
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+/// Kinds of conflict, most harmful first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConflictKind {
+    /// A Docker or libvirt network covering a range that is reached over a VPN
+    VpnCollision,
+    DuplicateAddress,
+    OverlappingSubnets,
+    /// A route losing part or all of its traffic to a more specific one
+    ShadowedRoute,
+}
+
+impl ConflictKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            ConflictKind::VpnCollision => "Container and VM networks colliding with VPN ranges",
+            ConflictKind::DuplicateAddress => "Duplicate addresses",
+            ConflictKind::OverlappingSubnets => "Overlapping interface subnets",
+            ConflictKind::ShadowedRoute => "Shadowed routes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub summary: String,
+    /// What goes wrong because of it, and what to change
+    pub detail: String,
+}
+
+/// Look at every interface address, route and VPN tunnel for addresses and
+/// ranges that get in each other's way. Each pair of interfaces is reported
+/// at most once per kind of conflict; the connected routes of two overlapping
+/// interface subnets aren't reported again as shadowed routes.
+pub fn detect(interfaces: &[NetworkInterface], routes: &[RouteEntry]) -> Vec<Conflict> {
+    let mut findings = Findings::default();
+
+    let subnets = interface_subnets(interfaces);
+    let vpn_ranges = vpn_ranges(interfaces, routes);
+
+    for iface in interfaces {
+        let Some(tool) = bridge::managed_by(&iface.name) else {
+            continue;
+        };
+        let guests = if tool == "Docker" {
+            "containers"
+        } else {
+            "VMs"
+        };
+        for (_, subnet) in subnets.iter().filter(|(name, _)| *name == iface.name) {
+            for (vpn, range) in &vpn_ranges {
+                if !subnet.overlaps(range)
+                    || findings.reported(&iface.name, vpn, ConflictKind::VpnCollision)
+                {
+                    continue;
+                }
+                let detail = if subnet.prefix() >= range.prefix() {
+                    format!(
+                        "Hosts in {} behind the VPN can't be reached: traffic for them goes to {}. {}",
+                        subnet,
+                        iface.name,
+                        move_hint(tool)
+                    )
+                } else {
+                    format!(
+                        "{} takes {} out of the {} network, so {} there can't be reached. {}",
+                        vpn,
+                        range,
+                        tool,
+                        guests,
+                        move_hint(tool)
+                    )
+                };
+                findings.add(
+                    &iface.name,
+                    vpn,
+                    Conflict {
+                        kind: ConflictKind::VpnCollision,
+                        summary: format!(
+                            "{} network {} on {} collides with VPN range {} on {}",
+                            tool, subnet, iface.name, range, vpn
+                        ),
+                        detail,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut owners: Vec<(IpAddr, Vec<&str>)> = Vec::new();
+    for iface in interfaces {
+        for addr in iface
+            .ip_addresses
+            .iter()
+            .filter(|addr| is_routable(addr.ip))
+        {
+            match owners.iter_mut().find(|(ip, _)| *ip == addr.ip) {
+                Some((_, names)) if !names.contains(&iface.name.as_str()) => {
+                    names.push(&iface.name)
+                }
+                Some(_) => {}
+                None => owners.push((addr.ip, vec![&iface.name])),
+            }
+        }
+    }
+    for (ip, names) in owners.iter().filter(|(_, names)| names.len() > 1) {
+        findings.add(
+            names[0],
+            names[1],
+            Conflict {
+                kind: ConflictKind::DuplicateAddress,
+                summary: format!("{} is assigned to {}", ip, names.join(" and ")),
+                detail: "Replies can leave through either interface and both answer ARP or \
+                         neighbour discovery for it; remove it from all but one"
+                    .to_string(),
+            },
+        );
+    }
+
+    for (index, (name, subnet)) in subnets.iter().enumerate() {
+        for (other_name, other) in &subnets[index + 1..] {
+            if name == other_name
+                || !subnet.overlaps(other)
+                || findings.reported(name, other_name, ConflictKind::OverlappingSubnets)
+            {
+                continue;
+            }
+            let detail = if subnet.prefix() == other.prefix() {
+                "Both interfaces claim the whole range; the route with the lower metric gets \
+                 all of its traffic"
+                    .to_string()
+            } else {
+                let ((narrow_name, narrow), (wide_name, wide)) = if subnet.prefix() > other.prefix()
+                {
+                    ((name, subnet), (other_name, other))
+                } else {
+                    ((other_name, other), (name, subnet))
+                };
+                format!(
+                    "Addresses in {} go to {}, the rest of {} to {}",
+                    narrow, narrow_name, wide, wide_name
+                )
+            };
+            findings.add(
+                name,
+                other_name,
+                Conflict {
+                    kind: ConflictKind::OverlappingSubnets,
+                    summary: format!(
+                        "{} on {} overlaps {} on {}",
+                        subnet, name, other, other_name
+                    ),
+                    detail,
+                },
+            );
+        }
+    }
+
+    // The kernel's route for an interface's own subnet
+    let connected = |route: &RouteEntry, network: &IpNetwork| {
+        route.gateway.is_none() && subnets.contains(&(route.interface.as_str(), *network))
+    };
+    let routes: Vec<&RouteEntry> = routes
+        .iter()
+        .filter(|route| !route.is_default() && is_routable(route.destination))
+        .collect();
+    for route in &routes {
+        let Ok(wide) = IpNetwork::new(route.destination, route.prefix) else {
+            continue;
+        };
+        for other in &routes {
+            let Ok(narrow) = IpNetwork::new(other.destination, other.prefix) else {
+                continue;
+            };
+            if other.interface == route.interface
+                || !wide.contains_network(&narrow)
+                || findings.reported(
+                    &route.interface,
+                    &other.interface,
+                    ConflictKind::ShadowedRoute,
+                )
+                || (connected(route, &wide) && connected(other, &narrow))
+            {
+                continue;
+            }
+
+            let conflict = if narrow.prefix() > wide.prefix() {
+                Conflict {
+                    kind: ConflictKind::ShadowedRoute,
+                    summary: format!(
+                        "{} via {} shadows part of {} via {}",
+                        narrow, other.interface, wide, route.interface
+                    ),
+                    detail: format!(
+                        "Addresses in {} are sent to {} instead of {}",
+                        narrow, other.interface, route.interface
+                    ),
+                }
+            } else if other.metric < route.metric {
+                Conflict {
+                    kind: ConflictKind::ShadowedRoute,
+                    summary: format!(
+                        "{} via {} (metric {}) is shadowed by the same route via {} (metric {})",
+                        wide, route.interface, route.metric, other.interface, other.metric
+                    ),
+                    detail: format!(
+                        "Only {} carries traffic for {} while both routes exist",
+                        other.interface, wide
+                    ),
+                }
+            } else {
+                continue;
+            };
+            findings.add(&route.interface, &other.interface, conflict);
+        }
+    }
+
+    findings.conflicts.sort_by_key(|conflict| conflict.kind);
+    findings.conflicts
+}
+
+#[derive(Default)]
+struct Findings {
+    conflicts: Vec<Conflict>,
+    /// Interface pairs already reported under each kind, both ways round
+    pairs: HashSet<(String, String, ConflictKind)>,
+}
+
+impl Findings {
+    fn add(&mut self, a: &str, b: &str, conflict: Conflict) {
+        self.pairs
+            .insert((a.to_string(), b.to_string(), conflict.kind));
+        self.pairs
+            .insert((b.to_string(), a.to_string(), conflict.kind));
+        self.conflicts.push(conflict);
+    }
+
+    fn reported(&self, a: &str, b: &str, kind: ConflictKind) -> bool {
+        self.pairs.contains(&(a.to_string(), b.to_string(), kind))
+    }
+}
+
+/// Interface subnets other than loopback and link-local, once per interface
+fn interface_subnets(interfaces: &[NetworkInterface]) -> Vec<(&str, IpNetwork)> {
+    let mut subnets: Vec<(&str, IpNetwork)> = Vec::new();
+    for iface in interfaces {
+        for addr in iface
+            .ip_addresses
+            .iter()
+            .filter(|addr| is_routable(addr.ip))
+        {
+            let Some(subnet) = addr.network().map(|network| network.trunc()) else {
+                continue;
+            };
+            if !subnets.contains(&(iface.name.as_str(), subnet)) {
+                subnets.push((&iface.name, subnet));
+            }
+        }
+    }
+    subnets
+}
+
+/// Ranges reached through a tunnel: its routes, its own subnets and the
+/// allowed IPs of WireGuard peers. Full tunnels (/0, and the /1 pairs
+/// OpenVPN installs instead) would collide with everything and are left out.
+fn vpn_ranges(interfaces: &[NetworkInterface], routes: &[RouteEntry]) -> Vec<(String, IpNetwork)> {
+    let mut ranges: Vec<(String, IpNetwork)> = Vec::new();
+    let mut add = |name: &str, range: IpNetwork| {
+        let range = range.trunc();
+        if range.prefix() > 1 && !ranges.contains(&(name.to_string(), range)) {
+            ranges.push((name.to_string(), range));
+        }
+    };
+
+    for iface in interfaces.iter().filter(|iface| {
+        matches!(
+            iface.interface_type,
+            InterfaceType::WireGuard | InterfaceType::Tunnel | InterfaceType::Ppp
+        )
+    }) {
+        for addr in iface
+            .ip_addresses
+            .iter()
+            .filter(|addr| is_routable(addr.ip))
+        {
+            if let Some(subnet) = addr.network() {
+                add(&iface.name, subnet);
+            }
+        }
+        for route in routes.iter().filter(|route| route.interface == iface.name) {
+            if let Ok(range) = IpNetwork::new(route.destination, route.prefix) {
+                add(&iface.name, range);
+            }
+        }
+        let peers = iface
+            .wireguard
+            .iter()
+            .flat_map(|wireguard| &wireguard.peers);
+        for allowed in peers.flat_map(|peer| &peer.allowed_ips) {
+            if let Ok(range) = IpNetwork::new(allowed.address, allowed.prefix) {
+                add(&iface.name, range);
+            }
+        }
+    }
+    ranges
+}
+
+/// Loopback, link-local and multicast addresses are the same everywhere
+/// and never conflict
+fn is_routable(ip: IpAddr) -> bool {
+    let link_local = match ip {
+        IpAddr::V4(v4) => v4.is_link_local(),
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) == 0xfe80,
+    };
+    !ip.is_loopback() && !ip.is_multicast() && !ip.is_unspecified() && !link_local
+}
+
+fn move_hint(tool: &str) -> &'static str {
+    match tool {
+        "Docker" => "Move Docker's networks with default-address-pools in /etc/docker/daemon.json",
+        _ => "Move the network to another range with virsh net-edit",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InterfaceAddress;
+    use crate::network;
+
+    fn iface(name: &str, interface_type: InterfaceType, addresses: &[&str]) -> NetworkInterface {
+        let mut iface = network::new_interface(name, false);
+        iface.interface_type = interface_type;
+        iface.ip_addresses = addresses
+            .iter()
+            .map(|address| {
+                let network: IpNetwork = address.parse().unwrap();
+                InterfaceAddress {
+                    ip: network.ip(),
+                    prefix: Some(network.prefix()),
+                    broadcast: None,
+                    label: None,
+                    secondary: false,
+                    ipv6_info: None,
+                }
+            })
+            .collect();
+        iface
+    }
+
+    fn ethernet(name: &str, addresses: &[&str]) -> NetworkInterface {
+        iface(name, InterfaceType::Ethernet, addresses)
+    }
+
+    fn route(destination: &str, interface: &str, gateway: Option<&str>, metric: u32) -> RouteEntry {
+        let destination: IpNetwork = destination.parse().unwrap();
+        RouteEntry {
+            destination: destination.ip(),
+            prefix: destination.prefix(),
+            gateway: gateway.map(|gateway| gateway.parse().unwrap()),
+            interface: interface.to_string(),
+            metric,
+        }
+    }
+
+    /// What the kernel adds for each address's subnet
+    fn connected(interfaces: &[NetworkInterface]) -> Vec<RouteEntry> {
+        interface_subnets(interfaces)
+            .into_iter()
+            .map(|(name, subnet)| route(&subnet.to_string(), name, None, 100))
+            .collect()
+    }
+
+    fn kinds(conflicts: &[Conflict]) -> Vec<ConflictKind> {
+        conflicts.iter().map(|conflict| conflict.kind).collect()
+    }
+
+    #[test]
+    fn nothing_to_report() {
+        let interfaces = [
+            iface("lo", InterfaceType::Loopback, &["127.0.0.1/8", "::1/128"]),
+            ethernet("eth0", &["192.0.2.10/24", "fe80::1/64"]),
+            ethernet("eth1", &["198.51.100.10/24", "fe80::1/64"]),
+            iface("lo2", InterfaceType::Loopback, &["127.0.0.1/8"]),
+        ];
+        let mut routes = connected(&interfaces);
+        routes.push(route("0.0.0.0/0", "eth0", Some("192.0.2.1"), 100));
+        routes.push(route("0.0.0.0/0", "eth1", Some("198.51.100.1"), 200));
+
+        assert_eq!(detect(&interfaces, &routes), []);
+    }
+
+    #[test]
+    fn overlapping_subnets_without_route_echo() {
+        let interfaces = [
+            ethernet("eth0", &["10.0.0.1/16"]),
+            ethernet("eth1", &["10.0.1.1/24"]),
+        ];
+
+        let conflicts = detect(&interfaces, &connected(&interfaces));
+        assert_eq!(kinds(&conflicts), [ConflictKind::OverlappingSubnets]);
+        assert_eq!(
+            conflicts[0].summary,
+            "10.0.0.0/16 on eth0 overlaps 10.0.1.0/24 on eth1"
+        );
+        assert_eq!(
+            conflicts[0].detail,
+            "Addresses in 10.0.1.0/24 go to eth1, the rest of 10.0.0.0/16 to eth0"
+        );
+    }
+
+    #[test]
+    fn each_pair_once_per_kind() {
+        let interfaces = [
+            ethernet("eth0", &["10.0.0.1/24", "10.1.0.1/24"]),
+            ethernet("eth1", &["10.0.0.2/24", "10.1.0.2/24"]),
+        ];
+        let mut routes = connected(&interfaces);
+        routes.push(route("172.16.0.0/12", "eth0", Some("10.0.0.254"), 100));
+        routes.push(route("172.16.1.0/24", "eth1", Some("10.0.0.253"), 100));
+        routes.push(route("172.16.2.0/24", "eth1", Some("10.0.0.253"), 100));
+
+        let conflicts = detect(&interfaces, &routes);
+        assert_eq!(
+            kinds(&conflicts),
+            [
+                ConflictKind::OverlappingSubnets,
+                ConflictKind::ShadowedRoute
+            ]
+        );
+        assert_eq!(
+            conflicts[1].summary,
+            "172.16.1.0/24 via eth1 shadows part of 172.16.0.0/12 via eth0"
+        );
+    }
+
+    #[test]
+    fn duplicate_address_does_not_hide_shadowed_route() {
+        let interfaces = [
+            ethernet("eth0", &["192.0.2.10/24"]),
+            ethernet("eth1", &["192.0.2.10/24"]),
+        ];
+        let mut routes = connected(&interfaces);
+        routes.push(route("10.20.0.0/16", "eth0", Some("192.0.2.1"), 100));
+        routes.push(route("10.20.5.0/24", "eth1", Some("192.0.2.254"), 100));
+
+        let conflicts = detect(&interfaces, &routes);
+        assert_eq!(
+            kinds(&conflicts),
+            [
+                ConflictKind::DuplicateAddress,
+                ConflictKind::OverlappingSubnets,
+                ConflictKind::ShadowedRoute,
+            ]
+        );
+        assert_eq!(
+            conflicts[0].summary,
+            "192.0.2.10 is assigned to eth0 and eth1"
+        );
+        assert_eq!(
+            conflicts[2].detail,
+            "Addresses in 10.20.5.0/24 are sent to eth1 instead of eth0"
+        );
+    }
+
+    #[test]
+    fn same_route_with_lower_metric() {
+        let interfaces = [
+            ethernet("eth0", &["192.0.2.10/24"]),
+            ethernet("eth1", &["198.51.100.10/24"]),
+        ];
+        let mut routes = connected(&interfaces);
+        routes.push(route("10.30.0.0/16", "eth0", Some("192.0.2.1"), 100));
+        routes.push(route("10.30.0.0/16", "eth1", Some("198.51.100.1"), 50));
+
+        let conflicts = detect(&interfaces, &routes);
+        assert_eq!(kinds(&conflicts), [ConflictKind::ShadowedRoute]);
+        assert_eq!(
+            conflicts[0].summary,
+            "10.30.0.0/16 via eth0 (metric 100) is shadowed by the same route via eth1 (metric 50)"
+        );
+
+        // Equal metrics share the traffic rather than shadowing
+        routes.last_mut().unwrap().metric = 100;
+        assert_eq!(detect(&interfaces, &routes), []);
+    }
+
+    #[test]
+    fn docker_network_inside_vpn_range() {
+        let interfaces = [
+            iface("docker0", InterfaceType::Bridge, &["172.17.0.1/16"]),
+            iface("wg0", InterfaceType::WireGuard, &["10.8.0.2/24"]),
+        ];
+        let mut routes = connected(&interfaces);
+        routes.push(route("172.16.0.0/12", "wg0", None, 100));
+        // A full tunnel covers everything and is no collision
+        routes.push(route("0.0.0.0/1", "wg0", None, 100));
+        routes.push(route("128.0.0.0/1", "wg0", None, 100));
+
+        let conflicts = detect(&interfaces, &routes);
+        assert_eq!(
+            kinds(&conflicts),
+            [ConflictKind::VpnCollision, ConflictKind::ShadowedRoute]
+        );
+        assert_eq!(
+            conflicts[0].summary,
+            "Docker network 172.17.0.0/16 on docker0 collides with VPN range 172.16.0.0/12 on wg0"
+        );
+        assert!(conflicts[0]
+            .detail
+            .starts_with("Hosts in 172.17.0.0/16 behind the VPN can't be reached"));
+        assert_eq!(
+            conflicts[1].summary,
+            "172.17.0.0/16 via docker0 shadows part of 172.16.0.0/12 via wg0"
+        );
+    }
+}
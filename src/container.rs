@@ -0,0 +1,364 @@
+//! Containers behind veth interfaces.
+//!
+//! The far end of a container's veth lives in a network namespace held by
+//! the container's processes. Their cgroup names the runtime and container
+//! id, and the runtime's state files give its name, image and, under
+//! Kubernetes, the pod it belongs to.
+
+use crate::models::{ContainerInfo, ContainerRuntime};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+const DOCKER_CONTAINERS: &str = "/var/lib/docker/containers";
+const PODMAN_CONTAINERS: &str = "/var/lib/containers/storage/overlay-containers/containers.json";
+const CONTAINERD_TASKS: &str = "/run/containerd/io.containerd.runtime.v2.task";
+const CRIO_CONTAINERS: &str = "/run/containers/storage/overlay-containers";
+
+/// Containers whose processes hold the network namespaces with these inodes
+pub fn by_namespace(inodes: &HashSet<u64>) -> HashMap<u64, ContainerInfo> {
+    let mut found = HashMap::new();
+    // Containers sharing the host network don't own it
+    let own = fs::metadata("/proc/self/ns/net")
+        .map(|meta| meta.ino())
+        .ok();
+    let Ok(processes) = procfs::process::all_processes() else {
+        return found;
+    };
+
+    // Lowest pid first: a pod's pause container comes before its workloads
+    for process in processes.flatten() {
+        let Some(inode) = process.namespaces().ok().and_then(|namespaces| {
+            namespaces
+                .0
+                .get(OsStr::new("net"))
+                .map(|net| net.identifier)
+        }) else {
+            continue;
+        };
+        if !inodes.contains(&inode) || found.contains_key(&inode) || Some(inode) == own {
+            continue;
+        }
+
+        // Anything else in there, such as a shell from `ip netns exec`, has no container cgroup
+        let container = process.cgroups().ok().and_then(|cgroups| {
+            cgroups
+                .0
+                .iter()
+                .find_map(|cgroup| parse_cgroup(&cgroup.pathname))
+        });
+        if let Some((runtime, id)) = container {
+            found.insert(inode, lookup(runtime, &id));
+        }
+    }
+
+    found
+}
+
+/// Runtime and container id from a cgroup path such as
+/// `/system.slice/docker-<id>.scope` or `/kubepods/besteffort/pod<uid>/<id>`.
+/// Containers running systemd add cgroups of their own below that.
+fn parse_cgroup(path: &str) -> Option<(ContainerRuntime, String)> {
+    path.rsplit('/').find_map(|component| {
+        let component = component.strip_suffix(".scope").unwrap_or(component);
+        let (runtime, id) = if let Some(id) = component.strip_prefix("docker-") {
+            (ContainerRuntime::Docker, id)
+        } else if let Some(id) = component.strip_prefix("libpod-") {
+            (ContainerRuntime::Podman, id)
+        } else if let Some(id) = component.strip_prefix("cri-containerd-") {
+            (ContainerRuntime::Containerd, id)
+        } else if let Some(id) = component.strip_prefix("crio-") {
+            (ContainerRuntime::CriO, id)
+        } else if path.contains("/docker/") {
+            (ContainerRuntime::Docker, component)
+        } else if path.contains("kubepods") {
+            // The cgroupfs driver doesn't say; CRI-O is told apart by its state below
+            (ContainerRuntime::Containerd, component)
+        } else {
+            return None;
+        };
+
+        is_container_id(id).then(|| (runtime, id.to_string()))
+    })
+}
+
+/// Full ids are 64 hex digits; this also skips `libpod-conmon-<id>`
+fn is_container_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Name, image and pod from the runtime's state, or just the id when that
+/// can't be read
+fn lookup(runtime: ContainerRuntime, id: &str) -> ContainerInfo {
+    let found = match runtime {
+        ContainerRuntime::Docker => docker(Path::new(DOCKER_CONTAINERS), id),
+        ContainerRuntime::Podman => podman(Path::new(PODMAN_CONTAINERS), id),
+        ContainerRuntime::Containerd => containerd(Path::new(CONTAINERD_TASKS), id)
+            .or_else(|| crio(Path::new(CRIO_CONTAINERS), id)),
+        ContainerRuntime::CriO => crio(Path::new(CRIO_CONTAINERS), id),
+    };
+
+    found.unwrap_or_else(|| ContainerInfo {
+        runtime,
+        id: id.to_string(),
+        name: id[..12].to_string(),
+        image: None,
+        pod: None,
+    })
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+fn string(value: &Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// `namespace/name`, when both are there
+fn pod_name(namespace: &Value, name: &Value) -> Option<String> {
+    Some(format!("{}/{}", namespace.as_str()?, name.as_str()?))
+}
+
+/// `<dir>/<id>/config.v2.json`; Kubernetes on dockershim labels its containers
+fn docker(dir: &Path, id: &str) -> Option<ContainerInfo> {
+    let config = read_json(&dir.join(id).join("config.v2.json"))?;
+    let labels = &config["Config"]["Labels"];
+
+    Some(ContainerInfo {
+        runtime: ContainerRuntime::Docker,
+        id: id.to_string(),
+        name: config["Name"].as_str()?.trim_start_matches('/').to_string(),
+        image: string(&config["Config"]["Image"]),
+        pod: pod_name(
+            &labels["io.kubernetes.pod.namespace"],
+            &labels["io.kubernetes.pod.name"],
+        ),
+    })
+}
+
+/// containers/storage keeps every container in one file, with the image
+/// name in a metadata string of its own
+fn podman(path: &Path, id: &str) -> Option<ContainerInfo> {
+    let containers = read_json(path)?;
+    let container = containers
+        .as_array()?
+        .iter()
+        .find(|container| container["id"].as_str() == Some(id))?;
+    let metadata: Value = container["metadata"]
+        .as_str()
+        .and_then(|metadata| serde_json::from_str(metadata).ok())
+        .unwrap_or_default();
+
+    Some(ContainerInfo {
+        runtime: ContainerRuntime::Podman,
+        id: id.to_string(),
+        name: string(&container["names"][0]).or_else(|| string(&metadata["name"]))?,
+        image: string(&metadata["image-name"]),
+        pod: None,
+    })
+}
+
+/// The OCI bundle of a task, in any containerd namespace (`k8s.io`,
+/// `default`, `moby`, ...); the CRI plugin and nerdctl annotate it
+fn containerd(dir: &Path, id: &str) -> Option<ContainerInfo> {
+    let config = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .find_map(|namespace| read_json(&namespace.path().join(id).join("config.json")))?;
+    let annotations = &config["annotations"];
+    let pod = pod_name(
+        &annotations["io.kubernetes.cri.sandbox-namespace"],
+        &annotations["io.kubernetes.cri.sandbox-name"],
+    );
+
+    Some(ContainerInfo {
+        runtime: ContainerRuntime::Containerd,
+        id: id.to_string(),
+        name: string(&annotations["io.kubernetes.cri.container-name"])
+            .or_else(|| string(&annotations["nerdctl/name"]))
+            .or_else(|| string(&annotations["io.kubernetes.cri.sandbox-name"]))
+            .unwrap_or_else(|| id[..12].to_string()),
+        image: string(&annotations["io.kubernetes.cri.image-name"]),
+        pod,
+    })
+}
+
+/// `<dir>/<id>/userdata/config.json`; the sandbox's container name is `POD`
+fn crio(dir: &Path, id: &str) -> Option<ContainerInfo> {
+    let config = read_json(&dir.join(id).join("userdata").join("config.json"))?;
+    let annotations = &config["annotations"];
+
+    Some(ContainerInfo {
+        runtime: ContainerRuntime::CriO,
+        id: id.to_string(),
+        name: string(&annotations["io.kubernetes.container.name"])
+            .unwrap_or_else(|| id[..12].to_string()),
+        image: string(&annotations["io.kubernetes.cri-o.ImageName"]),
+        pod: pod_name(
+            &annotations["io.kubernetes.pod.namespace"],
+            &annotations["io.kubernetes.pod.name"],
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const DOCKER_ID: &str = "3f4e1c2b9a8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f";
+    const PODMAN_ID: &str = "8c1d2e3f4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0c1d";
+    const CONTAINERD_ID: &str = "b7e2f1a0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2";
+    const NERDCTL_ID: &str = "0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b";
+    const CRIO_ID: &str = "d41e7a2c8b9f0e3d6a5c4b7f2e1d0c9b8a7f6e5d4c3b2a1f0e9d8c7b6a5f4e3d";
+
+    fn fixtures(path: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/containers")
+            .join(path)
+    }
+
+    #[test]
+    fn parse_cgroup_finds_runtime_and_id() {
+        let cases = [
+            (
+                format!("/system.slice/docker-{}.scope", DOCKER_ID),
+                ContainerRuntime::Docker,
+                DOCKER_ID,
+            ),
+            (format!("/docker/{}", DOCKER_ID), ContainerRuntime::Docker, DOCKER_ID),
+            (
+                format!("/machine.slice/libpod-{}.scope/container", PODMAN_ID),
+                ContainerRuntime::Podman,
+                PODMAN_ID,
+            ),
+            (
+                format!(
+                    "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod0b1c2d3e.slice/cri-containerd-{}.scope",
+                    CONTAINERD_ID
+                ),
+                ContainerRuntime::Containerd,
+                CONTAINERD_ID,
+            ),
+            (
+                format!("/kubepods/besteffort/pod0b1c2d3e/{}", CONTAINERD_ID),
+                ContainerRuntime::Containerd,
+                CONTAINERD_ID,
+            ),
+            (
+                format!(
+                    "/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod9a8b.slice/crio-{}.scope",
+                    CRIO_ID
+                ),
+                ContainerRuntime::CriO,
+                CRIO_ID,
+            ),
+            // systemd inside the container adds its own cgroups below
+            (
+                format!("/system.slice/docker-{}.scope/init.scope", DOCKER_ID),
+                ContainerRuntime::Docker,
+                DOCKER_ID,
+            ),
+        ];
+
+        for (path, runtime, id) in cases {
+            assert_eq!(
+                parse_cgroup(&path),
+                Some((runtime, id.to_string())),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn parse_cgroup_ignores_everything_else() {
+        for path in [
+            "/".to_string(),
+            "/user.slice/user-1000.slice/session-2.scope".to_string(),
+            "/system.slice/docker.service".to_string(),
+            format!("/machine.slice/libpod-conmon-{}.scope", PODMAN_ID),
+            "/system.slice/docker-3f4e1c2b9a8d.scope".to_string(),
+            format!("/system.slice/docker-{}x.scope", &DOCKER_ID[1..]),
+            "/kubepods/besteffort/pod0b1c2d3e".to_string(),
+        ] {
+            assert_eq!(parse_cgroup(&path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn docker_reads_config() {
+        assert_eq!(
+            docker(&fixtures("docker"), DOCKER_ID),
+            Some(ContainerInfo {
+                runtime: ContainerRuntime::Docker,
+                id: DOCKER_ID.to_string(),
+                name: "web".to_string(),
+                image: Some("nginx:1.25".to_string()),
+                pod: None,
+            })
+        );
+        assert_eq!(docker(&fixtures("docker"), PODMAN_ID), None);
+    }
+
+    #[test]
+    fn podman_reads_containers_json() {
+        assert_eq!(
+            podman(&fixtures("podman/containers.json"), PODMAN_ID),
+            Some(ContainerInfo {
+                runtime: ContainerRuntime::Podman,
+                id: PODMAN_ID.to_string(),
+                name: "db".to_string(),
+                image: Some("docker.io/library/postgres:16".to_string()),
+                pod: None,
+            })
+        );
+        assert_eq!(podman(&fixtures("podman/containers.json"), DOCKER_ID), None);
+        assert_eq!(podman(&fixtures("podman/missing.json"), PODMAN_ID), None);
+    }
+
+    #[test]
+    fn containerd_searches_every_namespace() {
+        assert_eq!(
+            containerd(&fixtures("containerd"), CONTAINERD_ID),
+            Some(ContainerInfo {
+                runtime: ContainerRuntime::Containerd,
+                id: CONTAINERD_ID.to_string(),
+                name: "coredns".to_string(),
+                image: Some("registry.k8s.io/coredns/coredns:v1.11.1".to_string()),
+                pod: Some("kube-system/coredns-5dd5756b68-x7k2p".to_string()),
+            })
+        );
+        assert_eq!(
+            containerd(&fixtures("containerd"), NERDCTL_ID),
+            Some(ContainerInfo {
+                runtime: ContainerRuntime::Containerd,
+                id: NERDCTL_ID.to_string(),
+                name: "cache".to_string(),
+                image: None,
+                pod: None,
+            })
+        );
+        assert_eq!(containerd(&fixtures("containerd"), CRIO_ID), None);
+    }
+
+    #[test]
+    fn crio_reads_userdata() {
+        assert_eq!(
+            crio(&fixtures("crio"), CRIO_ID),
+            Some(ContainerInfo {
+                runtime: ContainerRuntime::CriO,
+                id: CRIO_ID.to_string(),
+                name: "kube-proxy".to_string(),
+                image: Some("registry.k8s.io/kube-proxy:v1.29.4".to_string()),
+                pod: Some("kube-system/kube-proxy-9xq4z".to_string()),
+            })
+        );
+        // A kubepods cgroup without a runtime prefix is tried as containerd first
+        assert_eq!(containerd(&fixtures("containerd"), CRIO_ID), None);
+    }
+}
@@ -0,0 +1,935 @@
+//! DHCPv4 lease inspection.
+//!
+//! Each client keeps its lease somewhere else and in its own format: dhclient
+//! writes `lease { ... }` blocks, dhcpcd stores the raw DHCP message,
+//! systemd-networkd and NetworkManager's internal client write `KEY=VALUE`
+//! files. All of them are turned into a `DhcpLease`.
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The DHCP client that owns a lease, and so the one to renew or release it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DhcpClient {
+    Dhclient,
+    Dhcpcd,
+    NetworkManager,
+    Networkd,
+    /// macOS, where configd runs DHCP for every service
+    Configd,
+}
+
+impl DhcpClient {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DhcpClient::Dhclient => "dhclient",
+            DhcpClient::Dhcpcd => "dhcpcd",
+            DhcpClient::NetworkManager => "NetworkManager",
+            DhcpClient::Networkd => "systemd-networkd",
+            DhcpClient::Configd => "configd",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DhcpLease {
+    pub address: Option<Ipv4Addr>,
+    pub subnet_mask: Option<Ipv4Addr>,
+    pub server: Option<Ipv4Addr>,
+    pub routers: Vec<Ipv4Addr>,
+    pub dns: Vec<Ipv4Addr>,
+    pub domain: Option<String>,
+    pub ntp: Vec<Ipv4Addr>,
+    pub mtu: Option<u16>,
+    /// RFC 3442 routes as `destination/prefix via gateway`
+    pub classless_routes: Vec<String>,
+    /// Lease time granted by the server, in seconds
+    pub lease_time: Option<u32>,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl DhcpLease {
+    /// Time left on the lease, zero once it has expired
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires
+            .map(|expires| (expires - Utc::now()).max(Duration::zero()))
+    }
+}
+
+/// Find the current lease of `interface`, along with the client that holds it
+pub fn find_lease(interface: &str) -> Option<(DhcpClient, DhcpLease)> {
+    #[cfg(target_os = "macos")]
+    if let Some(lease) = getpacket(interface) {
+        return Some((DhcpClient::Configd, lease));
+    }
+
+    lease_files(interface)
+        .into_iter()
+        .filter_map(|(client, path)| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            let lease = read_lease(&path, interface, modified)?;
+            Some((modified, client, lease))
+        })
+        // Several clients may have left leases behind; the newest one is live
+        .max_by_key(|(modified, ..)| *modified)
+        .map(|(_, client, lease)| (client, lease))
+}
+
+/// `ipconfig getpacket` prints the DHCPACK, e.g. `router (ip_mult): {192.168.1.1}`
+#[cfg(target_os = "macos")]
+fn getpacket(interface: &str) -> Option<DhcpLease> {
+    let output = std::process::Command::new("ipconfig")
+        .args(["getpacket", interface])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut lease = DhcpLease::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" = ").or_else(|| line.split_once(": ")) else {
+            continue;
+        };
+        let key = key.split_whitespace().next().unwrap_or_default();
+        let value = value.trim().trim_matches(|c| c == '{' || c == '}');
+
+        match key {
+            "yiaddr" => lease.address = value.parse().ok(),
+            "subnet_mask" => lease.subnet_mask = value.parse().ok(),
+            "server_identifier" => lease.server = value.parse().ok(),
+            "router" => lease.routers = parse_addresses(value),
+            "domain_name_server" => lease.dns = parse_addresses(value),
+            "domain_name" => lease.domain = Some(value.to_string()),
+            "ntp_server" => lease.ntp = parse_addresses(value),
+            "interface_mtu" => lease.mtu = parse_number(value).map(|mtu| mtu as u16),
+            "lease_time" => lease.lease_time = parse_number(value),
+            _ => {}
+        }
+    }
+
+    // No bind time is printed, so count down from now
+    lease.expires = lease
+        .lease_time
+        .map(|secs| Utc::now() + Duration::seconds(secs as i64));
+    lease.address.map(|_| lease)
+}
+
+#[cfg(target_os = "macos")]
+fn parse_number(value: &str) -> Option<u32> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// Candidate lease files for `interface`, per client
+fn lease_files(interface: &str) -> Vec<(DhcpClient, PathBuf)> {
+    let mut files = Vec::new();
+
+    for dir in ["/var/lib/dhcp", "/var/lib/dhclient", "/var/db"] {
+        for path in list_dir(dir) {
+            if is_dhclient_lease_file(file_name(&path), interface) {
+                files.push((DhcpClient::Dhclient, path));
+            }
+        }
+    }
+
+    for path in [
+        format!("/var/lib/dhcpcd/{}.lease", interface),
+        format!("/var/lib/dhcpcd5/dhcpcd-{}.lease", interface),
+        format!("/var/lib/dhcpcd/dhcpcd-{}.lease", interface),
+        format!("/var/db/dhcpcd/{}.lease", interface),
+        format!("/var/db/dhcpcd-{}.lease", interface),
+    ] {
+        files.push((DhcpClient::Dhcpcd, PathBuf::from(path)));
+    }
+
+    // internal-<uuid>-<iface>.lease or dhclient-<uuid>-<iface>.lease
+    let suffix = format!("-{}.lease", interface);
+    for path in list_dir("/var/lib/NetworkManager") {
+        if file_name(&path).ends_with(&suffix) {
+            files.push((DhcpClient::NetworkManager, path));
+        }
+    }
+
+    if let Ok(index) = nix::net::if_::if_nametoindex(interface) {
+        files.push((
+            DhcpClient::Networkd,
+            PathBuf::from(format!("/run/systemd/netif/leases/{}", index)),
+        ));
+    }
+
+    files
+}
+
+/// `dhclient-<iface>.leases` (Debian), `dhclient.<iface>.leases` (the BSDs)
+/// or `dhclient6-<iface>.leases`, and no other interface's
+fn is_dhclient_lease_file(name: &str, interface: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".leases") else {
+        return false;
+    };
+    ["dhclient-", "dhclient.", "dhclient6-"]
+        .iter()
+        .any(|prefix| stem.strip_prefix(prefix) == Some(interface))
+}
+
+fn list_dir(dir: &str) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+}
+
+fn read_lease(path: &Path, interface: &str, modified: SystemTime) -> Option<DhcpLease> {
+    let data = std::fs::read(path).ok()?;
+
+    let mut lease = if data.starts_with(b"lease") || contains(&data, b"\nlease {") {
+        parse_dhclient(&String::from_utf8_lossy(&data), interface)?
+    } else if data.len() > 240 && data[236..240] == DHCP_MAGIC_COOKIE {
+        parse_dhcp_message(&data)?
+    } else {
+        parse_key_value(&String::from_utf8_lossy(&data))?
+    };
+
+    // Only dhclient records the expiry; the others are written when the lease is bound
+    if lease.expires.is_none() {
+        if let Some(lease_time) = lease.lease_time {
+            let bound: DateTime<Utc> = modified.into();
+            lease.expires = Some(bound + Duration::seconds(lease_time as i64));
+        }
+    }
+
+    Some(lease)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+fn parse_addresses(value: &str) -> Vec<Ipv4Addr> {
+    value
+        .split([',', ' '])
+        .filter_map(|addr| addr.trim().parse().ok())
+        .collect()
+}
+
+/// Parse the last `lease { ... }` block for `interface` from a dhclient file
+fn parse_dhclient(content: &str, interface: &str) -> Option<DhcpLease> {
+    let mut current: Option<(Option<String>, DhcpLease)> = None;
+    let mut last = None;
+
+    for line in content.lines() {
+        let line = line.trim().trim_end_matches(';');
+
+        // DHCPv6 `lease6` blocks nest and hold no IPv4 lease
+        if line == "lease {" {
+            current = Some((None, DhcpLease::default()));
+            continue;
+        }
+        if line == "}" {
+            if let Some((iface, lease)) = current.take() {
+                if iface.as_deref().is_none_or(|iface| iface == interface) {
+                    last = Some(lease);
+                }
+            }
+            continue;
+        }
+
+        let Some((iface, lease)) = current.as_mut() else {
+            continue;
+        };
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let value = value.trim();
+
+        match key {
+            "interface" => *iface = Some(value.trim_matches('"').to_string()),
+            "fixed-address" => lease.address = value.parse().ok(),
+            "expire" => lease.expires = parse_dhclient_time(value),
+            "option" => {
+                let (option, value) = value.split_once(' ').unwrap_or((value, ""));
+                let value = value.trim();
+                match option {
+                    "subnet-mask" => lease.subnet_mask = value.parse().ok(),
+                    "routers" => lease.routers = parse_addresses(value),
+                    "domain-name-servers" => lease.dns = parse_addresses(value),
+                    "domain-name" => lease.domain = Some(value.trim_matches('"').to_string()),
+                    "ntp-servers" => lease.ntp = parse_addresses(value),
+                    "interface-mtu" => lease.mtu = value.parse().ok(),
+                    "dhcp-server-identifier" => lease.server = value.parse().ok(),
+                    "dhcp-lease-time" => lease.lease_time = value.parse().ok(),
+                    "rfc3442-classless-static-routes" => {
+                        let bytes: Vec<u8> = value
+                            .split(',')
+                            .filter_map(|b| b.trim().parse().ok())
+                            .collect();
+                        lease.classless_routes = decode_classless_routes(&bytes);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    last
+}
+
+/// `3 2024/01/02 03:04:05` in UTC, or `epoch 1704164645` with db-time-format local
+fn parse_dhclient_time(value: &str) -> Option<DateTime<Utc>> {
+    let mut parts = value.split_whitespace();
+    match parts.next()? {
+        "epoch" => DateTime::from_timestamp(parts.next()?.parse().ok()?, 0),
+        "never" => None,
+        _ => {
+            let rest = parts.collect::<Vec<_>>().join(" ");
+            NaiveDateTime::parse_from_str(&rest, "%Y/%m/%d %H:%M:%S")
+                .ok()
+                .map(|time| time.and_utc())
+        }
+    }
+}
+
+/// systemd-networkd (and NetworkManager's internal client) lease format
+fn parse_key_value(content: &str) -> Option<DhcpLease> {
+    let mut lease = DhcpLease::default();
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+
+        match key.trim() {
+            "ADDRESS" => lease.address = value.parse().ok(),
+            "NETMASK" => lease.subnet_mask = value.parse().ok(),
+            "SERVER_ADDRESS" => lease.server = value.parse().ok(),
+            "ROUTER" => lease.routers = parse_addresses(value),
+            "DNS" => lease.dns = parse_addresses(value),
+            "NTP" => lease.ntp = parse_addresses(value),
+            "DOMAINNAME" => lease.domain = Some(value.to_string()),
+            "MTU" => lease.mtu = value.parse().ok(),
+            "LIFETIME" => lease.lease_time = value.parse().ok(),
+            // `10.0.0.0/24,192.168.1.254 ...`
+            "CLASSLESS_ROUTES" | "ROUTES" => {
+                lease.classless_routes = value
+                    .split_whitespace()
+                    .filter_map(|route| route.split_once(','))
+                    .map(|(dest, gw)| format!("{} via {}", dest, gw))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    lease.address.map(|_| lease)
+}
+
+const DHCP_MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+
+/// Walk the options of a DHCP message as (code, value) pairs
+fn dhcp_options(data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut found = Vec::new();
+    let mut options = data.get(240..).unwrap_or_default();
+
+    while let [code, rest @ ..] = options {
+        match *code {
+            0 => {
+                options = rest;
+                continue;
+            }
+            255 => break,
+            _ => {}
+        }
+        let [len, rest @ ..] = rest else {
+            break;
+        };
+        let Some(value) = rest.get(..*len as usize) else {
+            break;
+        };
+
+        found.push((*code, value));
+        options = &rest[*len as usize..];
+    }
+
+    found
+}
+
+fn ipv4(bytes: &[u8]) -> Option<Ipv4Addr> {
+    let octets: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    Some(Ipv4Addr::from(octets))
+}
+
+/// dhcpcd stores the DHCPACK it received as is
+fn parse_dhcp_message(data: &[u8]) -> Option<DhcpLease> {
+    let ipv4_list =
+        |bytes: &[u8]| -> Vec<Ipv4Addr> { bytes.chunks_exact(4).filter_map(ipv4).collect() };
+
+    let mut lease = DhcpLease {
+        address: ipv4(data.get(16..20)?).filter(|addr| !addr.is_unspecified()),
+        ..Default::default()
+    };
+
+    for (code, value) in dhcp_options(data) {
+        match code {
+            1 => lease.subnet_mask = ipv4(value),
+            3 => lease.routers = ipv4_list(value),
+            6 => lease.dns = ipv4_list(value),
+            15 => {
+                lease.domain = Some(
+                    String::from_utf8_lossy(value)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                )
+            }
+            26 if value.len() == 2 => lease.mtu = Some(u16::from_be_bytes([value[0], value[1]])),
+            42 => lease.ntp = ipv4_list(value),
+            51 if value.len() == 4 => {
+                lease.lease_time =
+                    Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+            }
+            54 => lease.server = ipv4(value),
+            121 => lease.classless_routes = decode_classless_routes(value),
+            _ => {}
+        }
+    }
+
+    Some(lease)
+}
+
+/// Decode option 121: width, significant destination octets, then the router
+fn decode_classless_routes(mut bytes: &[u8]) -> Vec<String> {
+    let mut routes = Vec::new();
+
+    while let [width, rest @ ..] = bytes {
+        let significant = (*width as usize).div_ceil(8);
+        if *width > 32 || rest.len() < significant + 4 {
+            break;
+        }
+
+        let mut destination = [0u8; 4];
+        destination[..significant].copy_from_slice(&rest[..significant]);
+        let gateway = Ipv4Addr::new(
+            rest[significant],
+            rest[significant + 1],
+            rest[significant + 2],
+            rest[significant + 3],
+        );
+
+        routes.push(format!(
+            "{}/{} via {}",
+            Ipv4Addr::from(destination),
+            width,
+            gateway
+        ));
+        bytes = &rest[significant + 4..];
+    }
+
+    routes
+}
+
+/// One server's answer to the DHCPDISCOVER sent by `probe`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DhcpOffer {
+    /// Sender of the offer, the relay agent when it was relayed
+    pub source: Ipv4Addr,
+    /// `sname` field, if the server fills it in
+    pub server_name: Option<String>,
+    /// Relay agent that forwarded the DISCOVER (`giaddr`)
+    pub relay: Option<Ipv4Addr>,
+    /// Offered address and options
+    pub lease: DhcpLease,
+}
+
+impl DhcpOffer {
+    /// The server identifier, falling back to the sender
+    pub fn server(&self) -> Ipv4Addr {
+        self.lease.server.unwrap_or(self.source)
+    }
+}
+
+/// Options asked for in the DISCOVER, i.e. the ones a `DhcpLease` holds
+const REQUESTED_OPTIONS: [u8; 9] = [1, 3, 6, 15, 26, 42, 51, 54, 121];
+
+/// Broadcast a DHCPDISCOVER on `interface` and collect every DHCPOFFER that
+/// arrives within `timeout`. No DHCPREQUEST follows, so no lease is taken.
+#[cfg(target_os = "linux")]
+pub fn probe(interface: &str, timeout: std::time::Duration) -> anyhow::Result<Vec<DhcpOffer>> {
+    use anyhow::Context;
+    use nix::sys::socket::{
+        bind, setsockopt, socket, sockopt, AddressFamily, SockFlag, SockType, SockaddrIn,
+    };
+    use std::io::ErrorKind;
+    use std::os::fd::AsRawFd;
+    use std::time::Instant;
+
+    let mac = crate::link::get_links()?
+        .into_iter()
+        .find(|link| link.name == interface)
+        .with_context(|| format!("Interface {} not found", interface))?
+        .mac
+        .map(|mac| mac.0)
+        .filter(|mac| mac.iter().any(|&b| b != 0))
+        .with_context(|| format!("{} has no Ethernet address to probe with", interface))?;
+
+    let fd = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )?;
+    // dhclient keeps its own socket on port 68 with SO_REUSEADDR set
+    setsockopt(&fd, sockopt::ReuseAddr, &true)?;
+    setsockopt(&fd, sockopt::Broadcast, &true)?;
+    setsockopt(&fd, sockopt::BindToDevice, &interface.into())
+        .with_context(|| format!("Cannot bind to {}", interface))?;
+    bind(fd.as_raw_fd(), &SockaddrIn::new(0, 0, 0, 0, 68))
+        .context("Cannot listen on UDP port 68")?;
+    let socket = std::net::UdpSocket::from(fd);
+
+    let xid = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|now| now.subsec_nanos())
+        .unwrap_or_default()
+        ^ std::process::id();
+    socket
+        .send_to(&discover_message(xid, mac), (Ipv4Addr::BROADCAST, 67))
+        .context("Failed to send DHCPDISCOVER")?;
+
+    let deadline = Instant::now() + timeout;
+    let mut offers: Vec<DhcpOffer> = Vec::new();
+    let mut buf = [0u8; 1500];
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).context("Failed to receive DHCP offers"),
+        };
+        let std::net::SocketAddr::V4(from) = from else {
+            continue;
+        };
+
+        let Some(offer) = parse_offer(&buf[..len], xid, &mac, *from.ip()) else {
+            continue;
+        };
+        // Some servers send the same offer more than once
+        if !offers.contains(&offer) {
+            offers.push(offer);
+        }
+    }
+
+    Ok(offers)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn probe(_interface: &str, _timeout: std::time::Duration) -> anyhow::Result<Vec<DhcpOffer>> {
+    anyhow::bail!("DHCP probing is only supported on Linux")
+}
+
+#[cfg(target_os = "linux")]
+fn discover_message(xid: u32, mac: [u8; 6]) -> Vec<u8> {
+    let mut message = vec![0u8; 236];
+    message[0] = 1; // BOOTREQUEST
+    message[1] = 1; // Ethernet
+    message[2] = 6;
+    message[4..8].copy_from_slice(&xid.to_be_bytes());
+    // We have no address yet, so ask for the offer to be broadcast
+    message[10] = 0x80;
+    message[28..34].copy_from_slice(&mac);
+
+    message.extend_from_slice(&DHCP_MAGIC_COOKIE);
+    message.extend_from_slice(&[53, 1, 1]);
+    message.extend_from_slice(&[55, REQUESTED_OPTIONS.len() as u8]);
+    message.extend_from_slice(&REQUESTED_OPTIONS);
+    message.push(255);
+    // Pad to the BOOTP minimum; some relays drop shorter messages
+    message.resize(300, 0);
+    message
+}
+
+/// Accept a DHCPOFFER answering our DISCOVER, ignoring other traffic on port 68
+#[cfg(target_os = "linux")]
+fn parse_offer(data: &[u8], xid: u32, mac: &[u8; 6], source: Ipv4Addr) -> Option<DhcpOffer> {
+    if data.len() <= 240
+        || data[0] != 2
+        || data[4..8] != xid.to_be_bytes()
+        || data[28..34] != mac[..]
+        || data[236..240] != DHCP_MAGIC_COOKIE
+    {
+        return None;
+    }
+    let is_offer = dhcp_options(data)
+        .iter()
+        .any(|(code, value)| *code == 53 && *value == [2]);
+    if !is_offer {
+        return None;
+    }
+
+    let server_name = String::from_utf8_lossy(&data[44..108])
+        .split('\0')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    Some(DhcpOffer {
+        source,
+        server_name: Some(server_name).filter(|name| !name.is_empty()),
+        relay: ipv4(&data[24..28]).filter(|relay| !relay.is_unspecified()),
+        lease: parse_dhcp_message(data)?,
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    const XID: u32 = 0x1234_5678;
+    const MAC: [u8; 6] = [0xc6, 0x17, 0x4a, 0xf7, 0x45, 0x2a];
+    const SOURCE: Ipv4Addr = Ipv4Addr::new(10, 99, 0, 1);
+
+    /// A DHCPOFFER as sent by the test responder: 10.99.0.50 from 10.99.0.1
+    fn offer_message(options: &[u8]) -> Vec<u8> {
+        let mut message = vec![0u8; 236];
+        message[0] = 2; // BOOTREPLY
+        message[1] = 1;
+        message[2] = 6;
+        message[4..8].copy_from_slice(&XID.to_be_bytes());
+        message[10] = 0x80;
+        message[16..20].copy_from_slice(&[10, 99, 0, 50]);
+        message[20..24].copy_from_slice(&[10, 99, 0, 1]);
+        message[28..34].copy_from_slice(&MAC);
+        message[44..52].copy_from_slice(b"fakesrv\0");
+        message.extend_from_slice(&DHCP_MAGIC_COOKIE);
+        message.extend_from_slice(options);
+        message
+    }
+
+    const OFFER_OPTIONS: &[u8] = &[
+        53, 1, 2, // DHCPOFFER
+        54, 4, 10, 99, 0, 1, // server identifier
+        1, 4, 255, 255, 255, 0, // subnet mask
+        3, 4, 10, 99, 0, 1, // router
+        6, 8, 1, 1, 1, 1, 8, 8, 8, 8, // DNS
+        15, 8, b'l', b'a', b'b', b'.', b't', b'e', b's', b't', // domain
+        51, 4, 0, 0, 0x0e, 0x10, // lease time 3600
+        26, 2, 0x05, 0x78, // MTU 1400
+        121, 8, 24, 10, 50, 0, 10, 99, 0, 1, // 10.50.0.0/24 via 10.99.0.1
+        255,
+    ];
+
+    #[test]
+    fn discover_message_layout() {
+        let message = discover_message(XID, MAC);
+
+        assert_eq!(message.len(), 300);
+        assert_eq!(&message[..4], &[1, 1, 6, 0]);
+        assert_eq!(&message[4..8], &XID.to_be_bytes());
+        assert_eq!(&message[8..10], &[0, 0]);
+        assert_eq!(&message[10..12], &[0x80, 0]);
+        assert!(message[12..28].iter().all(|&b| b == 0));
+        assert_eq!(&message[28..34], &MAC);
+        assert!(message[34..236].iter().all(|&b| b == 0));
+        assert_eq!(&message[236..240], &DHCP_MAGIC_COOKIE);
+
+        let options: Vec<(u8, &[u8])> = dhcp_options(&message);
+        assert_eq!(options, vec![(53, &[1][..]), (55, &REQUESTED_OPTIONS[..])],);
+        let end = 240 + 3 + 2 + REQUESTED_OPTIONS.len();
+        assert_eq!(message[end], 255);
+        assert!(message[end + 1..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn parse_offer_reads_lease() {
+        let offer = parse_offer(&offer_message(OFFER_OPTIONS), XID, &MAC, SOURCE).unwrap();
+
+        assert_eq!(offer.source, SOURCE);
+        assert_eq!(offer.server_name.as_deref(), Some("fakesrv"));
+        assert_eq!(offer.relay, None);
+        assert_eq!(offer.server(), SOURCE);
+        assert_eq!(
+            offer.lease,
+            DhcpLease {
+                address: Some(Ipv4Addr::new(10, 99, 0, 50)),
+                subnet_mask: Some(Ipv4Addr::new(255, 255, 255, 0)),
+                server: Some(SOURCE),
+                routers: vec![SOURCE],
+                dns: vec![Ipv4Addr::new(1, 1, 1, 1), Ipv4Addr::new(8, 8, 8, 8)],
+                domain: Some("lab.test".to_string()),
+                ntp: Vec::new(),
+                mtu: Some(1400),
+                classless_routes: vec!["10.50.0.0/24 via 10.99.0.1".to_string()],
+                lease_time: Some(3600),
+                expires: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_offer_reports_relay() {
+        let mut data = offer_message(OFFER_OPTIONS);
+        data[24..28].copy_from_slice(&[192, 0, 2, 254]);
+
+        let offer = parse_offer(&data, XID, &MAC, Ipv4Addr::new(192, 0, 2, 254)).unwrap();
+        assert_eq!(offer.relay, Some(Ipv4Addr::new(192, 0, 2, 254)));
+        // The server identifier wins over the relay that sent it
+        assert_eq!(offer.server(), SOURCE);
+    }
+
+    #[test]
+    fn parse_offer_ignores_other_traffic() {
+        let data = offer_message(OFFER_OPTIONS);
+        assert!(parse_offer(&data, XID + 1, &MAC, SOURCE).is_none());
+        assert!(parse_offer(&data, XID, &[0; 6], SOURCE).is_none());
+
+        let mut request = data.clone();
+        request[0] = 1;
+        assert!(parse_offer(&request, XID, &MAC, SOURCE).is_none());
+
+        let mut cookie = data.clone();
+        cookie[236] = 0;
+        assert!(parse_offer(&cookie, XID, &MAC, SOURCE).is_none());
+
+        // A DHCPACK for someone else's exchange, and no message type at all
+        let ack = offer_message(&[53, 1, 5, 255]);
+        assert!(parse_offer(&ack, XID, &MAC, SOURCE).is_none());
+        let untyped = offer_message(&[54, 4, 10, 99, 0, 1, 255]);
+        assert!(parse_offer(&untyped, XID, &MAC, SOURCE).is_none());
+    }
+
+    #[test]
+    fn parse_offer_rejects_truncated_messages() {
+        let data = offer_message(OFFER_OPTIONS);
+        for len in [0, 1, 33, 236, 239, 240] {
+            assert!(
+                parse_offer(&data[..len], XID, &MAC, SOURCE).is_none(),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn parse_offer_stops_at_malformed_options() {
+        // Option 6 claims more bytes than are left; everything before it is kept
+        let offer = parse_offer(
+            &offer_message(&[0, 0, 53, 1, 2, 54, 4, 10, 99, 0, 1, 6, 200, 1, 1, 1, 1]),
+            XID,
+            &MAC,
+            SOURCE,
+        )
+        .unwrap();
+        assert_eq!(offer.lease.server, Some(SOURCE));
+        assert!(offer.lease.dns.is_empty());
+
+        // Options without a length byte or with the wrong size are dropped
+        let offer = parse_offer(
+            &offer_message(&[53, 1, 2, 51, 2, 0, 60, 26, 3, 1, 2, 3, 1, 3, 255, 0, 0, 54]),
+            XID,
+            &MAC,
+            SOURCE,
+        )
+        .unwrap();
+        assert_eq!(offer.lease.lease_time, None);
+        assert_eq!(offer.lease.mtu, None);
+        assert_eq!(offer.lease.subnet_mask, None);
+        assert_eq!(offer.lease.server, None);
+    }
+
+    #[test]
+    fn classless_routes_stop_at_bad_entries() {
+        // Default route, then a width over 32
+        assert_eq!(
+            decode_classless_routes(&[0, 10, 99, 0, 1, 33, 1, 2, 3, 4, 5, 6, 7, 8]),
+            vec!["0.0.0.0/0 via 10.99.0.1".to_string()]
+        );
+        // Router cut short
+        assert!(decode_classless_routes(&[8, 10, 10, 99, 0]).is_empty());
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/dhcp")
+            .join(name)
+    }
+
+    fn read_fixture(name: &str) -> String {
+        std::fs::read_to_string(fixture(name)).unwrap()
+    }
+
+    #[test]
+    fn dhclient_lease_file_names() {
+        for name in [
+            "dhclient-eth1.leases",
+            "dhclient.eth1.leases",
+            "dhclient6-eth1.leases",
+        ] {
+            assert!(is_dhclient_lease_file(name, "eth1"), "{}", name);
+        }
+        for name in [
+            "dhclient-eth10.leases",
+            "dhclient-eth0.leases",
+            "dhclient.leases",
+            "dhclient6.leases",
+            "dhclient-eth1.lease",
+            "dhclient-eth1.leases~",
+            "dhcpcd-eth1.lease",
+        ] {
+            assert!(!is_dhclient_lease_file(name, "eth1"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn dhclient_last_lease_wins() {
+        let lease = parse_dhclient(&read_fixture("dhclient.leases"), "eth0").unwrap();
+
+        assert_eq!(
+            lease,
+            DhcpLease {
+                address: Some(Ipv4Addr::new(192, 168, 1, 77)),
+                subnet_mask: Some(Ipv4Addr::new(255, 255, 255, 0)),
+                server: Some(Ipv4Addr::new(192, 168, 1, 1)),
+                routers: vec![Ipv4Addr::new(192, 168, 1, 1), Ipv4Addr::new(192, 168, 1, 2)],
+                dns: vec![Ipv4Addr::new(192, 168, 1, 1), Ipv4Addr::new(9, 9, 9, 9)],
+                domain: Some("home.example".to_string()),
+                ntp: vec![Ipv4Addr::new(192, 168, 1, 123)],
+                mtu: Some(1492),
+                classless_routes: vec![
+                    "10.50.0.0/24 via 192.168.1.254".to_string(),
+                    "0.0.0.0/0 via 192.168.1.1".to_string(),
+                ],
+                lease_time: Some(3600),
+                expires: DateTime::from_timestamp(1792406700, 0),
+            }
+        );
+    }
+
+    #[test]
+    fn dhclient_picks_the_interface() {
+        let content = read_fixture("dhclient.leases");
+
+        let lease = parse_dhclient(&content, "eth10").unwrap();
+        assert_eq!(lease.address, Some(Ipv4Addr::new(10, 10, 0, 9)));
+        assert_eq!(lease.routers, vec![Ipv4Addr::new(10, 10, 0, 1)]);
+        assert_eq!(lease.expires, DateTime::from_timestamp(1792400000, 0));
+
+        assert_eq!(parse_dhclient(&content, "eth1"), None);
+    }
+
+    #[test]
+    fn dhclient_times() {
+        assert_eq!(
+            parse_dhclient_time("4 2024/01/04 03:04:05"),
+            DateTime::from_timestamp(1704337445, 0)
+        );
+        assert_eq!(
+            parse_dhclient_time("epoch 1704337445"),
+            DateTime::from_timestamp(1704337445, 0)
+        );
+        assert_eq!(parse_dhclient_time("never"), None);
+        assert_eq!(parse_dhclient_time("4 2024/13/04 03:04:05"), None);
+        assert_eq!(parse_dhclient_time(""), None);
+    }
+
+    #[test]
+    fn networkd_lease() {
+        let lease = parse_key_value(&read_fixture("networkd.lease")).unwrap();
+
+        assert_eq!(
+            lease,
+            DhcpLease {
+                address: Some(Ipv4Addr::new(10, 0, 2, 15)),
+                subnet_mask: Some(Ipv4Addr::new(255, 255, 255, 0)),
+                server: Some(Ipv4Addr::new(10, 0, 2, 2)),
+                routers: vec![Ipv4Addr::new(10, 0, 2, 2)],
+                dns: vec![Ipv4Addr::new(10, 0, 2, 3), Ipv4Addr::new(1, 1, 1, 1)],
+                domain: Some("lan.example".to_string()),
+                ntp: vec![Ipv4Addr::new(10, 0, 2, 123)],
+                mtu: Some(1500),
+                classless_routes: vec![
+                    "10.20.0.0/16 via 10.0.2.254".to_string(),
+                    "0.0.0.0/0 via 10.0.2.2".to_string(),
+                ],
+                lease_time: Some(86400),
+                expires: None,
+            }
+        );
+
+        assert_eq!(
+            parse_key_value("NETMASK=255.255.255.0\nLIFETIME=60\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn dhcpcd_lease() {
+        let data = std::fs::read(fixture("dhcpcd-eth0.lease")).unwrap();
+        let lease = parse_dhcp_message(&data).unwrap();
+
+        assert_eq!(
+            lease,
+            DhcpLease {
+                address: Some(Ipv4Addr::new(172, 16, 5, 33)),
+                subnet_mask: Some(Ipv4Addr::new(255, 255, 254, 0)),
+                server: Some(Ipv4Addr::new(172, 16, 5, 1)),
+                routers: vec![Ipv4Addr::new(172, 16, 5, 1)],
+                dns: vec![Ipv4Addr::new(172, 16, 5, 1), Ipv4Addr::new(8, 8, 4, 4)],
+                domain: Some("corp.example".to_string()),
+                ntp: vec![Ipv4Addr::new(172, 16, 5, 2)],
+                mtu: Some(9000),
+                classless_routes: vec![
+                    "10.30.0.0/16 via 172.16.5.254".to_string(),
+                    "0.0.0.0/0 via 172.16.5.1".to_string(),
+                ],
+                lease_time: Some(7200),
+                expires: None,
+            }
+        );
+
+        // Cut inside the options: what was read before the cut is kept
+        let lease = parse_dhcp_message(&data[..0x105]).unwrap();
+        assert_eq!(lease.address, Some(Ipv4Addr::new(172, 16, 5, 33)));
+        assert_eq!(lease.subnet_mask, Some(Ipv4Addr::new(255, 255, 254, 0)));
+        assert!(lease.routers.is_empty());
+        assert!(lease.dns.is_empty());
+
+        assert_eq!(parse_dhcp_message(&data[..16]), None);
+    }
+
+    #[test]
+    fn read_lease_detects_the_format() {
+        let bound = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1792396800);
+        let bound_at = DateTime::from_timestamp(1792396800, 0).unwrap();
+
+        let lease = read_lease(&fixture("dhclient.leases"), "eth0", bound).unwrap();
+        assert_eq!(lease.address, Some(Ipv4Addr::new(192, 168, 1, 77)));
+        assert_eq!(lease.expires, DateTime::from_timestamp(1792406700, 0));
+
+        let lease = read_lease(&fixture("dhcpcd-eth0.lease"), "eth0", bound).unwrap();
+        assert_eq!(lease.address, Some(Ipv4Addr::new(172, 16, 5, 33)));
+        assert_eq!(lease.expires, Some(bound_at + Duration::seconds(7200)));
+
+        let lease = read_lease(&fixture("networkd.lease"), "eth0", bound).unwrap();
+        assert_eq!(lease.address, Some(Ipv4Addr::new(10, 0, 2, 15)));
+        assert_eq!(lease.expires, Some(bound_at + Duration::seconds(86400)));
+    }
+}
@@ -0,0 +1,560 @@
+//! Link settings and offloads from the ethtool netlink family.
+//!
+//! The `ethtool` generic netlink family (Linux 5.6+) reports speed, duplex,
+//! autonegotiation, port and link state, and reads and changes offload
+//! features by name. Driver and firmware versions and the driver's own
+//! statistics only exist behind the older `SIOCETHTOOL` ioctl. Reading works
+//! for everyone; changing a feature needs CAP_NET_ADMIN.
+
+use crate::models::{DriverInfo, Duplex, EthtoolInfo, Offload, OFFLOAD_FEATURES};
+use crate::netlink::{self, Netlink, NLA_F_NESTED, NLM_F_ACK};
+use anyhow::{Context, Result};
+use nix::libc;
+use nix::sys::socket::{socket, AddressFamily, SockFlag, SockType};
+use std::collections::HashSet;
+use std::os::fd::AsRawFd;
+
+const ETHTOOL_MSG_LINKINFO_GET: u8 = 2;
+const ETHTOOL_MSG_LINKMODES_GET: u8 = 4;
+const ETHTOOL_MSG_LINKSTATE_GET: u8 = 6;
+const ETHTOOL_MSG_FEATURES_GET: u8 = 11;
+const ETHTOOL_MSG_FEATURES_SET: u8 = 12;
+
+const ETHTOOL_A_HEADER_DEV_NAME: u16 = 2;
+const ETHTOOL_A_HEADER_FLAGS: u16 = 3;
+
+/// Bitsets as raw words instead of one nested attribute per named bit
+const ETHTOOL_FLAG_COMPACT_BITSETS: u32 = 1;
+
+/// Every request and reply starts with the device header
+const ETHTOOL_A_HEADER: u16 = 1;
+
+const ETHTOOL_A_LINKINFO_PORT: u16 = 2;
+
+const ETHTOOL_A_LINKMODES_AUTONEG: u16 = 2;
+const ETHTOOL_A_LINKMODES_SPEED: u16 = 5;
+const ETHTOOL_A_LINKMODES_DUPLEX: u16 = 6;
+
+const ETHTOOL_A_LINKSTATE_LINK: u16 = 2;
+
+const ETHTOOL_A_FEATURES_HW: u16 = 2;
+const ETHTOOL_A_FEATURES_WANTED: u16 = 3;
+const ETHTOOL_A_FEATURES_ACTIVE: u16 = 4;
+const ETHTOOL_A_FEATURES_NOCHANGE: u16 = 5;
+
+const ETHTOOL_A_BITSET_NOMASK: u16 = 1;
+const ETHTOOL_A_BITSET_BITS: u16 = 3;
+const ETHTOOL_A_BITSET_BITS_BIT: u16 = 1;
+const ETHTOOL_A_BITSET_BIT_NAME: u16 = 2;
+const ETHTOOL_A_BITSET_BIT_VALUE: u16 = 3;
+
+const SPEED_UNKNOWN: u32 = u32::MAX;
+const DUPLEX_HALF: u8 = 0;
+const DUPLEX_FULL: u8 = 1;
+
+const ETHTOOL_GDRVINFO: u32 = 0x3;
+const ETHTOOL_GSTRINGS: u32 = 0x1b;
+const ETHTOOL_GSTATS: u32 = 0x1d;
+
+/// The string set naming the counters of `ETHTOOL_GSTATS`
+const ETH_SS_STATS: u32 = 1;
+const ETH_GSTRING_LEN: usize = 32;
+
+/// `struct ethtool_drvinfo`
+#[repr(C)]
+struct EthtoolDrvinfo {
+    cmd: u32,
+    driver: [u8; 32],
+    version: [u8; 32],
+    fw_version: [u8; 32],
+    bus_info: [u8; 32],
+    erom_version: [u8; 32],
+    reserved2: [u8; 12],
+    n_priv_flags: u32,
+    n_stats: u32,
+    testinfo_len: u32,
+    eedump_len: u32,
+    regdump_len: u32,
+}
+
+nix::ioctl_readwrite_bad!(siocethtool, libc::SIOCETHTOOL, libc::ifreq);
+
+/// Query the ethtool family for `interface`. `None` when the kernel has no
+/// ethtool netlink; parts the driver doesn't implement are left empty.
+pub fn get_ethtool_info(interface: &str) -> Option<EthtoolInfo> {
+    let (mut socket, family) = open().ok()??;
+    let mut info = EthtoolInfo {
+        driver: driver_info(interface),
+        ..Default::default()
+    };
+
+    let request = header(interface, 0);
+    if let Ok(replies) = socket.genl_request(family, ETHTOOL_MSG_LINKINFO_GET, 0, &request) {
+        for reply in replies {
+            parse_linkinfo(&reply, &mut info);
+        }
+    }
+    // Without compact bitsets the reply names every link mode there is
+    let compact = header(interface, ETHTOOL_FLAG_COMPACT_BITSETS);
+    if let Ok(replies) = socket.genl_request(family, ETHTOOL_MSG_LINKMODES_GET, 0, &compact) {
+        for reply in replies {
+            parse_linkmodes(&reply, &mut info);
+        }
+    }
+    if let Ok(replies) = socket.genl_request(family, ETHTOOL_MSG_LINKSTATE_GET, 0, &request) {
+        for reply in replies {
+            parse_linkstate(&reply, &mut info);
+        }
+    }
+    if let Ok(replies) = socket.genl_request(family, ETHTOOL_MSG_FEATURES_GET, 0, &request) {
+        if let Some(reply) = replies.first() {
+            info.offloads = parse_features(reply);
+        }
+    }
+
+    Some(info)
+}
+
+/// Ask for `feature` on `interface` to be turned on or off. The kernel
+/// quietly keeps a feature whose dependencies are off, so the result is
+/// read back.
+pub fn set_feature(interface: &str, feature: &str, enabled: bool) -> Result<()> {
+    let (mut socket, family) =
+        open()?.ok_or_else(|| anyhow::anyhow!("The kernel has no ethtool netlink support"))?;
+
+    let replies =
+        socket.genl_request(family, ETHTOOL_MSG_FEATURES_GET, 0, &header(interface, 0))?;
+    let offloads = replies
+        .first()
+        .map(|reply| parse_features(reply))
+        .unwrap_or_default();
+    match offloads.iter().find(|offload| offload.name == feature) {
+        None => anyhow::bail!("{} has no {}", interface, feature),
+        Some(offload) if offload.fixed => {
+            anyhow::bail!(
+                "the {} driver doesn't let {} be changed",
+                interface,
+                feature
+            )
+        }
+        _ => {}
+    }
+
+    let mut name = feature.as_bytes().to_vec();
+    name.push(0);
+    let mut bit = Vec::new();
+    netlink::push_attr(&mut bit, ETHTOOL_A_BITSET_BIT_NAME, &name);
+    if enabled {
+        netlink::push_attr(&mut bit, ETHTOOL_A_BITSET_BIT_VALUE, &[]);
+    }
+    let mut bits = Vec::new();
+    netlink::push_attr(&mut bits, ETHTOOL_A_BITSET_BITS_BIT | NLA_F_NESTED, &bit);
+    // Without ETHTOOL_A_BITSET_NOMASK the listed bits are the ones to change
+    let mut wanted = Vec::new();
+    netlink::push_attr(&mut wanted, ETHTOOL_A_BITSET_BITS | NLA_F_NESTED, &bits);
+
+    let mut request = header(interface, 0);
+    netlink::push_attr(
+        &mut request,
+        ETHTOOL_A_FEATURES_WANTED | NLA_F_NESTED,
+        &wanted,
+    );
+    socket.genl_request(family, ETHTOOL_MSG_FEATURES_SET, NLM_F_ACK, &request)?;
+
+    let replies =
+        socket.genl_request(family, ETHTOOL_MSG_FEATURES_GET, 0, &header(interface, 0))?;
+    let active = replies
+        .first()
+        .and_then(|reply| feature_set(reply, ETHTOOL_A_FEATURES_ACTIVE))
+        .unwrap_or_default();
+    if active.contains(feature) != enabled {
+        anyhow::bail!(
+            "{} kept {} {}; it depends on other offloads",
+            interface,
+            feature,
+            if enabled { "off" } else { "on" }
+        );
+    }
+    Ok(())
+}
+
+fn open() -> Result<Option<(Netlink, u16)>> {
+    let mut socket = Netlink::generic()?;
+    Ok(socket.family_id("ethtool")?.map(|family| (socket, family)))
+}
+
+/// `ETHTOOL_A_*_HEADER` naming the device
+fn header(interface: &str, flags: u32) -> Vec<u8> {
+    let mut name = interface.as_bytes().to_vec();
+    name.push(0);
+    let mut header = Vec::new();
+    netlink::push_attr(&mut header, ETHTOOL_A_HEADER_DEV_NAME, &name);
+    if flags != 0 {
+        netlink::push_attr(&mut header, ETHTOOL_A_HEADER_FLAGS, &flags.to_ne_bytes());
+    }
+
+    let mut request = Vec::new();
+    netlink::push_attr(&mut request, ETHTOOL_A_HEADER | NLA_F_NESTED, &header);
+    request
+}
+
+fn parse_linkinfo(payload: &[u8], info: &mut EthtoolInfo) {
+    for (kind, value) in netlink::attrs(payload) {
+        if kind == ETHTOOL_A_LINKINFO_PORT {
+            info.port = value.first().and_then(|&port| port_name(port));
+        }
+    }
+}
+
+/// `PORT_*` from `<linux/ethtool.h>`
+fn port_name(port: u8) -> Option<String> {
+    let name = match port {
+        0x00 => "Twisted Pair",
+        0x01 => "AUI",
+        0x02 => "BNC",
+        0x03 => "MII",
+        0x04 => "FIBRE",
+        0x05 => "Direct Attach Copper",
+        0xef => "None",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+fn parse_linkmodes(payload: &[u8], info: &mut EthtoolInfo) {
+    for (kind, value) in netlink::attrs(payload) {
+        match kind {
+            ETHTOOL_A_LINKMODES_AUTONEG => info.autoneg = value.first().map(|&on| on != 0),
+            // Unknown, and zero from some virtual drivers, while the link is down
+            ETHTOOL_A_LINKMODES_SPEED => {
+                info.speed =
+                    netlink::read_u32(value).filter(|&speed| speed != 0 && speed != SPEED_UNKNOWN)
+            }
+            ETHTOOL_A_LINKMODES_DUPLEX => {
+                info.duplex = match value.first() {
+                    Some(&DUPLEX_HALF) => Some(Duplex::Half),
+                    Some(&DUPLEX_FULL) => Some(Duplex::Full),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_linkstate(payload: &[u8], info: &mut EthtoolInfo) {
+    for (kind, value) in netlink::attrs(payload) {
+        if kind == ETHTOOL_A_LINKSTATE_LINK {
+            info.link_detected = value.first().map(|&link| link != 0);
+        }
+    }
+}
+
+/// Offloads of `OFFLOAD_FEATURES` the device can do or has on
+fn parse_features(payload: &[u8]) -> Vec<Offload> {
+    let changeable = feature_set(payload, ETHTOOL_A_FEATURES_HW).unwrap_or_default();
+    let active = feature_set(payload, ETHTOOL_A_FEATURES_ACTIVE).unwrap_or_default();
+    let nochange = feature_set(payload, ETHTOOL_A_FEATURES_NOCHANGE).unwrap_or_default();
+
+    OFFLOAD_FEATURES
+        .iter()
+        .filter(|(name, _)| changeable.contains(*name) || active.contains(*name))
+        .map(|(name, _)| Offload {
+            name: name.to_string(),
+            enabled: active.contains(*name),
+            fixed: !changeable.contains(*name) || nochange.contains(*name),
+        })
+        .collect()
+}
+
+/// Names of the bits set in one of the reply's bitsets. A bitset without
+/// a mask lists only set bits; one with a mask (`HW`) lists every bit of
+/// the mask and flags the set ones.
+fn feature_set(payload: &[u8], attribute: u16) -> Option<HashSet<String>> {
+    let (_, bitset) = netlink::attrs(payload)
+        .into_iter()
+        .find(|(kind, _)| *kind == attribute)?;
+    let bitset = netlink::attrs(bitset);
+    let nomask = bitset
+        .iter()
+        .any(|(kind, _)| *kind == ETHTOOL_A_BITSET_NOMASK);
+    let (_, bits) = bitset
+        .into_iter()
+        .find(|(kind, _)| *kind == ETHTOOL_A_BITSET_BITS)?;
+
+    Some(
+        netlink::attrs(bits)
+            .into_iter()
+            .filter(|(kind, _)| *kind == ETHTOOL_A_BITSET_BITS_BIT)
+            .filter_map(|(_, bit)| {
+                let bit = netlink::attrs(bit);
+                let set = nomask
+                    || bit
+                        .iter()
+                        .any(|(kind, _)| *kind == ETHTOOL_A_BITSET_BIT_VALUE);
+                let (_, name) = bit
+                    .into_iter()
+                    .find(|(kind, _)| *kind == ETHTOOL_A_BITSET_BIT_NAME)?;
+                set.then(|| netlink::read_string(name))
+            })
+            .collect(),
+    )
+}
+
+/// Driver, firmware and bus through `ETHTOOL_GDRVINFO`, which has no
+/// netlink counterpart
+fn driver_info(interface: &str) -> Option<DriverInfo> {
+    let drvinfo = drvinfo(interface).ok()?;
+    let text = |field: &[u8]| Some(netlink::read_string(field)).filter(|text| !text.is_empty());
+    Some(DriverInfo {
+        driver: text(&drvinfo.driver)?,
+        version: text(&drvinfo.version),
+        // Drivers without firmware report "N/A"
+        firmware: text(&drvinfo.fw_version).filter(|firmware| firmware != "N/A"),
+        bus_info: text(&drvinfo.bus_info).filter(|bus| bus != "N/A"),
+    })
+}
+
+/// The driver's own counters, what `ethtool -S` prints. Empty for drivers
+/// without any, such as the loopback.
+pub fn get_driver_statistics(interface: &str) -> Result<Vec<(String, u64)>> {
+    let count = drvinfo(interface)?.n_stats as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    // `struct ethtool_gstrings`: cmd, string set, count, then the names
+    let mut strings = vec![0u8; 12 + count * ETH_GSTRING_LEN];
+    strings[..4].copy_from_slice(&ETHTOOL_GSTRINGS.to_ne_bytes());
+    strings[4..8].copy_from_slice(&ETH_SS_STATS.to_ne_bytes());
+    strings[8..12].copy_from_slice(&(count as u32).to_ne_bytes());
+    ethtool_ioctl(interface, strings.as_mut_ptr().cast())
+        .context("Failed to read the names of the driver's statistics")?;
+
+    // `struct ethtool_stats`: cmd, count, then the values
+    let mut stats = vec![0u8; 8 + count * 8];
+    stats[..4].copy_from_slice(&ETHTOOL_GSTATS.to_ne_bytes());
+    stats[4..8].copy_from_slice(&(count as u32).to_ne_bytes());
+    ethtool_ioctl(interface, stats.as_mut_ptr().cast())
+        .context("Failed to read the driver's statistics")?;
+
+    // The kernel writes back how many it filled in
+    let filled = netlink::read_u32(&stats[4..8]).unwrap_or(0) as usize;
+    Ok(strings[12..]
+        .chunks(ETH_GSTRING_LEN)
+        .zip(stats[8..].chunks(8))
+        .take(count.min(filled))
+        .filter_map(|(name, value)| Some((netlink::read_string(name), netlink::read_u64(value)?)))
+        .collect())
+}
+
+fn drvinfo(interface: &str) -> Result<EthtoolDrvinfo> {
+    let mut drvinfo = EthtoolDrvinfo {
+        cmd: ETHTOOL_GDRVINFO,
+        driver: [0; 32],
+        version: [0; 32],
+        fw_version: [0; 32],
+        bus_info: [0; 32],
+        erom_version: [0; 32],
+        reserved2: [0; 12],
+        n_priv_flags: 0,
+        n_stats: 0,
+        testinfo_len: 0,
+        eedump_len: 0,
+        regdump_len: 0,
+    };
+    ethtool_ioctl(interface, (&mut drvinfo as *mut EthtoolDrvinfo).cast())?;
+    Ok(drvinfo)
+}
+
+/// Run the `SIOCETHTOOL` command at the start of `data` on `interface`.
+/// `data` must point at the whole structure the command expects.
+fn ethtool_ioctl(interface: &str, data: *mut libc::c_char) -> Result<()> {
+    let socket = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )?;
+
+    let mut name = [0 as libc::c_char; libc::IFNAMSIZ];
+    if interface.len() >= name.len() {
+        anyhow::bail!("invalid interface name '{}'", interface);
+    }
+    for (dst, src) in name.iter_mut().zip(interface.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    let mut request = libc::ifreq {
+        ifr_name: name,
+        ifr_ifru: libc::__c_anonymous_ifr_ifru { ifru_data: data },
+    };
+
+    // SAFETY: `request` names the device and points at the structure of
+    // the command the caller passed, which outlives the call and is as
+    // large as the kernel reads and writes for that command
+    unsafe { siocethtool(socket.as_raw_fd(), &mut request) }
+        .with_context(|| format!("ethtool request on {} failed", interface))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linkmodes(speed: Option<u32>, duplex: Option<u8>, autoneg: Option<u8>) -> EthtoolInfo {
+        let mut payload = header("eth0", 0);
+        if let Some(autoneg) = autoneg {
+            netlink::push_attr(&mut payload, ETHTOOL_A_LINKMODES_AUTONEG, &[autoneg]);
+        }
+        if let Some(speed) = speed {
+            netlink::push_attr(
+                &mut payload,
+                ETHTOOL_A_LINKMODES_SPEED,
+                &speed.to_ne_bytes(),
+            );
+        }
+        if let Some(duplex) = duplex {
+            netlink::push_attr(&mut payload, ETHTOOL_A_LINKMODES_DUPLEX, &[duplex]);
+        }
+
+        let mut info = EthtoolInfo::default();
+        parse_linkmodes(&payload, &mut info);
+        info
+    }
+
+    #[test]
+    fn speed_and_duplex() {
+        for (speed, duplex, expected) in [
+            (
+                Some(1000),
+                Some(DUPLEX_FULL),
+                (Some(1000), Some(Duplex::Full)),
+            ),
+            (Some(10), Some(DUPLEX_HALF), (Some(10), Some(Duplex::Half))),
+            (
+                Some(2500),
+                Some(DUPLEX_FULL),
+                (Some(2500), Some(Duplex::Full)),
+            ),
+            (
+                Some(400_000),
+                Some(DUPLEX_FULL),
+                (Some(400_000), Some(Duplex::Full)),
+            ),
+            // Link down: SPEED_UNKNOWN and DUPLEX_UNKNOWN
+            (Some(SPEED_UNKNOWN), Some(0xff), (None, None)),
+            // Virtual drivers that report nothing useful
+            (Some(0), Some(DUPLEX_FULL), (None, Some(Duplex::Full))),
+            (None, None, (None, None)),
+        ] {
+            let info = linkmodes(speed, duplex, None);
+            assert_eq!(
+                (info.speed, info.duplex),
+                expected,
+                "{:?} {:?}",
+                speed,
+                duplex
+            );
+        }
+    }
+
+    #[test]
+    fn autoneg() {
+        assert_eq!(linkmodes(None, None, Some(1)).autoneg, Some(true));
+        assert_eq!(linkmodes(None, None, Some(0)).autoneg, Some(false));
+        assert_eq!(linkmodes(None, None, None).autoneg, None);
+    }
+
+    #[test]
+    fn short_attributes_are_ignored() {
+        let mut payload = Vec::new();
+        netlink::push_attr(&mut payload, ETHTOOL_A_LINKMODES_SPEED, &[0xe8, 0x03]);
+        netlink::push_attr(&mut payload, ETHTOOL_A_LINKMODES_DUPLEX, &[]);
+
+        let mut info = EthtoolInfo::default();
+        parse_linkmodes(&payload, &mut info);
+        assert_eq!((info.speed, info.duplex), (None, None));
+    }
+
+    #[test]
+    fn port_and_link() {
+        let mut info = EthtoolInfo::default();
+        let mut payload = header("eth0", 0);
+        netlink::push_attr(&mut payload, ETHTOOL_A_LINKINFO_PORT, &[0x04]);
+        parse_linkinfo(&payload, &mut info);
+        assert_eq!(info.port.as_deref(), Some("FIBRE"));
+        assert_eq!(port_name(0xef).as_deref(), Some("None"));
+        assert_eq!(port_name(0x42), None);
+
+        let mut payload = header("eth0", 0);
+        netlink::push_attr(&mut payload, ETHTOOL_A_LINKSTATE_LINK, &[1]);
+        parse_linkstate(&payload, &mut info);
+        assert_eq!(info.link_detected, Some(true));
+    }
+
+    /// A bitset naming `bits`, each with its value flag when `set`
+    fn bitset(bits: &[(&str, bool)], nomask: bool) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (name, set) in bits {
+            let mut bit = Vec::new();
+            let mut name = name.as_bytes().to_vec();
+            name.push(0);
+            netlink::push_attr(&mut bit, ETHTOOL_A_BITSET_BIT_NAME, &name);
+            if *set {
+                netlink::push_attr(&mut bit, ETHTOOL_A_BITSET_BIT_VALUE, &[]);
+            }
+            netlink::push_attr(&mut list, ETHTOOL_A_BITSET_BITS_BIT | NLA_F_NESTED, &bit);
+        }
+
+        let mut bitset = Vec::new();
+        if nomask {
+            netlink::push_attr(&mut bitset, ETHTOOL_A_BITSET_NOMASK, &[]);
+        }
+        netlink::push_attr(&mut bitset, ETHTOOL_A_BITSET_BITS | NLA_F_NESTED, &list);
+        bitset
+    }
+
+    #[test]
+    fn features() {
+        let mut payload = header("eth0", 0);
+        let hw = bitset(
+            &[
+                ("rx-checksum", true),
+                ("tx-scatter-gather", true),
+                ("rx-lro", false),
+            ],
+            false,
+        );
+        netlink::push_attr(&mut payload, ETHTOOL_A_FEATURES_HW | NLA_F_NESTED, &hw);
+        let active = bitset(
+            &[("rx-checksum", true), ("rx-gro", true), ("highdma", true)],
+            true,
+        );
+        netlink::push_attr(
+            &mut payload,
+            ETHTOOL_A_FEATURES_ACTIVE | NLA_F_NESTED,
+            &active,
+        );
+        let nochange = bitset(&[("tx-scatter-gather", true)], true);
+        netlink::push_attr(
+            &mut payload,
+            ETHTOOL_A_FEATURES_NOCHANGE | NLA_F_NESTED,
+            &nochange,
+        );
+
+        let offload = |name: &str, enabled, fixed| Offload {
+            name: name.to_string(),
+            enabled,
+            fixed,
+        };
+        // In OFFLOAD_FEATURES order; rx-lro is in the mask but not supported
+        assert_eq!(
+            parse_features(&payload),
+            [
+                offload("rx-checksum", true, false),
+                offload("tx-scatter-gather", false, true),
+                offload("rx-gro", true, true),
+            ]
+        );
+    }
+}
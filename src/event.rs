@@ -19,10 +19,30 @@ fn handle_key_event(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<
         AppMode::EditIp => handle_edit_ip_mode(app, key)?,
         AppMode::EditDns => handle_edit_dns_mode(app, key)?,
         AppMode::EditIpv6 => handle_edit_ipv6_mode(app, key)?,
+        AppMode::EditVlan => handle_edit_vlan_mode(app, key)?,
+        AppMode::EditMac => handle_edit_mac_mode(app, key)?,
         AppMode::Details => handle_details_mode(app, key)?,
         AppMode::Help => handle_help_mode(app, key)?,
         AppMode::ConfirmDialog => handle_confirm_mode(app, key)?,
+        AppMode::SudoPassword => handle_password_mode(app, key)?,
         AppMode::Terminal => handle_terminal_mode(app, key)?,
+        AppMode::History => handle_history_mode(app, key)?,
+        AppMode::DhcpOffers => handle_dhcp_offers_mode(app, key)?,
+        AppMode::Wifi => handle_wifi_mode(app, key)?,
+        AppMode::WifiPassword => handle_wifi_password_mode(app, key)?,
+        AppMode::Bonds => handle_bonds_mode(app, key)?,
+        AppMode::CreateBond => handle_create_bond_mode(app, key)?,
+        AppMode::Bridges => handle_bridges_mode(app, key)?,
+        AppMode::CreateBridge => handle_create_bridge_mode(app, key)?,
+        AppMode::EnslaveInterface => handle_enslave_mode(app, key)?,
+        AppMode::AddWireGuardPeer => handle_add_wireguard_peer_mode(app, key)?,
+        AppMode::RemoveWireGuardPeer => handle_remove_wireguard_peer_mode(app, key)?,
+        AppMode::WireGuardKeys => handle_wireguard_keys_mode(app, key)?,
+        AppMode::Namespaces => handle_namespaces_mode(app, key)?,
+        AppMode::Offloads => handle_offloads_mode(app, key)?,
+        AppMode::Statistics => handle_statistics_mode(app, key)?,
+        AppMode::SubnetCalculator => handle_subnet_calculator_mode(app, key)?,
+        AppMode::Conflicts => handle_conflicts_mode(app, key)?,
     }
 
     Ok(())
@@ -67,15 +87,20 @@ fn handle_normal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resul
 
         // Sorting
         KeyCode::Char('s') => {
-            app.sort_column = app.sort_column.next();
-            app.apply_sort();
+            app.next_sort_column();
             app.set_status(format!("Sorted by {}", app.sort_column.as_str()));
         }
         KeyCode::Char('S') => {
-            app.sort_column = app.sort_column.prev();
-            app.apply_sort();
+            app.previous_sort_column();
             app.set_status(format!("Sorted by {}", app.sort_column.as_str()));
         }
+        KeyCode::Char('L') => {
+            app.toggle_speed_column();
+        }
+        KeyCode::Char('C') => {
+            app.open_subnet_calculator();
+            app.clear_status();
+        }
 
         // Search
         KeyCode::Char('/') => {
@@ -89,8 +114,11 @@ fn handle_normal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resul
 
         // Refresh
         KeyCode::Char('r') | KeyCode::Char('R') => {
-            app.refresh_data()?;
-            app.set_status("Data refreshed".to_string());
+            app.clear_status();
+            // Warnings about new conflicts and errors say enough on their own
+            if app.refresh_or_report() && app.status_message.is_none() {
+                app.set_status("Data refreshed".to_string());
+            }
         }
 
         // Help
@@ -130,6 +158,9 @@ fn handle_normal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resul
         KeyCode::Char('6') => {
             app.start_edit_ipv6();
         }
+        KeyCode::Char('v') => {
+            app.start_edit_vlan();
+        }
 
         // Toggle interface
         KeyCode::Char('t') => {
@@ -137,12 +168,67 @@ fn handle_normal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resul
                 app.set_status(format!("Failed to toggle interface: {}", e));
             }
         }
+        KeyCode::Char('M') => {
+            app.start_edit_mac();
+        }
+
+        // Expand or collapse the extra addresses of the selected row
+        KeyCode::Char(' ') => {
+            app.toggle_expanded();
+        }
 
         // Terminal
         KeyCode::Char('x') => {
             app.open_terminal();
         }
 
+        // Undo the most recent change
+        KeyCode::Char('u') => {
+            if let Err(e) = app.prepare_undo() {
+                app.set_status(format!("Failed to prepare undo: {}", e));
+            }
+        }
+
+        // Nearby WiFi networks
+        KeyCode::Char('w') => {
+            if let Err(e) = app.open_wifi() {
+                app.cancel_confirm();
+                app.set_status(format!("WiFi scan failed: {}", e));
+            }
+        }
+
+        // Bonds and teams
+        KeyCode::Char('b') => {
+            if let Err(e) = app.open_bonds() {
+                app.set_status(format!("Failed to read bonds: {}", e));
+            }
+        }
+
+        // Bridges, their ports and forwarding databases
+        KeyCode::Char('B') => {
+            if let Err(e) = app.open_bridges() {
+                app.set_status(format!("Failed to read bridges: {}", e));
+            }
+        }
+
+        // Network namespaces
+        KeyCode::Char('n') => {
+            if let Err(e) = app.open_namespaces() {
+                app.set_status(format!("Failed to list network namespaces: {}", e));
+            }
+        }
+
+        // Audit history
+        KeyCode::Char('H') => {
+            if let Err(e) = app.open_history() {
+                app.set_status(format!("Failed to read audit log: {}", e));
+            }
+        }
+        KeyCode::Char('O') => {
+            app.open_conflicts();
+            app.clear_status();
+        }
+
         // Flush DNS cache
         KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
             if let Err(e) = app.flush_dns_cache() {
@@ -179,51 +265,78 @@ fn handle_search_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resul
 fn handle_edit_ip_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
     use crate::models::IpConfigMode;
 
+    let state = &mut app.ip_edit_state;
+    let is_static = matches!(state.mode, IpConfigMode::Static);
+
     match key.code {
         KeyCode::Esc => {
             app.mode = crate::app::AppMode::Normal;
         }
         KeyCode::Tab => {
-            if app.ip_edit_state.current_field == 0 {
-                app.ip_edit_state.current_field = 1;
-            } else if matches!(app.ip_edit_state.mode, IpConfigMode::Static) {
-                app.ip_edit_state.current_field = (app.ip_edit_state.current_field + 1).min(3);
-            }
+            // The address list is always reachable, the new address fields only in static mode
+            let last_field = if is_static { 5 } else { 1 };
+            state.current_field = (state.current_field + 1).min(last_field);
         }
-        KeyCode::BackTab => {
-            if app.ip_edit_state.current_field > 0 {
-                app.ip_edit_state.current_field -= 1;
-            }
+        KeyCode::BackTab if state.current_field > 0 => {
+            state.current_field -= 1;
         }
-        KeyCode::Char(' ') if app.ip_edit_state.current_field == 0 => {
-            app.ip_edit_state.mode = match app.ip_edit_state.mode {
+        KeyCode::Char(' ') if state.current_field == 0 => {
+            state.mode = match state.mode {
                 IpConfigMode::DHCP => IpConfigMode::Static,
                 IpConfigMode::Static => IpConfigMode::DHCP,
             };
         }
-        KeyCode::Enter => match app.ip_edit_state.mode {
+
+        // Address list
+        KeyCode::Down | KeyCode::Char('j')
+            if state.current_field == 1 && state.address_index + 1 < state.addresses.len() =>
+        {
+            state.address_index += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') if state.current_field == 1 => {
+            state.address_index = state.address_index.saturating_sub(1);
+        }
+        KeyCode::Delete | KeyCode::Char('d') if state.current_field == 1 => {
+            app.prepare_remove_address();
+        }
+        KeyCode::Char('p') if state.current_field == 1 => {
+            app.prepare_make_primary();
+        }
+
+        KeyCode::Enter => match state.mode {
             IpConfigMode::DHCP => app.prepare_dhcp_config(),
             IpConfigMode::Static => app.prepare_static_ip_config(),
         },
-        KeyCode::Backspace => match app.ip_edit_state.current_field {
-            1 => {
-                app.ip_edit_state.ip_buffer.pop();
-            }
-            2 => {
-                app.ip_edit_state.netmask_buffer.pop();
-            }
-            3 => {
-                app.ip_edit_state.gateway_buffer.pop();
+        KeyCode::Backspace => {
+            match state.current_field {
+                2 => {
+                    state.ip_buffer.pop();
+                }
+                3 => {
+                    state.netmask_buffer.pop();
+                }
+                4 => {
+                    state.label_buffer.pop();
+                }
+                5 => {
+                    state.gateway_buffer.pop();
+                }
+                _ => {}
             }
-            _ => {}
-        },
-        KeyCode::Char(c) if app.ip_edit_state.current_field > 0 => {
-            match app.ip_edit_state.current_field {
-                1 => app.ip_edit_state.ip_buffer.push(c),
-                2 => app.ip_edit_state.netmask_buffer.push(c),
-                3 => app.ip_edit_state.gateway_buffer.push(c),
+            app.revalidate_ip_form();
+        }
+        KeyCode::Char(c) if state.current_field > 1 => {
+            // Only characters that can appear in each field
+            match state.current_field {
+                2 if c.is_ascii_digit() || c == '.' || c == '/' => state.ip_buffer.push(c),
+                3 if c.is_ascii_digit() || c == '.' => state.netmask_buffer.push(c),
+                4 if c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '_' | '.') => {
+                    state.label_buffer.push(c)
+                }
+                5 if c.is_ascii_digit() || c == '.' => state.gateway_buffer.push(c),
                 _ => {}
             }
+            app.revalidate_ip_form();
         }
         _ => {}
     }
@@ -343,41 +456,204 @@ fn handle_edit_dns_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Res
 }
 
 fn handle_edit_ipv6_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    let state = &mut app.ipv6_edit_state;
+
     match key.code {
         KeyCode::Esc => {
             app.mode = crate::app::AppMode::Normal;
         }
-        KeyCode::Tab => {
-            if app.ipv6_edit_state.enabled {
-                app.ipv6_edit_state.current_field = (app.ipv6_edit_state.current_field + 1) % 3;
-            }
+        KeyCode::Tab if state.enabled => {
+            // The sysctl fields only exist where the platform exposes them
+            let last_field = if state.settings.is_some() { 8 } else { 4 };
+            state.current_field = if state.current_field >= last_field {
+                0
+            } else if state.current_field == 4 && state.settings.is_none() {
+                8
+            } else {
+                state.current_field + 1
+            };
         }
-        KeyCode::BackTab => {
-            if app.ipv6_edit_state.current_field > 0 {
-                app.ipv6_edit_state.current_field -= 1;
+        KeyCode::BackTab if state.current_field > 0 => {
+            state.current_field = if state.current_field == 8 && state.settings.is_none() {
+                4
+            } else {
+                state.current_field - 1
+            };
+        }
+        KeyCode::Char(' ') => match state.current_field {
+            0 => state.enabled = !state.enabled,
+            5 => {
+                if let Some(settings) = state.settings.as_mut() {
+                    settings.addr_gen_mode = settings.addr_gen_mode.next();
+                }
             }
+            6 => {
+                if let Some(settings) = state.settings.as_mut() {
+                    settings.use_tempaddr = settings.use_tempaddr.next();
+                }
+            }
+            7 => {
+                if let Some(settings) = state.settings.as_mut() {
+                    settings.accept_ra = settings.accept_ra.next();
+                }
+            }
+            8 => state.dhcpv6 = !state.dhcpv6,
+            _ => {}
+        },
+
+        // Address list
+        KeyCode::Down | KeyCode::Char('j')
+            if state.current_field == 1 && state.address_index + 1 < state.addresses.len() =>
+        {
+            state.address_index += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') if state.current_field == 1 => {
+            state.address_index = state.address_index.saturating_sub(1);
         }
-        KeyCode::Char(' ') if app.ipv6_edit_state.current_field == 0 => {
-            app.ipv6_edit_state.enabled = !app.ipv6_edit_state.enabled;
+        KeyCode::Delete | KeyCode::Char('d') if state.current_field == 1 => {
+            app.prepare_remove_ipv6_address();
         }
+
         KeyCode::Enter => {
             app.prepare_ipv6_config();
         }
-        KeyCode::Backspace => match app.ipv6_edit_state.current_field {
-            1 => {
-                app.ipv6_edit_state.ip_buffer.pop();
+        KeyCode::Backspace => {
+            match state.current_field {
+                2 => {
+                    state.ip_buffer.pop();
+                }
+                3 => {
+                    state.prefix_buffer.pop();
+                }
+                4 => {
+                    state.gateway_buffer.pop();
+                }
+                _ => {}
             }
-            2 => {
-                app.ipv6_edit_state.prefix_buffer.pop();
+            app.revalidate_ipv6_form();
+        }
+        KeyCode::Char(c) => {
+            // Only characters that can appear in each field
+            let is_address_char = c.is_ascii_hexdigit() || c == ':' || c == '.';
+            match state.current_field {
+                2 if is_address_char || c == '/' => state.ip_buffer.push(c),
+                3 if c.is_ascii_digit() => state.prefix_buffer.push(c),
+                4 if is_address_char => state.gateway_buffer.push(c),
+                _ => {}
             }
-            _ => {}
-        },
-        KeyCode::Char(c) if app.ipv6_edit_state.current_field > 0 => {
-            match app.ipv6_edit_state.current_field {
-                1 => app.ipv6_edit_state.ip_buffer.push(c),
-                2 => app.ipv6_edit_state.prefix_buffer.push(c),
+            app.revalidate_ipv6_form();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_edit_mac_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = crate::app::AppMode::Normal;
+        }
+        KeyCode::Tab => {
+            app.randomize_mac();
+        }
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.fill_permanent_mac();
+        }
+        KeyCode::Enter => {
+            app.prepare_set_mac();
+        }
+        KeyCode::Backspace => {
+            app.mac_edit_state.buffer.pop();
+        }
+        KeyCode::Char(c) if c.is_ascii_hexdigit() || matches!(c, ':' | '-') => {
+            let state = &mut app.mac_edit_state;
+            if state.buffer.len() < 17 {
+                state.buffer.push(c.to_ascii_lowercase());
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_edit_vlan_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    let state = &mut app.vlan_edit_state;
+
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = crate::app::AppMode::Normal;
+        }
+        KeyCode::Tab => {
+            state.current_field = if state.current_field >= 4 {
+                if state.vlans.is_empty() {
+                    1
+                } else {
+                    0
+                }
+            } else {
+                state.current_field + 1
+            };
+        }
+        KeyCode::BackTab => {
+            let first_field = if state.vlans.is_empty() { 1 } else { 0 };
+            state.current_field = if state.current_field <= first_field {
+                4
+            } else {
+                state.current_field - 1
+            };
+        }
+
+        // Existing VLANs
+        KeyCode::Down | KeyCode::Char('j')
+            if state.current_field == 0 && state.vlan_index + 1 < state.vlans.len() =>
+        {
+            state.vlan_index += 1;
+        }
+        KeyCode::Up | KeyCode::Char('k') if state.current_field == 0 => {
+            state.vlan_index = state.vlan_index.saturating_sub(1);
+        }
+        KeyCode::Delete | KeyCode::Char('d') if state.current_field == 0 => {
+            app.prepare_delete_vlan();
+        }
+
+        KeyCode::Char(' ') if state.current_field == 3 => {
+            state.protocol = match state.protocol {
+                crate::models::VlanProtocol::Dot1Q => crate::models::VlanProtocol::Dot1Ad,
+                crate::models::VlanProtocol::Dot1Ad => crate::models::VlanProtocol::Dot1Q,
+            };
+            app.revalidate_vlan_form();
+        }
+        KeyCode::Enter => {
+            app.prepare_create_vlan();
+        }
+        KeyCode::Backspace => {
+            match state.current_field {
+                1 => {
+                    state.id_buffer.pop();
+                }
+                2 => {
+                    state.name_buffer.pop();
+                }
+                4 => {
+                    state.mtu_buffer.pop();
+                }
+                _ => {}
+            }
+            app.revalidate_vlan_form();
+        }
+        KeyCode::Char(c) => {
+            // Only characters that can appear in each field
+            match state.current_field {
+                1 if c.is_ascii_digit() => state.id_buffer.push(c),
+                2 if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') => {
+                    state.name_buffer.push(c)
+                }
+                4 if c.is_ascii_digit() => state.mtu_buffer.push(c),
                 _ => {}
             }
+            app.revalidate_vlan_form();
         }
         _ => {}
     }
@@ -388,7 +664,7 @@ fn handle_edit_ipv6_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Re
 fn handle_details_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') => {
-            app.mode = crate::app::AppMode::Normal;
+            app.close_details();
         }
         KeyCode::Char('e') => {
             app.start_edit_ip();
@@ -399,6 +675,502 @@ fn handle_details_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resu
         KeyCode::Char('6') => {
             app.start_edit_ipv6();
         }
+        KeyCode::Char('v') => {
+            app.start_edit_vlan();
+        }
+        KeyCode::Char('M') => {
+            app.start_edit_mac();
+        }
+        KeyCode::Char('r') => {
+            app.prepare_renew_dhcp();
+        }
+        KeyCode::Char('R') => {
+            app.prepare_release_dhcp();
+        }
+        KeyCode::Char('p') => {
+            if let Err(e) = app.probe_dhcp() {
+                app.cancel_confirm();
+                app.set_status(format!("DHCP probe failed: {}", e));
+            }
+        }
+        KeyCode::Char('w') => {
+            if let Err(e) = app.open_wifi() {
+                app.cancel_confirm();
+                app.set_status(format!("WiFi scan failed: {}", e));
+            }
+        }
+        KeyCode::Char('a') => {
+            if let Err(e) = app.start_add_wireguard_peer() {
+                app.cancel_confirm();
+                app.set_status(format!("Failed to read WireGuard peers: {}", e));
+            }
+        }
+        KeyCode::Char('x') => {
+            if let Err(e) = app.start_remove_wireguard_peer() {
+                app.cancel_confirm();
+                app.set_status(format!("Failed to read WireGuard peers: {}", e));
+            }
+        }
+        KeyCode::Char('K') => {
+            app.generate_wireguard_keys();
+        }
+        KeyCode::Char('o') => {
+            app.open_offloads();
+        }
+        KeyCode::Char('S') => {
+            if let Err(e) = app.open_statistics() {
+                app.set_status(format!("Failed to read statistics: {}", e));
+            }
+        }
+        KeyCode::Char('C') => {
+            app.open_subnet_calculator();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_dhcp_offers_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.mode = crate::app::AppMode::Details;
+        }
+        KeyCode::Char('p') => {
+            if let Err(e) = app.probe_dhcp() {
+                app.cancel_confirm();
+                app.set_status(format!("DHCP probe failed: {}", e));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_wifi_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_wifi();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_wifi_network();
+            app.clear_status();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_wifi_network();
+            app.clear_status();
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.prepare_wifi_connect() {
+                app.cancel_confirm();
+                app.set_status(format!("Error: {}", e));
+            }
+        }
+        KeyCode::Char('D') => {
+            app.prepare_wifi_disconnect();
+        }
+        KeyCode::Char('f') => {
+            app.prepare_wifi_forget();
+        }
+        KeyCode::Char('r') | KeyCode::Char('s') => {
+            if let Err(e) = app.rescan_wifi() {
+                app.cancel_confirm();
+                app.set_status(format!("WiFi scan failed: {}", e));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_bonds_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_bonds();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_bond_row();
+            app.clear_status();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_bond_row();
+            app.clear_status();
+        }
+        KeyCode::Char('n') => {
+            app.start_create_bond();
+        }
+        KeyCode::Char('a') => {
+            app.start_enslave();
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            app.prepare_release();
+        }
+        KeyCode::Char('f') => {
+            app.prepare_failover();
+        }
+        KeyCode::Char('X') => {
+            app.prepare_delete_bond();
+        }
+        KeyCode::Char('r') => {
+            if let Err(e) = app.refresh_data() {
+                app.set_status(format!("Failed to refresh: {}", e));
+            }
+            app.reload_bonds();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_create_bond_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    let Some(state) = app.bonds.as_mut() else {
+        app.mode = crate::app::AppMode::Normal;
+        return Ok(());
+    };
+
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = crate::app::AppMode::Bonds;
+        }
+        KeyCode::Tab => {
+            state.current_field = (state.current_field + 1) % 3;
+        }
+        KeyCode::BackTab => {
+            state.current_field = (state.current_field + 2) % 3;
+        }
+        KeyCode::Char(' ') if state.current_field == 1 => {
+            state.mode = state.mode.next();
+        }
+        KeyCode::Enter => {
+            app.prepare_create_bond();
+        }
+        KeyCode::Backspace => match state.current_field {
+            0 => {
+                state.name_buffer.pop();
+            }
+            2 => {
+                state.miimon_buffer.pop();
+            }
+            _ => {}
+        },
+        KeyCode::Char(c) => match state.current_field {
+            0 if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') => {
+                state.name_buffer.push(c)
+            }
+            2 if c.is_ascii_digit() => state.miimon_buffer.push(c),
+            _ => {}
+        },
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_bridges_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_bridges();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_bridge_row();
+            app.clear_status();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_bridge_row();
+            app.clear_status();
+        }
+        KeyCode::Char('F') | KeyCode::Tab => {
+            if let Err(e) = app.toggle_fdb() {
+                app.set_status(format!("Failed to read forwarding database: {}", e));
+            }
+        }
+        KeyCode::Char('n') => {
+            app.start_create_bridge();
+        }
+        KeyCode::Char('a') => {
+            app.start_add_bridge_port();
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            app.prepare_remove_bridge_port();
+        }
+        KeyCode::Char('s') => {
+            app.prepare_toggle_stp();
+        }
+        KeyCode::Char('X') => {
+            app.prepare_delete_bridge();
+        }
+        KeyCode::Char('r') => {
+            if let Err(e) = app.refresh_data() {
+                app.set_status(format!("Failed to refresh: {}", e));
+            }
+            app.reload_bridges();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_create_bridge_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    let Some(state) = app.bridges.as_mut() else {
+        app.mode = crate::app::AppMode::Normal;
+        return Ok(());
+    };
+
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = crate::app::AppMode::Bridges;
+        }
+        KeyCode::Tab | KeyCode::BackTab => {
+            state.current_field = 1 - state.current_field;
+        }
+        KeyCode::Char(' ') if state.current_field == 1 => {
+            state.stp = !state.stp;
+        }
+        KeyCode::Enter => {
+            app.prepare_create_bridge();
+        }
+        KeyCode::Backspace if state.current_field == 0 => {
+            state.name_buffer.pop();
+        }
+        KeyCode::Char(c)
+            if state.current_field == 0
+                && (c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) =>
+        {
+            state.name_buffer.push(c);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_enslave_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_port_picker();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_port_candidate();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_port_candidate();
+        }
+        KeyCode::Enter => {
+            app.prepare_enslave();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_namespaces_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_namespace_picker();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_namespace();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_namespace();
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.select_namespace() {
+                app.set_status(format!("Failed to switch network namespace: {}", e));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_offloads_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_offloads();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_offload();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_offload();
+        }
+        KeyCode::Enter | KeyCode::Char(' ') => {
+            app.prepare_toggle_offload();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_statistics_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_statistics();
+        }
+        KeyCode::Char('r') => {
+            if let Err(e) = app.refresh_statistics() {
+                app.set_status(format!("Failed to read statistics: {}", e));
+            }
+        }
+        KeyCode::Char('z') => {
+            app.toggle_zero_counters();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.scroll_statistics(1);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.scroll_statistics(-1);
+        }
+        KeyCode::PageDown => {
+            app.scroll_statistics(10);
+        }
+        KeyCode::PageUp => {
+            app.scroll_statistics(-10);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_subnet_calculator_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.close_subnet_calculator();
+        }
+        KeyCode::Tab => {
+            app.next_calculator_field();
+        }
+        KeyCode::BackTab => {
+            app.previous_calculator_field();
+        }
+        KeyCode::Down => {
+            app.scroll_calculator(1);
+        }
+        KeyCode::Up => {
+            app.scroll_calculator(-1);
+        }
+        KeyCode::PageDown => {
+            app.scroll_calculator(10);
+        }
+        KeyCode::PageUp => {
+            app.scroll_calculator(-10);
+        }
+        KeyCode::Backspace => {
+            if let Some(buffer) = app.calculator_buffer() {
+                buffer.pop();
+            }
+        }
+        KeyCode::Char(c) if c.is_ascii_graphic() || c == ' ' => {
+            if let Some(buffer) = app.calculator_buffer() {
+                buffer.push(c);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_add_wireguard_peer_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc => {
+            app.close_wireguard_dialog();
+        }
+        KeyCode::Tab | KeyCode::Down => {
+            app.next_wireguard_field();
+        }
+        KeyCode::BackTab | KeyCode::Up => {
+            app.previous_wireguard_field();
+        }
+        KeyCode::Enter => {
+            app.prepare_add_wireguard_peer();
+        }
+        KeyCode::Backspace => {
+            if let Some(buffer) = app.wireguard_buffer() {
+                buffer.pop();
+            }
+        }
+        KeyCode::Char(c) if c.is_ascii_graphic() => {
+            if let Some(buffer) = app.wireguard_buffer() {
+                buffer.push(c);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_remove_wireguard_peer_mode(
+    app: &mut crate::app::App,
+    key: KeyEvent,
+) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.close_wireguard_dialog();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_wireguard_peer();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_wireguard_peer();
+        }
+        KeyCode::Enter => {
+            app.prepare_remove_wireguard_peer();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_wireguard_keys_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+            app.close_wireguard_dialog();
+        }
+        KeyCode::Char('c') => {
+            app.copy_wireguard_public_key();
+        }
+        KeyCode::Char('K') => {
+            app.generate_wireguard_keys();
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Mirrors the sudo prompt, but the buffer is the network's passphrase
+fn handle_wifi_password_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Enter => {
+            if let Err(e) = app.submit_wifi_password() {
+                app.cancel_confirm();
+                app.set_status(format!("Error: {}", e));
+            }
+        }
+        KeyCode::Esc => {
+            app.cancel_confirm();
+        }
+        KeyCode::Backspace => {
+            app.remove_wifi_password_char();
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.clear_wifi_password();
+        }
+        KeyCode::Char(c) => {
+            app.add_wifi_password_char(c);
+        }
         _ => {}
     }
 
@@ -433,6 +1205,33 @@ fn handle_confirm_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Resu
     Ok(())
 }
 
+fn handle_password_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Enter => {
+            if let Err(e) = app.submit_password() {
+                app.set_status(format!("Error: {}", e));
+                app.cancel_confirm();
+            }
+        }
+        KeyCode::Esc => {
+            app.cancel_confirm();
+            app.set_status("Cancelled: sudo authentication required".to_string());
+        }
+        KeyCode::Backspace => {
+            app.remove_password_char();
+        }
+        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.clear_password();
+        }
+        KeyCode::Char(c) => {
+            app.add_password_char(c);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
 fn handle_terminal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
     match key.code {
         KeyCode::Esc => {
@@ -463,3 +1262,50 @@ fn handle_terminal_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Res
 
     Ok(())
 }
+
+fn handle_conflicts_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.mode = crate::app::AppMode::Normal;
+        }
+        KeyCode::Char('r') => {
+            app.clear_status();
+            if app.refresh_or_report() {
+                app.set_status(format!("{} conflict(s) found", app.conflicts.len()));
+                app.scroll_conflicts(0);
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.scroll_conflicts(1);
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.scroll_conflicts(-1);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn handle_history_mode(app: &mut crate::app::App, key: KeyEvent) -> anyhow::Result<()> {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.mode = crate::app::AppMode::Normal;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.next_history_entry();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.previous_history_entry();
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            app.history_index = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            app.history_index = app.history.len().saturating_sub(1);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
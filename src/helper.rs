@@ -0,0 +1,2461 @@
+//! Privileged helper process.
+//!
+//! Instead of running `ip`, `sysctl` and friends through sudo one by one, the
+//! TUI starts this same executable once with `--helper` through the selected
+//! privilege strategy. The helper reads typed JSON requests, one per line, from
+//! a Unix socket pair passed as its stdin/stdout, validates every argument and
+//! performs the change itself. Anything outside `HelperRequest` is refused.
+//! A request may name a network namespace, which the helper enters on a
+//! worker thread before handling it.
+
+use crate::dhcp::{self, DhcpClient};
+use crate::models::{
+    offload_label, BondMode, Ipv6Setting, MacAddr, VlanProtocol, WifiSecurity, WireGuardPeerConfig,
+};
+use crate::namespace;
+use crate::network;
+use crate::sudo;
+use crate::validation;
+use crate::wifi::{self, WifiBackend, WpaCtrl};
+use crate::wireguard;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::IpAddr;
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Bumped whenever requests or responses change shape
+const PROTOCOL_VERSION: u32 = 16;
+
+/// Upper bound for one request line, anything longer is rejected unread
+const MAX_REQUEST_LEN: u64 = 64 * 1024;
+
+/// Command line flag that starts the helper instead of the TUI
+pub const HELPER_FLAG: &str = "--helper";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum HelperRequest {
+    SetAddress {
+        interface: String,
+        address: IpAddr,
+        prefix: u8,
+        /// IPv4 alias label such as `eth0:1`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+    },
+    DelAddress {
+        interface: String,
+        address: IpAddr,
+        prefix: u8,
+    },
+    FlushAddresses {
+        interface: String,
+    },
+    SetRoute {
+        interface: String,
+        gateway: IpAddr,
+    },
+    DelRoute {
+        interface: String,
+        gateway: IpAddr,
+    },
+    SetDns {
+        /// Only used on macOS, where DNS is configured per network service
+        interface: String,
+        servers: Vec<IpAddr>,
+        search_domains: Vec<String>,
+    },
+    SetLink {
+        interface: String,
+        up: bool,
+    },
+    /// Take the link down, change its address and bring it back up
+    SetMac {
+        interface: String,
+        mac: MacAddr,
+    },
+    SetIpv6 {
+        interface: String,
+        enabled: bool,
+    },
+    SetIpv6Setting {
+        interface: String,
+        setting: Ipv6Setting,
+    },
+    StartDhcp {
+        interface: String,
+    },
+    StartDhcpv6 {
+        interface: String,
+    },
+    RenewDhcp {
+        interface: String,
+        client: DhcpClient,
+    },
+    ReleaseDhcp {
+        interface: String,
+        client: DhcpClient,
+    },
+    /// Broadcast a DHCPDISCOVER and collect the offers; never requests a lease
+    ProbeDhcp {
+        interface: String,
+        timeout_ms: u64,
+    },
+    /// List nearby networks through NetworkManager or wpa_supplicant
+    ScanWifi {
+        interface: String,
+    },
+    ConnectWifi {
+        interface: String,
+        ssid: String,
+        security: WifiSecurity,
+        /// Only for networks without a saved profile; never logged or recorded
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password: Option<String>,
+    },
+    DisconnectWifi {
+        interface: String,
+    },
+    ForgetWifi {
+        interface: String,
+        ssid: String,
+    },
+    /// VLAN sub-interface `name` of `parent`
+    CreateVlan {
+        parent: String,
+        name: String,
+        id: u16,
+        protocol: VlanProtocol,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mtu: Option<u32>,
+    },
+    /// Refused for anything but a VLAN
+    DeleteVlan {
+        interface: String,
+    },
+    CreateBond {
+        name: String,
+        mode: BondMode,
+        /// Link monitoring interval in ms
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        miimon: Option<u32>,
+    },
+    /// Refused for anything but a bond
+    DeleteBond {
+        interface: String,
+    },
+    CreateBridge {
+        name: String,
+        stp: bool,
+    },
+    /// Refused for anything but a bridge
+    DeleteBridge {
+        interface: String,
+    },
+    SetBridgeStp {
+        bridge: String,
+        enabled: bool,
+    },
+    /// Add `interface` to the bond, team or bridge `master`
+    EnslaveInterface {
+        master: String,
+        interface: String,
+    },
+    ReleaseInterface {
+        master: String,
+        interface: String,
+    },
+    /// Fail over to `interface` in a mode with one active slave
+    SetActiveSlave {
+        bond: String,
+        interface: String,
+    },
+    /// Turn one of `OFFLOAD_FEATURES` on or off, like `ethtool -K`
+    SetOffload {
+        interface: String,
+        feature: String,
+        enabled: bool,
+    },
+    /// Keys, port and peers of a WireGuard interface, which only root may read
+    #[serde(rename = "get_wireguard")]
+    GetWireGuard {
+        interface: String,
+    },
+    #[serde(rename = "add_wireguard_peer")]
+    AddWireGuardPeer {
+        interface: String,
+        peer: WireGuardPeerConfig,
+    },
+    #[serde(rename = "remove_wireguard_peer")]
+    RemoveWireGuardPeer {
+        interface: String,
+        public_key: String,
+    },
+    /// Interfaces, DNS and routes of a namespace we can't enter ourselves
+    GetInterfaces,
+    GetDnsConfiguration,
+    GetRoutes,
+    /// Kernel and driver counters of an interface in such a namespace
+    GetStatistics {
+        interface: String,
+    },
+    /// Namespaces of processes belonging to other users
+    ListNamespaces,
+    FlushDnsCache,
+}
+
+/// A request as sent to the helper, with the namespace to perform it in
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope {
+    /// `/run/netns/<name>` or `/proc/<pid>/ns/net`; the helper's own when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    netns: Option<PathBuf>,
+    #[serde(flatten)]
+    request: HelperRequest,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HelperResponse {
+    Ready {
+        version: u32,
+    },
+    Ok {
+        commands: Vec<CommandRecord>,
+        /// Result of requests that read something, such as `ProbeDhcp`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<serde_json::Value>,
+    },
+    Error {
+        message: String,
+        commands: Vec<CommandRecord>,
+    },
+}
+
+impl HelperRequest {
+    /// Human readable form for confirmation dialogs
+    pub fn describe(&self) -> String {
+        match self {
+            HelperRequest::SetAddress {
+                interface,
+                address,
+                prefix,
+                label: Some(label),
+            } => format!("Add {}/{} to {} as {}", address, prefix, interface, label),
+            HelperRequest::SetAddress {
+                interface,
+                address,
+                prefix,
+                label: None,
+            } => format!("Add {}/{} to {}", address, prefix, interface),
+            HelperRequest::DelAddress {
+                interface,
+                address,
+                prefix,
+            } => format!("Remove {}/{} from {}", address, prefix, interface),
+            HelperRequest::FlushAddresses { interface } => {
+                format!("Remove all addresses from {}", interface)
+            }
+            HelperRequest::SetRoute { interface, gateway } => {
+                format!("Add default route via {} on {}", gateway, interface)
+            }
+            HelperRequest::DelRoute { interface, gateway } => {
+                format!("Remove default route via {} on {}", gateway, interface)
+            }
+            HelperRequest::SetDns {
+                servers,
+                search_domains,
+                ..
+            } => format!(
+                "Set DNS [{}] search [{}]",
+                servers
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                search_domains.join(", ")
+            ),
+            HelperRequest::SetLink { interface, up } => {
+                format!("Set {} {}", interface, if *up { "up" } else { "down" })
+            }
+            HelperRequest::SetMac { interface, mac } => {
+                format!("Set MAC address of {} to {}", interface, mac)
+            }
+            HelperRequest::SetIpv6 { interface, enabled } => format!(
+                "{} IPv6 on {}",
+                if *enabled { "Enable" } else { "Disable" },
+                interface
+            ),
+            HelperRequest::SetIpv6Setting { interface, setting } => {
+                format!("Set {} on {}", setting.describe(), interface)
+            }
+            HelperRequest::StartDhcp { interface } => format!("Start DHCP on {}", interface),
+            HelperRequest::StartDhcpv6 { interface } => {
+                format!("Start DHCPv6 on {}", interface)
+            }
+            HelperRequest::RenewDhcp { interface, client } => {
+                format!("Renew the {} lease on {}", client.as_str(), interface)
+            }
+            HelperRequest::ReleaseDhcp { interface, client } => {
+                format!("Release the {} lease on {}", client.as_str(), interface)
+            }
+            HelperRequest::ProbeDhcp { interface, .. } => {
+                format!("Probe for DHCP servers on {}", interface)
+            }
+            HelperRequest::ScanWifi { interface } => {
+                format!("Scan for WiFi networks on {}", interface)
+            }
+            HelperRequest::ConnectWifi {
+                interface, ssid, ..
+            } => format!("Connect {} to '{}'", interface, ssid),
+            HelperRequest::DisconnectWifi { interface } => {
+                format!("Disconnect {} from WiFi", interface)
+            }
+            HelperRequest::ForgetWifi { ssid, .. } => {
+                format!("Forget WiFi network '{}'", ssid)
+            }
+            HelperRequest::CreateVlan {
+                parent,
+                name,
+                id,
+                protocol,
+                ..
+            } => format!(
+                "Create {} VLAN {} as {} on {}",
+                protocol.as_str(),
+                id,
+                name,
+                parent
+            ),
+            HelperRequest::DeleteVlan { interface } => format!("Delete VLAN {}", interface),
+            HelperRequest::CreateBond { name, mode, .. } => {
+                format!("Create {} bond {}", mode.as_str(), name)
+            }
+            HelperRequest::DeleteBond { interface } => format!("Delete bond {}", interface),
+            HelperRequest::CreateBridge { name, stp } => format!(
+                "Create bridge {}{}",
+                name,
+                if *stp { " with STP" } else { "" }
+            ),
+            HelperRequest::DeleteBridge { interface } => format!("Delete bridge {}", interface),
+            HelperRequest::SetBridgeStp { bridge, enabled } => format!(
+                "{} STP on {}",
+                if *enabled { "Enable" } else { "Disable" },
+                bridge
+            ),
+            HelperRequest::EnslaveInterface { master, interface } => {
+                format!("Add {} to {}", interface, master)
+            }
+            HelperRequest::ReleaseInterface { master, interface } => {
+                format!("Release {} from {}", interface, master)
+            }
+            HelperRequest::SetActiveSlave { bond, interface } => {
+                format!("Fail {} over to {}", bond, interface)
+            }
+            HelperRequest::SetOffload {
+                interface,
+                feature,
+                enabled,
+            } => format!(
+                "Turn {} {} on {}",
+                feature,
+                if *enabled { "on" } else { "off" },
+                interface
+            ),
+            HelperRequest::GetWireGuard { interface } => {
+                format!("Read the WireGuard configuration of {}", interface)
+            }
+            HelperRequest::AddWireGuardPeer { interface, peer } => format!(
+                "Add peer {} to {}{}",
+                wireguard::short_key(&peer.public_key),
+                interface,
+                if peer.allowed_ips.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " for {}",
+                        peer.allowed_ips
+                            .iter()
+                            .map(|allowed| allowed.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            ),
+            HelperRequest::RemoveWireGuardPeer {
+                interface,
+                public_key,
+            } => format!(
+                "Remove peer {} from {}",
+                wireguard::short_key(public_key),
+                interface
+            ),
+            HelperRequest::GetInterfaces => "Read network interfaces".to_string(),
+            HelperRequest::GetDnsConfiguration => "Read DNS configuration".to_string(),
+            HelperRequest::GetRoutes => "Read routing table".to_string(),
+            HelperRequest::GetStatistics { interface } => {
+                format!("Read statistics of {}", interface)
+            }
+            HelperRequest::ListNamespaces => "List network namespaces".to_string(),
+            HelperRequest::FlushDnsCache => "Flush DNS cache".to_string(),
+        }
+    }
+}
+
+/// A program the helper ran, or a file it wrote, while serving a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandRecord {
+    pub program: String,
+    pub args: Vec<String>,
+    /// `None` when the program could not be started or was killed by a signal
+    pub exit_status: Option<i32>,
+    pub stderr: String,
+}
+
+impl CommandRecord {
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.as_str())
+            .chain(self.args.iter().map(|arg| arg.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+struct HelperClient {
+    child: Child,
+    stream: BufReader<UnixStream>,
+}
+
+impl HelperClient {
+    fn spawn() -> Result<Self> {
+        let exe = std::env::current_exe().context("Failed to locate own executable")?;
+        let exe = exe.to_str().context("Executable path is not valid UTF-8")?;
+
+        let (ours, theirs) = UnixStream::pair().context("Failed to create helper socket")?;
+        let theirs_out = theirs.try_clone()?;
+
+        let child = sudo::privileged_command(exe, &[HELPER_FLAG])?
+            .stdin(Stdio::from(OwnedFd::from(theirs)))
+            .stdout(Stdio::from(OwnedFd::from(theirs_out)))
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start privileged helper")?;
+
+        let mut client = Self {
+            child,
+            stream: BufReader::new(ours),
+        };
+
+        match client.read_response() {
+            Ok(HelperResponse::Ready { version }) if version == PROTOCOL_VERSION => Ok(client),
+            Ok(HelperResponse::Ready { version }) => {
+                anyhow::bail!(
+                    "Helper speaks protocol {}, expected {}",
+                    version,
+                    PROTOCOL_VERSION
+                )
+            }
+            Ok(HelperResponse::Error { message, .. }) => {
+                anyhow::bail!("Helper failed: {}", message)
+            }
+            Ok(HelperResponse::Ok { .. }) => anyhow::bail!("Helper sent no handshake"),
+            Err(_) => {
+                // The escalation tool exited before the helper came up
+                let mut stderr = String::new();
+                if let Some(mut pipe) = client.child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr);
+                }
+                let _ = client.child.wait();
+                Err(sudo::escalation_error(&stderr))
+            }
+        }
+    }
+
+    fn call(&mut self, request: &Envelope) -> Result<HelperResponse> {
+        // The line may carry a WiFi password: sized up front so serde never
+        // reallocates and leaves a copy behind, and wiped once sent
+        let mut line = Zeroizing::new(Vec::with_capacity(MAX_REQUEST_LEN as usize));
+        serde_json::to_writer(&mut *line, request)?;
+        line.push(b'\n');
+        self.stream
+            .get_mut()
+            .write_all(&line)
+            .context("Privileged helper is gone")?;
+        self.read_response()
+    }
+
+    fn read_response(&mut self) -> Result<HelperResponse> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line)? == 0 {
+            anyhow::bail!("Privileged helper exited");
+        }
+        serde_json::from_str(&line).context("Malformed response from helper")
+    }
+}
+
+static CLIENT: Mutex<Option<HelperClient>> = Mutex::new(None);
+
+/// Everything the helper reported running since the last `take_command_log`
+static COMMAND_LOG: Mutex<Vec<CommandRecord>> = Mutex::new(Vec::new());
+
+/// Drain the commands executed on our behalf, successful or not
+pub fn take_command_log() -> Vec<CommandRecord> {
+    std::mem::take(&mut *COMMAND_LOG.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+fn log_commands(commands: Vec<CommandRecord>) {
+    COMMAND_LOG
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .extend(commands);
+}
+
+/// Send a request to the helper, starting it on first use
+pub fn call(request: HelperRequest) -> Result<()> {
+    send(request).map(|_| ())
+}
+
+/// Send a request that returns data, such as `ProbeDhcp`
+pub fn query<T: DeserializeOwned>(request: HelperRequest) -> Result<T> {
+    let data = send(request)?.context("Helper returned no data")?;
+    serde_json::from_value(data).context("Unexpected data from helper")
+}
+
+fn send(request: HelperRequest) -> Result<Option<serde_json::Value>> {
+    let mut guard = CLIENT.lock().unwrap_or_else(|e| e.into_inner());
+
+    if guard.is_none() {
+        *guard = Some(HelperClient::spawn()?);
+    }
+
+    let envelope = Envelope {
+        netns: namespace::selected().map(|selected| selected.path),
+        request,
+    };
+    let response = guard.as_mut().map(|client| client.call(&envelope));
+    match response {
+        Some(Ok(HelperResponse::Ok { commands, data })) => {
+            log_commands(commands);
+            Ok(data)
+        }
+        Some(Ok(HelperResponse::Error { message, commands })) => {
+            log_commands(commands);
+            anyhow::bail!(message)
+        }
+        Some(Ok(response)) => anyhow::bail!("Unexpected helper response: {:?}", response),
+        Some(Err(e)) => {
+            // Broken connection, start a fresh helper next time
+            if let Some(mut client) = guard.take() {
+                drop(client.stream);
+                let _ = client.child.wait();
+            }
+            Err(e)
+        }
+        None => unreachable!("helper client initialised above"),
+    }
+}
+
+/// Whether a helper is already running, i.e. no further authentication is needed
+pub fn is_running() -> bool {
+    CLIENT.lock().map(|guard| guard.is_some()).unwrap_or(false)
+}
+
+/// Entry point for `netutil-tui --helper`
+pub fn run() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = std::io::stdout().lock();
+
+    if !nix::unistd::geteuid().is_root() {
+        return respond(
+            &mut stdout,
+            &HelperResponse::Error {
+                message: "helper must run as root".to_string(),
+                commands: Vec::new(),
+            },
+        );
+    }
+
+    respond(
+        &mut stdout,
+        &HelperResponse::Ready {
+            version: PROTOCOL_VERSION,
+        },
+    )?;
+
+    loop {
+        // Requests may carry a WiFi password, see `HelperClient::call`
+        let mut line = Zeroizing::new(String::with_capacity(MAX_REQUEST_LEN as usize + 1));
+        let read = (&mut reader).take(MAX_REQUEST_LEN).read_line(&mut line)?;
+        if read == 0 {
+            // The TUI closed its end
+            return Ok(());
+        }
+        if !line.ends_with('\n') {
+            respond(
+                &mut stdout,
+                &HelperResponse::Error {
+                    message: "request too long".to_string(),
+                    commands: Vec::new(),
+                },
+            )?;
+            return Ok(());
+        }
+
+        let response = match serde_json::from_str::<Envelope>(&line) {
+            Ok(Envelope { netns, request }) => {
+                let (result, commands) = perform(netns.as_deref(), &request);
+                match result {
+                    Ok(data) => HelperResponse::Ok { commands, data },
+                    Err(e) => HelperResponse::Error {
+                        message: format!("{:#}", e),
+                        commands,
+                    },
+                }
+            }
+            Err(e) => HelperResponse::Error {
+                message: format!("invalid request: {}", e),
+                commands: Vec::new(),
+            },
+        };
+
+        respond(&mut stdout, &response)?;
+    }
+}
+
+fn respond(out: &mut impl Write, response: &HelperResponse) -> Result<()> {
+    let mut line = serde_json::to_string(response)?;
+    line.push('\n');
+    out.write_all(line.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Handle `request` inside `netns`, on a worker thread that entered it, and
+/// collect the commands it ran there
+fn perform(
+    netns: Option<&Path>,
+    request: &HelperRequest,
+) -> (Result<Option<serde_json::Value>>, Vec<CommandRecord>) {
+    let run = || {
+        let result = handle(request);
+        (result, EXECUTED.with(|executed| executed.take()))
+    };
+
+    let Some(path) = netns else {
+        return run();
+    };
+    match namespace::validate_path(path).and_then(|()| namespace::run_in(path, run)) {
+        Ok(outcome) => outcome,
+        Err(e) => (Err(e), Vec::new()),
+    }
+}
+
+/// Perform a request; only queries return data
+fn handle(request: &HelperRequest) -> Result<Option<serde_json::Value>> {
+    let result = match request {
+        HelperRequest::SetAddress {
+            interface,
+            address,
+            prefix,
+            label,
+        } => {
+            validate_interface(interface)?;
+            validate_address(address, *prefix)?;
+            if let Some(label) = label {
+                validation::check_label(label, interface, address).map_err(anyhow::Error::msg)?;
+            }
+            platform::set_address(interface, address, *prefix, label.as_deref())
+        }
+        HelperRequest::DelAddress {
+            interface,
+            address,
+            prefix,
+        } => {
+            validate_interface(interface)?;
+            validate_address(address, *prefix)?;
+            platform::del_address(interface, address, *prefix)
+        }
+        HelperRequest::FlushAddresses { interface } => {
+            validate_interface(interface)?;
+            platform::flush_addresses(interface)
+        }
+        HelperRequest::SetRoute { interface, gateway } => {
+            validate_interface(interface)?;
+            validate_unicast(gateway)?;
+            platform::set_route(interface, gateway)
+        }
+        HelperRequest::DelRoute { interface, gateway } => {
+            validate_interface(interface)?;
+            validate_unicast(gateway)?;
+            platform::del_route(interface, gateway)
+        }
+        HelperRequest::SetDns {
+            interface,
+            servers,
+            search_domains,
+        } => {
+            if !interface.is_empty() {
+                validate_interface(interface)?;
+            }
+            for server in servers {
+                validate_unicast(server)?;
+            }
+            for domain in search_domains {
+                validate_domain(domain)?;
+            }
+            platform::set_dns(interface, servers, search_domains)
+        }
+        HelperRequest::SetLink { interface, up } => {
+            validate_interface(interface)?;
+            platform::set_link(interface, *up)
+        }
+        HelperRequest::SetMac { interface, mac } => {
+            validate_interface(interface)?;
+            if mac.is_zero() || mac.is_multicast() {
+                anyhow::bail!("{} is not a unicast MAC address", mac);
+            }
+            platform::set_mac(interface, mac)
+        }
+        HelperRequest::SetIpv6 { interface, enabled } => {
+            validate_interface(interface)?;
+            platform::set_ipv6(interface, *enabled)
+        }
+        HelperRequest::SetIpv6Setting { interface, setting } => {
+            validate_interface(interface)?;
+            platform::set_ipv6_setting(interface, setting)
+        }
+        HelperRequest::StartDhcp { interface } => {
+            validate_interface(interface)?;
+            platform::start_dhcp(interface)
+        }
+        HelperRequest::StartDhcpv6 { interface } => {
+            validate_interface(interface)?;
+            platform::start_dhcpv6(interface)
+        }
+        HelperRequest::RenewDhcp { interface, client } => {
+            validate_interface(interface)?;
+            platform::renew_dhcp(interface, *client)
+        }
+        HelperRequest::ReleaseDhcp { interface, client } => {
+            validate_interface(interface)?;
+            platform::release_dhcp(interface, *client)
+        }
+        HelperRequest::ProbeDhcp {
+            interface,
+            timeout_ms,
+        } => {
+            validate_interface(interface)?;
+            if !(100..=30_000).contains(timeout_ms) {
+                anyhow::bail!("probe timeout must be between 100ms and 30s");
+            }
+            let offers = dhcp::probe(interface, Duration::from_millis(*timeout_ms))?;
+            return Ok(Some(serde_json::to_value(offers)?));
+        }
+        HelperRequest::ScanWifi { interface } => {
+            validate_interface(interface)?;
+            let networks = wifi::scan(interface)?;
+            return Ok(Some(serde_json::to_value(networks)?));
+        }
+        HelperRequest::ConnectWifi {
+            interface,
+            ssid,
+            security,
+            password,
+        } => {
+            validate_interface(interface)?;
+            validate_ssid(ssid)?;
+            if let Some(password) = password {
+                validation::check_wifi_password(password, *security).map_err(anyhow::Error::msg)?;
+            }
+            connect_wifi(interface, ssid, *security, password.as_deref())
+        }
+        HelperRequest::DisconnectWifi { interface } => {
+            validate_interface(interface)?;
+            disconnect_wifi(interface)
+        }
+        HelperRequest::ForgetWifi { interface, ssid } => {
+            validate_interface(interface)?;
+            validate_ssid(ssid)?;
+            forget_wifi(interface, ssid)
+        }
+        HelperRequest::CreateVlan {
+            parent,
+            name,
+            id,
+            protocol,
+            mtu,
+        } => {
+            validate_interface(parent)?;
+            validate_interface_name(name)?;
+            if nix::net::if_::if_nametoindex(name.as_str()).is_ok() {
+                anyhow::bail!("interface '{}' already exists", name);
+            }
+            if !(1..=4094).contains(id) {
+                anyhow::bail!("VLAN id {} is out of range 1-4094", id);
+            }
+            if mtu.is_some_and(|mtu| !(68..=65535).contains(&mtu)) {
+                anyhow::bail!("MTU must be between 68 and 65535");
+            }
+            platform::create_vlan(parent, name, *id, *protocol, *mtu)
+        }
+        HelperRequest::DeleteVlan { interface } => {
+            validate_interface(interface)?;
+            platform::delete_vlan(interface)
+        }
+        HelperRequest::CreateBond { name, mode, miimon } => {
+            validate_interface_name(name)?;
+            if nix::net::if_::if_nametoindex(name.as_str()).is_ok() {
+                anyhow::bail!("interface '{}' already exists", name);
+            }
+            if miimon.is_some_and(|miimon| miimon > 60_000) {
+                anyhow::bail!("MII polling interval must be at most 60000 ms");
+            }
+            platform::create_bond(name, *mode, *miimon)
+        }
+        HelperRequest::DeleteBond { interface } => {
+            validate_interface(interface)?;
+            platform::delete_bond(interface)
+        }
+        HelperRequest::CreateBridge { name, stp } => {
+            validate_interface_name(name)?;
+            if nix::net::if_::if_nametoindex(name.as_str()).is_ok() {
+                anyhow::bail!("interface '{}' already exists", name);
+            }
+            platform::create_bridge(name, *stp)
+        }
+        HelperRequest::DeleteBridge { interface } => {
+            validate_interface(interface)?;
+            platform::delete_bridge(interface)
+        }
+        HelperRequest::SetBridgeStp { bridge, enabled } => {
+            validate_interface(bridge)?;
+            platform::set_bridge_stp(bridge, *enabled)
+        }
+        HelperRequest::EnslaveInterface { master, interface } => {
+            validate_interface(master)?;
+            validate_interface(interface)?;
+            if master == interface {
+                anyhow::bail!("'{}' can't be enslaved to itself", interface);
+            }
+            platform::enslave_interface(master, interface)
+        }
+        HelperRequest::ReleaseInterface { master, interface } => {
+            validate_interface(master)?;
+            validate_interface(interface)?;
+            platform::release_interface(master, interface)
+        }
+        HelperRequest::SetActiveSlave { bond, interface } => {
+            validate_interface(bond)?;
+            validate_interface(interface)?;
+            platform::set_active_slave(bond, interface)
+        }
+        HelperRequest::SetOffload {
+            interface,
+            feature,
+            enabled,
+        } => {
+            validate_interface(interface)?;
+            if offload_label(feature).is_none() {
+                anyhow::bail!("'{}' is not an offload that can be changed here", feature);
+            }
+            platform::set_offload(interface, feature, *enabled)
+        }
+        HelperRequest::GetWireGuard { interface } => {
+            validate_interface(interface)?;
+            let info = wireguard::get_wireguard_info(interface)?;
+            return Ok(Some(serde_json::to_value(info)?));
+        }
+        HelperRequest::AddWireGuardPeer { interface, peer } => {
+            validate_interface(interface)?;
+            validate_wireguard_key(&peer.public_key)?;
+            for allowed in &peer.allowed_ips {
+                validate_prefix(&allowed.address, allowed.prefix)?;
+            }
+            if let Some(endpoint) = peer.endpoint {
+                validate_unicast(&endpoint.ip())?;
+                if endpoint.port() == 0 {
+                    anyhow::bail!("endpoint {} has no port", endpoint);
+                }
+            }
+            // Adding an existing key would quietly change that peer instead
+            if wireguard::get_wireguard_info(interface)?
+                .peer(&peer.public_key)
+                .is_some()
+            {
+                anyhow::bail!("{} already has this peer", interface);
+            }
+            let mut args = vec![interface.as_str(), "peer", peer.public_key.as_str()];
+            let allowed_ips = peer
+                .allowed_ips
+                .iter()
+                .map(|allowed| allowed.to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            let endpoint = peer.endpoint.map(|endpoint| endpoint.to_string());
+            let keepalive = peer.persistent_keepalive.map(|secs| secs.to_string());
+            args.extend(["allowed-ips", &allowed_ips]);
+            if let Some(ref endpoint) = endpoint {
+                args.extend(["endpoint", endpoint]);
+            }
+            if let Some(ref keepalive) = keepalive {
+                args.extend(["persistent-keepalive", keepalive]);
+            }
+            record_netlink("wireguard set", &args, wireguard::add_peer(interface, peer))
+        }
+        HelperRequest::RemoveWireGuardPeer {
+            interface,
+            public_key,
+        } => {
+            validate_interface(interface)?;
+            validate_wireguard_key(public_key)?;
+            if wireguard::get_wireguard_info(interface)?
+                .peer(public_key)
+                .is_none()
+            {
+                anyhow::bail!("{} has no such peer", interface);
+            }
+            record_netlink(
+                "wireguard set",
+                &[interface, "peer", public_key, "remove"],
+                wireguard::remove_peer(interface, public_key),
+            )
+        }
+        HelperRequest::GetInterfaces => {
+            return Ok(Some(serde_json::to_value(
+                network::get_network_interfaces()?,
+            )?));
+        }
+        HelperRequest::GetDnsConfiguration => {
+            return Ok(Some(serde_json::to_value(
+                network::get_dns_configuration()?
+            )?));
+        }
+        HelperRequest::GetRoutes => {
+            return Ok(Some(serde_json::to_value(network::get_routes()?)?));
+        }
+        HelperRequest::GetStatistics { interface } => {
+            validate_interface(interface)?;
+            return Ok(Some(serde_json::to_value(network::get_statistics(
+                interface,
+            )?)?));
+        }
+        HelperRequest::ListNamespaces => {
+            return Ok(Some(serde_json::to_value(namespace::list()?)?));
+        }
+        HelperRequest::FlushDnsCache => platform::flush_dns_cache(),
+    };
+
+    result.map(|()| None)
+}
+
+/// Interface names must look like a kernel name and refer to an existing link
+fn validate_interface(name: &str) -> Result<()> {
+    validate_interface_name(name)?;
+
+    nix::net::if_::if_nametoindex(name)
+        .map_err(|_| anyhow::anyhow!("no such interface '{}'", name))?;
+
+    Ok(())
+}
+
+/// For links about to be created, which don't exist yet
+fn validate_interface_name(name: &str) -> Result<()> {
+    if !validation::is_valid_interface_name(name) {
+        anyhow::bail!("invalid interface name '{}'", name);
+    }
+    Ok(())
+}
+
+fn validate_address(address: &IpAddr, prefix: u8) -> Result<()> {
+    validate_unicast(address)?;
+
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    if prefix > max_prefix {
+        anyhow::bail!("prefix /{} is too long for {}", prefix, address);
+    }
+
+    Ok(())
+}
+
+fn validate_unicast(address: &IpAddr) -> Result<()> {
+    if address.is_unspecified() || address.is_multicast() {
+        anyhow::bail!("{} is not a unicast address", address);
+    }
+
+    if let IpAddr::V4(v4) = address {
+        if v4.is_broadcast() {
+            anyhow::bail!("{} is not a unicast address", address);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_domain(domain: &str) -> Result<()> {
+    let domain = domain.trim_end_matches('.');
+    let valid_labels = domain.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+
+    if domain.is_empty() || domain.len() > 253 || !valid_labels {
+        anyhow::bail!("invalid search domain '{}'", domain);
+    }
+
+    Ok(())
+}
+
+/// Networks routed into a tunnel; `0.0.0.0/0` sends everything through it
+fn validate_prefix(address: &IpAddr, prefix: u8) -> Result<()> {
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    if prefix > max_prefix || address.is_multicast() {
+        anyhow::bail!("invalid network {}/{}", address, prefix);
+    }
+    Ok(())
+}
+
+fn validate_wireguard_key(key: &str) -> Result<()> {
+    validation::decode_wireguard_key(key).map_err(anyhow::Error::msg)?;
+    Ok(())
+}
+
+/// SSIDs are up to 32 bytes; control characters would break nmcli's and
+/// wpa_supplicant's line based output
+fn validate_ssid(ssid: &str) -> Result<()> {
+    if ssid.is_empty() || ssid.len() > 32 || ssid.chars().any(|c| c.is_control()) {
+        anyhow::bail!("invalid SSID '{}'", ssid.escape_default());
+    }
+    Ok(())
+}
+
+thread_local! {
+    /// Commands run while handling the current request
+    static EXECUTED: RefCell<Vec<CommandRecord>> = const { RefCell::new(Vec::new()) };
+}
+
+fn record(program: &str, args: &[&str], exit_status: Option<i32>, stderr: &str) {
+    EXECUTED.with(|executed| {
+        executed.borrow_mut().push(CommandRecord {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            exit_status,
+            stderr: stderr.trim().to_string(),
+        })
+    });
+}
+
+/// Run a program directly; the helper is already root
+fn run_command(program: &str, args: &[&str]) -> Result<String> {
+    run_command_with_input(program, args, None)
+}
+
+/// Run a program with `input` on its stdin, which keeps secrets out of the
+/// argument list and the recorded command
+fn run_command_with_input(program: &str, args: &[&str], input: Option<&[u8]>) -> Result<String> {
+    let spawned = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let output = match spawned.and_then(|mut child| {
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+            stdin.write_all(input)?;
+        }
+        child.wait_with_output()
+    }) {
+        Ok(output) => output,
+        Err(e) => {
+            record(program, args, None, &e.to_string());
+            return Err(e).with_context(|| format!("Failed to run {}", program));
+        }
+    };
+
+    record(
+        program,
+        args,
+        output.status.code(),
+        &String::from_utf8_lossy(&output.stderr),
+    );
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("{} failed: {}", program, stderr.trim())
+    }
+}
+
+/// Write a file directly, recorded like a command so it shows up in the audit log
+#[cfg(target_os = "linux")]
+fn write_file(path: &std::path::Path, content: &str) -> Result<()> {
+    let display = path.display().to_string();
+    match std::fs::write(path, content) {
+        Ok(()) => {
+            record("write", &[&display, content.trim()], Some(0), "");
+            Ok(())
+        }
+        Err(e) => {
+            record("write", &[&display, content.trim()], None, &e.to_string());
+            Err(e).with_context(|| format!("Failed to write {}", display))
+        }
+    }
+}
+
+/// wpa_supplicant conversations are recorded as one step, without secrets
+fn record_wpa(socket: &Path, args: &[&str], result: Result<()>) -> Result<()> {
+    let socket = socket.display().to_string();
+    let args: Vec<&str> = std::iter::once(socket.as_str())
+        .chain(args.iter().copied())
+        .collect();
+    match &result {
+        Ok(()) => record("wpa_ctrl", &args, Some(0), ""),
+        Err(e) => record("wpa_ctrl", &args, Some(1), &format!("{:#}", e)),
+    }
+    result
+}
+
+/// Netlink changes made without a program, recorded like one for the audit log
+fn record_netlink(operation: &str, args: &[&str], result: Result<()>) -> Result<()> {
+    match &result {
+        Ok(()) => record(operation, args, Some(0), ""),
+        Err(e) => record(operation, args, Some(1), &format!("{:#}", e)),
+    }
+    result
+}
+
+/// NetworkManager connects open and saved networks by itself; a new secret
+/// goes into a connection profile and is handed over on stdin
+fn connect_wifi(
+    interface: &str,
+    ssid: &str,
+    security: WifiSecurity,
+    password: Option<&str>,
+) -> Result<()> {
+    match wifi::backend(interface)? {
+        WifiBackend::NetworkManager => {
+            let Some(password) = password else {
+                run_command(
+                    "nmcli",
+                    &["device", "wifi", "connect", ssid, "ifname", interface],
+                )?;
+                return Ok(());
+            };
+            let key_mgmt = match security {
+                WifiSecurity::Wpa | WifiSecurity::Wpa2Personal | WifiSecurity::Wpa2Wpa3Personal => {
+                    "wpa-psk"
+                }
+                WifiSecurity::Wpa3Personal => "sae",
+                _ => anyhow::bail!("{} networks are not supported", security.as_str()),
+            };
+
+            let saved = run_command("nmcli", &["-t", "-f", "NAME,TYPE", "connection", "show"])?;
+            if wifi::parse_nmcli_connections(&saved)
+                .iter()
+                .any(|name| name == ssid)
+            {
+                run_command(
+                    "nmcli",
+                    &[
+                        "connection",
+                        "modify",
+                        "id",
+                        ssid,
+                        "wifi-sec.key-mgmt",
+                        key_mgmt,
+                    ],
+                )?;
+            } else {
+                run_command(
+                    "nmcli",
+                    &[
+                        "connection",
+                        "add",
+                        "type",
+                        "wifi",
+                        "con-name",
+                        ssid,
+                        "ifname",
+                        interface,
+                        "ssid",
+                        ssid,
+                        "wifi-sec.key-mgmt",
+                        key_mgmt,
+                    ],
+                )?;
+            }
+
+            let secret = Zeroizing::new(format!("802-11-wireless-security.psk:{}\n", password));
+            run_command_with_input(
+                "nmcli",
+                &[
+                    "connection",
+                    "up",
+                    "id",
+                    ssid,
+                    "ifname",
+                    interface,
+                    "passwd-file",
+                    "/dev/stdin",
+                ],
+                Some(secret.as_bytes()),
+            )?;
+        }
+        WifiBackend::WpaSupplicant(socket) => {
+            let result = WpaCtrl::open(&socket)
+                .and_then(|ctrl| wifi::wpa_connect(&ctrl, ssid, security, password));
+            record_wpa(&socket, &["connect", ssid], result)?;
+        }
+    }
+    Ok(())
+}
+
+fn disconnect_wifi(interface: &str) -> Result<()> {
+    match wifi::backend(interface)? {
+        WifiBackend::NetworkManager => {
+            run_command("nmcli", &["device", "disconnect", interface])?;
+        }
+        WifiBackend::WpaSupplicant(socket) => {
+            let result = WpaCtrl::open(&socket).and_then(|ctrl| ctrl.command("DISCONNECT"));
+            record_wpa(&socket, &["disconnect"], result)?;
+        }
+    }
+    Ok(())
+}
+
+fn forget_wifi(interface: &str, ssid: &str) -> Result<()> {
+    match wifi::backend(interface)? {
+        WifiBackend::NetworkManager => {
+            run_command("nmcli", &["connection", "delete", "id", ssid])?;
+        }
+        WifiBackend::WpaSupplicant(socket) => {
+            let result = WpaCtrl::open(&socket).and_then(|ctrl| wifi::wpa_forget(&ctrl, ssid));
+            record_wpa(&socket, &["forget", ssid], result)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{
+        namespace, record, record_netlink, run_command, sudo, write_file, BondMode, DhcpClient,
+        Ipv6Setting, MacAddr, VlanProtocol,
+    };
+    use anyhow::{Context, Result};
+    use std::fs::File;
+    use std::io::Write;
+    use std::net::IpAddr;
+    use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+    use std::path::{Path, PathBuf};
+
+    pub fn set_address(
+        interface: &str,
+        address: &IpAddr,
+        prefix: u8,
+        label: Option<&str>,
+    ) -> Result<()> {
+        let addr = format!("{}/{}", address, prefix);
+        let mut args = vec!["addr", "add", &addr, "dev", interface];
+        if let Some(label) = label {
+            args.extend(["label", label]);
+        }
+        run_command("ip", &args)?;
+        Ok(())
+    }
+
+    pub fn flush_addresses(interface: &str) -> Result<()> {
+        run_command("ip", &["addr", "flush", "dev", interface])?;
+        Ok(())
+    }
+
+    pub fn del_address(interface: &str, address: &IpAddr, prefix: u8) -> Result<()> {
+        let addr = format!("{}/{}", address, prefix);
+        run_command("ip", &["addr", "del", &addr, "dev", interface])?;
+        Ok(())
+    }
+
+    pub fn set_route(interface: &str, gateway: &IpAddr) -> Result<()> {
+        let gw = gateway.to_string();
+        run_command(
+            "ip",
+            &["route", "add", "default", "via", &gw, "dev", interface],
+        )?;
+        Ok(())
+    }
+
+    pub fn del_route(interface: &str, gateway: &IpAddr) -> Result<()> {
+        let gw = gateway.to_string();
+        run_command(
+            "ip",
+            &["route", "del", "default", "via", &gw, "dev", interface],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_link(interface: &str, up: bool) -> Result<()> {
+        let status = if up { "up" } else { "down" };
+        run_command("ip", &["link", "set", interface, status])?;
+        Ok(())
+    }
+
+    /// Most drivers refuse a new address while the link is up; it comes
+    /// back up even when the change fails
+    pub fn set_mac(interface: &str, mac: &MacAddr) -> Result<()> {
+        let was_up = crate::link::get_links()?
+            .into_iter()
+            .find(|link| link.name == interface)
+            .is_some_and(|link| link.up);
+
+        if was_up {
+            set_link(interface, false)?;
+        }
+        let result = run_command(
+            "ip",
+            &["link", "set", "dev", interface, "address", &mac.to_string()],
+        );
+        if was_up {
+            set_link(interface, true)?;
+        }
+        result?;
+        Ok(())
+    }
+
+    pub fn set_ipv6(interface: &str, enabled: bool) -> Result<()> {
+        let path = format!("/proc/sys/net/ipv6/conf/{}/disable_ipv6", interface);
+        if !Path::new(&path).exists() {
+            anyhow::bail!(
+                "IPv6 is not available on this interface (kernel module may be disabled)"
+            );
+        }
+
+        write_file(Path::new(&path), if enabled { "0\n" } else { "1\n" })
+    }
+
+    pub fn set_ipv6_setting(interface: &str, setting: &Ipv6Setting) -> Result<()> {
+        let path = format!(
+            "/proc/sys/net/ipv6/conf/{}/{}",
+            interface,
+            setting.sysctl_name()
+        );
+        if !Path::new(&path).exists() {
+            anyhow::bail!(
+                "{} is not available on {}",
+                setting.sysctl_name(),
+                interface
+            );
+        }
+
+        write_file(Path::new(&path), &format!("{}\n", setting.sysctl_value()))
+    }
+
+    /// Ask for an address over DHCPv6, keeping the IPv4 configuration
+    pub fn start_dhcpv6(interface: &str) -> Result<()> {
+        if run_command("dhclient", &["-6", interface]).is_err() {
+            run_command("dhcpcd", &["-6", interface])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn start_dhcp(interface: &str) -> Result<()> {
+        // Pick the client before touching anything, so a missing one doesn't
+        // leave the interface without addresses
+        let client = ["dhclient", "dhcpcd"]
+            .into_iter()
+            .find(|program| sudo::find_in_path(program))
+            .context("No DHCP client found: install dhclient or dhcpcd")?;
+
+        // Remove any static IP, then ask for a lease
+        let _ = flush_addresses(interface);
+        run_command(client, &[interface])?;
+
+        Ok(())
+    }
+
+    pub fn renew_dhcp(interface: &str, client: DhcpClient) -> Result<()> {
+        match client {
+            DhcpClient::Dhclient => {
+                // Stop the running client without releasing, then let a new one
+                // re-request the lease it already holds
+                let _ = run_command("dhclient", &["-x", interface]);
+                run_command("dhclient", &[interface])?;
+            }
+            DhcpClient::Dhcpcd => {
+                run_command("dhcpcd", &["-n", interface])?;
+            }
+            DhcpClient::NetworkManager => {
+                run_command("nmcli", &["device", "reapply", interface])?;
+            }
+            DhcpClient::Networkd => {
+                run_command("networkctl", &["renew", interface])?;
+            }
+            DhcpClient::Configd => anyhow::bail!("configd only runs on macOS"),
+        }
+        Ok(())
+    }
+
+    pub fn release_dhcp(interface: &str, client: DhcpClient) -> Result<()> {
+        match client {
+            DhcpClient::Dhclient => {
+                run_command("dhclient", &["-r", interface])?;
+            }
+            DhcpClient::Dhcpcd => {
+                run_command("dhcpcd", &["-k", interface])?;
+            }
+            DhcpClient::NetworkManager => {
+                run_command("nmcli", &["device", "disconnect", interface])?;
+            }
+            DhcpClient::Networkd => {
+                anyhow::bail!(
+                    "systemd-networkd cannot release a lease on its own; bring {} down instead",
+                    interface
+                )
+            }
+            DhcpClient::Configd => anyhow::bail!("configd only runs on macOS"),
+        }
+        Ok(())
+    }
+
+    /// Rewrite resolv.conf atomically: write a sibling file that only root can
+    /// create, then rename it over the target. Follows the symlink that
+    /// systemd-resolved and friends install so the real file gets replaced.
+    ///
+    /// Inside a network namespace the namespace's own resolv.conf is written
+    /// instead, and one that would really be the host's is refused.
+    pub fn set_dns(_interface: &str, servers: &[IpAddr], search_domains: &[String]) -> Result<()> {
+        let mut content = String::new();
+        for server in servers {
+            content.push_str(&format!("nameserver {}\n", server));
+        }
+        if !search_domains.is_empty() {
+            content.push_str("search ");
+            content.push_str(&search_domains.join(" "));
+            content.push('\n');
+        }
+
+        if let Some(netns) = namespace::entered() {
+            return write_namespace_resolv_conf(&netns, &content);
+        }
+
+        let target = std::fs::canonicalize("/etc/resolv.conf")
+            .unwrap_or_else(|_| Path::new("/etc/resolv.conf").to_path_buf());
+        let dir = target.parent().unwrap_or(Path::new("/etc"));
+        let tmp = dir.join(".resolv.conf.netutil");
+
+        // A leftover from an interrupted run is ours to replace
+        let _ = std::fs::remove_file(&tmp);
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o644)
+            .open(&tmp)
+            .with_context(|| format!("Failed to create {}", tmp.display()))?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        let tmp_display = tmp.display().to_string();
+        let target_display = target.display().to_string();
+        match std::fs::rename(&tmp, &target) {
+            Ok(()) => {
+                record("rename", &[&tmp_display, &target_display], Some(0), "");
+                Ok(())
+            }
+            Err(e) => {
+                record(
+                    "rename",
+                    &[&tmp_display, &target_display],
+                    None,
+                    &e.to_string(),
+                );
+                Err(e).with_context(|| format!("Failed to replace {}", target_display))
+            }
+        }
+    }
+
+    pub fn create_vlan(
+        parent: &str,
+        name: &str,
+        id: u16,
+        protocol: VlanProtocol,
+        mtu: Option<u32>,
+    ) -> Result<()> {
+        let id = id.to_string();
+        let mut args = vec![
+            "link",
+            "add",
+            "link",
+            parent,
+            "name",
+            name,
+            "type",
+            "vlan",
+            "protocol",
+            protocol.as_str(),
+            "id",
+            &id,
+        ];
+        let mtu = mtu.map(|mtu| mtu.to_string());
+        if let Some(ref mtu) = mtu {
+            args.extend(["mtu", mtu]);
+        }
+        run_command("ip", &args)?;
+        Ok(())
+    }
+
+    pub fn delete_vlan(interface: &str) -> Result<()> {
+        let is_vlan = crate::link::get_links()?
+            .iter()
+            .any(|link| link.name == interface && link.kind.as_deref() == Some("vlan"));
+        if !is_vlan {
+            anyhow::bail!("'{}' is not a VLAN", interface);
+        }
+        run_command("ip", &["link", "delete", "dev", interface])?;
+        Ok(())
+    }
+
+    pub fn create_bond(name: &str, mode: BondMode, miimon: Option<u32>) -> Result<()> {
+        let mut args = vec![
+            "link",
+            "add",
+            "name",
+            name,
+            "type",
+            "bond",
+            "mode",
+            mode.as_str(),
+        ];
+        let miimon = miimon.map(|miimon| miimon.to_string());
+        if let Some(ref miimon) = miimon {
+            args.extend(["miimon", miimon]);
+        }
+        run_command("ip", &args)?;
+        Ok(())
+    }
+
+    pub fn delete_bond(interface: &str) -> Result<()> {
+        if link_kind(interface)?.as_deref() != Some("bond") {
+            anyhow::bail!("'{}' is not a bond", interface);
+        }
+        run_command("ip", &["link", "delete", "dev", interface])?;
+        Ok(())
+    }
+
+    pub fn create_bridge(name: &str, stp: bool) -> Result<()> {
+        let stp_state = if stp { "1" } else { "0" };
+        run_command(
+            "ip",
+            &[
+                "link",
+                "add",
+                "name",
+                name,
+                "type",
+                "bridge",
+                "stp_state",
+                stp_state,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_bridge(interface: &str) -> Result<()> {
+        if link_kind(interface)?.as_deref() != Some("bridge") {
+            anyhow::bail!("'{}' is not a bridge", interface);
+        }
+        run_command("ip", &["link", "delete", "dev", interface])?;
+        Ok(())
+    }
+
+    pub fn set_bridge_stp(bridge: &str, enabled: bool) -> Result<()> {
+        if link_kind(bridge)?.as_deref() != Some("bridge") {
+            anyhow::bail!("'{}' is not a bridge", bridge);
+        }
+        let stp_state = if enabled { "1" } else { "0" };
+        run_command(
+            "ip",
+            &[
+                "link",
+                "set",
+                "dev",
+                bridge,
+                "type",
+                "bridge",
+                "stp_state",
+                stp_state,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The bonding driver refuses slaves that are up, and brings them up
+    /// itself; bridge ports keep their state
+    pub fn enslave_interface(master: &str, interface: &str) -> Result<()> {
+        let kind = link_kind(master)?;
+        if !matches!(kind.as_deref(), Some("bond" | "team" | "bridge")) {
+            anyhow::bail!("'{}' is not a bond, team or bridge", master);
+        }
+        let links = crate::link::get_links()?;
+        let link = links
+            .iter()
+            .find(|link| link.name == interface)
+            .with_context(|| format!("no such interface '{}'", interface))?;
+        if link.loopback {
+            anyhow::bail!("the loopback interface can't be enslaved");
+        }
+        if let Some(current) = link.master {
+            let current = links.iter().find(|other| other.index == current);
+            anyhow::bail!(
+                "'{}' is already enslaved to '{}'",
+                interface,
+                current.map(|other| other.name.as_str()).unwrap_or("?")
+            );
+        }
+
+        if kind.as_deref() != Some("bridge") {
+            run_command("ip", &["link", "set", "dev", interface, "down"])?;
+        }
+        run_command("ip", &["link", "set", "dev", interface, "master", master])?;
+        Ok(())
+    }
+
+    pub fn release_interface(master: &str, interface: &str) -> Result<()> {
+        if master_of(interface)?.as_deref() != Some(master) {
+            anyhow::bail!("'{}' is not enslaved to '{}'", interface, master);
+        }
+        run_command("ip", &["link", "set", "dev", interface, "nomaster"])?;
+        Ok(())
+    }
+
+    pub fn set_active_slave(bond: &str, interface: &str) -> Result<()> {
+        if master_of(interface)?.as_deref() != Some(bond) {
+            anyhow::bail!("'{}' is not a slave of '{}'", interface, bond);
+        }
+        run_command(
+            "ip",
+            &[
+                "link",
+                "set",
+                "dev",
+                bond,
+                "type",
+                "bond",
+                "active_slave",
+                interface,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_offload(interface: &str, feature: &str, enabled: bool) -> Result<()> {
+        record_netlink(
+            "ethtool",
+            &["-K", interface, feature, if enabled { "on" } else { "off" }],
+            crate::ethtool::set_feature(interface, feature, enabled),
+        )
+    }
+
+    fn link_kind(interface: &str) -> Result<Option<String>> {
+        Ok(crate::link::get_links()?
+            .into_iter()
+            .find(|link| link.name == interface)
+            .and_then(|link| link.kind))
+    }
+
+    fn master_of(interface: &str) -> Result<Option<String>> {
+        let links = crate::link::get_links()?;
+        Ok(links
+            .iter()
+            .find(|link| link.name == interface)
+            .and_then(|link| link.master)
+            .and_then(|master| links.iter().find(|other| other.index == master))
+            .map(|master| master.name.clone()))
+    }
+
+    /// The namespace's resolv.conf, opened with `flags`, provided it isn't
+    /// the host's
+    fn open_namespace_resolv_conf(netns: &Path, flags: i32) -> Result<(File, PathBuf)> {
+        let target = namespace::resolv_conf(netns);
+        let file = open_in_root(&target, flags)
+            .with_context(|| format!("Failed to open {}", target.display()))?;
+
+        let host = std::fs::metadata("/etc/resolv.conf").ok();
+        let own = file.metadata()?;
+        if host.is_some_and(|host| host.dev() == own.dev() && host.ino() == own.ino()) {
+            anyhow::bail!(
+                "network namespace {} uses the host's resolv.conf; give it its own \
+                 (e.g. /etc/netns/<name>/resolv.conf) before changing its DNS",
+                netns.display()
+            );
+        }
+        Ok((file, target))
+    }
+
+    /// Open `path` the way its owner sees it. Below `/proc/<pid>/root` every
+    /// symlink, `..` included, is resolved inside that process's root, so a
+    /// container can't point the write at a file of ours. Anywhere else no
+    /// symlinks are followed at all.
+    fn open_in_root(path: &Path, flags: i32) -> std::io::Result<File> {
+        use nix::libc::{RESOLVE_IN_ROOT, RESOLVE_NO_MAGICLINKS, RESOLVE_NO_SYMLINKS};
+
+        match split_process_root(path) {
+            Some((root, rest)) => {
+                open_beneath(&root, rest, RESOLVE_IN_ROOT | RESOLVE_NO_MAGICLINKS, flags)
+            }
+            None => open_beneath(
+                Path::new("/"),
+                path.strip_prefix("/").unwrap_or(path),
+                RESOLVE_NO_SYMLINKS,
+                flags,
+            ),
+        }
+    }
+
+    /// openat2(2) of `path` relative to the directory `root`
+    fn open_beneath(root: &Path, path: &Path, resolve: u64, flags: i32) -> std::io::Result<File> {
+        use nix::libc;
+        use std::ffi::CString;
+        use std::os::fd::{AsRawFd, FromRawFd};
+        use std::os::unix::ffi::OsStrExt;
+
+        let root = File::open(root)?;
+        let path = CString::new(path.as_os_str().as_bytes())?;
+
+        // SAFETY: all zeros is a valid `open_how`, which is non-exhaustive
+        // and can't be built from its fields
+        let mut how: libc::open_how = unsafe { std::mem::zeroed() };
+        how.flags = (flags | libc::O_CLOEXEC) as u64;
+        how.resolve = resolve;
+
+        // SAFETY: `path` is NUL terminated and `how` is a complete `open_how`
+        // of the size passed; both outlive the call
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_openat2,
+                root.as_raw_fd(),
+                path.as_ptr(),
+                &how as *const libc::open_how,
+                std::mem::size_of::<libc::open_how>(),
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // SAFETY: openat2 returned a new descriptor that nothing else owns
+        Ok(unsafe { File::from_raw_fd(fd as i32) })
+    }
+
+    /// `/proc/<pid>/root/etc/resolv.conf` as its root and `etc/resolv.conf`
+    fn split_process_root(path: &Path) -> Option<(PathBuf, &Path)> {
+        let rest = path.strip_prefix("/proc").ok()?;
+        let mut components = rest.components();
+        let pid = components.next()?.as_os_str().to_str()?;
+        if !pid.bytes().all(|b| b.is_ascii_digit()) || components.next()?.as_os_str() != "root" {
+            return None;
+        }
+        Some((
+            Path::new("/proc").join(pid).join("root"),
+            components.as_path(),
+        ))
+    }
+
+    /// Overwrite in place: a container's resolv.conf is usually a bind mount,
+    /// which can't be renamed over. The file is emptied only once it is known
+    /// not to be the host's.
+    fn write_namespace_resolv_conf(netns: &Path, content: &str) -> Result<()> {
+        let (mut file, target) = open_namespace_resolv_conf(netns, nix::libc::O_WRONLY)?;
+        let target_display = target.display().to_string();
+
+        let result = file.set_len(0).and_then(|()| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+        match result {
+            Ok(()) => {
+                record("write", &[&target_display], Some(0), "");
+                Ok(())
+            }
+            Err(e) => {
+                record("write", &[&target_display], None, &e.to_string());
+                Err(e).with_context(|| format!("Failed to write {}", target_display))
+            }
+        }
+    }
+
+    pub fn flush_dns_cache() -> Result<()> {
+        if let Some(netns) = namespace::entered() {
+            return flush_namespace_dns_cache(&netns);
+        }
+
+        // Try systemd-resolved first (most common on modern Linux)
+        if run_command("resolvectl", &["flush-caches"]).is_ok() {
+            return Ok(());
+        }
+
+        // Try systemd-resolve (older systemd versions)
+        if run_command("systemd-resolve", &["--flush-caches"]).is_ok() {
+            return Ok(());
+        }
+
+        // Try nscd (Name Service Cache Daemon)
+        if run_command("nscd", &["-i", "hosts"]).is_ok() {
+            return Ok(());
+        }
+
+        // Try dnsmasq
+        if run_command("killall", &["-HUP", "dnsmasq"]).is_ok() {
+            return Ok(());
+        }
+
+        anyhow::bail!("Could not flush DNS cache. No supported DNS caching service found.")
+    }
+
+    /// resolved and nscd serve the host, and `killall` would reach every
+    /// dnsmasq on it, so only dnsmasq instances running inside the namespace
+    /// are told to drop their cache
+    fn flush_namespace_dns_cache(netns: &Path) -> Result<()> {
+        open_namespace_resolv_conf(netns, nix::libc::O_RDONLY)?;
+        // This thread entered the namespace, the process as a whole did not
+        let own = std::fs::metadata("/proc/thread-self/ns/net")?.ino();
+
+        let mut flushed = false;
+        for entry in std::fs::read_dir("/proc")?.flatten() {
+            let pid = entry.file_name().to_string_lossy().to_string();
+            if !pid.bytes().all(|b| b.is_ascii_digit()) {
+                continue;
+            }
+            let comm = std::fs::read_to_string(entry.path().join("comm")).unwrap_or_default();
+            let netns_ino = std::fs::metadata(entry.path().join("ns/net")).map(|m| m.ino());
+            if comm.trim() == "dnsmasq" && netns_ino.is_ok_and(|ino| ino == own) {
+                run_command("kill", &["-HUP", &pid])?;
+                flushed = true;
+            }
+        }
+
+        if !flushed {
+            anyhow::bail!(
+                "No DNS cache runs in network namespace {}; only dnsmasq can be flushed there",
+                netns.display()
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use nix::libc::{O_RDONLY, RESOLVE_IN_ROOT, RESOLVE_NO_MAGICLINKS};
+        use std::io::Read;
+        use std::os::unix::fs::symlink;
+
+        fn read(mut file: File) -> String {
+            let mut content = String::new();
+            file.read_to_string(&mut content).unwrap();
+            content
+        }
+
+        #[test]
+        fn process_roots() {
+            assert_eq!(
+                split_process_root(Path::new("/proc/42/root/etc/resolv.conf")),
+                Some((PathBuf::from("/proc/42/root"), Path::new("etc/resolv.conf")))
+            );
+            for path in [
+                "/etc/netns/blue/resolv.conf",
+                "/proc/self/root/etc/resolv.conf",
+                "/proc/42/cwd/etc/resolv.conf",
+                "/proc/42",
+            ] {
+                assert_eq!(split_process_root(Path::new(path)), None, "{}", path);
+            }
+        }
+
+        #[test]
+        fn symlinks_stay_inside_the_root() {
+            let dir =
+                std::env::temp_dir().join(format!("netutil-resolv-test-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            let root = dir.join("root");
+            std::fs::create_dir_all(root.join("run")).unwrap();
+            std::fs::write(root.join("run/resolv.conf"), "nameserver 192.0.2.53\n").unwrap();
+            std::fs::write(dir.join("secret"), "outside\n").unwrap();
+            // An absolute link as containers have them, and one trying to climb out
+            symlink("/run", root.join("etc")).unwrap();
+            symlink("../../secret", root.join("run/escape")).unwrap();
+            let in_root = RESOLVE_IN_ROOT | RESOLVE_NO_MAGICLINKS;
+
+            let file = open_beneath(&root, Path::new("etc/resolv.conf"), in_root, O_RDONLY);
+            assert_eq!(read(file.unwrap()), "nameserver 192.0.2.53\n");
+
+            let escape = open_beneath(&root, Path::new("etc/escape"), in_root, O_RDONLY);
+            assert_eq!(escape.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+            // Outside a process root no link is followed
+            let linked = dir.join("root/etc/resolv.conf");
+            let error = open_in_root(&linked, O_RDONLY).unwrap_err();
+            assert_eq!(error.raw_os_error(), Some(nix::libc::ELOOP));
+            let plain = dir.join("root/run/resolv.conf");
+            assert_eq!(
+                read(open_in_root(&plain, O_RDONLY).unwrap()),
+                "nameserver 192.0.2.53\n"
+            );
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{run_command, BondMode, DhcpClient, Ipv6Setting, MacAddr, VlanProtocol};
+    use anyhow::Result;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    pub fn set_address(
+        interface: &str,
+        address: &IpAddr,
+        prefix: u8,
+        label: Option<&str>,
+    ) -> Result<()> {
+        if label.is_some() {
+            anyhow::bail!("address labels are not supported on macOS");
+        }
+
+        // `alias` adds the address next to the existing ones, like `ip addr add`
+        match address {
+            IpAddr::V4(v4) => {
+                let mask = Ipv4Addr::from(u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0));
+                run_command(
+                    "ifconfig",
+                    &[
+                        interface,
+                        "alias",
+                        &v4.to_string(),
+                        "netmask",
+                        &mask.to_string(),
+                    ],
+                )?;
+            }
+            IpAddr::V6(v6) => {
+                run_command(
+                    "ifconfig",
+                    &[
+                        interface,
+                        "inet6",
+                        &v6.to_string(),
+                        "prefixlen",
+                        &prefix.to_string(),
+                        "alias",
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn flush_addresses(interface: &str) -> Result<()> {
+        run_command("ifconfig", &[interface, "inet", "0.0.0.0", "delete"])?;
+        Ok(())
+    }
+
+    pub fn del_address(interface: &str, address: &IpAddr, _prefix: u8) -> Result<()> {
+        match address {
+            IpAddr::V4(v4) => {
+                run_command("ifconfig", &[interface, "inet", &v4.to_string(), "-alias"])?
+            }
+            IpAddr::V6(v6) => {
+                run_command("ifconfig", &[interface, "inet6", &v6.to_string(), "delete"])?
+            }
+        };
+        Ok(())
+    }
+
+    pub fn set_route(_interface: &str, gateway: &IpAddr) -> Result<()> {
+        run_command("route", &["-n", "add", "default", &gateway.to_string()])?;
+        Ok(())
+    }
+
+    pub fn del_route(_interface: &str, gateway: &IpAddr) -> Result<()> {
+        run_command("route", &["-n", "delete", "default", &gateway.to_string()])?;
+        Ok(())
+    }
+
+    pub fn set_link(interface: &str, up: bool) -> Result<()> {
+        let status = if up { "up" } else { "down" };
+        run_command("ifconfig", &[interface, status])?;
+        Ok(())
+    }
+
+    /// WiFi interfaces have to be disassociated first, or the change is ignored
+    pub fn set_mac(interface: &str, mac: &MacAddr) -> Result<()> {
+        run_command("ifconfig", &[interface, "ether", &mac.to_string()])?;
+        Ok(())
+    }
+
+    pub fn set_ipv6(interface: &str, enabled: bool) -> Result<()> {
+        let flag = if enabled {
+            "-setv6automatic"
+        } else {
+            "-setv6off"
+        };
+        run_command("networksetup", &[flag, interface])?;
+        Ok(())
+    }
+
+    pub fn start_dhcp(interface: &str) -> Result<()> {
+        run_command("networksetup", &["-setdhcp", interface])?;
+        Ok(())
+    }
+
+    pub fn set_ipv6_setting(_interface: &str, setting: &Ipv6Setting) -> Result<()> {
+        anyhow::bail!("{} is not configurable on macOS", setting.sysctl_name())
+    }
+
+    /// configd owns the lease regardless of `client`
+    pub fn renew_dhcp(interface: &str, _client: DhcpClient) -> Result<()> {
+        run_command("ipconfig", &["set", interface, "DHCP"])?;
+        Ok(())
+    }
+
+    pub fn release_dhcp(interface: &str, _client: DhcpClient) -> Result<()> {
+        run_command("ipconfig", &["set", interface, "NONE"])?;
+        Ok(())
+    }
+
+    /// configd runs DHCPv6 itself when IPv6 is set to automatic
+    pub fn start_dhcpv6(interface: &str) -> Result<()> {
+        run_command("networksetup", &["-setv6automatic", interface])?;
+        Ok(())
+    }
+
+    pub fn set_dns(interface: &str, servers: &[IpAddr], search_domains: &[String]) -> Result<()> {
+        let servers: Vec<String> = servers.iter().map(|s| s.to_string()).collect();
+        let mut args = vec!["-setdnsservers", interface];
+        args.extend(servers.iter().map(|s| s.as_str()));
+        run_command("networksetup", &args)?;
+
+        if !search_domains.is_empty() {
+            let mut args = vec!["-setsearchdomains", interface];
+            args.extend(search_domains.iter().map(|s| s.as_str()));
+            run_command("networksetup", &args)?;
+        }
+        Ok(())
+    }
+
+    /// VLAN interfaces on macOS must be called vlanN and only speak 802.1Q
+    pub fn create_vlan(
+        parent: &str,
+        name: &str,
+        id: u16,
+        protocol: VlanProtocol,
+        mtu: Option<u32>,
+    ) -> Result<()> {
+        if !is_vlan_name(name) {
+            anyhow::bail!("VLAN interfaces must be named vlan<N> on macOS");
+        }
+        if protocol != VlanProtocol::Dot1Q {
+            anyhow::bail!("macOS only supports 802.1Q VLANs");
+        }
+        let id = id.to_string();
+        run_command("ifconfig", &[name, "create"])?;
+        run_command("ifconfig", &[name, "vlan", &id, "vlandev", parent])?;
+        if let Some(mtu) = mtu {
+            run_command("ifconfig", &[name, "mtu", &mtu.to_string()])?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_vlan(interface: &str) -> Result<()> {
+        if !is_vlan_name(interface) {
+            anyhow::bail!("'{}' is not a VLAN", interface);
+        }
+        run_command("ifconfig", &[interface, "destroy"])?;
+        Ok(())
+    }
+
+    fn is_vlan_name(name: &str) -> bool {
+        name.strip_prefix("vlan")
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    /// macOS bonds are always 802.3ad and must be called bondN
+    pub fn create_bond(name: &str, mode: BondMode, _miimon: Option<u32>) -> Result<()> {
+        if !is_bond_name(name) {
+            anyhow::bail!("bond interfaces must be named bond<N> on macOS");
+        }
+        if mode != BondMode::Ieee8023ad {
+            anyhow::bail!("macOS only supports 802.3ad bonds");
+        }
+        run_command("ifconfig", &[name, "create"])?;
+        Ok(())
+    }
+
+    pub fn delete_bond(interface: &str) -> Result<()> {
+        if !is_bond_name(interface) {
+            anyhow::bail!("'{}' is not a bond", interface);
+        }
+        run_command("ifconfig", &[interface, "destroy"])?;
+        Ok(())
+    }
+
+    pub fn enslave_interface(master: &str, interface: &str) -> Result<()> {
+        if is_bond_name(master) {
+            run_command("ifconfig", &[master, "bonddev", interface])?;
+        } else if is_bridge_name(master) {
+            run_command("ifconfig", &[master, "addm", interface])?;
+        } else {
+            anyhow::bail!("'{}' is not a bond or bridge", master);
+        }
+        Ok(())
+    }
+
+    pub fn release_interface(master: &str, interface: &str) -> Result<()> {
+        if is_bond_name(master) {
+            run_command("ifconfig", &[master, "-bonddev", interface])?;
+        } else if is_bridge_name(master) {
+            run_command("ifconfig", &[master, "deletem", interface])?;
+        } else {
+            anyhow::bail!("'{}' is not a bond or bridge", master);
+        }
+        Ok(())
+    }
+
+    /// Bridges on macOS must be called bridgeN; STP is switched per member
+    pub fn create_bridge(name: &str, _stp: bool) -> Result<()> {
+        if !is_bridge_name(name) {
+            anyhow::bail!("bridge interfaces must be named bridge<N> on macOS");
+        }
+        run_command("ifconfig", &[name, "create"])?;
+        Ok(())
+    }
+
+    pub fn delete_bridge(interface: &str) -> Result<()> {
+        if !is_bridge_name(interface) {
+            anyhow::bail!("'{}' is not a bridge", interface);
+        }
+        run_command("ifconfig", &[interface, "destroy"])?;
+        Ok(())
+    }
+
+    pub fn set_bridge_stp(_bridge: &str, _enabled: bool) -> Result<()> {
+        anyhow::bail!("STP is set per member on macOS; use ifconfig <bridge> stp <member>")
+    }
+
+    fn is_bridge_name(name: &str) -> bool {
+        name.strip_prefix("bridge")
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    pub fn set_active_slave(_bond: &str, _interface: &str) -> Result<()> {
+        anyhow::bail!("macOS bonds have no active slave to fail over")
+    }
+
+    pub fn set_offload(_interface: &str, _feature: &str, _enabled: bool) -> Result<()> {
+        anyhow::bail!("Changing offloads is only supported on Linux")
+    }
+
+    fn is_bond_name(name: &str) -> bool {
+        name.strip_prefix("bond")
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    pub fn flush_dns_cache() -> Result<()> {
+        run_command("dscacheutil", &["-flushcache"])?;
+        run_command("killall", &["-HUP", "mDNSResponder"])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const LOOPBACK: &str = if cfg!(target_os = "macos") {
+        "lo0"
+    } else {
+        "lo"
+    };
+
+    fn error(request: HelperRequest) -> String {
+        format!("{:#}", handle(&request).unwrap_err())
+    }
+
+    #[test]
+    fn envelope_wire_format() {
+        let envelope = Envelope {
+            netns: Some(PathBuf::from("/run/netns/blue")),
+            request: HelperRequest::SetAddress {
+                interface: "eth0".to_string(),
+                address: "192.0.2.10".parse().unwrap(),
+                prefix: 24,
+                label: None,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            json!({
+                "netns": "/run/netns/blue",
+                "op": "set_address",
+                "interface": "eth0",
+                "address": "192.0.2.10",
+                "prefix": 24,
+            })
+        );
+
+        let envelope = Envelope {
+            netns: None,
+            request: HelperRequest::GetWireGuard {
+                interface: "wg0".to_string(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&envelope).unwrap(),
+            json!({"op": "get_wireguard", "interface": "wg0"})
+        );
+        assert_eq!(
+            serde_json::to_value(HelperRequest::GetInterfaces).unwrap(),
+            json!({"op": "get_interfaces"})
+        );
+    }
+
+    #[test]
+    fn envelope_parses_requests() {
+        let envelope: Envelope = serde_json::from_str(
+            r#"{"netns":"/proc/42/ns/net","op":"probe_dhcp","interface":"eth0","timeout_ms":3000}"#,
+        )
+        .unwrap();
+        assert_eq!(envelope.netns, Some(PathBuf::from("/proc/42/ns/net")));
+        assert!(matches!(
+            envelope.request,
+            HelperRequest::ProbeDhcp { ref interface, timeout_ms: 3000 } if interface == "eth0"
+        ));
+
+        let envelope: Envelope = serde_json::from_str(
+            r#"{"op":"create_vlan","parent":"eth0","name":"eth0.10","id":10,"protocol":"Dot1Q"}"#,
+        )
+        .unwrap();
+        assert_eq!(envelope.netns, None);
+        assert!(matches!(
+            envelope.request,
+            HelperRequest::CreateVlan {
+                id: 10,
+                mtu: None,
+                ..
+            }
+        ));
+
+        for line in [
+            r#"{"op":"reboot"}"#,
+            r#"{"interface":"eth0"}"#,
+            r#"{"op":"set_link","interface":"eth0"}"#,
+            r#"{"op":"set_address","interface":"eth0","address":"not an ip","prefix":24}"#,
+            r#"{"op":"create_vlan","parent":"eth0","name":"v","id":70000,"protocol":"Dot1Q"}"#,
+        ] {
+            assert!(serde_json::from_str::<Envelope>(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn password_is_left_out_when_absent() {
+        let request = HelperRequest::ConnectWifi {
+            interface: "wlan0".to_string(),
+            ssid: "home".to_string(),
+            security: WifiSecurity::Open,
+            password: None,
+        };
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["op"], "connect_wifi");
+        assert!(value.get("password").is_none());
+    }
+
+    #[test]
+    fn rejects_bad_interface_names() {
+        for name in [
+            "",
+            "-eth0",
+            "../eth0",
+            "eth0/1",
+            "eth 0",
+            "eth0;reboot",
+            "averyveryverylongname",
+        ] {
+            let message = error(HelperRequest::SetLink {
+                interface: name.to_string(),
+                up: true,
+            });
+            assert_eq!(message, format!("invalid interface name '{}'", name));
+        }
+
+        assert_eq!(
+            error(HelperRequest::SetLink {
+                interface: "nosuch0".to_string(),
+                up: true,
+            }),
+            "no such interface 'nosuch0'"
+        );
+        assert_eq!(
+            error(HelperRequest::CreateBridge {
+                name: "br/0".to_string(),
+                stp: false,
+            }),
+            "invalid interface name 'br/0'"
+        );
+        assert_eq!(
+            error(HelperRequest::CreateBridge {
+                name: LOOPBACK.to_string(),
+                stp: false,
+            }),
+            format!("interface '{}' already exists", LOOPBACK)
+        );
+    }
+
+    #[test]
+    fn domains() {
+        for domain in ["example.com", "example.com.", "a-b.c0", "lan"] {
+            assert!(validate_domain(domain).is_ok(), "{}", domain);
+        }
+        let long_label = "a".repeat(64);
+        let long_name = ["abcdefghi"; 26].join(".");
+        for domain in [
+            "",
+            ".",
+            "a..b",
+            "-lead.com",
+            "under_score.com",
+            "spa ce.com",
+            "evil.com\nnameserver 1.2.3.4",
+            &long_label,
+            &long_name,
+        ] {
+            assert!(validate_domain(domain).is_err(), "{:?}", domain);
+        }
+
+        // Reaches no further than the check
+        assert_eq!(
+            error(HelperRequest::SetDns {
+                interface: String::new(),
+                servers: vec!["192.0.2.53".parse().unwrap()],
+                search_domains: vec!["ok.example".to_string(), "bad domain".to_string()],
+            }),
+            "invalid search domain 'bad domain'"
+        );
+    }
+
+    #[test]
+    fn ssids() {
+        for ssid in ["home", "Café Wi-Fi", "a b", &"x".repeat(32)] {
+            assert!(validate_ssid(ssid).is_ok(), "{}", ssid);
+        }
+        for ssid in ["", "line\nbreak", "tab\there", "nul\0", &"x".repeat(33)] {
+            assert!(validate_ssid(ssid).is_err(), "{:?}", ssid);
+        }
+        // 11 three-byte characters are 33 bytes
+        assert!(validate_ssid(&"€".repeat(11)).is_err());
+
+        assert_eq!(
+            error(HelperRequest::ForgetWifi {
+                interface: LOOPBACK.to_string(),
+                ssid: "bad\nssid".to_string(),
+            }),
+            "invalid SSID 'bad\\nssid'"
+        );
+    }
+
+    #[test]
+    fn unicast_addresses() {
+        for address in ["192.0.2.1", "10.0.0.255", "2001:db8::1", "fe80::1"] {
+            assert!(
+                validate_unicast(&address.parse().unwrap()).is_ok(),
+                "{}",
+                address
+            );
+        }
+        for address in ["0.0.0.0", "255.255.255.255", "224.0.0.1", "::", "ff02::1"] {
+            assert!(
+                validate_unicast(&address.parse().unwrap()).is_err(),
+                "{}",
+                address
+            );
+            let message = error(HelperRequest::SetRoute {
+                interface: LOOPBACK.to_string(),
+                gateway: address.parse().unwrap(),
+            });
+            assert_eq!(message, format!("{} is not a unicast address", address));
+        }
+
+        assert!(validate_address(&"192.0.2.1".parse().unwrap(), 32).is_ok());
+        assert!(validate_address(&"192.0.2.1".parse().unwrap(), 33).is_err());
+        assert!(validate_address(&"2001:db8::1".parse().unwrap(), 128).is_ok());
+        assert!(validate_address(&"2001:db8::1".parse().unwrap(), 129).is_err());
+    }
+
+    #[test]
+    fn probe_timeout_bounds() {
+        for timeout_ms in [0, 99, 30_001, u64::MAX] {
+            assert_eq!(
+                error(HelperRequest::ProbeDhcp {
+                    interface: LOOPBACK.to_string(),
+                    timeout_ms,
+                }),
+                "probe timeout must be between 100ms and 30s",
+                "{}",
+                timeout_ms
+            );
+        }
+    }
+
+    #[test]
+    fn vlan_bounds() {
+        let vlan = |id, mtu| HelperRequest::CreateVlan {
+            parent: LOOPBACK.to_string(),
+            name: "nutest.v0".to_string(),
+            id,
+            protocol: VlanProtocol::Dot1Q,
+            mtu,
+        };
+
+        for id in [0, 4095, u16::MAX] {
+            assert_eq!(
+                error(vlan(id, None)),
+                format!("VLAN id {} is out of range 1-4094", id)
+            );
+        }
+        for mtu in [0, 67, 65536] {
+            assert_eq!(
+                error(vlan(10, Some(mtu))),
+                "MTU must be between 68 and 65535",
+                "{}",
+                mtu
+            );
+        }
+        assert_eq!(
+            error(HelperRequest::CreateVlan {
+                parent: "nosuch0".to_string(),
+                name: "nutest.v0".to_string(),
+                id: 10,
+                protocol: VlanProtocol::Dot1Q,
+                mtu: None,
+            }),
+            "no such interface 'nosuch0'"
+        );
+    }
+}
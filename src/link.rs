@@ -0,0 +1,193 @@
+//! Links as the kernel sees them, from an rtnetlink `RTM_GETLINK` dump.
+//!
+//! `if_addrs` only knows interfaces that have addresses; this lists every
+//! link with what the kernel tracks about it, such as the kind of a virtual
+//! device, the parent of a VLAN, the bond or bridge a link belongs to and
+//! the namespace the other end of a veth pair lives in.
+
+use crate::models::{LinkCounters, MacAddr, VlanProtocol};
+use crate::netlink::{self, Netlink, NLM_F_DUMP};
+use anyhow::Result;
+use std::fs::File;
+use std::os::fd::AsRawFd;
+
+const RTM_GETLINK: u16 = 18;
+const RTM_GETNSID: u16 = 90;
+
+/// `struct ifinfomsg`: family, padding, type, index, flags, change
+const IFINFOMSG_LEN: usize = 16;
+
+const IFF_UP: u32 = 0x1;
+const IFF_LOOPBACK: u32 = 0x8;
+
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_MTU: u16 = 4;
+const IFLA_LINK: u16 = 5;
+const IFLA_MASTER: u16 = 10;
+const IFLA_LINKINFO: u16 = 18;
+const IFLA_STATS64: u16 = 23;
+const IFLA_LINK_NETNSID: u16 = 37;
+const IFLA_PERM_ADDRESS: u16 = 54;
+
+const IFLA_INFO_KIND: u16 = 1;
+const IFLA_INFO_DATA: u16 = 2;
+
+const IFLA_VLAN_ID: u16 = 1;
+const IFLA_VLAN_PROTOCOL: u16 = 5;
+
+const NETNSA_NSID: u16 = 1;
+const NETNSA_FD: u16 = 3;
+
+/// `struct rtgenmsg`: just the family, padded
+const RTGENMSG_LEN: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct LinkInfo {
+    pub index: u32,
+    pub name: String,
+    /// Administratively up
+    pub up: bool,
+    pub loopback: bool,
+    /// `ARPHRD_*` hardware type
+    pub hardware_type: u16,
+    pub mac: Option<MacAddr>,
+    /// Burned-in address; drivers without one leave it all zeros
+    pub permanent_mac: Option<MacAddr>,
+    pub mtu: Option<u32>,
+    /// `IFLA_INFO_KIND` of virtual devices, e.g. `bridge`, `vlan` or `wireguard`
+    pub kind: Option<String>,
+    /// Index of the lower device, e.g. the parent of a VLAN or the other end
+    /// of a veth pair
+    pub parent: Option<u32>,
+    /// Namespace `parent` lives in, when it isn't this one
+    pub link_netnsid: Option<i32>,
+    /// Index of the bond, team or bridge this link is enslaved to
+    pub master: Option<u32>,
+    /// VLAN id and protocol, for `vlan` links
+    pub vlan: Option<(u16, VlanProtocol)>,
+    pub counters: Option<LinkCounters>,
+}
+
+/// Every link in the current network namespace
+pub fn get_links() -> Result<Vec<LinkInfo>> {
+    let mut socket = Netlink::route()?;
+    let replies = socket.request(RTM_GETLINK, NLM_F_DUMP, &[0u8; IFINFOMSG_LEN])?;
+    Ok(replies
+        .iter()
+        .filter_map(|reply| parse_link(reply))
+        .collect())
+}
+
+fn parse_link(payload: &[u8]) -> Option<LinkInfo> {
+    let header = payload.get(..IFINFOMSG_LEN)?;
+    let flags = netlink::read_u32(&header[8..12])?;
+    let mut link = LinkInfo {
+        index: netlink::read_u32(&header[4..8])?,
+        hardware_type: netlink::read_u16(&header[2..4])?,
+        up: flags & IFF_UP != 0,
+        loopback: flags & IFF_LOOPBACK != 0,
+        ..Default::default()
+    };
+
+    for (kind, value) in netlink::attrs(&payload[IFINFOMSG_LEN..]) {
+        match kind {
+            IFLA_ADDRESS => link.mac = MacAddr::from_bytes(value),
+            IFLA_PERM_ADDRESS => {
+                link.permanent_mac = MacAddr::from_bytes(value).filter(|mac| !mac.is_zero())
+            }
+            IFLA_IFNAME => link.name = netlink::read_string(value),
+            IFLA_MTU => link.mtu = netlink::read_u32(value),
+            // Zero, or the link itself, for devices without a lower one
+            IFLA_LINK => link.parent = netlink::read_u32(value).filter(|&parent| parent != 0),
+            IFLA_MASTER => link.master = netlink::read_u32(value),
+            IFLA_LINKINFO => parse_link_info(value, &mut link),
+            IFLA_LINK_NETNSID => link.link_netnsid = netlink::read_i32(value),
+            IFLA_STATS64 => link.counters = parse_stats64(value),
+            _ => {}
+        }
+    }
+    if link.parent == Some(link.index) && link.link_netnsid.is_none() {
+        link.parent = None;
+    }
+
+    (!link.name.is_empty()).then_some(link)
+}
+
+/// `struct rtnl_link_stats64`, of which newer kernels append more fields
+/// than the ones read here
+fn parse_stats64(value: &[u8]) -> Option<LinkCounters> {
+    let field = |i: usize| netlink::read_u64(value.get(i * 8..)?);
+    Some(LinkCounters {
+        rx_packets: field(0)?,
+        tx_packets: field(1)?,
+        rx_bytes: field(2)?,
+        tx_bytes: field(3)?,
+        rx_errors: field(4)?,
+        tx_errors: field(5)?,
+        rx_dropped: field(6)?,
+        tx_dropped: field(7)?,
+        multicast: field(8)?,
+        collisions: field(9)?,
+        rx_length_errors: field(10)?,
+        rx_over_errors: field(11)?,
+        rx_crc_errors: field(12)?,
+        rx_frame_errors: field(13)?,
+        rx_fifo_errors: field(14)?,
+        rx_missed_errors: field(15)?,
+        tx_aborted_errors: field(16)?,
+        tx_carrier_errors: field(17)?,
+        tx_fifo_errors: field(18)?,
+        tx_heartbeat_errors: field(19)?,
+        tx_window_errors: field(20)?,
+    })
+}
+
+/// The id the current namespace knows the namespace open in `file` by, as
+/// used in `IFLA_LINK_NETNSID`; `None` when it hasn't been given one
+pub fn namespace_id(file: &File) -> Result<Option<i32>> {
+    let mut payload = vec![0u8; RTGENMSG_LEN];
+    netlink::push_attr(
+        &mut payload,
+        NETNSA_FD,
+        &(file.as_raw_fd() as u32).to_ne_bytes(),
+    );
+
+    let mut socket = Netlink::route()?;
+    let replies = socket.request(RTM_GETNSID, 0, &payload)?;
+    Ok(replies
+        .iter()
+        .filter_map(|reply| reply.get(RTGENMSG_LEN..))
+        .flat_map(netlink::attrs)
+        .find(|(kind, _)| *kind == NETNSA_NSID)
+        .and_then(|(_, value)| netlink::read_i32(value))
+        .filter(|&id| id >= 0))
+}
+
+fn parse_link_info(payload: &[u8], link: &mut LinkInfo) {
+    let attrs = netlink::attrs(payload);
+    link.kind = attrs
+        .iter()
+        .find(|(kind, _)| *kind == IFLA_INFO_KIND)
+        .map(|(_, value)| netlink::read_string(value));
+    let Some((_, data)) = attrs.iter().find(|(kind, _)| *kind == IFLA_INFO_DATA) else {
+        return;
+    };
+
+    if link.kind.as_deref() == Some("vlan") {
+        let data = netlink::attrs(data);
+        let find = |wanted: u16| {
+            data.iter()
+                .find(|(kind, _)| *kind == wanted)
+                .map(|(_, value)| *value)
+        };
+        let id = find(IFLA_VLAN_ID).and_then(netlink::read_u16);
+        // The protocol is an ethertype in network byte order
+        let protocol = find(IFLA_VLAN_PROTOCOL)
+            .and_then(netlink::read_u16)
+            .map(u16::from_be)
+            .and_then(VlanProtocol::from_ethertype)
+            .unwrap_or_default();
+        link.vlan = id.map(|id| (id, protocol));
+    }
+}
@@ -1,9 +1,32 @@
 mod app;
+mod audit;
+mod bond;
+mod bridge;
+mod config;
+mod conflicts;
+#[cfg(target_os = "linux")]
+mod container;
+mod dhcp;
+#[cfg(target_os = "linux")]
+mod ethtool;
 mod event;
+mod helper;
+#[cfg(target_os = "linux")]
+mod link;
 mod models;
+mod namespace;
+#[cfg(target_os = "linux")]
+mod netlink;
 mod network;
+mod subnet;
 mod sudo;
 mod ui;
+mod undo;
+mod validation;
+mod wifi;
+mod wireguard;
+#[cfg(target_os = "linux")]
+mod wireless;
 
 use anyhow::Result;
 use crossterm::{
@@ -14,6 +37,25 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
 
 fn main() -> Result<()> {
+    // Started by ourselves through sudo/doas/pkexec to perform privileged changes
+    if std::env::args().nth(1).as_deref() == Some(helper::HELPER_FLAG) {
+        return helper::run();
+    }
+
+    // Non-interactive DHCP probe for scripts, e.g. against a test responder
+    if std::env::args().nth(1).as_deref() == Some("--probe-dhcp") {
+        let interface = std::env::args()
+            .nth(2)
+            .ok_or_else(|| anyhow::anyhow!("usage: netutil-tui --probe-dhcp <interface>"))?;
+        let offers = dhcp::probe(&interface, std::time::Duration::from_secs(3))?;
+        println!("{}", serde_json::to_string_pretty(&offers)?);
+        return Ok(());
+    }
+
+    // Load config before touching the terminal so errors print normally
+    let config = config::Config::load()?;
+    sudo::init(config.privilege)?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -22,7 +64,7 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run
-    let result = run_app(&mut terminal);
+    let result = run_app(&mut terminal, config);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -37,8 +79,11 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    let mut app = app::App::new()?;
+fn run_app<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    config: config::Config,
+) -> Result<()> {
+    let mut app = app::App::new(config)?;
 
     loop {
         // Force clear the terminal buffer if requested
@@ -1,5 +1,7 @@
-use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InterfaceType {
@@ -9,15 +11,35 @@ pub enum InterfaceType {
     Bridge,
     Virtual,
     Tunnel,
+    Bond,
+    Vlan,
+    WireGuard,
+    Vxlan,
+    /// macvlan, macvtap and ipvlan
+    MacVlan,
+    Ppp,
+    InfiniBand,
+    Can,
+    Dummy,
+    Team,
     Unknown,
 }
 
 impl InterfaceType {
+    /// Guess from naming conventions; only used where the kernel doesn't say
     pub fn from_name(name: &str) -> Self {
         let name_lower = name.to_lowercase();
 
         if name_lower.starts_with("lo") {
             InterfaceType::Loopback
+        } else if name_lower.starts_with("bond") {
+            InterfaceType::Bond
+        } else if name_lower.starts_with("vlan") {
+            InterfaceType::Vlan
+        } else if name_lower.starts_with("wg") {
+            InterfaceType::WireGuard
+        } else if name_lower.starts_with("ppp") {
+            InterfaceType::Ppp
         } else if name_lower.starts_with("eth")
             || name_lower.starts_with("en") && !name_lower.contains("wlan")
         {
@@ -36,6 +58,7 @@ impl InterfaceType {
             InterfaceType::Virtual
         } else if name_lower.starts_with("tun")
             || name_lower.starts_with("tap")
+            || name_lower.starts_with("utun")
             || name_lower.contains("vpn")
         {
             InterfaceType::Tunnel
@@ -44,6 +67,60 @@ impl InterfaceType {
         }
     }
 
+    /// Map the `IFLA_INFO_KIND` of a virtual link (as in `ip -d link`)
+    #[cfg(target_os = "linux")]
+    pub fn from_link_kind(kind: &str) -> Option<Self> {
+        Some(match kind {
+            "bridge" => InterfaceType::Bridge,
+            "bond" => InterfaceType::Bond,
+            "team" => InterfaceType::Team,
+            "vlan" => InterfaceType::Vlan,
+            "wireguard" => InterfaceType::WireGuard,
+            "vxlan" => InterfaceType::Vxlan,
+            "macvlan" | "macvtap" | "ipvlan" | "ipvtap" => InterfaceType::MacVlan,
+            "dummy" => InterfaceType::Dummy,
+            "ppp" => InterfaceType::Ppp,
+            "can" | "vcan" | "vxcan" => InterfaceType::Can,
+            "ipoib" => InterfaceType::InfiniBand,
+            "veth" | "netkit" | "nlmon" => InterfaceType::Virtual,
+            "tun" | "gre" | "gretap" | "ip6gre" | "ip6gretap" | "ipip" | "ip6tnl" | "sit"
+            | "vti" | "vti6" | "geneve" | "erspan" | "ip6erspan" | "xfrm" => InterfaceType::Tunnel,
+            _ => return None,
+        })
+    }
+
+    /// Map the `ARPHRD_*` hardware type in `/sys/class/net/<if>/type` for
+    /// types that don't share ARPHRD_ETHER with everything else
+    #[cfg(target_os = "linux")]
+    pub fn from_arphrd(arphrd: u16) -> Option<Self> {
+        Some(match arphrd {
+            32 => InterfaceType::InfiniBand,
+            280 => InterfaceType::Can,
+            512 => InterfaceType::Ppp,
+            772 => InterfaceType::Loopback,
+            // ipip, ipv6-in-ipv6, sit, gre, ip6gre and raw IP devices such as tun
+            768 | 769 | 776 | 778 | 823 | 65534 => InterfaceType::Tunnel,
+            _ => return None,
+        })
+    }
+
+    /// Map the `DEVTYPE` from a device's `uevent`
+    #[cfg(target_os = "linux")]
+    pub fn from_devtype(devtype: &str) -> Option<Self> {
+        Some(match devtype {
+            "wlan" => InterfaceType::WiFi,
+            "bridge" => InterfaceType::Bridge,
+            "bond" => InterfaceType::Bond,
+            "vlan" => InterfaceType::Vlan,
+            "wireguard" => InterfaceType::WireGuard,
+            "vxlan" => InterfaceType::Vxlan,
+            "macvlan" => InterfaceType::MacVlan,
+            "ppp" => InterfaceType::Ppp,
+            "team" => InterfaceType::Team,
+            _ => return None,
+        })
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             InterfaceType::Ethernet => "Ethernet",
@@ -52,35 +129,1274 @@ impl InterfaceType {
             InterfaceType::Bridge => "Bridge",
             InterfaceType::Virtual => "Virtual",
             InterfaceType::Tunnel => "Tunnel/VPN",
+            InterfaceType::Bond => "Bond",
+            InterfaceType::Vlan => "VLAN",
+            InterfaceType::WireGuard => "WireGuard",
+            InterfaceType::Vxlan => "VXLAN",
+            InterfaceType::MacVlan => "MACVLAN",
+            InterfaceType::Ppp => "PPP",
+            InterfaceType::InfiniBand => "InfiniBand",
+            InterfaceType::Can => "CAN",
+            InterfaceType::Dummy => "Dummy",
+            InterfaceType::Team => "Team",
             InterfaceType::Unknown => "Unknown",
         }
     }
 }
 
+/// A 48-bit Ethernet address, written `aa:bb:cc:dd:ee:ff`. Hardware
+/// addresses of other lengths, such as InfiniBand's, aren't MACs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    pub fn from_bytes(bytes: &[u8]) -> Option<MacAddr> {
+        Some(MacAddr(bytes.try_into().ok()?))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 6]
+    }
+
+    /// Group bit: multicast and broadcast addresses can't be given to a link
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Set by whoever chose the address rather than the manufacturer
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// A random unicast address with the locally administered bit set,
+    /// which can't collide with a manufacturer's
+    pub fn random_local() -> MacAddr {
+        let mut bytes = [0u8; 6];
+        // Only fails where the OS has no entropy source at all
+        getrandom::getrandom(&mut bytes).expect("no random number source");
+        bytes[0] = (bytes[0] & !0x01) | 0x02;
+        MacAddr(bytes)
+    }
+
+    /// The manufacturer's prefix; meaningless for locally administered addresses
+    pub fn oui(&self) -> [u8; 3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// `aa:bb:cc`, as OUI registries list it
+    pub fn oui_string(&self) -> String {
+        let [a, b, c] = self.oui();
+        format!("{:02x}:{:02x}:{:02x}", a, b, c)
+    }
+
+    pub fn format(&self, format: MacFormat) -> String {
+        let [a, b, c, d, e, g] = self.0;
+        match format {
+            MacFormat::Colon => self.to_string(),
+            MacFormat::Dash => format!(
+                "{:02x}-{:02x}-{:02x}-{:02x}-{:02x}-{:02x}",
+                a, b, c, d, e, g
+            ),
+            MacFormat::Cisco => format!("{:02x}{:02x}.{:02x}{:02x}.{:02x}{:02x}", a, b, c, d, e, g),
+        }
+    }
+}
+
+/// How MAC addresses are written in the table and copied to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MacFormat {
+    /// `aa:bb:cc:dd:ee:ff`
+    #[default]
+    Colon,
+    /// `aa-bb-cc-dd-ee-ff`, as Windows writes it
+    Dash,
+    /// `aabb.ccdd.eeff`, as Cisco IOS writes it
+    Cisco,
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = String;
+
+    /// Six groups of one or two hex digits split by `:` or `-`, or Cisco's
+    /// three groups of four split by `.`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a MAC address", input);
+        let input = input.trim();
+
+        if input.contains('.') {
+            let groups: Vec<&str> = input.split('.').collect();
+            if groups.len() != 3 || groups.iter().any(|group| group.len() != 4) {
+                return Err(invalid());
+            }
+            let mut bytes = [0u8; 6];
+            for (pair, group) in bytes.chunks_mut(2).zip(groups) {
+                let value = u16::from_str_radix(group, 16).map_err(|_| invalid())?;
+                pair.copy_from_slice(&value.to_be_bytes());
+            }
+            return Ok(MacAddr(bytes));
+        }
+
+        let separator = if input.contains('-') { '-' } else { ':' };
+        let groups: Vec<&str> = input.split(separator).collect();
+        if groups.len() != 6 {
+            return Err(invalid());
+        }
+
+        let mut bytes = [0u8; 6];
+        for (byte, group) in bytes.iter_mut().zip(groups) {
+            if group.is_empty() || group.len() > 2 {
+                return Err(invalid());
+            }
+            *byte = u8::from_str_radix(group, 16).map_err(|_| invalid())?;
+        }
+        Ok(MacAddr(bytes))
+    }
+}
+
+impl Serialize for MacAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MacAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Older audit logs stored whatever the platform printed, "N/A" included
+fn deserialize_mac_lenient<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<MacAddr>, D::Error> {
+    let text = Option::<String>::deserialize(deserializer)?;
+    Ok(text.and_then(|text| text.parse().ok()))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NetworkInterface {
     pub name: String,
     pub interface_type: InterfaceType,
     pub ip_addresses: Vec<InterfaceAddress>,
-    pub mac_address: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_mac_lenient")]
+    pub mac_address: Option<MacAddr>,
+    /// Burned-in address, when the driver reports one and it isn't all zeros
+    #[serde(default)]
+    pub permanent_mac: Option<MacAddr>,
     pub is_up: bool,
     pub is_loopback: bool,
     pub mtu: Option<u32>,
     pub ipv6_enabled: bool,
     pub ssid: Option<String>,
+    /// Per-interface IPv6 sysctls, where the platform exposes them
+    #[serde(default)]
+    pub ipv6_settings: Option<Ipv6Settings>,
+    /// Link details of an associated wireless interface
+    #[serde(default)]
+    pub wireless: Option<WirelessInfo>,
+    /// Set when this is a VLAN sub-interface
+    #[serde(default)]
+    pub vlan: Option<VlanInfo>,
+    /// Bond, team or bridge this interface is enslaved to
+    #[serde(default)]
+    pub master: Option<String>,
+    /// Keys and peers of a WireGuard interface, when they could be read
+    #[serde(default)]
+    pub wireguard: Option<WireGuardInfo>,
+    /// The other end, for one half of a veth pair
+    #[serde(default)]
+    pub veth_peer: Option<VethPeer>,
+    /// Link settings, driver and offloads, where the driver reports them
+    #[serde(default)]
+    pub ethtool: Option<EthtoolInfo>,
+    /// Traffic and error counters as of the last refresh
+    #[serde(default)]
+    pub counters: Option<LinkCounters>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VethPeer {
+    pub index: u32,
+    /// `None` when its namespace couldn't be entered to look it up
+    pub name: Option<String>,
+    /// `None` when both ends are in the same namespace
+    pub namespace: Option<String>,
+    /// The container owning the peer's namespace, when one could be found
+    #[serde(default)]
+    pub container: Option<ContainerInfo>,
+}
+
+impl VethPeer {
+    pub fn describe(&self) -> String {
+        let name = match self.name {
+            Some(ref name) => name.clone(),
+            None => format!("ifindex {}", self.index),
+        };
+        match self.namespace {
+            Some(ref namespace) => format!("{} in {}", name, namespace),
+            None => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    pub runtime: ContainerRuntime,
+    /// Full 64 hex digit id
+    pub id: String,
+    pub name: String,
+    pub image: Option<String>,
+    /// Kubernetes pod as `namespace/name`
+    pub pod: Option<String>,
+}
+
+impl ContainerInfo {
+    /// The id the way `docker ps` and `crictl ps` show it
+    pub fn short_id(&self) -> &str {
+        &self.id[..self.id.len().min(12)]
+    }
+
+    /// What to call it in the table: pods by their pod, the rest by name
+    pub fn label(&self) -> String {
+        match self.pod {
+            Some(ref pod) => format!("pod {}", pod),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+    Containerd,
+    CriO,
+}
+
+impl ContainerRuntime {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+            ContainerRuntime::Containerd => "containerd",
+            ContainerRuntime::CriO => "cri-o",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VlanInfo {
+    pub parent: String,
+    pub id: u16,
+    pub protocol: VlanProtocol,
+}
+
+/// Tag protocol of a VLAN: plain 802.1Q or the 802.1ad outer (S-)tag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VlanProtocol {
+    #[default]
+    Dot1Q,
+    Dot1Ad,
+}
+
+impl VlanProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VlanProtocol::Dot1Q => "802.1Q",
+            VlanProtocol::Dot1Ad => "802.1ad",
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn from_ethertype(ethertype: u16) -> Option<Self> {
+        match ethertype {
+            0x8100 => Some(VlanProtocol::Dot1Q),
+            0x88a8 => Some(VlanProtocol::Dot1Ad),
+            _ => None,
+        }
+    }
+}
+
+/// Bonding driver modes, by their kernel names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BondMode {
+    BalanceRr,
+    #[default]
+    ActiveBackup,
+    BalanceXor,
+    Broadcast,
+    Ieee8023ad,
+    BalanceTlb,
+    BalanceAlb,
+}
+
+impl BondMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BondMode::BalanceRr => "balance-rr",
+            BondMode::ActiveBackup => "active-backup",
+            BondMode::BalanceXor => "balance-xor",
+            BondMode::Broadcast => "broadcast",
+            BondMode::Ieee8023ad => "802.3ad",
+            BondMode::BalanceTlb => "balance-tlb",
+            BondMode::BalanceAlb => "balance-alb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "balance-rr" => BondMode::BalanceRr,
+            "active-backup" => BondMode::ActiveBackup,
+            "balance-xor" => BondMode::BalanceXor,
+            "broadcast" => BondMode::Broadcast,
+            "802.3ad" => BondMode::Ieee8023ad,
+            "balance-tlb" => BondMode::BalanceTlb,
+            "balance-alb" => BondMode::BalanceAlb,
+            _ => return None,
+        })
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            BondMode::BalanceRr => BondMode::ActiveBackup,
+            BondMode::ActiveBackup => BondMode::BalanceXor,
+            BondMode::BalanceXor => BondMode::Broadcast,
+            BondMode::Broadcast => BondMode::Ieee8023ad,
+            BondMode::Ieee8023ad => BondMode::BalanceTlb,
+            BondMode::BalanceTlb => BondMode::BalanceAlb,
+            BondMode::BalanceAlb => BondMode::BalanceRr,
+        }
+    }
+
+    /// Modes with a single active slave that can be switched by hand
+    pub fn has_active_slave(&self) -> bool {
+        matches!(
+            self,
+            BondMode::ActiveBackup | BondMode::BalanceTlb | BondMode::BalanceAlb
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WirelessInfo {
+    pub ssid: Option<String>,
+    pub bssid: Option<String>,
+    /// MHz
+    pub frequency: Option<u32>,
+    pub signal_dbm: Option<i32>,
+    /// Mbit/s
+    pub tx_bitrate: Option<f32>,
+    pub rx_bitrate: Option<f32>,
+    pub security: Option<WifiSecurity>,
+    /// Seconds since association
+    pub connected_time: Option<u32>,
+}
+
+impl WirelessInfo {
+    pub fn channel(&self) -> Option<u32> {
+        wifi_channel(self.frequency?)
+    }
+
+    pub fn band(&self) -> Option<&'static str> {
+        wifi_band(self.frequency?)
+    }
+
+    pub fn signal_quality(&self) -> Option<u8> {
+        self.signal_dbm.map(signal_quality)
+    }
+}
+
+/// What `ethtool` shows for a link
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthtoolInfo {
+    /// Mbit/s; `None` while there is no link or the driver doesn't know
+    pub speed: Option<u32>,
+    pub duplex: Option<Duplex>,
+    pub autoneg: Option<bool>,
+    /// Connector, such as "Twisted Pair" or "FIBRE"
+    pub port: Option<String>,
+    pub link_detected: Option<bool>,
+    pub driver: Option<DriverInfo>,
+    /// The features of `OFFLOAD_FEATURES` the device has, in that order
+    pub offloads: Vec<Offload>,
+}
+
+impl EthtoolInfo {
+    pub fn offload(&self, name: &str) -> Option<&Offload> {
+        self.offloads.iter().find(|offload| offload.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Duplex {
+    Half,
+    Full,
+}
+
+impl Duplex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Duplex::Half => "Half",
+            Duplex::Full => "Full",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriverInfo {
+    pub driver: String,
+    pub version: Option<String>,
+    pub firmware: Option<String>,
+    /// PCI address or similar, such as `0000:00:1f.6`
+    pub bus_info: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offload {
+    /// Kernel feature name, as `ethtool -k` prints it
+    pub name: String,
+    pub enabled: bool,
+    /// The driver doesn't let it be changed
+    pub fixed: bool,
+}
+
+impl Offload {
+    pub fn label(&self) -> &'static str {
+        offload_label(&self.name).unwrap_or("")
+    }
+}
+
+/// The offloads worth looking at when chasing throughput or capture
+/// problems, as (kernel feature, label). Dependencies come first: TSO
+/// needs scatter-gather, which needs TX checksumming.
+pub const OFFLOAD_FEATURES: &[(&str, &str)] = &[
+    ("rx-checksum", "RX checksumming"),
+    ("tx-checksum-ipv4", "TX checksumming (IPv4)"),
+    ("tx-checksum-ipv6", "TX checksumming (IPv6)"),
+    ("tx-checksum-ip-generic", "TX checksumming"),
+    ("tx-scatter-gather", "Scatter-gather"),
+    ("tx-tcp-segmentation", "TCP segmentation (TSO)"),
+    ("tx-tcp6-segmentation", "TCP segmentation, IPv6 (TSO6)"),
+    ("tx-generic-segmentation", "Generic segmentation (GSO)"),
+    ("rx-gro", "Generic receive (GRO)"),
+    ("rx-gro-hw", "Hardware GRO"),
+    ("rx-lro", "Large receive (LRO)"),
+];
+
+pub fn offload_label(name: &str) -> Option<&'static str> {
+    OFFLOAD_FEATURES
+        .iter()
+        .find(|(feature, _)| *feature == name)
+        .map(|(_, label)| *label)
+}
+
+/// `100Mb/s`, or `10Gb/s` and `2.5Gb/s` from a gigabit up
+pub fn format_speed(mbps: u32) -> String {
+    if mbps >= 1000 {
+        format!("{}Gb/s", f64::from(mbps) / 1000.0)
+    } else {
+        format!("{}Mb/s", mbps)
+    }
+}
+
+/// Counters the kernel keeps for every link (`struct rtnl_link_stats64`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkCounters {
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_dropped: u64,
+    pub tx_dropped: u64,
+    pub multicast: u64,
+    pub collisions: u64,
+    pub rx_length_errors: u64,
+    pub rx_over_errors: u64,
+    pub rx_crc_errors: u64,
+    pub rx_frame_errors: u64,
+    pub rx_fifo_errors: u64,
+    pub rx_missed_errors: u64,
+    pub tx_aborted_errors: u64,
+    pub tx_carrier_errors: u64,
+    pub tx_fifo_errors: u64,
+    pub tx_heartbeat_errors: u64,
+    pub tx_window_errors: u64,
+}
+
+impl LinkCounters {
+    /// Every counter under the name `ip -s -s link` and sysfs give it
+    pub fn named(&self) -> Vec<(&'static str, u64)> {
+        vec![
+            ("rx_packets", self.rx_packets),
+            ("tx_packets", self.tx_packets),
+            ("rx_bytes", self.rx_bytes),
+            ("tx_bytes", self.tx_bytes),
+            ("rx_errors", self.rx_errors),
+            ("tx_errors", self.tx_errors),
+            ("rx_dropped", self.rx_dropped),
+            ("tx_dropped", self.tx_dropped),
+            ("multicast", self.multicast),
+            ("collisions", self.collisions),
+            ("rx_length_errors", self.rx_length_errors),
+            ("rx_over_errors", self.rx_over_errors),
+            ("rx_crc_errors", self.rx_crc_errors),
+            ("rx_frame_errors", self.rx_frame_errors),
+            ("rx_fifo_errors", self.rx_fifo_errors),
+            ("rx_missed_errors", self.rx_missed_errors),
+            ("tx_aborted_errors", self.tx_aborted_errors),
+            ("tx_carrier_errors", self.tx_carrier_errors),
+            ("tx_fifo_errors", self.tx_fifo_errors),
+            ("tx_heartbeat_errors", self.tx_heartbeat_errors),
+            ("tx_window_errors", self.tx_window_errors),
+        ]
+    }
+}
+
+/// Everything the statistics page shows for one interface
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceStatistics {
+    pub counters: LinkCounters,
+    /// Driver specific counters, as `ethtool -S` lists them
+    pub driver: Vec<(String, u64)>,
+}
+
+/// Whether a counter going up means trouble: errors, drops, misses and
+/// overruns, under the names drivers give them
+pub fn is_error_counter(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [
+        "err",
+        "drop",
+        "miss",
+        "crc",
+        "fifo",
+        "over",
+        "carrier",
+        "abort",
+        "collision",
+        "heartbeat",
+        "window",
+        "fail",
+        "timeout",
+        "discard",
+        "no_buf",
+        "nobuf",
+    ]
+    .iter()
+    .any(|marker| name.contains(marker))
+}
+
+/// A WireGuard device as the kernel reports it; never holds the private key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireGuardInfo {
+    /// Base64, like `wg` prints it
+    pub public_key: Option<String>,
+    pub listen_port: Option<u16>,
+    /// 0 when unset
+    pub fwmark: u32,
+    pub peers: Vec<WireGuardPeer>,
+}
+
+impl WireGuardInfo {
+    pub fn peer(&self, public_key: &str) -> Option<&WireGuardPeer> {
+        self.peers.iter().find(|peer| peer.public_key == public_key)
+    }
+}
+
+/// Without a handshake for this long the session keys have expired
+pub const WIREGUARD_STALE_AFTER: u64 = 180;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireGuardPeer {
+    pub public_key: String,
+    pub has_preshared_key: bool,
+    pub endpoint: Option<std::net::SocketAddr>,
+    pub allowed_ips: Vec<AllowedIp>,
+    /// Seconds between keepalives, `None` when off
+    pub persistent_keepalive: Option<u16>,
+    /// Unix time of the latest handshake
+    pub last_handshake: Option<i64>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl WireGuardPeer {
+    /// Seconds since the latest handshake, measured against `now` (Unix time)
+    pub fn handshake_age(&self, now: i64) -> Option<u64> {
+        self.last_handshake
+            .map(|handshake| now.saturating_sub(handshake).max(0) as u64)
+    }
+
+    /// Settings needed to add this peer again
+    pub fn config(&self) -> WireGuardPeerConfig {
+        WireGuardPeerConfig {
+            public_key: self.public_key.clone(),
+            endpoint: self.endpoint,
+            allowed_ips: self.allowed_ips.clone(),
+            persistent_keepalive: self.persistent_keepalive,
+        }
+    }
+}
+
+/// A peer to add; preshared keys are left to `wg`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireGuardPeerConfig {
+    pub public_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<std::net::SocketAddr>,
+    pub allowed_ips: Vec<AllowedIp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistent_keepalive: Option<u16>,
+}
+
+/// A network routed to a WireGuard peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowedIp {
+    pub address: IpAddr,
+    pub prefix: u8,
+}
+
+impl std::fmt::Display for AllowedIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Channel number for a frequency in MHz
+pub fn wifi_channel(frequency: u32) -> Option<u32> {
+    match frequency {
+        2484 => Some(14),
+        f @ 2412..=2472 => Some((f - 2407) / 5),
+        f @ 5160..=5885 => Some((f - 5000) / 5),
+        f @ 5955..=7115 => Some((f - 5950) / 5),
+        f @ 58320..=70200 => Some((f - 56160) / 2160),
+        _ => None,
+    }
+}
+
+pub fn wifi_band(frequency: u32) -> Option<&'static str> {
+    match frequency {
+        2400..=2500 => Some("2.4 GHz"),
+        5150..=5925 => Some("5 GHz"),
+        5926..=7125 => Some("6 GHz"),
+        58320..=70200 => Some("60 GHz"),
+        _ => None,
+    }
+}
+
+/// 0-100, linear between -100 dBm (unusable) and -50 dBm (excellent)
+pub fn signal_quality(dbm: i32) -> u8 {
+    ((dbm + 100) * 2).clamp(0, 100) as u8
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WifiSecurity {
+    #[default]
+    Open,
+    /// Opportunistic Wireless Encryption, "Enhanced Open"
+    Owe,
+    Wep,
+    Wpa,
+    Wpa2Personal,
+    Wpa2Enterprise,
+    /// WPA3 transition mode, accepting both PSK and SAE
+    Wpa2Wpa3Personal,
+    Wpa3Personal,
+    Wpa3Enterprise,
+}
+
+impl WifiSecurity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WifiSecurity::Open => "Open",
+            WifiSecurity::Owe => "Enhanced Open (OWE)",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::Wpa => "WPA",
+            WifiSecurity::Wpa2Personal => "WPA2-Personal",
+            WifiSecurity::Wpa2Enterprise => "WPA2-Enterprise",
+            WifiSecurity::Wpa2Wpa3Personal => "WPA2/WPA3-Personal",
+            WifiSecurity::Wpa3Personal => "WPA3-Personal",
+            WifiSecurity::Wpa3Enterprise => "WPA3-Enterprise",
+        }
+    }
+
+    pub fn needs_password(&self) -> bool {
+        !matches!(self, WifiSecurity::Open | WifiSecurity::Owe)
+    }
+
+    /// 802.1X networks need certificates and identities we don't ask for
+    pub fn is_enterprise(&self) -> bool {
+        matches!(
+            self,
+            WifiSecurity::Wpa2Enterprise | WifiSecurity::Wpa3Enterprise
+        )
+    }
+}
+
+/// `net.ipv6.conf.<iface>.*` settings that shape automatic addressing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ipv6Settings {
+    pub addr_gen_mode: AddrGenMode,
+    pub use_tempaddr: TempAddrMode,
+    pub accept_ra: AcceptRa,
+}
+
+impl Ipv6Settings {
+    /// The settings in `self` that differ from `current`
+    pub fn changes_from(&self, current: &Ipv6Settings) -> Vec<Ipv6Setting> {
+        let mut changes = Vec::new();
+        if self.addr_gen_mode != current.addr_gen_mode {
+            changes.push(Ipv6Setting::AddrGenMode(self.addr_gen_mode));
+        }
+        if self.use_tempaddr != current.use_tempaddr {
+            changes.push(Ipv6Setting::UseTempaddr(self.use_tempaddr));
+        }
+        if self.accept_ra != current.accept_ra {
+            changes.push(Ipv6Setting::AcceptRa(self.accept_ra));
+        }
+        changes
+    }
+}
+
+/// How the kernel derives the interface identifier of SLAAC addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddrGenMode {
+    Eui64,
+    None,
+    StablePrivacy,
+    Random,
+}
+
+impl AddrGenMode {
+    pub fn from_sysctl(value: i32) -> Self {
+        match value {
+            1 => AddrGenMode::None,
+            2 => AddrGenMode::StablePrivacy,
+            3 => AddrGenMode::Random,
+            _ => AddrGenMode::Eui64,
+        }
+    }
+
+    pub fn sysctl_value(&self) -> i32 {
+        match self {
+            AddrGenMode::Eui64 => 0,
+            AddrGenMode::None => 1,
+            AddrGenMode::StablePrivacy => 2,
+            AddrGenMode::Random => 3,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddrGenMode::Eui64 => "EUI-64",
+            AddrGenMode::None => "None",
+            AddrGenMode::StablePrivacy => "Stable privacy",
+            AddrGenMode::Random => "Random",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AddrGenMode::Eui64 => AddrGenMode::StablePrivacy,
+            AddrGenMode::StablePrivacy => AddrGenMode::Random,
+            AddrGenMode::Random => AddrGenMode::None,
+            AddrGenMode::None => AddrGenMode::Eui64,
+        }
+    }
+}
+
+/// Privacy extensions (RFC 8981), `use_tempaddr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TempAddrMode {
+    Disabled,
+    /// Temporary addresses exist but public ones are preferred
+    Enabled,
+    Preferred,
+}
+
+impl TempAddrMode {
+    pub fn from_sysctl(value: i32) -> Self {
+        match value {
+            1 => TempAddrMode::Enabled,
+            v if v >= 2 => TempAddrMode::Preferred,
+            _ => TempAddrMode::Disabled,
+        }
+    }
+
+    pub fn sysctl_value(&self) -> i32 {
+        match self {
+            TempAddrMode::Disabled => 0,
+            TempAddrMode::Enabled => 1,
+            TempAddrMode::Preferred => 2,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TempAddrMode::Disabled => "Disabled",
+            TempAddrMode::Enabled => "Enabled, prefer public",
+            TempAddrMode::Preferred => "Enabled, prefer temporary",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            TempAddrMode::Disabled => TempAddrMode::Enabled,
+            TempAddrMode::Enabled => TempAddrMode::Preferred,
+            TempAddrMode::Preferred => TempAddrMode::Disabled,
+        }
+    }
+}
+
+/// Whether router advertisements are processed, `accept_ra`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AcceptRa {
+    Off,
+    /// Only while forwarding is disabled
+    On,
+    /// Even when forwarding is enabled
+    Always,
+}
+
+impl AcceptRa {
+    pub fn from_sysctl(value: i32) -> Self {
+        match value {
+            0 => AcceptRa::Off,
+            2 => AcceptRa::Always,
+            _ => AcceptRa::On,
+        }
+    }
+
+    pub fn sysctl_value(&self) -> i32 {
+        match self {
+            AcceptRa::Off => 0,
+            AcceptRa::On => 1,
+            AcceptRa::Always => 2,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AcceptRa::Off => "Off",
+            AcceptRa::On => "On (unless forwarding)",
+            AcceptRa::Always => "Always",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            AcceptRa::Off => AcceptRa::On,
+            AcceptRa::On => AcceptRa::Always,
+            AcceptRa::Always => AcceptRa::Off,
+        }
+    }
+}
+
+/// One IPv6 sysctl change, as sent to the helper
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name", content = "value", rename_all = "snake_case")]
+pub enum Ipv6Setting {
+    AddrGenMode(AddrGenMode),
+    UseTempaddr(TempAddrMode),
+    AcceptRa(AcceptRa),
+}
+
+impl Ipv6Setting {
+    pub fn sysctl_name(&self) -> &'static str {
+        match self {
+            Ipv6Setting::AddrGenMode(_) => "addr_gen_mode",
+            Ipv6Setting::UseTempaddr(_) => "use_tempaddr",
+            Ipv6Setting::AcceptRa(_) => "accept_ra",
+        }
+    }
+
+    pub fn sysctl_value(&self) -> i32 {
+        match self {
+            Ipv6Setting::AddrGenMode(mode) => mode.sysctl_value(),
+            Ipv6Setting::UseTempaddr(mode) => mode.sysctl_value(),
+            Ipv6Setting::AcceptRa(mode) => mode.sysctl_value(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Ipv6Setting::AddrGenMode(mode) => format!("address generation {}", mode.as_str()),
+            Ipv6Setting::UseTempaddr(mode) => format!("privacy extensions {}", mode.as_str()),
+            Ipv6Setting::AcceptRa(mode) => format!("accept RA {}", mode.as_str()),
+        }
+    }
+}
+
+/// An address together with its prefix length, written `10.0.0.5/24`. The
+/// host bits are kept, so it can describe both an interface address and the
+/// subnet it sits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpNetwork {
+    ip: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub fn new(ip: IpAddr, prefix: u8) -> Result<IpNetwork, String> {
+        if prefix > max_prefix(&ip) {
+            return Err(format!(
+                "prefix length must be between 0 and {}",
+                max_prefix(&ip)
+            ));
+        }
+        Ok(IpNetwork { ip, prefix })
+    }
+
+    /// `None` when the mask's family differs from the address's or its ones
+    /// aren't contiguous
+    pub fn from_netmask(ip: IpAddr, netmask: IpAddr) -> Option<IpNetwork> {
+        let mask = match (ip, netmask) {
+            (IpAddr::V4(_), IpAddr::V4(mask)) => u128::from(u32::from(mask)) << 96,
+            (IpAddr::V6(_), IpAddr::V6(mask)) => u128::from(mask),
+            _ => return None,
+        };
+        if mask.leading_ones() + mask.trailing_zeros() != 128 && mask != 0 {
+            return None;
+        }
+        Some(IpNetwork {
+            ip,
+            prefix: mask.count_ones() as u8,
+        })
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.ip
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        self.ip.is_ipv4()
+    }
+
+    /// The first address of the subnet
+    pub fn network(&self) -> IpAddr {
+        from_bits(&self.ip, to_bits(&self.ip) & self.mask_bits())
+    }
+
+    /// The last address of the subnet, its broadcast address on IPv4
+    pub fn last(&self) -> IpAddr {
+        let host = !self.mask_bits() & family_bits(&self.ip);
+        from_bits(&self.ip, to_bits(&self.ip) | host)
+    }
+
+    /// The subnet itself, with the host bits cleared
+    pub fn trunc(&self) -> IpNetwork {
+        IpNetwork {
+            ip: self.network(),
+            prefix: self.prefix,
+        }
+    }
+
+    /// Whether the host bits are clear
+    pub fn is_network(&self) -> bool {
+        self.ip == self.network()
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        ip.is_ipv4() == self.ip.is_ipv4()
+            && to_bits(&ip) & self.mask_bits() == to_bits(&self.ip) & self.mask_bits()
+    }
+
+    /// Whether `other` lies entirely inside this subnet
+    pub fn contains_network(&self, other: &IpNetwork) -> bool {
+        other.prefix >= self.prefix && self.contains(other.ip)
+    }
+
+    /// Two subnets overlap when one contains the other
+    pub fn overlaps(&self, other: &IpNetwork) -> bool {
+        self.contains_network(other) || other.contains_network(self)
+    }
+
+    /// Longest prefix the address family allows
+    pub fn max_prefix(&self) -> u8 {
+        max_prefix(&self.ip)
+    }
+
+    pub fn netmask(&self) -> IpAddr {
+        from_bits(&self.ip, self.mask_bits())
+    }
+
+    /// The inverted netmask, as ACLs and OSPF write it
+    pub fn wildcard(&self) -> IpAddr {
+        from_bits(&self.ip, !self.mask_bits() & family_bits(&self.ip))
+    }
+
+    /// First address a host can use: IPv4 subnets up to /30 reserve their
+    /// network and broadcast addresses (RFC 3021 lifts that for /31)
+    pub fn first_host(&self) -> IpAddr {
+        match self.ip {
+            IpAddr::V4(_) if self.prefix <= 30 => from_bits(&self.ip, to_bits(&self.network()) + 1),
+            _ => self.network(),
+        }
+    }
+
+    pub fn last_host(&self) -> IpAddr {
+        match self.ip {
+            IpAddr::V4(_) if self.prefix <= 30 => from_bits(&self.ip, to_bits(&self.last()) - 1),
+            _ => self.last(),
+        }
+    }
+
+    /// Usable host addresses, `None` for an IPv6 /0 whose 2^128 doesn't fit
+    pub fn host_count(&self) -> Option<u128> {
+        let size = 1u128.checked_shl((self.max_prefix() - self.prefix) as u32)?;
+        match self.ip {
+            IpAddr::V4(_) if self.prefix <= 30 => Some(size - 2),
+            _ => Some(size),
+        }
+    }
+
+    /// The subnet one level up with the given shorter prefix
+    pub fn supernet(&self, prefix: u8) -> Option<IpNetwork> {
+        (prefix <= self.prefix).then(|| {
+            IpNetwork {
+                ip: self.ip,
+                prefix,
+            }
+            .trunc()
+        })
+    }
+
+    /// How many subnets of a longer `prefix` fit in this one, `None` when
+    /// the count doesn't fit in 128 bits
+    pub fn subnet_count(&self, prefix: u8) -> Option<u128> {
+        if prefix < self.prefix || prefix > self.max_prefix() {
+            return Some(0);
+        }
+        1u128.checked_shl((prefix - self.prefix) as u32)
+    }
+
+    /// The `index`th subnet with the longer `prefix`
+    pub fn subnet(&self, prefix: u8, index: u128) -> Option<IpNetwork> {
+        // No count means 2^128 subnets, which every index fits in
+        if self
+            .subnet_count(prefix)
+            .is_some_and(|count| index >= count)
+        {
+            return None;
+        }
+        // Only a /0 split into /0s shifts by the full width, at index 0
+        let offset = index
+            .checked_shl((self.max_prefix() - prefix) as u32)
+            .unwrap_or(0);
+        Some(IpNetwork {
+            ip: from_bits(&self.ip, to_bits(&self.network()) | offset),
+            prefix,
+        })
+    }
+
+    fn mask_bits(&self) -> u128 {
+        let host = family_bits(&self.ip)
+            .checked_shr(self.prefix as u32)
+            .unwrap_or(0);
+        family_bits(&self.ip) & !host
+    }
+}
+
+fn max_prefix(ip: &IpAddr) -> u8 {
+    if ip.is_ipv4() {
+        32
+    } else {
+        128
+    }
+}
+
+fn family_bits(ip: &IpAddr) -> u128 {
+    if ip.is_ipv4() {
+        u128::from(u32::MAX)
+    } else {
+        u128::MAX
+    }
+}
+
+fn to_bits(ip: &IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(v4) => u128::from(u32::from(*v4)),
+        IpAddr::V6(v6) => u128::from(*v6),
+    }
+}
+
+fn from_bits(family: &IpAddr, bits: u128) -> IpAddr {
+    match family {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix)
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    /// `address/prefix`, or a bare address meaning just that host
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        let (ip, prefix) = match input.split_once('/') {
+            Some((ip, prefix)) => (ip, Some(prefix)),
+            None => (input, None),
+        };
+        let ip: IpAddr = ip
+            .parse()
+            .map_err(|_| format!("'{}' is not an IP address", ip))?;
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|&prefix| prefix <= max_prefix(&ip))
+                .ok_or_else(|| format!("invalid prefix length in '{}'", input))?,
+            None => max_prefix(&ip),
+        };
+        Ok(IpNetwork { ip, prefix })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InterfaceAddress {
     pub ip: IpAddr,
-    pub netmask: Option<IpAddr>,
+    /// Prefix length of the subnet the address sits in
+    #[serde(
+        default,
+        alias = "netmask",
+        deserialize_with = "deserialize_prefix_lenient"
+    )]
+    pub prefix: Option<u8>,
     pub broadcast: Option<IpAddr>,
+    /// Alias label such as `eth0:1`, when it differs from the interface name
+    #[serde(default)]
+    pub label: Option<String>,
+    /// IPv4 address added after another one in the same subnet
+    #[serde(default)]
+    pub secondary: bool,
+    /// Kernel state of an IPv6 address, where available
+    #[serde(default)]
+    pub ipv6_info: Option<Ipv6AddressInfo>,
+}
+
+/// Older audit logs stored the netmask rather than the prefix length
+fn deserialize_prefix_lenient<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u8>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Prefix {
+        Length(u8),
+        Netmask(IpAddr),
+    }
+
+    Ok(match Option::<Prefix>::deserialize(deserializer)? {
+        Some(Prefix::Length(prefix)) => Some(prefix),
+        Some(Prefix::Netmask(mask)) => {
+            let ip = match mask {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            IpNetwork::from_netmask(ip, mask).map(|network| network.prefix())
+        }
+        None => None,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ipv6AddressInfo {
+    /// `global`, `link`, `host` or `site`
+    pub scope: String,
+    /// Address flags such as `tentative`, `deprecated`, `temporary`, `dadfailed`
+    pub flags: Vec<String>,
+    /// Seconds left, `None` meaning forever
+    pub preferred_lifetime: Option<u32>,
+    pub valid_lifetime: Option<u32>,
+}
+
+impl Ipv6AddressInfo {
+    /// Whether the kernel configured the address itself (SLAAC or temporary)
+    pub fn is_dynamic(&self) -> bool {
+        self.valid_lifetime.is_some()
+            || self
+                .flags
+                .iter()
+                .any(|flag| flag == "temporary" || flag == "dynamic")
+    }
 }
 
 impl InterfaceAddress {
     pub fn is_ipv6(&self) -> bool {
         matches!(self.ip, IpAddr::V6(_))
     }
+
+    /// The address with its prefix, when the prefix is known
+    pub fn network(&self) -> Option<IpNetwork> {
+        IpNetwork::new(self.ip, self.prefix?).ok()
+    }
+
+    pub fn netmask(&self) -> Option<IpAddr> {
+        self.network().map(|network| network.netmask())
+    }
+
+    /// `ip/prefix`, followed by the label when there is one
+    pub fn display(&self) -> String {
+        let mut text = match self.network() {
+            Some(network) => network.to_string(),
+            None => self.ip.to_string(),
+        };
+        if let Some(label) = &self.label {
+            text.push_str(&format!(" ({})", label));
+        }
+        text
+    }
+
+    /// fe80::/10 addresses are managed by the kernel, not by the user
+    pub fn is_link_local(&self) -> bool {
+        match self.ip {
+            IpAddr::V4(v4) => v4.is_link_local(),
+            IpAddr::V6(v6) => (v6.segments()[0] & 0xffc0) == 0xfe80,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RouteEntry {
+    pub destination: IpAddr,
+    pub prefix: u8,
+    pub gateway: Option<IpAddr>,
+    pub interface: String,
+    pub metric: u32,
+}
+
+impl RouteEntry {
+    pub fn is_default(&self) -> bool {
+        self.prefix == 0
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,12 +1412,25 @@ pub struct InterfaceTableRow {
     pub ip_address: String,
     pub mac_address: String,
     pub subnet_mask: String,
+    /// Parsed forms of the columns above, for sorting
+    pub ip: Option<IpAddr>,
+    pub mac: Option<MacAddr>,
+    pub netmask: Option<IpAddr>,
     pub dns_servers: String,
     pub status: String,
+    pub speed: String,
+    /// Mbit/s, for sorting
+    pub speed_mbps: Option<u32>,
+    /// Addresses after the first one, shown when the row is expanded
+    pub extra_addresses: Vec<String>,
 }
 
 impl InterfaceTableRow {
-    pub fn from_interface(iface: &NetworkInterface, dns: &DnsConfiguration) -> Self {
+    pub fn from_interface(
+        iface: &NetworkInterface,
+        dns: &DnsConfiguration,
+        mac_format: MacFormat,
+    ) -> Self {
         let ip_address = iface
             .ip_addresses
             .first()
@@ -111,13 +1440,13 @@ impl InterfaceTableRow {
         let subnet_mask = iface
             .ip_addresses
             .first()
-            .and_then(|addr| addr.netmask.as_ref())
+            .and_then(|addr| addr.netmask())
             .map(|mask| mask.to_string())
             .unwrap_or_else(|| "N/A".to_string());
 
         let mac_address = iface
             .mac_address
-            .clone()
+            .map(|mac| mac.format(mac_format))
             .unwrap_or_else(|| "N/A".to_string());
 
         let dns_servers = dns
@@ -129,18 +1458,44 @@ impl InterfaceTableRow {
 
         let status = if iface.is_up { "UP" } else { "DOWN" }.to_string();
 
+        let speed_mbps = iface.ethtool.as_ref().and_then(|ethtool| ethtool.speed);
+        let speed = speed_mbps
+            .map(format_speed)
+            .unwrap_or_else(|| "N/A".to_string());
+
+        // Search finds a container's veth by its name or pod
+        let interface_type = match iface
+            .veth_peer
+            .as_ref()
+            .and_then(|peer| peer.container.as_ref())
+        {
+            Some(container) => format!("{}: {}", iface.interface_type.as_str(), container.label()),
+            None => iface.interface_type.as_str().to_string(),
+        };
+
         Self {
             name: iface.name.clone(),
-            interface_type: iface.interface_type.as_str().to_string(),
+            interface_type,
             ip_address,
             mac_address,
             subnet_mask,
+            ip: iface.ip_addresses.first().map(|addr| addr.ip),
+            mac: iface.mac_address,
+            netmask: iface.ip_addresses.first().and_then(|addr| addr.netmask()),
             dns_servers: if dns_servers.is_empty() {
                 "N/A".to_string()
             } else {
                 dns_servers
             },
             status,
+            speed,
+            speed_mbps,
+            extra_addresses: iface
+                .ip_addresses
+                .iter()
+                .skip(1)
+                .map(|addr| addr.display())
+                .collect(),
         }
     }
 
@@ -153,6 +1508,7 @@ impl InterfaceTableRow {
             "Subnet Mask" => &self.subnet_mask,
             "DNS Servers" => &self.dns_servers,
             "Status" => &self.status,
+            "Speed" => &self.speed,
             _ => "",
         }
     }
@@ -167,6 +1523,7 @@ pub enum SortColumn {
     SubnetMask,
     DnsServers,
     Status,
+    Speed,
 }
 
 impl SortColumn {
@@ -179,6 +1536,7 @@ impl SortColumn {
             SortColumn::SubnetMask => "Subnet Mask",
             SortColumn::DnsServers => "DNS Servers",
             SortColumn::Status => "Status",
+            SortColumn::Speed => "Speed",
         }
     }
 
@@ -189,7 +1547,8 @@ impl SortColumn {
             SortColumn::IpAddress => SortColumn::MacAddress,
             SortColumn::MacAddress => SortColumn::SubnetMask,
             SortColumn::SubnetMask => SortColumn::DnsServers,
-            SortColumn::DnsServers => SortColumn::Status,
+            SortColumn::DnsServers => SortColumn::Speed,
+            SortColumn::Speed => SortColumn::Status,
             SortColumn::Status => SortColumn::Interface,
         }
     }
@@ -202,12 +1561,14 @@ impl SortColumn {
             SortColumn::MacAddress => SortColumn::IpAddress,
             SortColumn::SubnetMask => SortColumn::MacAddress,
             SortColumn::DnsServers => SortColumn::SubnetMask,
-            SortColumn::Status => SortColumn::DnsServers,
+            SortColumn::Speed => SortColumn::DnsServers,
+            SortColumn::Status => SortColumn::Speed,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum IpConfigMode {
     DHCP,
     Static,
@@ -222,3 +1583,276 @@ impl IpConfigMode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(input: &str) -> IpNetwork {
+        input.parse().unwrap()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn link_kinds() {
+        use InterfaceType::*;
+        for (kind, expected) in [
+            ("bridge", Some(Bridge)),
+            ("bond", Some(Bond)),
+            ("team", Some(Team)),
+            ("vlan", Some(Vlan)),
+            ("wireguard", Some(WireGuard)),
+            ("vxlan", Some(Vxlan)),
+            ("macvlan", Some(MacVlan)),
+            ("macvtap", Some(MacVlan)),
+            ("ipvlan", Some(MacVlan)),
+            ("ipvtap", Some(MacVlan)),
+            ("dummy", Some(Dummy)),
+            ("ppp", Some(Ppp)),
+            ("vcan", Some(Can)),
+            ("vxcan", Some(Can)),
+            ("ipoib", Some(InfiniBand)),
+            ("veth", Some(Virtual)),
+            ("netkit", Some(Virtual)),
+            ("tun", Some(Tunnel)),
+            ("gretap", Some(Tunnel)),
+            ("ip6tnl", Some(Tunnel)),
+            ("sit", Some(Tunnel)),
+            ("geneve", Some(Tunnel)),
+            ("xfrm", Some(Tunnel)),
+            ("", None),
+            ("Bridge", None),
+            ("openvswitch", None),
+        ] {
+            assert_eq!(InterfaceType::from_link_kind(kind), expected, "{}", kind);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn arphrd_types() {
+        use InterfaceType::*;
+        for (arphrd, expected) in [
+            (32, Some(InfiniBand)),
+            (280, Some(Can)),
+            (512, Some(Ppp)),
+            (772, Some(Loopback)),
+            (768, Some(Tunnel)),
+            (769, Some(Tunnel)),
+            (776, Some(Tunnel)),
+            (778, Some(Tunnel)),
+            (823, Some(Tunnel)),
+            (65534, Some(Tunnel)),
+            // ARPHRD_ETHER is shared by real and virtual devices
+            (1, None),
+            (801, None),
+            (0, None),
+        ] {
+            assert_eq!(InterfaceType::from_arphrd(arphrd), expected, "{}", arphrd);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn devtypes() {
+        use InterfaceType::*;
+        for (devtype, expected) in [
+            ("wlan", Some(WiFi)),
+            ("bridge", Some(Bridge)),
+            ("bond", Some(Bond)),
+            ("vlan", Some(Vlan)),
+            ("wireguard", Some(WireGuard)),
+            ("vxlan", Some(Vxlan)),
+            ("macvlan", Some(MacVlan)),
+            ("ppp", Some(Ppp)),
+            ("team", Some(Team)),
+            ("gadget", None),
+            ("wwan", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                InterfaceType::from_devtype(devtype),
+                expected,
+                "{}",
+                devtype
+            );
+        }
+    }
+
+    #[test]
+    fn speeds() {
+        for (mbps, text) in [
+            (10, "10Mb/s"),
+            (100, "100Mb/s"),
+            (1000, "1Gb/s"),
+            (2500, "2.5Gb/s"),
+            (25000, "25Gb/s"),
+            (400_000, "400Gb/s"),
+        ] {
+            assert_eq!(format_speed(mbps), text);
+        }
+    }
+
+    #[test]
+    fn random_macs_are_local_unicast() {
+        let macs: std::collections::HashSet<MacAddr> =
+            (0..1000).map(|_| MacAddr::random_local()).collect();
+
+        for mac in &macs {
+            assert!(!mac.is_multicast(), "{}", mac);
+            assert!(mac.is_locally_administered(), "{}", mac);
+            assert!(!mac.is_zero(), "{}", mac);
+        }
+        // Random in the other 46 bits
+        assert!(macs.len() > 990);
+    }
+
+    #[test]
+    fn mac_address_bits() {
+        let mac = |input: &str| input.parse::<MacAddr>().unwrap();
+
+        assert!(!mac("00:1b:21:0a:0b:0c").is_multicast());
+        assert!(!mac("00:1b:21:0a:0b:0c").is_locally_administered());
+        assert!(mac("01:00:5e:00:00:fb").is_multicast());
+        assert!(mac("ff:ff:ff:ff:ff:ff").is_multicast());
+        assert!(mac("02:42:ac:11:00:02").is_locally_administered());
+        assert!(!mac("02:42:ac:11:00:02").is_multicast());
+        assert!(mac("00:00:00:00:00:00").is_zero());
+        assert_eq!(mac("00:1B:21:0a:0b:0c").oui_string(), "00:1b:21");
+    }
+
+    #[test]
+    fn mac_parses_every_format() {
+        let mac = MacAddr([0xaa, 0xbb, 0xcc, 0x0d, 0xee, 0xff]);
+        for input in [
+            "aa:bb:cc:0d:ee:ff",
+            "AA:BB:CC:0D:EE:FF",
+            "aa:bb:cc:d:ee:ff",
+            "aa-bb-cc-0d-ee-ff",
+            "aabb.cc0d.eeff",
+            " aabb.cc0d.eeff ",
+        ] {
+            assert_eq!(input.parse::<MacAddr>(), Ok(mac), "{}", input);
+        }
+
+        assert_eq!(mac.format(MacFormat::Colon), "aa:bb:cc:0d:ee:ff");
+        assert_eq!(mac.format(MacFormat::Dash), "aa-bb-cc-0d-ee-ff");
+        assert_eq!(mac.format(MacFormat::Cisco), "aabb.cc0d.eeff");
+        for format in [MacFormat::Colon, MacFormat::Dash, MacFormat::Cisco] {
+            assert_eq!(mac.format(format).parse::<MacAddr>(), Ok(mac));
+        }
+    }
+
+    #[test]
+    fn mac_rejects_malformed_input() {
+        for input in [
+            "",
+            "aa:bb:cc:dd:ee",
+            "aa:bb:cc:dd:ee:ff:00",
+            "aa:bb:cc:dd:ee:fff",
+            "aa:bb::dd:ee:ff",
+            "aa:bb:cc:dd:ee:gg",
+            "aa-bb-cc:dd:ee:ff",
+            "aabb.ccdd",
+            "aabb.ccdd.eef",
+            "aabb.ccdd.eeff.0011",
+            "aabb.ccdd.eefg",
+            "N/A",
+        ] {
+            assert!(input.parse::<MacAddr>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn networks_sort_by_address_then_prefix() {
+        let mut networks = vec![
+            net("2001:db8::/32"),
+            net("10.0.0.0/16"),
+            net("10.0.0.0/8"),
+            net("9.255.255.255/32"),
+            net("192.168.1.0/24"),
+            net("10.0.0.10/24"),
+            net("::1/128"),
+        ];
+        networks.sort();
+        assert_eq!(
+            networks,
+            [
+                net("9.255.255.255/32"),
+                net("10.0.0.0/8"),
+                net("10.0.0.0/16"),
+                net("10.0.0.10/24"),
+                net("192.168.1.0/24"),
+                net("::1/128"),
+                net("2001:db8::/32"),
+            ]
+        );
+    }
+
+    #[test]
+    fn network_containment() {
+        let subnet = net("192.168.1.77/24");
+        assert_eq!(subnet.network(), "192.168.1.0".parse::<IpAddr>().unwrap());
+        assert_eq!(subnet.last(), "192.168.1.255".parse::<IpAddr>().unwrap());
+        assert!(subnet.contains("192.168.1.0".parse().unwrap()));
+        assert!(subnet.contains("192.168.1.255".parse().unwrap()));
+        assert!(!subnet.contains("192.168.2.0".parse().unwrap()));
+        assert!(!subnet.contains("::ffff:192.168.1.1".parse().unwrap()));
+
+        assert!(subnet.contains_network(&net("192.168.1.128/25")));
+        assert!(subnet.contains_network(&subnet.trunc()));
+        assert!(!subnet.contains_network(&net("192.168.0.0/16")));
+        assert!(net("192.168.0.0/16").overlaps(&subnet));
+        assert!(subnet.overlaps(&net("192.168.0.0/16")));
+        assert!(!subnet.overlaps(&net("192.168.2.0/24")));
+
+        assert!(net("0.0.0.0/0").contains("255.255.255.255".parse().unwrap()));
+        assert!(!net("0.0.0.0/0").contains("::".parse().unwrap()));
+        assert!(net("::/0").contains("ffff::1".parse().unwrap()));
+        assert!(net("2001:db8::/32").contains_network(&net("2001:db8:ffff::/48")));
+        assert!(!net("2001:db8::1/128").contains("2001:db8::2".parse().unwrap()));
+    }
+
+    #[test]
+    fn network_from_netmask() {
+        let ip: IpAddr = "10.1.2.3".parse().unwrap();
+        let from = |mask: &str| IpNetwork::from_netmask(ip, mask.parse().unwrap());
+        assert_eq!(from("255.255.255.0"), Some(net("10.1.2.3/24")));
+        assert_eq!(from("255.255.255.255"), Some(net("10.1.2.3/32")));
+        assert_eq!(from("0.0.0.0"), Some(net("10.1.2.3/0")));
+        assert_eq!(from("255.0.255.0"), None);
+        assert_eq!(from("ffff:ffff::"), None);
+        assert_eq!(net("10.1.2.3/20").netmask().to_string(), "255.255.240.0");
+
+        let ip: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(
+            IpNetwork::from_netmask(ip, "ffff:ffff:ffff:ffff::".parse().unwrap()),
+            Some(net("2001:db8::1/64"))
+        );
+    }
+
+    #[test]
+    fn interface_address_reads_old_netmask_field() {
+        let old: InterfaceAddress =
+            serde_json::from_str(r#"{"ip":"10.0.0.5","netmask":"255.255.255.0","broadcast":null}"#)
+                .unwrap();
+        assert_eq!(old.prefix, Some(24));
+        assert_eq!(old.netmask(), Some("255.255.255.0".parse().unwrap()));
+        assert_eq!(old.display(), "10.0.0.5/24");
+
+        let new: InterfaceAddress =
+            serde_json::from_str(&serde_json::to_string(&old).unwrap()).unwrap();
+        assert_eq!(new, old);
+
+        let v6: InterfaceAddress = serde_json::from_str(
+            r#"{"ip":"fe80::1","netmask":"ffff:ffff:ffff:ffff::","broadcast":null}"#,
+        )
+        .unwrap();
+        assert_eq!(v6.prefix, Some(64));
+
+        let unknown: InterfaceAddress =
+            serde_json::from_str(r#"{"ip":"10.0.0.5","netmask":null,"broadcast":null}"#).unwrap();
+        assert_eq!(unknown.prefix, None);
+        assert_eq!(unknown.display(), "10.0.0.5");
+    }
+}
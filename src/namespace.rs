@@ -0,0 +1,320 @@
+//! Network namespaces.
+//!
+//! Containers keep their interfaces in namespaces of their own. Named ones
+//! are mounted under `/run/netns` by `ip netns`; the rest are only reachable
+//! through the `/proc/<pid>/ns/net` link of a process living in them.
+//! `setns` moves just the calling thread, so `run_in` enters a namespace on a
+//! worker thread and everything else stays in the namespace we started in.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[cfg(target_os = "linux")]
+pub use platform::{by_id, resolv_conf};
+pub use platform::{list, run_in, validate_path};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Namespace {
+    /// `ip netns` name, or the command of the first process found in it
+    pub name: String,
+    /// What gets opened for `setns`: `/run/netns/<name>` or `/proc/<pid>/ns/net`
+    pub path: PathBuf,
+    /// Set for namespaces found through a process
+    pub pid: Option<i32>,
+}
+
+impl Namespace {
+    pub fn label(&self) -> String {
+        match self.pid {
+            Some(pid) => format!("{} (pid {})", self.name, pid),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Chosen in the UI; `None` is the namespace we started in
+static SELECTED: Mutex<Option<Namespace>> = Mutex::new(None);
+
+thread_local! {
+    /// Where `run_in` moved this thread
+    static ENTERED: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+pub fn select(namespace: Option<Namespace>) {
+    *SELECTED.lock().unwrap_or_else(|e| e.into_inner()) = namespace;
+}
+
+/// The namespace to read and change, unless this thread already runs inside one
+pub fn selected() -> Option<Namespace> {
+    if entered().is_some() {
+        return None;
+    }
+    SELECTED.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// `None` stands for the namespace we started in
+pub fn label_of(namespace: Option<&Namespace>) -> String {
+    namespace
+        .map(|namespace| namespace.label())
+        .unwrap_or_else(|| "host".to_string())
+}
+
+/// Name of the selected namespace for the title bar
+pub fn selected_label() -> String {
+    label_of(selected().as_ref())
+}
+
+/// The namespace `run_in` moved the calling thread into
+pub fn entered() -> Option<PathBuf> {
+    ENTERED.with(|entered| entered.borrow().clone())
+}
+
+/// `setns` needs CAP_SYS_ADMIN; without it the helper enters namespaces for us
+pub fn can_enter() -> bool {
+    nix::unistd::geteuid().is_root()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{Namespace, ENTERED};
+    use anyhow::{Context, Result};
+    use nix::sched::CloneFlags;
+    use std::collections::{HashMap, HashSet};
+    use std::ffi::OsStr;
+    use std::fs::{self, File};
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Component, Path, PathBuf};
+
+    const NAMED_DIR: &str = "/run/netns";
+
+    /// Workers never move the main thread, so this stays where we started
+    const OWN: &str = "/proc/self/ns/net";
+
+    /// Every network namespace besides our own: named ones first, then those
+    /// only reachable through a process, each once
+    pub fn list() -> Result<Vec<Namespace>> {
+        let own = fs::metadata(OWN).context("Failed to read our network namespace")?;
+        let mut seen = HashSet::from([own.ino()]);
+        let mut namespaces = Vec::new();
+
+        let mut named: Vec<PathBuf> = fs::read_dir(NAMED_DIR)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        named.sort();
+        for path in named {
+            // Files left behind without a namespace mounted on them live on tmpfs
+            let Ok(meta) = fs::metadata(&path) else {
+                continue;
+            };
+            if meta.dev() != own.dev() || !seen.insert(meta.ino()) {
+                continue;
+            }
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            namespaces.push(Namespace {
+                name,
+                path,
+                pid: None,
+            });
+        }
+
+        // Lowest pid first, usually the container's init
+        for process in procfs::process::all_processes()?.flatten() {
+            // Processes of other users without root, or gone meanwhile
+            let Ok(process_namespaces) = process.namespaces() else {
+                continue;
+            };
+            let Some(net) = process_namespaces.0.get(OsStr::new("net")) else {
+                continue;
+            };
+            if !seen.insert(net.identifier) {
+                continue;
+            }
+            namespaces.push(Namespace {
+                name: process
+                    .stat()
+                    .map(|stat| stat.comm)
+                    .unwrap_or_else(|_| "?".to_string()),
+                path: PathBuf::from(format!("/proc/{}/ns/net", process.pid)),
+                pid: Some(process.pid),
+            });
+        }
+
+        Ok(namespaces)
+    }
+
+    /// Run `f` on a worker thread inside the namespace at `path`
+    pub fn run_in<T: Send>(path: &Path, f: impl FnOnce() -> T + Send) -> Result<T> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    nix::sched::setns(&file, CloneFlags::CLONE_NEWNET).with_context(|| {
+                        format!("Failed to enter network namespace {}", path.display())
+                    })?;
+                    ENTERED.with(|entered| *entered.borrow_mut() = Some(path.to_path_buf()));
+                    Ok(f())
+                })
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    }
+
+    /// Namespaces are only entered through the two places `list` finds them
+    pub fn validate_path(path: &Path) -> Result<()> {
+        validate_in(path, Path::new(NAMED_DIR))
+    }
+
+    fn validate_in(path: &Path, named_dir: &Path) -> Result<()> {
+        let path_str = path.to_str().unwrap_or_default();
+        let valid = if let Ok(name) = path.strip_prefix(named_dir) {
+            // One plain name, not a symlink someone left there to point elsewhere
+            let mut components = name.components();
+            matches!(
+                (components.next(), components.next()),
+                (Some(Component::Normal(_)), None)
+            ) && !fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink())
+        } else if let Some(pid) = process_of(path_str) {
+            pid == "self" || (!pid.is_empty() && pid.bytes().all(|b| b.is_ascii_digit()))
+        } else {
+            false
+        };
+
+        if !valid {
+            anyhow::bail!("invalid network namespace '{}'", path.display());
+        }
+        Ok(())
+    }
+
+    fn process_of(path: &str) -> Option<&str> {
+        path.strip_prefix("/proc/")?.strip_suffix("/ns/net")
+    }
+
+    /// The resolv.conf processes in the namespace use: `ip netns exec` binds
+    /// `/etc/netns/<name>/resolv.conf` over ours, containers bring their own
+    pub fn resolv_conf(path: &Path) -> PathBuf {
+        let path_str = path.to_string_lossy();
+        if let Some(name) = path_str.strip_prefix("/run/netns/") {
+            let own = Path::new("/etc/netns").join(name).join("resolv.conf");
+            if own.exists() {
+                return own;
+            }
+        } else if let Some(pid) = process_of(&path_str) {
+            return PathBuf::from(format!("/proc/{}/root/etc/resolv.conf", pid));
+        }
+        PathBuf::from("/etc/resolv.conf")
+    }
+
+    /// Every namespace the calling thread's namespace has an id for, as
+    /// found in the `IFLA_LINK_NETNSID` of links pointing into them
+    pub fn by_id() -> HashMap<i32, Namespace> {
+        let host = Namespace {
+            name: "host".to_string(),
+            path: PathBuf::from(OWN),
+            pid: None,
+        };
+
+        std::iter::once(host)
+            .chain(list().unwrap_or_default())
+            .filter_map(|namespace| {
+                let file = File::open(&namespace.path).ok()?;
+                let id = crate::link::namespace_id(&file).ok()??;
+                Some((id, namespace))
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::os::unix::fs::symlink;
+
+        #[test]
+        fn accepts_named_and_process_namespaces() {
+            for path in [
+                "/run/netns/blue",
+                "/run/netns/my-ns.1",
+                "/proc/1/ns/net",
+                "/proc/4242/ns/net",
+                "/proc/self/ns/net",
+            ] {
+                assert!(validate_path(Path::new(path)).is_ok(), "{}", path);
+            }
+        }
+
+        #[test]
+        fn rejects_other_paths() {
+            for path in [
+                "",
+                "/run/netns",
+                "/run/netns/",
+                "/run/netns/.",
+                "/run/netns/..",
+                "/run/netns/../../etc/shadow",
+                "/run/netns/blue/../../../etc/shadow",
+                "/run/netns/a/b",
+                "/run/netnsx/blue",
+                "run/netns/blue",
+                "/etc/netns/blue",
+                "/proc//ns/net",
+                "/proc/ns/net",
+                "/proc/thread-self/ns/net",
+                "/proc/1/ns/mnt",
+                "/proc/1/ns/net/..",
+                "/proc/-1/ns/net",
+                "/proc/1a/ns/net",
+                "/proc/../1/ns/net",
+                "/proc/self/../1/ns/net",
+                "/proc/1/root/proc/1/ns/net",
+                "/proc/1/ns/../ns/net",
+            ] {
+                assert!(validate_path(Path::new(path)).is_err(), "{}", path);
+            }
+        }
+
+        #[test]
+        fn rejects_symlinks_in_named_dir() {
+            let dir =
+                std::env::temp_dir().join(format!("netutil-netns-test-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            File::create(dir.join("plain")).unwrap();
+            symlink("/proc/1/ns/net", dir.join("init")).unwrap();
+            symlink("plain", dir.join("alias")).unwrap();
+
+            let result = |name: &str| validate_in(&dir.join(name), &dir).is_ok();
+            assert!(result("plain"));
+            // Not created yet is fine, `run_in` fails to open it later
+            assert!(result("missing"));
+            assert!(!result("init"));
+            assert!(!result("alias"));
+
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Namespace;
+    use anyhow::Result;
+    use std::path::Path;
+
+    pub fn list() -> Result<Vec<Namespace>> {
+        Ok(Vec::new())
+    }
+
+    pub fn run_in<T: Send>(_path: &Path, _f: impl FnOnce() -> T + Send) -> Result<T> {
+        anyhow::bail!("Network namespaces are only supported on Linux")
+    }
+
+    pub fn validate_path(_path: &Path) -> Result<()> {
+        anyhow::bail!("Network namespaces are only supported on Linux")
+    }
+}
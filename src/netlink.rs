@@ -0,0 +1,230 @@
+//! Minimal netlink client.
+//!
+//! Just enough of the protocol to send a request to the kernel and collect
+//! the (possibly multipart) replies, plus the generic netlink controller
+//! lookup needed to talk to families such as nl80211. Message payloads are
+//! left to the callers, which parse them with `attrs`.
+
+use anyhow::{Context, Result};
+use nix::sys::socket::{
+    bind, recv, send, setsockopt, socket, sockopt, AddressFamily, MsgFlags, NetlinkAddr, SockFlag,
+    SockProtocol, SockType,
+};
+use nix::sys::time::TimeVal;
+use std::os::fd::{AsRawFd, OwnedFd};
+
+const NLM_F_REQUEST: u16 = 0x1;
+const NLM_F_MULTI: u16 = 0x2;
+pub const NLM_F_ACK: u16 = 0x4;
+pub const NLM_F_DUMP: u16 = 0x300;
+
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+
+/// Attribute type bits that carry flags (nested, byte order) rather than the type
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+/// Marks an attribute whose value is a run of attributes
+pub const NLA_F_NESTED: u16 = 0x8000;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const GENL_HEADER_LEN: usize = 4;
+
+const GENL_ID_CTRL: u16 = 0x10;
+const CTRL_CMD_GETFAMILY: u8 = 3;
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+pub struct Netlink {
+    fd: OwnedFd,
+    seq: u32,
+}
+
+impl Netlink {
+    /// rtnetlink, for links, addresses and routes
+    pub fn route() -> Result<Self> {
+        Self::open(SockProtocol::NetlinkRoute)
+    }
+
+    /// Generic netlink, for nl80211, WireGuard and the like
+    pub fn generic() -> Result<Self> {
+        Self::open(SockProtocol::NetlinkGeneric)
+    }
+
+    fn open(protocol: SockProtocol) -> Result<Self> {
+        let fd = socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC,
+            protocol,
+        )
+        .context("Failed to open netlink socket")?;
+        bind(fd.as_raw_fd(), &NetlinkAddr::new(0, 0)).context("Failed to bind netlink socket")?;
+        // The kernel answers right away; never let a lost reply hang the UI
+        setsockopt(&fd, sockopt::ReceiveTimeout, &TimeVal::new(1, 0))?;
+
+        Ok(Self { fd, seq: 0 })
+    }
+
+    /// Send one request and return the payload of every reply, following
+    /// multipart dumps until the kernel says it is done
+    pub fn request(&mut self, msg_type: u16, flags: u16, payload: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.seq = self.seq.wrapping_add(1);
+
+        let mut message = Vec::with_capacity(NLMSG_HEADER_LEN + payload.len());
+        message.extend_from_slice(&((NLMSG_HEADER_LEN + payload.len()) as u32).to_ne_bytes());
+        message.extend_from_slice(&msg_type.to_ne_bytes());
+        message.extend_from_slice(&(flags | NLM_F_REQUEST).to_ne_bytes());
+        message.extend_from_slice(&self.seq.to_ne_bytes());
+        message.extend_from_slice(&0u32.to_ne_bytes());
+        message.extend_from_slice(payload);
+        send(self.fd.as_raw_fd(), &message, MsgFlags::empty())
+            .context("Failed to send netlink request")?;
+
+        let mut replies = Vec::new();
+        let mut buf = vec![0u8; 64 * 1024];
+
+        loop {
+            let len = recv(self.fd.as_raw_fd(), &mut buf, MsgFlags::empty())
+                .context("Failed to read netlink reply")?;
+            let mut data = &buf[..len];
+            let mut more = false;
+
+            while data.len() >= NLMSG_HEADER_LEN {
+                let msg_len = u32::from_ne_bytes(data[0..4].try_into()?) as usize;
+                let msg_type = u16::from_ne_bytes(data[4..6].try_into()?);
+                let msg_flags = u16::from_ne_bytes(data[6..8].try_into()?);
+                let seq = u32::from_ne_bytes(data[8..12].try_into()?);
+                if msg_len < NLMSG_HEADER_LEN || msg_len > data.len() {
+                    anyhow::bail!("Malformed netlink message");
+                }
+                let body = &data[NLMSG_HEADER_LEN..msg_len];
+                data = &data[align(msg_len).min(data.len())..];
+
+                if seq != self.seq {
+                    continue;
+                }
+                match msg_type {
+                    NLMSG_DONE => return Ok(replies),
+                    NLMSG_ERROR => {
+                        let code = body
+                            .get(..4)
+                            .map(|code| i32::from_ne_bytes(code.try_into().unwrap_or_default()))
+                            .unwrap_or_default();
+                        if code != 0 {
+                            return Err(std::io::Error::from_raw_os_error(-code).into());
+                        }
+                        // Acknowledgement of a request without replies
+                        return Ok(replies);
+                    }
+                    _ => replies.push(body.to_vec()),
+                }
+                more |= msg_flags & NLM_F_MULTI != 0;
+            }
+
+            if !more && flags & NLM_F_ACK == 0 {
+                return Ok(replies);
+            }
+        }
+    }
+
+    /// Look up the id of a generic netlink family such as `nl80211`;
+    /// `None` when the kernel doesn't provide it
+    pub fn family_id(&mut self, name: &str) -> Result<Option<u16>> {
+        let mut name_attr = name.as_bytes().to_vec();
+        name_attr.push(0);
+        let mut payload = Vec::new();
+        push_attr(&mut payload, CTRL_ATTR_FAMILY_NAME, &name_attr);
+
+        let replies = match self.genl_request(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, 0, &payload) {
+            Ok(replies) => replies,
+            Err(e) if is_errno(&e, nix::errno::Errno::ENOENT) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Ok(replies.iter().find_map(|reply| {
+            attrs(reply)
+                .into_iter()
+                .find(|(kind, _)| *kind == CTRL_ATTR_FAMILY_ID)
+                .and_then(|(_, value)| read_u16(value))
+        }))
+    }
+
+    /// Send a generic netlink command; replies come back without their genl header
+    pub fn genl_request(
+        &mut self,
+        family: u16,
+        command: u8,
+        flags: u16,
+        attributes: &[u8],
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut payload = vec![command, 1, 0, 0];
+        payload.extend_from_slice(attributes);
+
+        Ok(self
+            .request(family, flags, &payload)?
+            .into_iter()
+            .filter(|reply| reply.len() >= GENL_HEADER_LEN)
+            .map(|reply| reply[GENL_HEADER_LEN..].to_vec())
+            .collect())
+    }
+}
+
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Whether `error` is the OS error `errno`
+pub fn is_errno(error: &anyhow::Error, errno: nix::errno::Errno) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .and_then(|e| e.raw_os_error())
+        == Some(errno as i32)
+}
+
+/// Append a netlink attribute, padded to four bytes
+pub fn push_attr(buf: &mut Vec<u8>, kind: u16, value: &[u8]) {
+    buf.extend_from_slice(&((4 + value.len()) as u16).to_ne_bytes());
+    buf.extend_from_slice(&kind.to_ne_bytes());
+    buf.extend_from_slice(value);
+    buf.resize(align(buf.len()), 0);
+}
+
+/// Split a run of netlink attributes into (type, value) pairs, flags masked off
+pub fn attrs(mut data: &[u8]) -> Vec<(u16, &[u8])> {
+    let mut found = Vec::new();
+
+    while data.len() >= 4 {
+        let len = u16::from_ne_bytes([data[0], data[1]]) as usize;
+        let kind = u16::from_ne_bytes([data[2], data[3]]) & NLA_TYPE_MASK;
+        if len < 4 || len > data.len() {
+            break;
+        }
+        found.push((kind, &data[4..len]));
+        data = &data[align(len).min(data.len())..];
+    }
+
+    found
+}
+
+/// A NUL terminated string attribute
+pub fn read_string(value: &[u8]) -> String {
+    let end = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+    String::from_utf8_lossy(&value[..end]).to_string()
+}
+
+pub fn read_u16(value: &[u8]) -> Option<u16> {
+    Some(u16::from_ne_bytes(value.get(..2)?.try_into().ok()?))
+}
+
+pub fn read_u32(value: &[u8]) -> Option<u32> {
+    Some(u32::from_ne_bytes(value.get(..4)?.try_into().ok()?))
+}
+
+pub fn read_u64(value: &[u8]) -> Option<u64> {
+    Some(u64::from_ne_bytes(value.get(..8)?.try_into().ok()?))
+}
+
+pub fn read_i32(value: &[u8]) -> Option<i32> {
+    Some(i32::from_ne_bytes(value.get(..4)?.try_into().ok()?))
+}
@@ -1,37 +1,70 @@
-use crate::models::{DnsConfiguration, InterfaceAddress, InterfaceType, NetworkInterface};
+#[cfg(target_os = "linux")]
+use crate::container;
+#[cfg(target_os = "linux")]
+use crate::link::LinkInfo;
+#[cfg(target_os = "macos")]
+use crate::models::MacAddr;
+use crate::models::{
+    AcceptRa, AddrGenMode, DnsConfiguration, InterfaceAddress, InterfaceStatistics, InterfaceType,
+    IpNetwork, Ipv6AddressInfo, Ipv6Settings, NetworkInterface, RouteEntry, TempAddrMode,
+};
+#[cfg(target_os = "linux")]
+use crate::models::{ContainerInfo, VethPeer, VlanInfo};
+use crate::namespace;
+use crate::sudo;
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::net::IpAddr;
 use std::process::Command;
 
+#[cfg(target_os = "linux")]
+use std::collections::BTreeSet;
 #[cfg(target_os = "linux")]
 use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+#[cfg(target_os = "linux")]
+use std::sync::Mutex;
 
-/// Get all network interfaces on the system
+/// Get all network interfaces in the selected namespace
 pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>> {
+    in_selected_namespace(read_network_interfaces, sudo::get_interfaces)
+}
+
+/// Run `read` in the namespace selected in the UI: on a thread that enters
+/// it when we may, otherwise in the helper, which enters it for us
+fn in_selected_namespace<T: Send>(
+    read: impl FnOnce() -> Result<T> + Send,
+    through_helper: impl FnOnce() -> Result<T>,
+) -> Result<T> {
+    match namespace::selected() {
+        None => read(),
+        Some(selected) if namespace::can_enter() => namespace::run_in(&selected.path, read)?,
+        Some(_) => through_helper(),
+    }
+}
+
+fn read_network_interfaces() -> Result<Vec<NetworkInterface>> {
     let addrs = if_addrs::get_if_addrs().context("Failed to get network interfaces")?;
     let mut interfaces_map: HashMap<String, NetworkInterface> = HashMap::new();
 
     for iface in addrs {
+        // Linux reports aliased addresses under their label, e.g. `eth0:1`
+        let (name, label) = match iface.name.split_once(':') {
+            Some((base, _)) => (base.to_string(), Some(iface.name.clone())),
+            None => (iface.name.clone(), None),
+        };
+
         let entry = interfaces_map
-            .entry(iface.name.clone())
-            .or_insert_with(|| NetworkInterface {
-                name: iface.name.clone(),
-                interface_type: InterfaceType::from_name(&iface.name),
-                ip_addresses: Vec::new(),
-                mac_address: None,
-                is_up: !iface.is_loopback(),
-                is_loopback: iface.is_loopback(),
-                mtu: None,
-                ipv6_enabled: true,
-                ssid: None,
-            });
+            .entry(name.clone())
+            .or_insert_with(|| new_interface(&name, iface.is_loopback()));
 
         let ip = iface.addr.ip();
         let netmask = match &iface.addr {
-            if_addrs::IfAddr::V4(v4) => Some(IpAddr::V4(v4.netmask)),
-            if_addrs::IfAddr::V6(v6) => Some(IpAddr::V6(v6.netmask)),
+            if_addrs::IfAddr::V4(v4) => IpAddr::V4(v4.netmask),
+            if_addrs::IfAddr::V6(v6) => IpAddr::V6(v6.netmask),
         };
+        let prefix = IpNetwork::from_netmask(ip, netmask).map(|network| network.prefix());
 
         let broadcast = match &iface.addr {
             if_addrs::IfAddr::V4(v4) => v4.broadcast.map(IpAddr::V4),
@@ -40,17 +73,24 @@ pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>> {
 
         entry.ip_addresses.push(InterfaceAddress {
             ip,
-            netmask,
+            prefix,
             broadcast,
+            label,
+            secondary: false,
+            ipv6_info: None,
         });
     }
 
+    for iface in interfaces_map.values_mut() {
+        mark_secondary_addresses(&mut iface.ip_addresses);
+    }
+
     // Get MAC addresses and detect WiFi interfaces
     #[cfg(target_os = "macos")]
     {
         for (name, iface) in interfaces_map.iter_mut() {
             if let Ok(mac) = get_mac_address_macos(name) {
-                iface.mac_address = Some(mac);
+                iface.mac_address = mac;
             }
 
             // Detect WiFi and get SSID
@@ -65,17 +105,64 @@ pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>> {
 
     #[cfg(target_os = "linux")]
     {
+        let ipv6_info = get_ipv6_address_info_linux().unwrap_or_default();
+        let links = crate::link::get_links().unwrap_or_default();
+        let mut veth_peers = get_veth_peers(&links);
+
+        // Links without addresses, such as a fresh VLAN, are listed too
+        for link in &links {
+            let iface = interfaces_map
+                .entry(link.name.clone())
+                .or_insert_with(|| new_interface(&link.name, link.loopback));
+            iface.is_up = link.up;
+            iface.mac_address = link.mac;
+            iface.permanent_mac = link.permanent_mac;
+            iface.mtu = link.mtu;
+            iface.master = link
+                .master
+                .and_then(|master| links.iter().find(|other| other.index == master))
+                .map(|master| master.name.clone());
+            iface.vlan = link.vlan.and_then(|(id, protocol)| {
+                let parent = links
+                    .iter()
+                    .find(|other| Some(other.index) == link.parent)?;
+                Some(VlanInfo {
+                    parent: parent.name.clone(),
+                    id,
+                    protocol,
+                })
+            });
+            iface.veth_peer = veth_peers.remove(&link.index);
+            iface.counters = link.counters;
+        }
+
         for (name, iface) in interfaces_map.iter_mut() {
-            if let Ok(mac) = get_mac_address_linux(name) {
-                iface.mac_address = Some(mac);
+            iface.ipv6_enabled = read_ipv6_sysctl(name, "disable_ipv6") == Some(0);
+            iface.ipv6_settings = get_ipv6_settings_linux(name);
+            for addr in iface.ip_addresses.iter_mut() {
+                if let IpAddr::V6(ip) = addr.ip {
+                    addr.ipv6_info = ipv6_info.get(&(name.clone(), ip)).cloned();
+                }
             }
 
-            // Detect WiFi and get SSID
-            if is_wifi_interface_linux(name) {
-                iface.interface_type = InterfaceType::WiFi;
-                if let Ok(ssid) = get_wifi_ssid_linux(name) {
-                    iface.ssid = Some(ssid);
-                }
+            let link = links.iter().find(|link| &link.name == name);
+            if let Some(kind) = get_interface_type_linux(name, link) {
+                iface.interface_type = kind;
+            }
+
+            iface.ethtool = crate::ethtool::get_ethtool_info(name);
+
+            if iface.interface_type == InterfaceType::WireGuard {
+                iface.wireguard = crate::wireguard::get_wireguard_info(name).ok();
+            }
+
+            // Get the SSID, from nl80211 when the kernel has it
+            if iface.interface_type == InterfaceType::WiFi {
+                iface.wireless = crate::wireless::get_wireless_info(name);
+                iface.ssid = match iface.wireless.as_ref().and_then(|w| w.ssid.clone()) {
+                    Some(ssid) => Some(ssid),
+                    None => get_wifi_ssid_linux(name).ok(),
+                };
             }
         }
     }
@@ -86,8 +173,186 @@ pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>> {
     Ok(result)
 }
 
+pub fn new_interface(name: &str, is_loopback: bool) -> NetworkInterface {
+    NetworkInterface {
+        name: name.to_string(),
+        interface_type: InterfaceType::from_name(name),
+        ip_addresses: Vec::new(),
+        mac_address: None,
+        permanent_mac: None,
+        is_up: !is_loopback,
+        is_loopback,
+        mtu: None,
+        ipv6_enabled: true,
+        ssid: None,
+        ipv6_settings: None,
+        wireless: None,
+        vlan: None,
+        master: None,
+        wireguard: None,
+        veth_peer: None,
+        ethtool: None,
+        counters: None,
+    }
+}
+
+/// The other end of every veth pair by link index, followed into the
+/// namespace it lives in when that isn't this one
+#[cfg(target_os = "linux")]
+fn get_veth_peers(links: &[LinkInfo]) -> HashMap<u32, VethPeer> {
+    let veths: Vec<&LinkInfo> = links
+        .iter()
+        .filter(|link| link.kind.as_deref() == Some("veth"))
+        .collect();
+    let (namespaces, containers) = peer_namespaces(&veths);
+    let mut remote_links: HashMap<i32, Vec<LinkInfo>> = HashMap::new();
+    let mut peers = HashMap::new();
+
+    for veth in veths {
+        let Some(index) = veth.parent else {
+            continue;
+        };
+        let (peer_links, namespace) = match veth.link_netnsid {
+            None => (links, None),
+            Some(id) => {
+                let namespace = namespaces.get(&id);
+                let peer_links = remote_links.entry(id).or_insert_with(|| {
+                    namespace
+                        .filter(|_| namespace::can_enter())
+                        .and_then(|namespace| {
+                            namespace::run_in(&namespace.path, crate::link::get_links).ok()
+                        })
+                        .and_then(|links| links.ok())
+                        .unwrap_or_default()
+                });
+                let label = namespace
+                    .map(|namespace| namespace.label())
+                    .unwrap_or_else(|| format!("namespace id {}", id));
+                (peer_links.as_slice(), Some(label))
+            }
+        };
+
+        peers.insert(
+            veth.index,
+            VethPeer {
+                index,
+                name: peer_links
+                    .iter()
+                    .find(|link| link.index == index)
+                    .map(|link| link.name.clone()),
+                namespace,
+                container: veth
+                    .link_netnsid
+                    .and_then(|id| containers.get(&id).cloned()),
+            },
+        );
+    }
+
+    peers
+}
+
+/// Namespaces and containers on the far side of the veths, as of the last
+/// time the veths changed
+#[cfg(target_os = "linux")]
+struct PeerNamespaces {
+    /// Namespace looked from, and each veth with the id of its peer's namespace
+    veths: (Option<PathBuf>, BTreeSet<(u32, i32)>),
+    namespaces: HashMap<i32, namespace::Namespace>,
+    inodes: HashMap<i32, u64>,
+    containers: HashMap<i32, ContainerInfo>,
+}
+
+#[cfg(target_os = "linux")]
+static PEER_NAMESPACES: Mutex<Option<PeerNamespaces>> = Mutex::new(None);
+
+/// Namespaces and their containers by namespace id. Finding them walks
+/// `/proc`, so that only happens again when a veth comes or goes or one of
+/// the namespaces no longer is where it was found.
+#[cfg(target_os = "linux")]
+fn peer_namespaces(
+    veths: &[&LinkInfo],
+) -> (
+    HashMap<i32, namespace::Namespace>,
+    HashMap<i32, ContainerInfo>,
+) {
+    let key = (
+        namespace::entered(),
+        veths
+            .iter()
+            .filter_map(|veth| Some((veth.index, veth.link_netnsid?)))
+            .collect::<BTreeSet<_>>(),
+    );
+    if key.1.is_empty() {
+        return (HashMap::new(), HashMap::new());
+    }
+
+    let mut cache = PEER_NAMESPACES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.as_ref() {
+        let moved = cached.namespaces.iter().any(|(id, namespace)| {
+            namespace_inode(&namespace.path) != cached.inodes.get(id).copied()
+        });
+        if cached.veths == key && !moved {
+            return (cached.namespaces.clone(), cached.containers.clone());
+        }
+    }
+
+    let namespaces = namespace::by_id();
+    let inodes: HashMap<i32, u64> = namespaces
+        .iter()
+        .filter_map(|(id, namespace)| Some((*id, namespace_inode(&namespace.path)?)))
+        .collect();
+    let containers = containers_by_id(&inodes);
+
+    let found = (namespaces.clone(), containers.clone());
+    *cache = Some(PeerNamespaces {
+        veths: key,
+        namespaces,
+        inodes,
+        containers,
+    });
+    found
+}
+
+#[cfg(target_os = "linux")]
+fn namespace_inode(path: &std::path::Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path).ok().map(|meta| meta.ino())
+}
+
+/// Containers owning the namespaces by their id here, found through the
+/// namespace inodes
+#[cfg(target_os = "linux")]
+fn containers_by_id(inodes: &HashMap<i32, u64>) -> HashMap<i32, ContainerInfo> {
+    if inodes.is_empty() {
+        return HashMap::new();
+    }
+    let mut containers = container::by_namespace(&inodes.values().copied().collect());
+
+    inodes
+        .iter()
+        .filter_map(|(id, inode)| Some((*id, containers.remove(inode)?)))
+        .collect()
+}
+
+/// The kernel lists the primary address of a subnet before its secondaries
+fn mark_secondary_addresses(addresses: &mut [InterfaceAddress]) {
+    let mut seen: Vec<IpNetwork> = Vec::new();
+
+    for addr in addresses.iter_mut() {
+        let Some(subnet) = addr.network().filter(|network| network.is_ipv4()) else {
+            continue;
+        };
+
+        addr.secondary = seen.contains(&subnet.trunc());
+        if !addr.secondary {
+            seen.push(subnet.trunc());
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
-fn get_mac_address_macos(interface_name: &str) -> Result<String> {
+fn get_mac_address_macos(interface_name: &str) -> Result<Option<MacAddr>> {
     let output = Command::new("ifconfig")
         .arg(interface_name)
         .output()
@@ -100,26 +365,84 @@ fn get_mac_address_macos(interface_name: &str) -> Result<String> {
         if line.starts_with("ether ") {
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 2 {
-                return Ok(parts[1].to_string());
+                return Ok(parts[1].parse().ok());
             }
         }
     }
 
-    Ok("N/A".to_string())
+    Ok(None)
 }
 
+/// Read `/proc/sys/net/ipv6/conf/<iface>/<name>`; `None` when IPv6 is unavailable
 #[cfg(target_os = "linux")]
-fn get_mac_address_linux(interface_name: &str) -> Result<String> {
-    let path = format!("/sys/class/net/{}/address", interface_name);
-    match fs::read_to_string(&path) {
-        Ok(content) => Ok(content.trim().to_string()),
-        Err(_) => Ok("N/A".to_string()),
-    }
+fn read_ipv6_sysctl(interface: &str, name: &str) -> Option<i32> {
+    fs::read_to_string(format!("/proc/sys/net/ipv6/conf/{}/{}", interface, name))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn get_mac_address_fallback(_interface_name: &str) -> Result<String> {
-    Ok("N/A".to_string())
+#[cfg(target_os = "linux")]
+fn get_ipv6_settings_linux(interface: &str) -> Option<Ipv6Settings> {
+    Some(Ipv6Settings {
+        addr_gen_mode: AddrGenMode::from_sysctl(read_ipv6_sysctl(interface, "addr_gen_mode")?),
+        use_tempaddr: TempAddrMode::from_sysctl(read_ipv6_sysctl(interface, "use_tempaddr")?),
+        accept_ra: AcceptRa::from_sysctl(read_ipv6_sysctl(interface, "accept_ra")?),
+    })
+}
+
+/// Scope, flags and lifetimes of every IPv6 address, from `ip -j -6 addr`
+#[cfg(target_os = "linux")]
+fn get_ipv6_address_info_linux() -> Result<HashMap<(String, std::net::Ipv6Addr), Ipv6AddressInfo>> {
+    // Lifetimes of permanent addresses are reported as u32::MAX
+    const INFINITY: u64 = u32::MAX as u64;
+
+    let output = Command::new("ip")
+        .args(["-j", "-6", "addr", "show"])
+        .output()
+        .context("Failed to run ip")?;
+    let links: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)?;
+
+    let mut info = HashMap::new();
+    for link in &links {
+        let Some(name) = link["ifname"].as_str() else {
+            continue;
+        };
+
+        for addr in link["addr_info"].as_array().into_iter().flatten() {
+            let Some(ip) = addr["local"].as_str().and_then(|ip| ip.parse().ok()) else {
+                continue;
+            };
+            let lifetime = |key: &str| {
+                addr[key]
+                    .as_u64()
+                    .filter(|&secs| secs < INFINITY)
+                    .map(|secs| secs as u32)
+            };
+
+            // Flags are reported as boolean members, e.g. "tentative": true
+            let flags = addr
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter(|(_, value)| value.as_bool() == Some(true))
+                .map(|(flag, _)| flag.clone())
+                .collect();
+
+            info.insert(
+                (name.to_string(), ip),
+                Ipv6AddressInfo {
+                    scope: addr["scope"].as_str().unwrap_or("unknown").to_string(),
+                    flags,
+                    preferred_lifetime: lifetime("preferred_life_time"),
+                    valid_lifetime: lifetime("valid_life_time"),
+                },
+            );
+        }
+    }
+
+    Ok(info)
 }
 
 /// Check if an interface is WiFi on macOS
@@ -175,12 +498,62 @@ fn get_wifi_ssid_macos(_interface_name: &str) -> Result<String> {
     Ok("Not connected".to_string())
 }
 
-/// Check if an interface is WiFi on Linux
+/// Classify an interface from kernel metadata rather than its name: the
+/// wireless directory, the rtnetlink link kind of virtual devices, the uevent
+/// DEVTYPE, then the hardware type. Ethernet-type links with a backing
+/// `device` are NICs whatever they are called; those without one are virtual.
 #[cfg(target_os = "linux")]
-fn is_wifi_interface_linux(interface_name: &str) -> bool {
-    // Check if wireless directory exists
-    let wireless_path = format!("/sys/class/net/{}/wireless", interface_name);
-    std::path::Path::new(&wireless_path).exists()
+fn get_interface_type_linux(
+    interface_name: &str,
+    link: Option<&LinkInfo>,
+) -> Option<InterfaceType> {
+    let link_kind = link
+        .and_then(|link| link.kind.as_deref())
+        .and_then(InterfaceType::from_link_kind);
+
+    // sysfs describes the namespace it was mounted in, not the one entered
+    if namespace::entered().is_some() {
+        return link_kind.or_else(|| InterfaceType::from_arphrd(link?.hardware_type));
+    }
+
+    classify_sysfs(
+        &std::path::Path::new("/sys/class/net").join(interface_name),
+        link_kind,
+    )
+}
+
+/// Classify from the device's sysfs directory: WiFi first (wireless links
+/// report no kind), then the link kind, `DEVTYPE` and finally the ARPHRD type
+#[cfg(target_os = "linux")]
+fn classify_sysfs(
+    sys: &std::path::Path,
+    link_kind: Option<InterfaceType>,
+) -> Option<InterfaceType> {
+    if sys.join("wireless").exists() || sys.join("phy80211").exists() {
+        return Some(InterfaceType::WiFi);
+    }
+    if link_kind.is_some() {
+        return link_kind;
+    }
+
+    let uevent = fs::read_to_string(sys.join("uevent")).unwrap_or_default();
+    let devtype = uevent
+        .lines()
+        .find_map(|line| line.strip_prefix("DEVTYPE="));
+    if let Some(kind) = devtype.and_then(InterfaceType::from_devtype) {
+        return Some(kind);
+    }
+
+    let arphrd: u16 = fs::read_to_string(sys.join("type"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    match arphrd {
+        1 if sys.join("device").exists() => Some(InterfaceType::Ethernet),
+        1 => Some(InterfaceType::Virtual),
+        _ => InterfaceType::from_arphrd(arphrd),
+    }
 }
 
 /// Get WiFi SSID on Linux
@@ -224,8 +597,172 @@ fn get_wifi_ssid_linux(interface_name: &str) -> Result<String> {
     Ok("Not connected".to_string())
 }
 
-/// Get DNS configuration from the system
+/// Every counter of `interface` in the selected namespace, for the
+/// statistics page
+pub fn get_statistics(interface: &str) -> Result<InterfaceStatistics> {
+    in_selected_namespace(
+        || read_statistics(interface),
+        || sudo::get_statistics(interface),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn read_statistics(interface: &str) -> Result<InterfaceStatistics> {
+    let counters = crate::link::get_links()?
+        .into_iter()
+        .find(|link| link.name == interface)
+        .ok_or_else(|| anyhow::anyhow!("{} no longer exists", interface))?
+        .counters
+        .unwrap_or_default();
+
+    Ok(InterfaceStatistics {
+        counters,
+        // Virtual devices mostly have no driver statistics
+        driver: crate::ethtool::get_driver_statistics(interface).unwrap_or_default(),
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_statistics(_interface: &str) -> Result<InterfaceStatistics> {
+    anyhow::bail!("Interface statistics are only available on Linux")
+}
+
+/// Get the routing table (main table only) of the selected namespace
+pub fn get_routes() -> Result<Vec<RouteEntry>> {
+    in_selected_namespace(read_routes, sudo::get_routes)
+}
+
+/// `/proc/net` is the namespace of the main thread, `thread-self` our own
+#[cfg(target_os = "linux")]
+fn read_routes() -> Result<Vec<RouteEntry>> {
+    let mut routes = Vec::new();
+
+    // Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+    let ipv4 = fs::read_to_string("/proc/thread-self/net/route")
+        .context("Failed to read /proc/net/route")?;
+    for line in ipv4.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+
+        let parse = |hex: &str| u32::from_str_radix(hex, 16).ok();
+        let (Some(dest), Some(gateway), Some(mask)) =
+            (parse(fields[1]), parse(fields[2]), parse(fields[7]))
+        else {
+            continue;
+        };
+
+        // The kernel prints these in host (little endian) byte order
+        let gateway = std::net::Ipv4Addr::from(gateway.to_le_bytes());
+        routes.push(RouteEntry {
+            destination: IpAddr::V4(std::net::Ipv4Addr::from(dest.to_le_bytes())),
+            prefix: mask.count_ones() as u8,
+            gateway: (!gateway.is_unspecified()).then_some(IpAddr::V4(gateway)),
+            interface: fields[0].to_string(),
+            metric: fields[6].parse().unwrap_or(0),
+        });
+    }
+
+    // dest dest_plen src src_plen next_hop metric refcnt use flags iface
+    if let Ok(ipv6) = fs::read_to_string("/proc/thread-self/net/ipv6_route") {
+        for line in ipv6.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[9] == "lo" {
+                continue;
+            }
+
+            let parse = |hex: &str| u128::from_str_radix(hex, 16).ok();
+            let (Some(dest), Some(prefix), Some(next_hop), Some(metric)) = (
+                parse(fields[0]),
+                u8::from_str_radix(fields[1], 16).ok(),
+                parse(fields[4]),
+                u32::from_str_radix(fields[5], 16).ok(),
+            ) else {
+                continue;
+            };
+
+            // Addresses of our own interfaces, from the local table
+            const RTF_LOCAL: u32 = 0x8000_0000;
+            if u32::from_str_radix(fields[8], 16).is_ok_and(|flags| flags & RTF_LOCAL != 0) {
+                continue;
+            }
+
+            let next_hop = std::net::Ipv6Addr::from(next_hop);
+            routes.push(RouteEntry {
+                destination: IpAddr::V6(std::net::Ipv6Addr::from(dest)),
+                prefix,
+                gateway: (!next_hop.is_unspecified()).then_some(IpAddr::V6(next_hop)),
+                interface: fields[9].to_string(),
+                metric,
+            });
+        }
+    }
+
+    Ok(routes)
+}
+
+/// Get the default routes; macOS only exposes these without parsing netstat
+#[cfg(target_os = "macos")]
+fn read_routes() -> Result<Vec<RouteEntry>> {
+    let mut routes = Vec::new();
+
+    for family in ["-inet", "-inet6"] {
+        let output = Command::new("route")
+            .args(["-n", "get", family, "default"])
+            .output()
+            .context("Failed to execute route")?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut gateway = None;
+        let mut interface = None;
+        for line in stdout.lines() {
+            let line = line.trim();
+            if let Some(gw) = line.strip_prefix("gateway:") {
+                gateway = gw.trim().split('%').next().and_then(|gw| gw.parse().ok());
+            } else if let Some(name) = line.strip_prefix("interface:") {
+                interface = Some(name.trim().to_string());
+            }
+        }
+
+        if let (Some(gateway), Some(interface)) = (gateway, interface) {
+            let destination = match gateway {
+                IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+                IpAddr::V6(_) => IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+            };
+            routes.push(RouteEntry {
+                destination,
+                prefix: 0,
+                gateway: Some(gateway),
+                interface,
+                metric: 0,
+            });
+        }
+    }
+
+    Ok(routes)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn read_routes() -> Result<Vec<RouteEntry>> {
+    Ok(Vec::new())
+}
+
+/// Default gateways reachable through `interface`
+pub fn get_default_gateways(interface: &str) -> Result<Vec<IpAddr>> {
+    Ok(get_routes()?
+        .into_iter()
+        .filter(|route| route.is_default() && route.interface == interface)
+        .filter_map(|route| route.gateway)
+        .collect())
+}
+
+/// Get the DNS configuration of the selected namespace
 pub fn get_dns_configuration() -> Result<DnsConfiguration> {
+    in_selected_namespace(read_dns_configuration, sudo::get_dns_configuration)
+}
+
+fn read_dns_configuration() -> Result<DnsConfiguration> {
     #[cfg(target_os = "macos")]
     {
         get_dns_configuration_macos()
@@ -284,14 +821,24 @@ fn get_dns_configuration_macos() -> Result<DnsConfiguration> {
 
 #[cfg(target_os = "linux")]
 fn get_dns_configuration_linux() -> Result<DnsConfiguration> {
+    // systemd-resolved and /etc/resolv.conf belong to the namespace we started in
+    if let Some(path) = namespace::entered() {
+        return Ok(parse_resolv_conf(&namespace::resolv_conf(&path)));
+    }
+
     // Try systemd-resolved first
     if let Ok(config) = get_dns_from_systemd_resolved() {
         return Ok(config);
     }
 
     // Fallback to /etc/resolv.conf
-    match resolv_conf::Config::parse("/etc/resolv.conf") {
-        Ok(config) => {
+    Ok(parse_resolv_conf(std::path::Path::new("/etc/resolv.conf")))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_resolv_conf(path: &std::path::Path) -> DnsConfiguration {
+    match fs::read(path).map(resolv_conf::Config::parse) {
+        Ok(Ok(config)) => {
             let nameservers = config.nameservers.iter().map(|ns| ns.into()).collect();
 
             let search_domains = if let Some(domain) = config.get_system_domain() {
@@ -300,15 +847,15 @@ fn get_dns_configuration_linux() -> Result<DnsConfiguration> {
                 Vec::new()
             };
 
-            Ok(DnsConfiguration {
+            DnsConfiguration {
                 nameservers,
                 search_domains,
-            })
+            }
         }
-        Err(_) => Ok(DnsConfiguration {
+        _ => DnsConfiguration {
             nameservers: Vec::new(),
             search_domains: Vec::new(),
-        }),
+        },
     }
 }
 
@@ -350,87 +897,123 @@ fn get_dns_from_systemd_resolved() -> Result<DnsConfiguration> {
     })
 }
 
-/// Set a static IP address for an interface
-#[cfg(target_os = "macos")]
-#[allow(dead_code)]
-pub fn set_static_ip(interface: &str, ip: &str, netmask: &str) -> Result<()> {
-    let output = Command::new("sudo")
-        .args(&["ifconfig", interface, ip, "netmask", netmask])
-        .output()
-        .context("Failed to set static IP")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to set static IP: {}", stderr);
-    }
-
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-#[allow(dead_code)]
-pub fn set_static_ip(interface: &str, ip: &str, netmask: &str) -> Result<()> {
-    // Calculate CIDR prefix from netmask
-    let prefix = netmask_to_cidr(netmask)?;
-    let ip_with_prefix = format!("{}/{}", ip, prefix);
-
-    let output = Command::new("sudo")
-        .args(&["ip", "addr", "add", &ip_with_prefix, "dev", interface])
-        .output()
-        .context("Failed to set static IP")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to set static IP: {}", stderr);
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// File names and contents, an empty content makes a directory
+    type Files = &'static [(&'static str, &'static str)];
+
+    /// A fake `/sys/class/net/<if>` holding `files`
+    fn sysfs(name: &str, files: Files) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "netutil-sysfs-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            let path = dir.join(file);
+            if content.is_empty() {
+                fs::create_dir_all(path).unwrap();
+            } else {
+                fs::write(path, content).unwrap();
+            }
+        }
+        dir
     }
 
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-fn netmask_to_cidr(netmask: &str) -> Result<u8> {
-    let ip: std::net::Ipv4Addr = netmask.parse()?;
-    let octets = ip.octets();
-    let mut cidr = 0u8;
-
-    for octet in &octets {
-        cidr += octet.count_ones() as u8;
+    fn classify(name: &str, files: Files, kind: Option<&str>) -> Option<InterfaceType> {
+        let dir = sysfs(name, files);
+        let found = classify_sysfs(&dir, kind.and_then(InterfaceType::from_link_kind));
+        fs::remove_dir_all(&dir).unwrap();
+        found
     }
 
-    Ok(cidr)
-}
-
-/// Enable or disable a network interface
-#[cfg(target_os = "macos")]
-#[allow(dead_code)]
-pub fn set_interface_status(interface: &str, enabled: bool) -> Result<()> {
-    let status = if enabled { "up" } else { "down" };
-    let output = Command::new("sudo")
-        .args(&["ifconfig", interface, status])
-        .output()
-        .context("Failed to set interface status")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to set interface status: {}", stderr);
+    #[test]
+    fn classification_order() {
+        use InterfaceType::*;
+
+        let cases: &[(&str, Files, Option<&str>, Option<InterfaceType>)] = &[
+            // A wireless directory wins over everything else
+            (
+                "wifi",
+                &[
+                    ("phy80211", ""),
+                    ("uevent", "DEVTYPE=bridge\n"),
+                    ("type", "1\n"),
+                ],
+                Some("bridge"),
+                Some(WiFi),
+            ),
+            (
+                "wireless",
+                &[("wireless", ""), ("type", "1\n")],
+                None,
+                Some(WiFi),
+            ),
+            // The link kind beats DEVTYPE and the hardware type
+            (
+                "kind",
+                &[("uevent", "DEVTYPE=vlan\n"), ("type", "772\n")],
+                Some("bond"),
+                Some(Bond),
+            ),
+            // An unknown kind falls through to DEVTYPE
+            (
+                "unknown-kind",
+                &[
+                    ("uevent", "INTERFACE=x\nDEVTYPE=vlan\nIFINDEX=9\n"),
+                    ("type", "1\n"),
+                ],
+                Some("somethingnew"),
+                Some(Vlan),
+            ),
+            // DEVTYPE beats the hardware type
+            (
+                "devtype",
+                &[("uevent", "DEVTYPE=ppp\n"), ("type", "1\n")],
+                None,
+                Some(Ppp),
+            ),
+            // An unknown DEVTYPE falls through to the hardware type
+            (
+                "unknown-devtype",
+                &[("uevent", "DEVTYPE=gadget\n"), ("type", "772\n")],
+                None,
+                Some(Loopback),
+            ),
+            // ARPHRD_ETHER is hardware only with a backing device
+            (
+                "ether",
+                &[
+                    ("device", ""),
+                    ("uevent", "INTERFACE=eth0\n"),
+                    ("type", "1\n"),
+                ],
+                None,
+                Some(Ethernet),
+            ),
+            ("ether-virtual", &[("type", "1\n")], None, Some(Virtual)),
+            ("raw-ip", &[("type", "65534\n")], None, Some(Tunnel)),
+            ("unknown-type", &[("type", "9999\n")], None, None),
+            ("no-type", &[], None, None),
+        ];
+
+        for (name, files, kind, expected) in cases {
+            assert_eq!(classify(name, files, *kind), *expected, "{}", name);
+        }
     }
 
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-#[allow(dead_code)]
-pub fn set_interface_status(interface: &str, enabled: bool) -> Result<()> {
-    let status = if enabled { "up" } else { "down" };
-    let output = Command::new("sudo")
-        .args(&["ip", "link", "set", interface, status])
-        .output()
-        .context("Failed to set interface status")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to set interface status: {}", stderr);
+    #[test]
+    fn classification_without_sysfs() {
+        let missing = Path::new("/nonexistent/netutil/sys/class/net/eth0");
+        assert_eq!(
+            classify_sysfs(missing, InterfaceType::from_link_kind("veth")),
+            Some(InterfaceType::Virtual)
+        );
+        assert_eq!(classify_sysfs(missing, None), None);
     }
-
-    Ok(())
 }
@@ -0,0 +1,385 @@
+use crate::models::{IpNetwork, NetworkInterface};
+use std::net::{IpAddr, Ipv6Addr};
+
+/// The fewest prefixes covering exactly the same addresses: duplicates and
+/// prefixes inside others are dropped and adjacent halves are merged.
+pub fn summarize(networks: &[IpNetwork]) -> Vec<IpNetwork> {
+    let mut networks: Vec<IpNetwork> = networks.iter().map(|network| network.trunc()).collect();
+    networks.sort();
+    networks.dedup();
+
+    loop {
+        let mut merged: Vec<IpNetwork> = Vec::with_capacity(networks.len());
+        for network in networks.iter().copied() {
+            if merged.iter().any(|kept| kept.contains_network(&network)) {
+                continue;
+            }
+            // Sorted order puts the lower half right before its sibling
+            let parent = network
+                .prefix()
+                .checked_sub(1)
+                .and_then(|prefix| network.supernet(prefix));
+            match (merged.last(), parent) {
+                (Some(last), Some(parent))
+                    if last.prefix() == network.prefix()
+                        && last.is_ipv4() == network.is_ipv4()
+                        && parent.contains_network(last) =>
+                {
+                    *merged.last_mut().expect("checked above") = parent;
+                }
+                _ => merged.push(network),
+            }
+        }
+
+        merged.sort();
+        if merged == networks {
+            return merged;
+        }
+        networks = merged;
+    }
+}
+
+/// The longest prefix containing all of `networks`, which must share an
+/// address family
+pub fn covering_supernet(networks: &[IpNetwork]) -> Option<IpNetwork> {
+    let (first, rest) = networks.split_first()?;
+    if rest
+        .iter()
+        .any(|network| network.is_ipv4() != first.is_ipv4())
+    {
+        return None;
+    }
+
+    let mut cover = first.trunc();
+    for network in rest {
+        while !cover.contains_network(network) {
+            cover = cover.supernet(cover.prefix() - 1)?;
+        }
+    }
+    Some(cover)
+}
+
+/// Zones for reverse DNS of `network`. A prefix between label boundaries
+/// needs several zones (e.g. a /22 spans four /24 zones), listed up to
+/// `limit`; IPv4 subnets smaller than a /24 get an RFC 2317 name.
+pub fn reverse_zones(network: &IpNetwork, limit: usize) -> (Vec<String>, u128) {
+    let network = network.trunc();
+    let (label_bits, suffix) = if network.is_ipv4() {
+        (8, "in-addr.arpa")
+    } else {
+        (4, "ip6.arpa")
+    };
+
+    if network.is_ipv4() && network.prefix() > 24 && network.prefix() < 32 {
+        let IpAddr::V4(v4) = network.ip() else {
+            unreachable!()
+        };
+        let [a, b, c, d] = v4.octets();
+        let zone = format!("{}/{}.{}.{}.{}.{}", d, network.prefix(), c, b, a, suffix);
+        return (vec![zone], 1);
+    }
+
+    let zone_prefix = network.prefix().div_ceil(label_bits) * label_bits;
+    let count = network.subnet_count(zone_prefix).unwrap_or(u128::MAX);
+    let zones = (0..count.min(limit as u128))
+        .filter_map(|index| network.subnet(zone_prefix, index))
+        .map(|zone| {
+            let labels = (zone_prefix / label_bits) as usize;
+            let mut parts: Vec<String> = match zone.ip() {
+                IpAddr::V4(v4) => v4.octets().iter().map(|octet| octet.to_string()).collect(),
+                IpAddr::V6(v6) => expanded_ipv6(&v6)
+                    .chars()
+                    .filter(|c| *c != ':')
+                    .map(String::from)
+                    .collect(),
+            };
+            parts.truncate(labels);
+            parts.reverse();
+            parts.push(suffix.to_string());
+            parts.join(".")
+        })
+        .collect();
+    (zones, count)
+}
+
+/// All eight groups with their leading zeros
+pub fn expanded_ipv6(ip: &Ipv6Addr) -> String {
+    ip.segments()
+        .iter()
+        .map(|segment| format!("{:04x}", segment))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// How an existing subnet relates to the one being looked at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    Same,
+    /// The existing subnet lies inside the one being looked at
+    Contains,
+    /// The existing subnet is larger and holds the one being looked at
+    Inside,
+}
+
+impl Overlap {
+    pub fn of(network: &IpNetwork, existing: &IpNetwork) -> Option<Overlap> {
+        let (network, existing) = (network.trunc(), existing.trunc());
+        if network == existing {
+            Some(Overlap::Same)
+        } else if network.contains_network(&existing) {
+            Some(Overlap::Contains)
+        } else if existing.contains_network(&network) {
+            Some(Overlap::Inside)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Overlap::Same => "same subnet as",
+            Overlap::Contains => "contains",
+            Overlap::Inside => "inside",
+        }
+    }
+}
+
+/// Interface subnets that overlap `network`, skipping loopback and
+/// link-local ones that every host has
+pub fn interface_overlaps(
+    network: &IpNetwork,
+    interfaces: &[NetworkInterface],
+) -> Vec<(String, IpNetwork, Overlap)> {
+    interfaces
+        .iter()
+        .flat_map(|iface| {
+            iface
+                .ip_addresses
+                .iter()
+                .map(move |addr| (iface.name.as_str(), addr))
+        })
+        .filter(|(_, addr)| !addr.ip.is_loopback() && !addr.is_link_local())
+        .filter_map(|(name, addr)| {
+            let existing = addr.network()?;
+            let overlap = Overlap::of(network, &existing)?;
+            Some((name.to_string(), existing, overlap))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nets(inputs: &[&str]) -> Vec<IpNetwork> {
+        inputs.iter().map(|input| input.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn summarize_merges_and_drops() {
+        let cases: &[(&[&str], &[&str])] = &[
+            (&[], &[]),
+            (&["10.0.0.5/24"], &["10.0.0.0/24"]),
+            (&["10.0.0.0/24", "10.0.1.0/24"], &["10.0.0.0/23"]),
+            (
+                &["10.0.3.0/24", "10.0.1.0/24", "10.0.0.0/24", "10.0.2.0/24"],
+                &["10.0.0.0/22"],
+            ),
+            // Adjacent but not halves of the same parent
+            (
+                &["10.0.1.0/24", "10.0.2.0/24"],
+                &["10.0.1.0/24", "10.0.2.0/24"],
+            ),
+            (
+                &["10.0.0.0/16", "10.0.5.0/24", "10.0.0.0/16"],
+                &["10.0.0.0/16"],
+            ),
+            (&["0.0.0.0/0", "1.2.3.4/32"], &["0.0.0.0/0"]),
+            (&["0.0.0.0/1", "128.0.0.0/1"], &["0.0.0.0/0"]),
+            (&["::/1", "8000::/1"], &["::/0"]),
+            (&["2001:db8::/128", "2001:db8::1/128"], &["2001:db8::/127"]),
+            (
+                &["2001:db8::1/128", "2001:db8::2/128"],
+                &["2001:db8::1/128", "2001:db8::2/128"],
+            ),
+            (
+                &[
+                    "2001:db8:8000::/33",
+                    "10.0.0.128/25",
+                    "2001:db8::/33",
+                    "10.0.0.0/25",
+                ],
+                &["10.0.0.0/24", "2001:db8::/32"],
+            ),
+            // Same bits in both families never merge
+            (&["0.0.0.0/1", "::/1"], &["0.0.0.0/1", "::/1"]),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(summarize(&nets(input)), nets(expected), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn covering_supernet_is_longest_common_prefix() {
+        let cases: &[(&[&str], Option<&str>)] = &[
+            (&[], None),
+            (&["10.0.0.5/32"], Some("10.0.0.5/32")),
+            (&["10.0.0.5/24"], Some("10.0.0.0/24")),
+            (&["10.0.0.0/24", "10.0.3.0/24"], Some("10.0.0.0/22")),
+            (
+                &["192.168.1.0/24", "192.168.1.128/25"],
+                Some("192.168.1.0/24"),
+            ),
+            (&["0.0.0.0/1", "128.0.0.0/1"], Some("0.0.0.0/0")),
+            (&["0.0.0.0/32", "255.255.255.255/32"], Some("0.0.0.0/0")),
+            (&["::/128", "8000::/128"], Some("::/0")),
+            (
+                &["2001:db8::/128", "2001:db8::1/128"],
+                Some("2001:db8::/127"),
+            ),
+            (&["2001:db8::/48", "2001:db8:1::/48"], Some("2001:db8::/47")),
+            (&["10.0.0.0/24", "2001:db8::/32"], None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                covering_supernet(&nets(input)),
+                expected.map(|expected| expected.parse().unwrap()),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn reverse_zones_per_label_boundary() {
+        let cases: &[(&str, usize, &[&str], u128)] = &[
+            ("192.168.1.77/24", 16, &["1.168.192.in-addr.arpa"], 1),
+            ("10.0.0.0/8", 16, &["10.in-addr.arpa"], 1),
+            (
+                "10.0.0.0/22",
+                16,
+                &[
+                    "0.0.10.in-addr.arpa",
+                    "1.0.10.in-addr.arpa",
+                    "2.0.10.in-addr.arpa",
+                    "3.0.10.in-addr.arpa",
+                ],
+                4,
+            ),
+            (
+                "10.0.0.0/22",
+                2,
+                &["0.0.10.in-addr.arpa", "1.0.10.in-addr.arpa"],
+                4,
+            ),
+            ("192.0.2.64/26", 16, &["64/26.2.0.192.in-addr.arpa"], 1),
+            ("192.0.2.1/32", 16, &["1.2.0.192.in-addr.arpa"], 1),
+            ("0.0.0.0/0", 16, &["in-addr.arpa"], 1),
+            ("2001:db8::/32", 16, &["8.b.d.0.1.0.0.2.ip6.arpa"], 1),
+            (
+                "2001:db8::/30",
+                16,
+                &[
+                    "8.b.d.0.1.0.0.2.ip6.arpa",
+                    "9.b.d.0.1.0.0.2.ip6.arpa",
+                    "a.b.d.0.1.0.0.2.ip6.arpa",
+                    "b.b.d.0.1.0.0.2.ip6.arpa",
+                ],
+                4,
+            ),
+            (
+                "2001:db8::1/128",
+                16,
+                &["1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"],
+                1,
+            ),
+            ("::/0", 16, &["ip6.arpa"], 1),
+            ("::/1", 3, &["0.ip6.arpa", "1.ip6.arpa", "2.ip6.arpa"], 8),
+        ];
+
+        for &(input, limit, zones, count) in cases {
+            let network: IpNetwork = input.parse().unwrap();
+            assert_eq!(
+                reverse_zones(&network, limit),
+                (zones.iter().map(|zone| zone.to_string()).collect(), count),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn split_counts() {
+        let cases: &[(&str, u8, Option<u128>)] = &[
+            ("10.0.0.0/24", 26, Some(4)),
+            ("10.0.0.0/24", 24, Some(1)),
+            ("10.0.0.0/24", 23, Some(0)),
+            ("10.0.0.0/8", 33, Some(0)),
+            ("0.0.0.0/0", 32, Some(1 << 32)),
+            ("2001:db8::/32", 48, Some(65536)),
+            ("2001:db8::/48", 64, Some(65536)),
+            ("2001:db8::/64", 128, Some(1 << 64)),
+            ("::/0", 127, Some(1 << 127)),
+            // 2^128 doesn't fit
+            ("::/0", 128, None),
+            ("::/0", 129, Some(0)),
+        ];
+
+        for &(input, prefix, count) in cases {
+            let network: IpNetwork = input.parse().unwrap();
+            assert_eq!(
+                network.subnet_count(prefix),
+                count,
+                "{} into /{}",
+                input,
+                prefix
+            );
+        }
+
+        let all: IpNetwork = "::/0".parse().unwrap();
+        assert_eq!(all.subnet(128, 0), Some("::/128".parse().unwrap()));
+        assert_eq!(
+            all.subnet(128, u128::MAX),
+            Some(
+                "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128"
+                    .parse()
+                    .unwrap()
+            )
+        );
+        assert_eq!(all.subnet(0, 0), Some(all));
+        assert_eq!(all.subnet(0, 1), None);
+
+        let site: IpNetwork = "2001:db8::/48".parse().unwrap();
+        assert_eq!(
+            site.subnet(64, 1),
+            Some("2001:db8:0:1::/64".parse().unwrap())
+        );
+        assert_eq!(
+            site.subnet(64, 65535),
+            Some("2001:db8:0:ffff::/64".parse().unwrap())
+        );
+        assert_eq!(site.subnet(64, 65536), None);
+    }
+
+    #[test]
+    fn host_counts() {
+        let cases: &[(&str, Option<u128>)] = &[
+            ("10.0.0.0/24", Some(254)),
+            ("10.0.0.0/30", Some(2)),
+            ("10.0.0.0/31", Some(2)),
+            ("10.0.0.1/32", Some(1)),
+            ("0.0.0.0/0", Some((1 << 32) - 2)),
+            ("2001:db8::/64", Some(1 << 64)),
+            ("2001:db8::1/128", Some(1)),
+            ("::/1", Some(1 << 127)),
+            ("::/0", None),
+        ];
+
+        for &(input, count) in cases {
+            let network: IpNetwork = input.parse().unwrap();
+            assert_eq!(network.host_count(), count, "{}", input);
+        }
+    }
+}
@@ -1,275 +1,593 @@
+use crate::dhcp::{DhcpClient, DhcpOffer};
+use crate::helper::{self, HelperRequest};
+use crate::models::{
+    BondMode, DnsConfiguration, InterfaceStatistics, IpNetwork, Ipv6Setting, MacAddr,
+    NetworkInterface, RouteEntry, VlanProtocol, WifiSecurity, WireGuardInfo, WireGuardPeerConfig,
+};
+use crate::namespace::Namespace;
+use crate::network;
+use crate::wifi::WifiNetwork;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::net::{IpAddr, Ipv6Addr};
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+/// Errors from sudo that the UI needs to react to instead of just reporting
+#[derive(Debug, thiserror::Error)]
+pub enum SudoError {
+    #[error("sudo requires a password")]
+    PasswordRequired,
+    #[error("incorrect sudo password")]
+    IncorrectPassword,
+}
+
+/// How privileged commands get their elevated rights
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivilegeStrategy {
+    Sudo,
+    Doas,
+    Pkexec,
+    /// Already running with euid 0, commands are executed directly
+    Root,
+    None,
+}
+
+impl PrivilegeStrategy {
+    /// Pick the first usable strategy for this process
+    pub fn detect() -> Self {
+        if nix::unistd::geteuid().is_root() {
+            PrivilegeStrategy::Root
+        } else if find_in_path("sudo") {
+            PrivilegeStrategy::Sudo
+        } else if find_in_path("doas") {
+            PrivilegeStrategy::Doas
+        } else if find_in_path("pkexec") {
+            PrivilegeStrategy::Pkexec
+        } else {
+            PrivilegeStrategy::None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrivilegeStrategy::Sudo => "sudo",
+            PrivilegeStrategy::Doas => "doas",
+            PrivilegeStrategy::Pkexec => "pkexec (polkit)",
+            PrivilegeStrategy::Root => "root (no escalation)",
+            PrivilegeStrategy::None => "none",
+        }
+    }
+
+    /// Only sudo can take a password on stdin; doas and pkexec talk to the
+    /// terminal or a polkit agent themselves.
+    pub fn supports_password_prompt(&self) -> bool {
+        matches!(self, PrivilegeStrategy::Sudo)
+    }
+
+    fn command(&self, command: &str, args: &[&str]) -> Result<Command> {
+        let mut cmd = match self {
+            PrivilegeStrategy::Sudo => {
+                let mut cmd = Command::new("sudo");
+                cmd.arg("-n").arg(command); // Never prompt, the TUI owns the terminal
+                cmd
+            }
+            PrivilegeStrategy::Doas => {
+                let mut cmd = Command::new("doas");
+                cmd.arg("-n").arg(command);
+                cmd
+            }
+            PrivilegeStrategy::Pkexec => {
+                let mut cmd = Command::new("pkexec");
+                cmd.arg(command);
+                cmd
+            }
+            PrivilegeStrategy::Root => Command::new(command),
+            PrivilegeStrategy::None => anyhow::bail!(
+                "No privilege escalation available: install sudo, doas or pkexec, or run as root"
+            ),
+        };
+        cmd.args(args);
+        Ok(cmd)
+    }
+}
+
+static STRATEGY: OnceLock<PrivilegeStrategy> = OnceLock::new();
+
+/// Select the privilege strategy, preferring the configured one over detection.
+///
+/// A configured `root` is refused unless the process really has euid 0,
+/// otherwise every command would run unprivileged and fail one by one.
+pub fn init(configured: Option<PrivilegeStrategy>) -> Result<PrivilegeStrategy> {
+    if configured == Some(PrivilegeStrategy::Root) && !nix::unistd::geteuid().is_root() {
+        anyhow::bail!(
+            "privilege = \"root\" is configured but netutil-tui is not running as root; \
+             start it as root or choose sudo, doas or pkexec"
+        );
+    }
+    Ok(*STRATEGY.get_or_init(|| configured.unwrap_or_else(PrivilegeStrategy::detect)))
+}
+
+pub fn strategy() -> PrivilegeStrategy {
+    *STRATEGY.get_or_init(PrivilegeStrategy::detect)
+}
+
+pub fn find_in_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+/// Build a command that runs `command` with elevated privileges.
+///
+/// sudo and doas are invoked non-interactively so they never write to the
+/// TUI's terminal; use `escalation_error` to interpret their stderr.
+pub fn privileged_command(command: &str, args: &[&str]) -> Result<Command> {
+    strategy().command(command, args)
+}
+
+/// Turn the stderr of a failed escalation into an error the UI understands.
+///
+/// Yields `SudoError::PasswordRequired` when sudo has no cached credentials,
+/// so the caller can ask for the password in the TUI.
+pub fn escalation_error(stderr: &str) -> anyhow::Error {
+    match strategy() {
+        PrivilegeStrategy::Sudo if is_password_prompt_error(stderr) => {
+            SudoError::PasswordRequired.into()
+        }
+        PrivilegeStrategy::Doas if is_password_prompt_error(stderr) => anyhow::anyhow!(
+            "doas needs a password; add 'persist' or 'nopass' for this user in doas.conf"
+        ),
+        strategy => anyhow::anyhow!("{} failed: {}", strategy.as_str(), stderr.trim()),
+    }
+}
 
-/// Execute a command with sudo, prompting for password if needed
-pub fn execute_with_sudo(command: &str, args: &[&str]) -> Result<String> {
-    let child = Command::new("sudo")
-        .arg("-S") // Read password from stdin
-        .arg(command)
-        .args(args)
+fn is_password_prompt_error(stderr: &str) -> bool {
+    stderr.contains("a password is required")
+        || stderr.contains("a terminal is required")
+        || stderr.contains("Authentication failed")
+}
+
+/// Check whether privileged commands can run without asking for a password
+pub fn has_cached_credentials() -> bool {
+    if helper::is_running() || !strategy().supports_password_prompt() {
+        return true;
+    }
+
+    Command::new("sudo")
+        .args(["-n", "-v"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Validate the password with sudo, refreshing its timestamp on success.
+///
+/// The password is written to sudo's stdin and the copy made here is wiped
+/// before returning.
+pub fn authenticate(password: &str) -> Result<()> {
+    let mut child = Command::new("sudo")
+        .args(["-S", "-v", "-p", ""])
         .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
+        .stdout(Stdio::null())
         .stderr(Stdio::piped())
         .spawn()
-        .context("Failed to spawn sudo command")?;
+        .context("Failed to spawn sudo")?;
+
+    {
+        let mut input = Zeroizing::new(Vec::with_capacity(password.len() + 1));
+        input.extend_from_slice(password.as_bytes());
+        input.push(b'\n');
+
+        let mut stdin = child.stdin.take().context("Failed to open sudo stdin")?;
+        // sudo may exit before reading everything, e.g. when no password is needed
+        let _ = stdin.write_all(&input);
+    }
 
-    // Wait for command to complete
     let output = child
         .wait_with_output()
-        .context("Failed to wait for command")?;
+        .context("Failed to wait for sudo")?;
 
     if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        Ok(())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Command failed: {}", stderr)
+        if stderr.contains("incorrect password") || stderr.contains("Sorry, try again") {
+            return Err(SudoError::IncorrectPassword.into());
+        }
+        anyhow::bail!("sudo: {}", stderr.trim())
     }
 }
 
 /// Set interface to use DHCP
-#[cfg(target_os = "macos")]
 pub fn set_dhcp(interface: &str) -> Result<()> {
-    execute_with_sudo("networksetup", &["-setdhcp", interface])?;
-    Ok(())
-}
-
-#[cfg(target_os = "linux")]
-pub fn set_dhcp(interface: &str) -> Result<()> {
-    // First, remove any static IP
-    let _ = execute_with_sudo("ip", &["addr", "flush", "dev", interface]);
-
-    // Then start DHCP client (try dhclient first, then dhcpcd)
-    if execute_with_sudo("dhclient", &[interface]).is_err() {
-        execute_with_sudo("dhcpcd", &[interface])?;
-    }
-
-    Ok(())
+    helper::call(HelperRequest::StartDhcp {
+        interface: interface.to_string(),
+    })
 }
 
-/// Set static IP address
-#[cfg(target_os = "macos")]
+/// Add a static IP address, optionally under an alias label
 pub fn set_static_ip(
     interface: &str,
     ip: &str,
     netmask: &str,
     gateway: Option<&str>,
+    label: Option<&str>,
 ) -> Result<()> {
+    let address: IpAddr = ip
+        .parse()
+        .with_context(|| format!("Invalid IP address '{}'", ip))?;
+    let mask: IpAddr = netmask
+        .parse()
+        .with_context(|| format!("Invalid netmask '{}'", netmask))?;
+    let prefix = IpNetwork::from_netmask(address, mask)
+        .map(|network| network.prefix())
+        .filter(|&prefix| prefix > 0)
+        .with_context(|| format!("{} is not a valid netmask for {}", netmask, address))?;
+
+    helper::call(HelperRequest::SetAddress {
+        interface: interface.to_string(),
+        address,
+        prefix,
+        label: label.map(|label| label.to_string()),
+    })?;
+
+    // Bring interface up
+    set_interface_status(interface, true)?;
+
+    // Set gateway if provided
     if let Some(gw) = gateway {
-        execute_with_sudo("networksetup", &["-setmanual", interface, ip, netmask, gw])?;
-    } else {
-        execute_with_sudo("ifconfig", &[interface, ip, "netmask", netmask])?;
+        let gateway: IpAddr = gw
+            .parse()
+            .with_context(|| format!("Invalid gateway '{}'", gw))?;
+        helper::call(HelperRequest::SetRoute {
+            interface: interface.to_string(),
+            gateway,
+        })?;
     }
+
     Ok(())
 }
 
-#[cfg(target_os = "linux")]
-pub fn set_static_ip(
-    interface: &str,
-    ip: &str,
-    netmask: &str,
-    gateway: Option<&str>,
-) -> Result<()> {
-    // Calculate CIDR prefix from netmask
-    let prefix = netmask_to_cidr(netmask)?;
-    let ip_with_prefix = format!("{}/{}", ip, prefix);
+/// Remove a single address, leaving the others in place
+pub fn remove_address(interface: &str, address: IpAddr, prefix: u8) -> Result<()> {
+    helper::call(HelperRequest::DelAddress {
+        interface: interface.to_string(),
+        address,
+        prefix,
+    })
+}
 
-    // Set IP address
-    execute_with_sudo("ip", &["addr", "add", &ip_with_prefix, "dev", interface])?;
+/// Make `address` the primary IPv4 address of its subnet.
+///
+/// The kernel keeps the first address added to a subnet as primary, so the
+/// subnet's addresses are removed and re-added with `address` first. Default
+/// routes that disappear along with them are restored afterwards.
+pub fn make_primary(interface: &str, address: IpAddr, prefix: u8) -> Result<()> {
+    let iface = network::get_network_interfaces()?
+        .into_iter()
+        .find(|iface| iface.name == interface)
+        .with_context(|| format!("Interface {} not found", interface))?;
+    let gateways = network::get_default_gateways(interface)?;
+
+    let subnet = IpNetwork::new(address, prefix).map_err(anyhow::Error::msg)?;
+    let mut group: Vec<_> = iface
+        .ip_addresses
+        .into_iter()
+        .filter(|addr| addr.network().map(|network| network.trunc()) == Some(subnet.trunc()))
+        .collect();
+
+    if !group.iter().any(|addr| addr.ip == address) {
+        anyhow::bail!("{} is not assigned to {}", address, interface);
+    }
 
-    // Bring interface up
-    execute_with_sudo("ip", &["link", "set", interface, "up"])?;
+    // Secondaries first, so removing the primary doesn't take them along
+    for addr in group.iter().rev() {
+        remove_address(interface, addr.ip, prefix)?;
+    }
 
-    // Set gateway if provided
-    if let Some(gw) = gateway {
-        execute_with_sudo(
-            "ip",
-            &["route", "add", "default", "via", gw, "dev", interface],
-        )?;
+    group.sort_by_key(|addr| addr.ip != address);
+    for addr in group {
+        helper::call(HelperRequest::SetAddress {
+            interface: interface.to_string(),
+            address: addr.ip,
+            prefix,
+            label: addr.label,
+        })?;
+    }
+
+    let remaining = network::get_default_gateways(interface)?;
+    for gateway in gateways.into_iter().filter(|gw| !remaining.contains(gw)) {
+        helper::call(HelperRequest::SetRoute {
+            interface: interface.to_string(),
+            gateway,
+        })?;
     }
 
     Ok(())
 }
 
-#[cfg(target_os = "linux")]
-fn netmask_to_cidr(netmask: &str) -> Result<u8> {
-    let ip: std::net::Ipv4Addr = netmask.parse()?;
-    let octets = ip.octets();
-    let mut cidr = 0u8;
+/// Set DNS servers and search domains
+pub fn set_dns(interface: &str, servers: &[&str], domains: &[&str]) -> Result<()> {
+    let servers = servers
+        .iter()
+        .map(|server| {
+            server
+                .parse()
+                .with_context(|| format!("Invalid DNS server '{}'", server))
+        })
+        .collect::<Result<Vec<IpAddr>>>()?;
+
+    helper::call(HelperRequest::SetDns {
+        interface: interface.to_string(),
+        servers,
+        search_domains: domains.iter().map(|d| d.to_string()).collect(),
+    })
+}
 
-    for octet in &octets {
-        cidr += octet.count_ones() as u8;
-    }
+/// Enable or disable interface
+pub fn set_interface_status(interface: &str, enabled: bool) -> Result<()> {
+    helper::call(HelperRequest::SetLink {
+        interface: interface.to_string(),
+        up: enabled,
+    })
+}
+
+/// Disable IPv6 on an interface
+pub fn disable_ipv6(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::SetIpv6 {
+        interface: interface.to_string(),
+        enabled: false,
+    })
+}
 
-    Ok(cidr)
+/// Enable IPv6 on an interface
+pub fn enable_ipv6(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::SetIpv6 {
+        interface: interface.to_string(),
+        enabled: true,
+    })
 }
 
-/// Set DNS servers
-#[cfg(target_os = "macos")]
-pub fn set_dns_servers(interface: &str, servers: &[&str]) -> Result<()> {
-    let mut args = vec!["-setdnsservers", interface];
-    args.extend(servers);
-    execute_with_sudo("networksetup", &args)?;
-    Ok(())
+/// Set static IPv6 address
+pub fn set_static_ipv6(interface: &str, ipv6: &str, prefix: u8) -> Result<()> {
+    let address: Ipv6Addr = ipv6
+        .parse()
+        .with_context(|| format!("Invalid IPv6 address '{}'", ipv6))?;
+
+    helper::call(HelperRequest::SetAddress {
+        interface: interface.to_string(),
+        address: IpAddr::V6(address),
+        prefix,
+        label: None,
+    })
 }
 
-#[cfg(target_os = "linux")]
-pub fn set_dns_servers(_interface: &str, servers: &[&str]) -> Result<()> {
-    // Create resolv.conf content
-    let mut content = String::new();
-    for server in servers {
-        content.push_str(&format!("nameserver {}\n", server));
-    }
+/// Change one of the per-interface IPv6 sysctls
+pub fn set_ipv6_setting(interface: &str, setting: Ipv6Setting) -> Result<()> {
+    helper::call(HelperRequest::SetIpv6Setting {
+        interface: interface.to_string(),
+        setting,
+    })
+}
 
-    // Write to a temporary file
-    std::fs::write("/tmp/resolv.conf.new", &content)?;
+/// Add an IPv6 default route through `gateway`
+pub fn set_ipv6_gateway(interface: &str, gateway: Ipv6Addr) -> Result<()> {
+    helper::call(HelperRequest::SetRoute {
+        interface: interface.to_string(),
+        gateway: IpAddr::V6(gateway),
+    })
+}
 
-    // Move to /etc/resolv.conf with sudo
-    execute_with_sudo("cp", &["/tmp/resolv.conf.new", "/etc/resolv.conf"])?;
+/// Start a DHCPv6 client on an interface
+pub fn start_dhcpv6(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::StartDhcpv6 {
+        interface: interface.to_string(),
+    })
+}
 
-    Ok(())
+/// Renew the DHCP lease held by `client`
+pub fn renew_dhcp(interface: &str, client: DhcpClient) -> Result<()> {
+    helper::call(HelperRequest::RenewDhcp {
+        interface: interface.to_string(),
+        client,
+    })
 }
 
-/// Set DNS search domains
-#[cfg(target_os = "macos")]
-pub fn set_search_domains(interface: &str, domains: &[&str]) -> Result<()> {
-    let mut args = vec!["-setsearchdomains", interface];
-    args.extend(domains);
-    execute_with_sudo("networksetup", &args)?;
-    Ok(())
+/// Give the DHCP lease held by `client` back to the server
+pub fn release_dhcp(interface: &str, client: DhcpClient) -> Result<()> {
+    helper::call(HelperRequest::ReleaseDhcp {
+        interface: interface.to_string(),
+        client,
+    })
 }
 
-#[cfg(target_os = "linux")]
-pub fn set_search_domains(_interface: &str, domains: &[&str]) -> Result<()> {
-    // Read existing resolv.conf
-    let existing = std::fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+/// Ask every DHCP server on the segment for an offer, without taking a lease
+pub fn probe_dhcp(interface: &str, timeout: std::time::Duration) -> Result<Vec<DhcpOffer>> {
+    helper::query(HelperRequest::ProbeDhcp {
+        interface: interface.to_string(),
+        timeout_ms: timeout.as_millis() as u64,
+    })
+}
 
-    // Create new content with search domains
-    let mut content = String::new();
+pub fn scan_wifi(interface: &str) -> Result<Vec<WifiNetwork>> {
+    helper::query(HelperRequest::ScanWifi {
+        interface: interface.to_string(),
+    })
+}
 
-    // Add nameservers from existing config
-    for line in existing.lines() {
-        if line.starts_with("nameserver") {
-            content.push_str(line);
-            content.push('\n');
-        }
-    }
+/// `password` is only needed for networks without a saved profile
+pub fn connect_wifi(
+    interface: &str,
+    ssid: &str,
+    security: WifiSecurity,
+    password: Option<&str>,
+) -> Result<()> {
+    helper::call(HelperRequest::ConnectWifi {
+        interface: interface.to_string(),
+        ssid: ssid.to_string(),
+        security,
+        password: password.map(|password| password.to_string()),
+    })
+}
 
-    // Add search domains
-    if !domains.is_empty() {
-        content.push_str("search ");
-        content.push_str(&domains.join(" "));
-        content.push('\n');
-    }
+pub fn disconnect_wifi(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::DisconnectWifi {
+        interface: interface.to_string(),
+    })
+}
 
-    // Write to temporary file and move
-    std::fs::write("/tmp/resolv.conf.new", &content)?;
-    execute_with_sudo("cp", &["/tmp/resolv.conf.new", "/etc/resolv.conf"])?;
+pub fn forget_wifi(interface: &str, ssid: &str) -> Result<()> {
+    helper::call(HelperRequest::ForgetWifi {
+        interface: interface.to_string(),
+        ssid: ssid.to_string(),
+    })
+}
 
-    Ok(())
+pub fn create_vlan(
+    parent: &str,
+    name: &str,
+    id: u16,
+    protocol: VlanProtocol,
+    mtu: Option<u32>,
+) -> Result<()> {
+    helper::call(HelperRequest::CreateVlan {
+        parent: parent.to_string(),
+        name: name.to_string(),
+        id,
+        protocol,
+        mtu,
+    })
 }
 
-/// Enable or disable interface
-pub fn set_interface_status(interface: &str, enabled: bool) -> Result<()> {
-    #[cfg(target_os = "macos")]
-    {
-        let status = if enabled { "up" } else { "down" };
-        execute_with_sudo("ifconfig", &[interface, status])?;
-    }
+pub fn delete_vlan(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::DeleteVlan {
+        interface: interface.to_string(),
+    })
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let status = if enabled { "up" } else { "down" };
-        execute_with_sudo("ip", &["link", "set", interface, status])?;
-    }
+pub fn create_bond(name: &str, mode: BondMode, miimon: Option<u32>) -> Result<()> {
+    helper::call(HelperRequest::CreateBond {
+        name: name.to_string(),
+        mode,
+        miimon,
+    })
+}
 
-    Ok(())
+pub fn delete_bond(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::DeleteBond {
+        interface: interface.to_string(),
+    })
 }
 
-/// Disable IPv6 on an interface
-#[cfg(target_os = "linux")]
-pub fn disable_ipv6(interface: &str) -> Result<()> {
-    // Check if IPv6 is available for this interface
-    let sysctl_path = format!("/proc/sys/net/ipv6/conf/{}/disable_ipv6", interface);
-    if !std::path::Path::new(&sysctl_path).exists() {
-        anyhow::bail!("IPv6 is not available on this interface (kernel module may be disabled)");
-    }
+pub fn create_bridge(name: &str, stp: bool) -> Result<()> {
+    helper::call(HelperRequest::CreateBridge {
+        name: name.to_string(),
+        stp,
+    })
+}
 
-    let sysctl_key = format!("net.ipv6.conf.{}.disable_ipv6", interface);
-    execute_with_sudo("sysctl", &["-w", &format!("{}=1", sysctl_key)])?;
-    Ok(())
+pub fn delete_bridge(interface: &str) -> Result<()> {
+    helper::call(HelperRequest::DeleteBridge {
+        interface: interface.to_string(),
+    })
 }
 
-#[cfg(target_os = "macos")]
-pub fn disable_ipv6(interface: &str) -> Result<()> {
-    execute_with_sudo("networksetup", &["-setv6off", interface])?;
-    Ok(())
+pub fn set_bridge_stp(bridge: &str, enabled: bool) -> Result<()> {
+    helper::call(HelperRequest::SetBridgeStp {
+        bridge: bridge.to_string(),
+        enabled,
+    })
 }
 
-/// Enable IPv6 on an interface
-#[cfg(target_os = "linux")]
-pub fn enable_ipv6(interface: &str) -> Result<()> {
-    // Check if IPv6 is available for this interface
-    let sysctl_path = format!("/proc/sys/net/ipv6/conf/{}/disable_ipv6", interface);
-    if !std::path::Path::new(&sysctl_path).exists() {
-        anyhow::bail!("IPv6 is not available on this interface (kernel module may be disabled)");
-    }
+pub fn set_mac(interface: &str, mac: MacAddr) -> Result<()> {
+    helper::call(HelperRequest::SetMac {
+        interface: interface.to_string(),
+        mac,
+    })
+}
 
-    let sysctl_key = format!("net.ipv6.conf.{}.disable_ipv6", interface);
-    execute_with_sudo("sysctl", &["-w", &format!("{}=0", sysctl_key)])?;
-    Ok(())
+pub fn set_offload(interface: &str, feature: &str, enabled: bool) -> Result<()> {
+    helper::call(HelperRequest::SetOffload {
+        interface: interface.to_string(),
+        feature: feature.to_string(),
+        enabled,
+    })
 }
 
-#[cfg(target_os = "macos")]
-pub fn enable_ipv6(interface: &str) -> Result<()> {
-    execute_with_sudo("networksetup", &["-setv6automatic", interface])?;
-    Ok(())
+pub fn enslave_interface(master: &str, interface: &str) -> Result<()> {
+    helper::call(HelperRequest::EnslaveInterface {
+        master: master.to_string(),
+        interface: interface.to_string(),
+    })
 }
 
-/// Set static IPv6 address
-#[cfg(target_os = "linux")]
-pub fn set_static_ipv6(interface: &str, ipv6: &str, prefix: u8) -> Result<()> {
-    let addr = format!("{}/{}", ipv6, prefix);
-    execute_with_sudo("ip", &["-6", "addr", "add", &addr, "dev", interface])?;
-    Ok(())
+pub fn release_interface(master: &str, interface: &str) -> Result<()> {
+    helper::call(HelperRequest::ReleaseInterface {
+        master: master.to_string(),
+        interface: interface.to_string(),
+    })
 }
 
-#[cfg(target_os = "macos")]
-pub fn set_static_ipv6(interface: &str, ipv6: &str, prefix: u8) -> Result<()> {
-    let addr = format!("{}/{}", ipv6, prefix);
-    execute_with_sudo("ifconfig", &[interface, "inet6", &addr])?;
-    Ok(())
+pub fn set_active_slave(bond: &str, interface: &str) -> Result<()> {
+    helper::call(HelperRequest::SetActiveSlave {
+        bond: bond.to_string(),
+        interface: interface.to_string(),
+    })
 }
 
-/// Flush DNS cache on macOS
-#[cfg(target_os = "macos")]
-pub fn flush_dns_cache() -> Result<()> {
-    execute_with_sudo("dscacheutil", &["-flushcache"])?;
-    execute_with_sudo("killall", &["-HUP", "mDNSResponder"])?;
-    Ok(())
+/// Read a WireGuard device as root; its keys and peers are hidden from users
+pub fn get_wireguard(interface: &str) -> Result<WireGuardInfo> {
+    helper::query(HelperRequest::GetWireGuard {
+        interface: interface.to_string(),
+    })
 }
 
-/// Flush DNS cache on Linux
-#[cfg(target_os = "linux")]
-pub fn flush_dns_cache() -> Result<()> {
-    // Try systemd-resolved first (most common on modern Linux)
-    if execute_with_sudo("resolvectl", &["flush-caches"]).is_ok() {
-        return Ok(());
-    }
+pub fn add_wireguard_peer(interface: &str, peer: &WireGuardPeerConfig) -> Result<()> {
+    helper::call(HelperRequest::AddWireGuardPeer {
+        interface: interface.to_string(),
+        peer: peer.clone(),
+    })
+}
 
-    // Try systemd-resolve (older systemd versions)
-    if execute_with_sudo("systemd-resolve", &["--flush-caches"]).is_ok() {
-        return Ok(());
-    }
+pub fn remove_wireguard_peer(interface: &str, public_key: &str) -> Result<()> {
+    helper::call(HelperRequest::RemoveWireGuardPeer {
+        interface: interface.to_string(),
+        public_key: public_key.to_string(),
+    })
+}
 
-    // Try nscd (Name Service Cache Daemon)
-    if execute_with_sudo("nscd", &["-i", "hosts"]).is_ok() {
-        return Ok(());
-    }
+/// Read the interfaces of the selected namespace as root, who may enter it
+pub fn get_interfaces() -> Result<Vec<NetworkInterface>> {
+    helper::query(HelperRequest::GetInterfaces)
+}
 
-    // Try dnsmasq
-    if execute_with_sudo("killall", &["-HUP", "dnsmasq"]).is_ok() {
-        return Ok(());
-    }
+pub fn get_dns_configuration() -> Result<DnsConfiguration> {
+    helper::query(HelperRequest::GetDnsConfiguration)
+}
 
-    anyhow::bail!("Could not flush DNS cache. No supported DNS caching service found.")
+pub fn get_routes() -> Result<Vec<RouteEntry>> {
+    helper::query(HelperRequest::GetRoutes)
+}
+
+pub fn get_statistics(interface: &str) -> Result<InterfaceStatistics> {
+    helper::query(HelperRequest::GetStatistics {
+        interface: interface.to_string(),
+    })
+}
+
+/// Every namespace, including those of processes we may not inspect
+pub fn list_namespaces() -> Result<Vec<Namespace>> {
+    helper::query(HelperRequest::ListNamespaces)
+}
+
+/// Flush the system DNS cache
+pub fn flush_dns_cache() -> Result<()> {
+    helper::call(HelperRequest::FlushDnsCache)
 }
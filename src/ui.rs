@@ -1,5 +1,16 @@
-use crate::app::{App, AppMode};
-use crate::models::IpConfigMode;
+use crate::app::{App, AppMode, CounterRow};
+use crate::audit::StateSnapshot;
+use crate::bridge::{self, FdbKind};
+use crate::conflicts::ConflictKind;
+use crate::dhcp::DhcpLease;
+use crate::models::{
+    format_speed, is_error_counter, BondMode, EthtoolInfo, InterfaceType, IpConfigMode, IpNetwork,
+    LinkCounters, MacAddr, NetworkInterface, WireGuardInfo, WirelessInfo, WIREGUARD_STALE_AFTER,
+};
+use crate::namespace;
+use crate::subnet;
+use crate::sudo::PrivilegeStrategy;
+use crate::validation;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -7,6 +18,7 @@ use ratatui::{
     widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, Wrap},
     Frame,
 };
+use std::net::{IpAddr, Ipv4Addr};
 
 pub fn draw(f: &mut Frame, app: &App) {
     match app.mode {
@@ -15,8 +27,28 @@ pub fn draw(f: &mut Frame, app: &App) {
         AppMode::EditIp => draw_edit_ip_screen(f, app),
         AppMode::EditDns => draw_edit_dns_screen(f, app),
         AppMode::EditIpv6 => draw_edit_ipv6_screen(f, app),
+        AppMode::EditVlan => draw_edit_vlan_screen(f, app),
+        AppMode::EditMac => draw_edit_mac_dialog(f, app),
         AppMode::ConfirmDialog => draw_confirm_dialog(f, app),
+        AppMode::SudoPassword => draw_password_dialog(f, app),
         AppMode::Terminal => draw_terminal_screen(f, app),
+        AppMode::History => draw_history_screen(f, app),
+        AppMode::DhcpOffers => draw_dhcp_offers_screen(f, app),
+        AppMode::Statistics => draw_statistics_screen(f, app),
+        AppMode::Wifi => draw_wifi_screen(f, app),
+        AppMode::WifiPassword => draw_wifi_password_dialog(f, app),
+        AppMode::Bonds => draw_bonds_screen(f, app),
+        AppMode::CreateBond => draw_create_bond_dialog(f, app),
+        AppMode::Bridges => draw_bridges_screen(f, app),
+        AppMode::CreateBridge => draw_create_bridge_dialog(f, app),
+        AppMode::EnslaveInterface => draw_enslave_dialog(f, app),
+        AppMode::AddWireGuardPeer => draw_add_wireguard_peer_dialog(f, app),
+        AppMode::RemoveWireGuardPeer => draw_remove_wireguard_peer_dialog(f, app),
+        AppMode::WireGuardKeys => draw_wireguard_keys_dialog(f, app),
+        AppMode::Namespaces => draw_namespaces_dialog(f, app),
+        AppMode::Offloads => draw_offloads_dialog(f, app),
+        AppMode::SubnetCalculator => draw_subnet_calculator(f, app),
+        AppMode::Conflicts => draw_conflicts_screen(f, app),
         _ => draw_main_screen(f, app),
     }
 }
@@ -39,20 +71,31 @@ fn draw_main_screen(f: &mut Frame, app: &App) {
 }
 
 fn draw_title(f: &mut Frame, area: Rect) {
-    let title = Paragraph::new("NetUtil - Network Interface Manager")
-        .style(
+    let netns_style = if namespace::selected().is_some() {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    let title = Paragraph::new(Line::from(vec![
+        Span::styled(
+            "NetUtil - Network Interface Manager",
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        ),
+        Span::styled(
+            format!("  [netns: {} ▾ n]", namespace::selected_label()),
+            netns_style,
+        ),
+    ]))
+    .alignment(Alignment::Center)
+    .block(Block::default().borders(Borders::ALL));
 
     f.render_widget(title, area);
 }
 
 fn draw_table(f: &mut Frame, area: Rect, app: &App) {
-    let header_cells = [
+    let mut columns = vec![
         "Interface",
         "Type",
         "IP Address",
@@ -60,24 +103,17 @@ fn draw_table(f: &mut Frame, area: Rect, app: &App) {
         "Subnet Mask",
         "DNS Servers",
         "Status",
-    ]
-    .iter()
-    .enumerate()
-    .map(|(i, h)| {
+    ];
+    if app.show_speed {
+        columns.insert(6, "Speed");
+    }
+
+    let header_cells = columns.iter().map(|h| {
         let mut style = Style::default()
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD);
 
-        let col_matches = match i {
-            0 => app.sort_column.as_str() == "Interface",
-            1 => app.sort_column.as_str() == "Type",
-            2 => app.sort_column.as_str() == "IP Address",
-            3 => app.sort_column.as_str() == "MAC Address",
-            4 => app.sort_column.as_str() == "Subnet Mask",
-            5 => app.sort_column.as_str() == "DNS Servers",
-            6 => app.sort_column.as_str() == "Status",
-            _ => false,
-        };
+        let col_matches = app.sort_column.as_str() == *h;
 
         if col_matches {
             style = style.fg(Color::Green);
@@ -104,9 +140,10 @@ fn draw_table(f: &mut Frame, area: Rect, app: &App) {
         .skip(app.scroll_offset)
         .take(app.page_size)
         .enumerate()
-        .map(|(i, &row_idx)| {
+        .flat_map(|(i, &row_idx)| {
             let row = &app.table_rows[row_idx];
             let is_selected = i + app.scroll_offset == app.selected_index;
+            let expanded = app.expanded_rows.contains(&row.name);
 
             let style = if is_selected {
                 Style::default()
@@ -117,38 +154,82 @@ fn draw_table(f: &mut Frame, area: Rect, app: &App) {
                 Style::default()
             };
 
-            let cells = vec![
-                Cell::from(row.name.clone()),
+            let (name, ip_address) = match (row.extra_addresses.len(), expanded) {
+                (0, _) => (row.name.clone(), row.ip_address.clone()),
+                (_, true) => (format!("▾ {}", row.name), row.ip_address.clone()),
+                (extra, false) => (
+                    format!("▸ {}", row.name),
+                    format!("{} (+{})", row.ip_address, extra),
+                ),
+            };
+
+            let mut cells = vec![
+                Cell::from(name),
                 Cell::from(row.interface_type.clone()),
-                Cell::from(row.ip_address.clone()),
+                Cell::from(ip_address),
                 Cell::from(row.mac_address.clone()),
                 Cell::from(row.subnet_mask.clone()),
                 Cell::from(row.dns_servers.clone()),
                 Cell::from(row.status.clone()),
             ];
+            if app.show_speed {
+                let speed_cell = Cell::from(row.speed.clone());
+                cells.insert(
+                    6,
+                    match row.speed_mbps {
+                        Some(mbps) if !is_selected => speed_cell.style(speed_style(mbps)),
+                        _ => speed_cell,
+                    },
+                );
+            }
 
-            Row::new(cells).style(style).height(1)
+            let mut rows = vec![Row::new(cells).style(style).height(1)];
+            if expanded {
+                rows.extend(row.extra_addresses.iter().map(|addr| {
+                    Row::new(vec![
+                        Cell::from("  └"),
+                        Cell::from(""),
+                        Cell::from(addr.clone()),
+                    ])
+                    .style(Style::default().fg(Color::DarkGray))
+                    .height(1)
+                }));
+            }
+            rows
         });
 
-    let widths = [
-        Constraint::Percentage(12),
-        Constraint::Percentage(10),
-        Constraint::Percentage(15),
-        Constraint::Percentage(18),
-        Constraint::Percentage(15),
-        Constraint::Percentage(22),
-        Constraint::Percentage(8),
-    ];
+    let widths = if app.show_speed {
+        vec![
+            Constraint::Percentage(12),
+            Constraint::Percentage(13),
+            Constraint::Percentage(14),
+            Constraint::Percentage(17),
+            Constraint::Percentage(12),
+            Constraint::Percentage(16),
+            Constraint::Percentage(8),
+            Constraint::Percentage(8),
+        ]
+    } else {
+        vec![
+            Constraint::Percentage(12),
+            Constraint::Percentage(14),
+            Constraint::Percentage(15),
+            Constraint::Percentage(18),
+            Constraint::Percentage(13),
+            Constraint::Percentage(20),
+            Constraint::Percentage(8),
+        ]
+    };
 
     let title = if app.filtered_rows.is_empty() {
-        format!(" Network Interfaces (0/0) - No matches ")
+        " Network Interfaces (0/0) - No matches ".to_string()
     } else {
         format!(
             " Network Interfaces ({}/{}) - Page {}/{} - Press 'i' for details ",
             app.selected_index + 1,
             app.filtered_rows.len(),
             app.scroll_offset / app.page_size + 1,
-            (app.filtered_rows.len() + app.page_size - 1) / app.page_size
+            app.filtered_rows.len().div_ceil(app.page_size)
         )
     };
 
@@ -162,20 +243,27 @@ fn draw_table(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
     let text = match app.mode {
-        AppMode::Search => {
-            format!("Search: {} (Esc to cancel)", app.search_query)
-        }
+        AppMode::Search => Line::from(format!("Search: {} (Esc to cancel)", app.search_query)),
         _ => {
             if let Some(ref msg) = app.status_message {
-                msg.clone()
+                Line::from(msg.clone())
             } else {
-                format!(
+                let mut spans = vec![Span::raw(format!(
                     "Total: {} | Filtered: {} | Sort: {} {}",
                     app.table_rows.len(),
                     app.filtered_rows.len(),
                     app.sort_column.as_str(),
                     if app.sort_ascending { "▲" } else { "▼" }
-                )
+                ))];
+                // Stays in view after the warning itself has been cleared
+                if !app.conflicts.is_empty() {
+                    spans.push(Span::raw(" | "));
+                    spans.push(Span::styled(
+                        format!("⚠ {} conflict(s), O: report", app.conflicts.len()),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                Line::from(spans)
             }
         }
     };
@@ -195,7 +283,7 @@ fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
 fn draw_help_bar(f: &mut Frame, area: Rect, app: &App) {
     let help_text = match app.mode {
         AppMode::Normal => {
-            "q:Quit | ?:Help | /:Search | i:Details | x:Terminal | Ctrl+f:FlushDNS | r:Refresh | e:IP | d:DNS | 6:IPv6"
+            "q:Quit | ?:Help | /:Search | i:Details | x:Terminal | Ctrl+f:FlushDNS | r:Refresh | e:IP | d:DNS | 6:IPv6 | v:VLAN | b:Bonds | B:Bridges | w:WiFi | n:Namespace | u:Undo | H:History"
         }
         AppMode::Search => "Type to search | Esc:Cancel | Enter:Done",
         AppMode::Terminal => "Enter:Execute | ↑↓:Scroll | Ctrl+l:Clear | Esc:Back",
@@ -272,10 +360,158 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD),
                 ),
-                Span::raw(iface.mac_address.as_deref().unwrap_or("N/A")),
+                Span::raw(
+                    iface
+                        .mac_address
+                        .map(|mac| mac.to_string())
+                        .unwrap_or_else(|| "N/A".to_string()),
+                ),
+                Span::styled(
+                    match (iface.mac_address, iface.permanent_mac) {
+                        (Some(mac), Some(permanent)) if mac != permanent => {
+                            format!("  (permanent {})", permanent)
+                        }
+                        (Some(mac), _) if mac.is_locally_administered() => {
+                            "  (locally administered)".to_string()
+                        }
+                        (Some(mac), _) => format!("  (OUI {})", mac.oui_string()),
+                        _ => String::new(),
+                    },
+                    Style::default().fg(Color::DarkGray),
+                ),
             ]),
         ];
 
+        // Parent/child relationships for 802.1Q sub-interfaces
+        if let Some(ref vlan) = iface.vlan {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "VLAN: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(
+                    "id {} ({}) on {}",
+                    vlan.id,
+                    vlan.protocol.as_str(),
+                    vlan.parent
+                )),
+            ]));
+        }
+        if let Some(tool) = bridge::managed_by(&iface.name) {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Managed by: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(tool),
+            ]));
+        }
+        if let Some(ref peer) = iface.veth_peer {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "veth Peer: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(peer.describe()),
+            ]));
+        }
+        if let Some(container) = iface
+            .veth_peer
+            .as_ref()
+            .and_then(|peer| peer.container.as_ref())
+        {
+            let mut text = container.name.clone();
+            if let Some(ref image) = container.image {
+                text.push_str(&format!(" ({})", image));
+            }
+            text.push_str(&format!(
+                " - {} {}",
+                container.runtime.as_str(),
+                container.short_id()
+            ));
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Container: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(text),
+            ]));
+            if let Some(ref pod) = container.pod {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        "Pod: ",
+                        Style::default()
+                            .fg(Color::Yellow)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(pod.clone()),
+                ]));
+            }
+        }
+        if let Some(ref master) = iface.master {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Master: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(master),
+            ]));
+        }
+        let slaves: Vec<&str> = app
+            .interfaces
+            .iter()
+            .filter(|other| other.master.as_deref() == Some(iface.name.as_str()))
+            .map(|other| other.name.as_str())
+            .collect();
+        if !slaves.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Slaves: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(slaves.join(", ")),
+            ]));
+        }
+        let mut children: Vec<_> = app
+            .interfaces
+            .iter()
+            .filter_map(|other| {
+                let vlan = other
+                    .vlan
+                    .as_ref()
+                    .filter(|vlan| vlan.parent == iface.name)?;
+                Some((vlan.id, &other.name))
+            })
+            .collect();
+        if !children.is_empty() {
+            children.sort();
+            let children: Vec<String> = children
+                .iter()
+                .map(|(id, name)| format!("{} ({})", name, id))
+                .collect();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "VLANs: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(children.join(", ")),
+            ]));
+        }
+
         // Show SSID for WiFi interfaces
         if let Some(ref ssid) = iface.ssid {
             lines.push(Line::from(vec![
@@ -288,6 +524,20 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
                 Span::styled(ssid, Style::default().fg(Color::Cyan)),
             ]));
         }
+        if let Some(ref wireless) = iface.wireless {
+            lines.extend(wireless_lines(wireless));
+        }
+        if iface.interface_type == InterfaceType::WireGuard {
+            match iface.wireguard {
+                Some(ref wireguard) => {
+                    lines.extend(wireguard_lines(wireguard, chrono::Utc::now().timestamp()))
+                }
+                None => lines.push(Line::from(Span::styled(
+                    "  Keys and peers need privileges to read: press a or x to load them",
+                    Style::default().fg(Color::DarkGray),
+                ))),
+            }
+        }
 
         if let Some(mtu) = iface.mtu {
             lines.push(Line::from(vec![
@@ -300,6 +550,34 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
                 Span::raw(mtu.to_string()),
             ]));
         }
+        if let Some(ref ethtool) = iface.ethtool {
+            lines.extend(ethtool_lines(ethtool));
+        }
+        if let Some(ref counters) = iface.counters {
+            lines.extend(counter_lines(counters));
+        }
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                "IPv6: ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(if iface.ipv6_enabled {
+                "Enabled"
+            } else {
+                "Disabled"
+            }),
+        ]));
+        if let Some(settings) = iface.ipv6_settings {
+            lines.push(Line::from(format!(
+                "  Address generation: {}  Privacy extensions: {}  Accept RA: {}",
+                settings.addr_gen_mode.as_str(),
+                settings.use_tempaddr.as_str(),
+                settings.accept_ra.as_str()
+            )));
+        }
 
         lines.push(Line::from(""));
         lines.push(Line::from(vec![Span::styled(
@@ -317,7 +595,7 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
                 Span::styled(ip_str, Style::default().fg(Color::Green)),
             ]));
 
-            if let Some(ref netmask) = addr.netmask {
+            if let Some(netmask) = addr.netmask() {
                 lines.push(Line::from(vec![
                     Span::raw("     Netmask: "),
                     Span::raw(netmask.to_string()),
@@ -330,6 +608,41 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
                     Span::raw(broadcast.to_string()),
                 ]));
             }
+
+            if let Some(ref label) = addr.label {
+                lines.push(Line::from(vec![
+                    Span::raw("     Label: "),
+                    Span::raw(label.clone()),
+                ]));
+            }
+
+            if addr.secondary {
+                lines.push(Line::from("     Secondary"));
+            }
+
+            if let Some(ref info) = addr.ipv6_info {
+                let mut detail = format!("     Scope: {}", info.scope);
+                if !info.flags.is_empty() {
+                    detail.push_str(&format!("  Flags: {}", info.flags.join(", ")));
+                }
+                lines.push(Line::from(detail));
+                lines.push(Line::from(format!(
+                    "     Lifetime: preferred {}, valid {}",
+                    format_lifetime(info.preferred_lifetime),
+                    format_lifetime(info.valid_lifetime)
+                )));
+            }
+        }
+
+        if let Some((client, lease)) = &app.details_lease {
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![Span::styled(
+                format!("DHCP Lease ({}):", client.as_str()),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            lines.extend(lease_lines(lease).into_iter().map(Line::from));
         }
 
         lines.push(Line::from(""));
@@ -357,6 +670,24 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
             }
         }
 
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(
+                "Privilege Escalation: ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                app.privilege.as_str(),
+                if matches!(app.privilege, PrivilegeStrategy::None) {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                },
+            ),
+        ]));
+
         let paragraph = Paragraph::new(lines)
             .block(Block::default().borders(Borders::ALL))
             .wrap(Wrap { trim: false });
@@ -364,13 +695,20 @@ fn draw_details_screen(f: &mut Frame, app: &App) {
         f.render_widget(paragraph, chunks[1]);
     }
 
-    let help = Paragraph::new("Press Esc or q to return | Press e/d/6 to edit configuration")
-        .style(Style::default().fg(Color::DarkGray))
-        .alignment(Alignment::Center);
+    let wireguard = app
+        .get_selected_interface()
+        .is_some_and(|iface| iface.interface_type == InterfaceType::WireGuard);
+    let help = Paragraph::new(if wireguard {
+        "Press Esc or q to return | Press e/d/6 to edit configuration | a/x: add/remove peer | K: generate keypair"
+    } else {
+        "Press Esc or q to return | Press e/d/6/v to edit configuration | r/R: renew/release DHCP | p: probe DHCP servers | w: WiFi networks | o: offloads | S: statistics | C: subnet calculator"
+    })
+    .style(Style::default().fg(Color::DarkGray))
+    .alignment(Alignment::Center);
     f.render_widget(help, chunks[2]);
 }
 
-fn draw_edit_ip_screen(f: &mut Frame, app: &App) {
+fn draw_dhcp_offers_screen(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -380,7 +718,12 @@ fn draw_edit_ip_screen(f: &mut Frame, app: &App) {
         ])
         .split(f.area());
 
-    let title = Paragraph::new("Edit IP Configuration")
+    let (interface, offers) = match &app.dhcp_offers {
+        Some((interface, offers)) => (interface.as_str(), offers.as_slice()),
+        None => ("", &[][..]),
+    };
+
+    let title = Paragraph::new(format!("DHCP Servers on {}", interface))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -390,88 +733,80 @@ fn draw_edit_ip_screen(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    if let Some(iface) = app.get_selected_interface() {
-        let mode_str = format!(
-            "[{}] DHCP  [{}] Static",
-            if matches!(app.ip_edit_state.mode, IpConfigMode::DHCP) {
-                "X"
-            } else {
-                " "
-            },
-            if matches!(app.ip_edit_state.mode, IpConfigMode::Static) {
-                "X"
-            } else {
-                " "
-            }
-        );
-
-        let mut items = vec![
-            ListItem::new(Line::from(vec![
-                Span::styled("Interface: ", Style::default().fg(Color::Yellow)),
-                Span::raw(&iface.name),
-            ])),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![
-                Span::styled("Configuration Mode: ", Style::default().fg(Color::Yellow)),
-                Span::raw(&mode_str),
-            ])),
-        ];
-
-        if matches!(app.ip_edit_state.mode, IpConfigMode::Static) {
-            items.push(ListItem::new(Line::from("")));
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled("IP Address: ", Style::default().fg(Color::Yellow)),
-                Span::styled(
-                    &app.ip_edit_state.ip_buffer,
-                    if app.ip_edit_state.current_field == 1 {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
-                    },
-                ),
-            ])));
-
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled("Netmask: ", Style::default().fg(Color::Yellow)),
-                Span::styled(
-                    &app.ip_edit_state.netmask_buffer,
-                    if app.ip_edit_state.current_field == 2 {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
-                    },
-                ),
-            ])));
+    let mut servers: Vec<Ipv4Addr> = offers.iter().map(|offer| offer.server()).collect();
+    servers.sort();
+    servers.dedup();
+    let lease_server = app
+        .details_lease
+        .as_ref()
+        .and_then(|(_, lease)| lease.server);
+
+    let mut lines = Vec::new();
+    if offers.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No offers received",
+            Style::default().fg(Color::Yellow),
+        )));
+    } else {
+        lines.push(Line::from(format!(
+            "{} offer(s) from {} server(s)",
+            offers.len(),
+            servers.len()
+        )));
+    }
+    if servers.len() > 1 {
+        lines.push(Line::from(Span::styled(
+            "⚠ More than one DHCP server answered; all but one may be rogue",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
 
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled("Gateway (optional): ", Style::default().fg(Color::Yellow)),
-                Span::styled(
-                    if app.ip_edit_state.gateway_buffer.is_empty() {
-                        "None"
-                    } else {
-                        &app.ip_edit_state.gateway_buffer
-                    },
-                    if app.ip_edit_state.current_field == 3 {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
-                    },
-                ),
-            ])));
+    for offer in offers {
+        let server = offer.server();
+        let mut heading = vec![Span::styled(
+            format!("Server {}", server),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )];
+        if let Some(ref name) = offer.server_name {
+            heading.push(Span::raw(format!(" ({})", name)));
+        }
+        if lease_server == Some(server) {
+            heading.push(Span::styled(
+                "  current lease",
+                Style::default().fg(Color::Green),
+            ));
+        } else if lease_server.is_some() {
+            heading.push(Span::styled(
+                "  not the server of the current lease",
+                Style::default().fg(Color::Yellow),
+            ));
         }
 
-        let list = List::new(items).block(Block::default().borders(Borders::ALL));
-
-        f.render_widget(list, chunks[1]);
+        lines.push(Line::from(""));
+        lines.push(Line::from(heading));
+        if offer.source != server {
+            lines.push(Line::from(format!("  Sent from: {}", offer.source)));
+        }
+        if let Some(relay) = offer.relay {
+            lines.push(Line::from(format!("  Relay: {}", relay)));
+        }
+        lines.extend(lease_lines(&offer.lease).into_iter().map(Line::from));
     }
 
-    let help = Paragraph::new("Tab:Next field | Space:Toggle mode | Enter:Apply | Esc:Cancel")
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunks[1]);
+
+    let help = Paragraph::new("p: Probe again | Esc/q: Back")
         .style(Style::default().fg(Color::DarkGray))
         .alignment(Alignment::Center);
     f.render_widget(help, chunks[2]);
 }
 
-fn draw_edit_dns_screen(f: &mut Frame, app: &App) {
+fn draw_statistics_screen(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -481,7 +816,11 @@ fn draw_edit_dns_screen(f: &mut Frame, app: &App) {
         ])
         .split(f.area());
 
-    let title = Paragraph::new("Edit DNS Configuration")
+    let Some(view) = &app.statistics else {
+        return;
+    };
+
+    let title = Paragraph::new(format!("Statistics of {}", view.interface))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -491,33 +830,1706 @@ fn draw_edit_dns_screen(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
 
-    let mut items = vec![ListItem::new(Line::from(vec![Span::styled(
-        "DNS Servers:",
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
-    )]))];
+    let heading = |text: String| {
+        Line::from(Span::styled(
+            text,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let change = match view.interval {
+        Some(interval) => format!("Change in {:.1}s", interval.as_secs_f64()),
+        None => "Change (r to read again)".to_string(),
+    };
 
-    for (i, server) in app.dns_edit_state.dns_servers.iter().enumerate() {
-        let is_selected =
-            app.dns_edit_state.current_field == 0 && app.dns_edit_state.server_index == i;
-        items.push(ListItem::new(Line::from(vec![
-            Span::raw(format!("  {}. ", i + 1)),
-            Span::styled(
-                if server.is_empty() { "<empty>" } else { server },
-                if is_selected {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
-                } else {
-                    Style::default()
-                },
-            ),
-        ])));
+    // The scroll offset counts counters; headings scroll along with them
+    let mut skip = view.scroll;
+    let mut lines = Vec::new();
+    for (title, rows) in [
+        ("Kernel counters", view.link_rows()),
+        ("Driver counters (ethtool -S)", view.driver_rows()),
+    ] {
+        let shown = rows.len().saturating_sub(skip);
+        let hidden = rows.len() - shown;
+        skip -= hidden;
+        if shown == 0 && !rows.is_empty() {
+            continue;
+        }
+
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(heading(format!("{:<36}{:>20}  {}", title, "Value", change)));
+        if rows.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  none",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+        lines.extend(rows.iter().skip(hidden).map(counter_row_line));
     }
 
-    items.push(ListItem::new(Line::from("")));
-    items.push(ListItem::new(Line::from(vec![Span::styled(
-        "Search Domains:",
-        Style::default()
+    let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(paragraph, chunks[1]);
+
+    let help = match &app.status_message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => Paragraph::new(format!(
+            "r: Read again | z: {} zero counters | j/k PgUp/PgDn: Scroll | Esc/q: Back",
+            if view.hide_zero { "Show" } else { "Hide" }
+        ))
+        .style(Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[2]);
+}
+
+fn draw_conflicts_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Address and Route Conflicts")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // The scroll offset counts conflicts; headings scroll along with them
+    let mut lines = Vec::new();
+    let mut kind = None;
+    for conflict in app.conflicts.iter().skip(app.conflicts_scroll) {
+        if kind != Some(conflict.kind) {
+            if kind.is_some() {
+                lines.push(Line::from(""));
+            }
+            kind = Some(conflict.kind);
+            lines.push(Line::from(Span::styled(
+                conflict.kind.title(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        let style = match conflict.kind {
+            ConflictKind::VpnCollision | ConflictKind::DuplicateAddress => {
+                Style::default().fg(Color::Red)
+            }
+            _ => Style::default(),
+        };
+        lines.push(Line::from(Span::styled(
+            format!("  ⚠ {}", conflict.summary),
+            style,
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("    {}", conflict.detail),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if app.conflicts.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No overlapping subnets, duplicate addresses or shadowed routes",
+            Style::default().fg(Color::Green),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(paragraph, chunks[1]);
+
+    let help = match &app.status_message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => Paragraph::new("r: Check again | j/k: Scroll | Esc/q: Back")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[2]);
+}
+
+fn draw_subnet_calculator(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let Some(calculator) = &app.subnet_calculator else {
+        return;
+    };
+
+    let title = Paragraph::new("Subnet Calculator")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let field = |index: usize, text: &str, placeholder: &'static str| {
+        let style = if calculator.current_field == index {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        if text.is_empty() {
+            Span::styled(placeholder, style.fg(Color::DarkGray))
+        } else {
+            Span::styled(text.to_string(), style)
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let inputs = vec![
+        Line::from(vec![
+            label("Subnet:    "),
+            field(0, &calculator.network_buffer, "e.g. 192.168.1.0/24"),
+        ]),
+        Line::from(vec![
+            label("Split to:  "),
+            field(1, &calculator.split_buffer, "prefix length, e.g. /27"),
+        ]),
+        Line::from(vec![
+            label("Summarize: "),
+            field(
+                2,
+                &calculator.summarize_buffer,
+                "e.g. 10.0.0.0/24 10.0.1.0/24",
+            ),
+        ]),
+    ];
+    f.render_widget(
+        Paragraph::new(inputs).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[2]);
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(6)])
+        .split(columns[1]);
+
+    let network = calculator.network();
+    let error = |text: String| Line::from(Span::styled(text, Style::default().fg(Color::Red)));
+
+    let subnet_lines = match &network {
+        Ok(network) => subnet_lines(network, &app.interfaces),
+        Err(e) => vec![error(e.clone())],
+    };
+    f.render_widget(
+        Paragraph::new(subnet_lines)
+            .block(Block::default().title(" Subnet ").borders(Borders::ALL)),
+        columns[0],
+    );
+
+    // Only the rows on screen are worked out; a /64 splits into 2^64 /128s
+    let split = network
+        .as_ref()
+        .ok()
+        .and_then(|network| Some((network, calculator.split_prefix(network)?)));
+    let (split_title, split_lines) = match split {
+        None => (
+            " Split ".to_string(),
+            vec![Line::from(Span::styled(
+                "Enter a longer prefix to split the subnet",
+                Style::default().fg(Color::DarkGray),
+            ))],
+        ),
+        Some((_, Err(e))) => (" Split ".to_string(), vec![error(e)]),
+        Some((network, Ok(prefix))) => {
+            let count = network.subnet_count(prefix);
+            let rows = right[0].height.saturating_sub(2) as u128;
+            let lines = (calculator.scroll as u128..)
+                .take(rows as usize)
+                .map_while(|index| network.subnet(prefix, index))
+                .map(|subnet| split_row_line(&subnet, &app.interfaces))
+                .collect();
+            (
+                format!(
+                    " Split into /{}: {} subnets ",
+                    prefix,
+                    count.map_or("2^128".to_string(), |count| count.to_string())
+                ),
+                lines,
+            )
+        }
+    };
+    f.render_widget(
+        Paragraph::new(split_lines)
+            .block(Block::default().title(split_title).borders(Borders::ALL)),
+        right[0],
+    );
+
+    let summary_lines = match calculator.summarize_input() {
+        Ok(networks) if networks.is_empty() => vec![Line::from(Span::styled(
+            "Enter prefixes to summarize",
+            Style::default().fg(Color::DarkGray),
+        ))],
+        Ok(networks) => {
+            let summary = subnet::summarize(&networks);
+            let mut lines = vec![Line::from(vec![
+                label("Summary:    "),
+                Span::raw(
+                    summary
+                        .iter()
+                        .map(|network| network.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            ])];
+            if let Some(cover) = subnet::covering_supernet(&networks) {
+                lines.push(Line::from(vec![
+                    label("Covered by: "),
+                    Span::raw(cover.to_string()),
+                ]));
+            }
+            lines
+        }
+        Err(e) => vec![error(e)],
+    };
+    f.render_widget(
+        Paragraph::new(summary_lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().title(" Summarize ").borders(Borders::ALL)),
+        right[1],
+    );
+
+    let help = match &app.status_message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => Paragraph::new("Tab: Next field | ↑↓ PgUp/PgDn: Scroll split | Esc: Back")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[3]);
+}
+
+/// Everything about one subnet, ending with the interface subnets it
+/// overlaps
+fn subnet_lines(network: &IpNetwork, interfaces: &[NetworkInterface]) -> Vec<Line<'static>> {
+    let row = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), Style::default().fg(Color::Yellow)),
+            Span::raw(value),
+        ])
+    };
+
+    let mut lines = Vec::new();
+    if !network.is_network() {
+        lines.push(row("Address", network.ip().to_string()));
+    }
+    lines.push(row("Network", network.trunc().to_string()));
+    if network.is_ipv4() {
+        lines.push(row("Netmask", network.netmask().to_string()));
+        lines.push(row("Wildcard", network.wildcard().to_string()));
+        if network.prefix() <= 30 {
+            lines.push(row("Broadcast", network.last().to_string()));
+        }
+    }
+    lines.push(row("First host", network.first_host().to_string()));
+    lines.push(row("Last host", network.last_host().to_string()));
+    lines.push(row(
+        "Hosts",
+        match network.host_count() {
+            Some(count) if count <= u32::MAX as u128 => count.to_string(),
+            _ => format!("2^{}", network.max_prefix() - network.prefix()),
+        },
+    ));
+    if let Some(supernet) = network
+        .prefix()
+        .checked_sub(1)
+        .and_then(|prefix| network.supernet(prefix))
+    {
+        lines.push(row("Supernet", supernet.to_string()));
+    }
+    if let IpAddr::V6(v6) = network.ip() {
+        lines.push(row("Compressed", v6.to_string()));
+        lines.push(row("Expanded", subnet::expanded_ipv6(&v6)));
+    }
+
+    let (zones, count) = subnet::reverse_zones(network, 4);
+    for (index, zone) in zones.into_iter().enumerate() {
+        lines.push(row(if index == 0 { "Reverse zone" } else { "" }, zone));
+    }
+    if count > 4 {
+        lines.push(row("", format!("… {} zones in all", count)));
+    }
+
+    lines.push(Line::from(""));
+    let overlaps = subnet::interface_overlaps(network, interfaces);
+    if overlaps.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No interface subnet overlaps this one",
+            Style::default().fg(Color::Green),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled(
+            "Overlaps interface subnets:",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+        for (name, existing, overlap) in overlaps {
+            lines.push(Line::from(Span::styled(
+                format!("  {} {} on {}", overlap.as_str(), existing.trunc(), name),
+                Style::default().fg(Color::Red),
+            )));
+        }
+    }
+    lines
+}
+
+/// One subnet of a split, flagged when an interface already uses it
+fn split_row_line(subnet: &IpNetwork, interfaces: &[NetworkInterface]) -> Line<'static> {
+    let mut spans = vec![
+        Span::raw(format!("{:<20}", subnet.to_string())),
+        Span::styled(
+            format!(
+                "{:<32}",
+                format!("{} - {}", subnet.first_host(), subnet.last_host())
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+    let mut users: Vec<String> = subnet::interface_overlaps(subnet, interfaces)
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    users.dedup();
+    if !users.is_empty() {
+        spans.push(Span::styled(
+            format!(" ⚠ {}", users.join(", ")),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    Line::from(spans)
+}
+
+/// Counters going up are marked: errors and drops in red, traffic in green.
+/// Errors from before the page was opened stay yellow.
+fn counter_row_line(row: &CounterRow) -> Line<'static> {
+    let error = is_error_counter(&row.name);
+    let increasing = row.delta.is_some_and(|delta| delta > 0);
+    let style = match (increasing, error) {
+        (true, true) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        (true, false) => Style::default().fg(Color::Green),
+        (false, true) if row.value > 0 => Style::default().fg(Color::Yellow),
+        _ => Style::default(),
+    };
+    let delta = match row.delta {
+        Some(0) => "".to_string(),
+        Some(delta) => format!("+{}", delta),
+        None => "".to_string(),
+    };
+
+    Line::from(Span::styled(
+        format!("  {:<34}{:>20}  {}", row.name, row.value, delta),
+        style,
+    ))
+}
+
+fn draw_wifi_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let Some(wifi) = &app.wifi else {
+        return;
+    };
+
+    let title = Paragraph::new(format!("WiFi Networks on {}", wifi.interface))
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let header = Row::new(
+        ["", "SSID", "BSSID", "Signal", "Channel", "Security"]
+            .iter()
+            .map(|h| {
+                Cell::from(*h).style(
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }),
+    )
+    .height(1)
+    .bottom_margin(1);
+
+    // Keep the selection on screen; rows are one line each
+    let visible = chunks[1].height.saturating_sub(4) as usize;
+    let offset = (wifi.selected + 1).saturating_sub(visible.max(1));
+
+    let rows = wifi
+        .networks
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, network)| {
+            let style = if i == wifi.selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else if network.in_use {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default()
+            };
+
+            let marker = match (network.in_use, network.saved) {
+                (true, _) => "●",
+                (false, true) => "★",
+                _ => "",
+            };
+            let ssid = if network.ssid.is_empty() {
+                "<hidden>".to_string()
+            } else {
+                network.ssid.clone()
+            };
+            let mut signal = signal_bar(network.signal).to_vec();
+            signal.push(Span::raw(format!(" {:>3}%", network.signal)));
+            let channel = match (network.channel(), network.frequency) {
+                (Some(channel), _) => channel.to_string(),
+                (None, Some(frequency)) => format!("{} MHz", frequency),
+                (None, None) => "-".to_string(),
+            };
+
+            Row::new(vec![
+                Cell::from(marker),
+                Cell::from(ssid),
+                Cell::from(network.bssid.clone()),
+                Cell::from(Line::from(signal)),
+                Cell::from(channel),
+                Cell::from(network.security.as_str()),
+            ])
+            .style(style)
+            .height(1)
+        });
+
+    let widths = [
+        Constraint::Length(2),
+        Constraint::Percentage(28),
+        Constraint::Length(18),
+        Constraint::Length(16),
+        Constraint::Length(8),
+        Constraint::Min(12),
+    ];
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} network(s) ", wifi.networks.len())),
+    );
+    f.render_widget(table, chunks[1]);
+
+    let legend = Paragraph::new("● connected  ★ saved")
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(legend, chunks[2]);
+
+    let help = match &app.status_message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => {
+            Paragraph::new("Enter: Connect | D: Disconnect | f: Forget | r: Rescan | Esc/q: Back")
+                .style(Style::default().fg(Color::DarkGray))
+        }
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[3]);
+}
+
+fn draw_bonds_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(6),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let Some(state) = &app.bonds else {
+        return;
+    };
+
+    let title = Paragraph::new("Bonds and Teams")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let header = Row::new(
+        [
+            "",
+            "Interface",
+            "Mode / State",
+            "Link",
+            "Speed",
+            "Failures",
+            "MAC",
+        ]
+        .iter()
+        .map(|h| {
+            Cell::from(*h).style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        }),
+    )
+    .height(1)
+    .bottom_margin(1);
+
+    let link_style = |up: bool| {
+        if up {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::Red)
+        }
+    };
+
+    // Keep the selection on screen; rows are one line each
+    let visible = chunks[1].height.saturating_sub(4) as usize;
+    let offset = (state.selected + 1).saturating_sub(visible.max(1));
+
+    let rows = state
+        .rows()
+        .into_iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(row, (i, slave))| {
+            let bond = &state.bonds[i];
+            let selected = row == state.selected;
+            let cells = match slave.map(|j| &bond.slaves[j]) {
+                None => {
+                    let up = bond.mii_status.as_deref() == Some("up");
+                    let miimon = match bond.miimon {
+                        Some(0) => " (no miimon)".to_string(),
+                        Some(ms) => format!(" ({} ms)", ms),
+                        None => String::new(),
+                    };
+                    vec![
+                        Cell::from(""),
+                        Cell::from(bond.name.clone())
+                            .style(Style::default().add_modifier(Modifier::BOLD)),
+                        Cell::from(format!("{}{}", bond.mode_name(), miimon)),
+                        Cell::from(bond.mii_status.clone().unwrap_or_default())
+                            .style(link_style(up)),
+                        Cell::from(""),
+                        Cell::from(""),
+                        Cell::from(format!("{} slave(s)", bond.slaves.len())),
+                    ]
+                }
+                Some(slave) => {
+                    let active = bond.is_active(slave);
+                    let state = match bond.mode {
+                        Some(mode) if mode.has_active_slave() => {
+                            if active {
+                                "active"
+                            } else {
+                                "backup"
+                            }
+                        }
+                        Some(BondMode::Ieee8023ad) if !active => "not aggregated",
+                        _ => "",
+                    };
+                    let speed = match (&slave.speed, &slave.duplex) {
+                        (Some(speed), Some(duplex)) => format!("{} {}", speed, duplex),
+                        (Some(speed), None) => speed.clone(),
+                        _ => "-".to_string(),
+                    };
+                    vec![
+                        Cell::from(if active { "●" } else { "" }),
+                        Cell::from(format!("  └ {}", slave.name)),
+                        Cell::from(state),
+                        Cell::from(slave.mii_status.clone().unwrap_or_default())
+                            .style(link_style(slave.is_up())),
+                        Cell::from(speed),
+                        Cell::from(slave.link_failures.to_string()).style(
+                            if slave.link_failures > 0 {
+                                Style::default().fg(Color::Yellow)
+                            } else {
+                                Style::default()
+                            },
+                        ),
+                        Cell::from(slave.permanent_mac.clone().unwrap_or_default()),
+                    ]
+                }
+            };
+
+            let style = if selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Row::new(cells).style(style).height(1)
+        });
+
+    let widths = [
+        Constraint::Length(2),
+        Constraint::Length(18),
+        Constraint::Length(24),
+        Constraint::Length(6),
+        Constraint::Length(16),
+        Constraint::Length(9),
+        Constraint::Min(17),
+    ];
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} bond(s) ", state.bonds.len())),
+    );
+    f.render_widget(table, chunks[1]);
+
+    // Settings of the bond the selection is in
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let mut lines = Vec::new();
+    match state.selected_bond() {
+        Some(bond) => {
+            lines.push(Line::from(vec![
+                label("Mode: "),
+                Span::raw(bond.mode_name()),
+                label("   Active slave: "),
+                Span::raw(bond.active_slave.as_deref().unwrap_or("-")),
+            ]));
+            if let Some(ref lacp) = bond.lacp {
+                lines.push(Line::from(vec![
+                    label("LACP rate: "),
+                    Span::raw(lacp.rate.as_deref().unwrap_or("-")),
+                    label("   Aggregator: "),
+                    Span::raw(
+                        lacp.aggregator_id
+                            .map(|id| format!("{} ({} port(s))", id, lacp.ports.unwrap_or(0)))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                ]));
+                lines.push(Line::from(vec![
+                    label("LACP partner: "),
+                    if lacp.has_partner() {
+                        Span::styled(
+                            lacp.partner_mac.clone().unwrap_or_default(),
+                            Style::default().fg(Color::Green),
+                        )
+                    } else {
+                        Span::styled(
+                            "none - is LACP configured on the switch?",
+                            Style::default().fg(Color::Red),
+                        )
+                    },
+                ]));
+            }
+            if bond.team {
+                lines.push(Line::from(Span::styled(
+                    "Run by teamd; see `teamdctl <team> state` for runner details",
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        }
+        None => lines.push(Line::from(Span::styled(
+            "No bonds. Press n to create one.",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+    let details = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(details, chunks[2]);
+
+    let help = match &app.status_message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => Paragraph::new(
+            "n: New bond | a: Add interface | d: Release slave | f: Fail over | X: Delete bond | r: Refresh | Esc/q: Back",
+        )
+        .style(Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[3]);
+}
+
+fn draw_create_bond_dialog(f: &mut Frame, app: &App) {
+    let Some(state) = &app.bonds else {
+        return;
+    };
+
+    let area = centered_rect(50, 40, f.area());
+    let block = Block::default()
+        .title(" New Bond ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let field_style = |field: usize| {
+        if state.current_field == field {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+    let default_name = validation::next_free_name("bond", &app.interfaces);
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            label("Name: "),
+            if state.name_buffer.is_empty() {
+                Span::styled(default_name, field_style(0).fg(Color::DarkGray))
+            } else {
+                Span::styled(state.name_buffer.as_str(), field_style(0))
+            },
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Mode: "),
+            Span::styled(state.mode.as_str(), field_style(1)),
+        ])),
+        ListItem::new(Line::from(vec![
+            label("MII polling (ms): "),
+            Span::styled(
+                if state.miimon_buffer.is_empty() {
+                    "driver default"
+                } else {
+                    &state.miimon_buffer
+                },
+                field_style(2),
+            ),
+        ])),
+    ];
+    items.extend(field_error(state.form_error.as_deref()));
+    f.render_widget(List::new(items), chunks[0]);
+
+    let help = if state.current_field == 1 {
+        "Space:Next mode | Tab:Next field | Enter:Create | Esc:Cancel"
+    } else {
+        "Tab:Next field | Enter:Create | Esc:Cancel"
+    };
+    let buttons = Paragraph::new(help)
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_enslave_dialog(f: &mut Frame, app: &App) {
+    let Some(picker) = &app.port_picker else {
+        return;
+    };
+
+    let area = centered_rect(50, 50, f.area());
+    let block = Block::default()
+        .title(format!(" Add to {} ", picker.master))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = picker
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let iface = app.interfaces.iter().find(|iface| iface.name == *name);
+            let details = iface
+                .map(|iface| {
+                    format!(
+                        "  {} {}",
+                        iface.interface_type.as_str(),
+                        if iface.is_up { "UP" } else { "DOWN" }
+                    )
+                })
+                .unwrap_or_default();
+            let selected = i == picker.index;
+            ListItem::new(Line::from(vec![
+                Span::raw(if selected { "> " } else { "  " }),
+                Span::styled(
+                    name.as_str(),
+                    if selected {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(details, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("j/k:Select | Enter:Add | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_namespaces_dialog(f: &mut Frame, app: &App) {
+    let Some(picker) = &app.namespace_picker else {
+        return;
+    };
+
+    let area = centered_rect(60, 60, f.area());
+    let block = Block::default()
+        .title(" Network Namespace ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let entries = std::iter::once((
+        "host".to_string(),
+        "the namespace we started in".to_string(),
+    ))
+    .chain(
+        picker
+            .namespaces
+            .iter()
+            .map(|ns| (ns.label(), ns.path.display().to_string())),
+    );
+    let items: Vec<ListItem> = entries
+        .enumerate()
+        .map(|(i, (label, details))| {
+            let selected = i == picker.index;
+            ListItem::new(Line::from(vec![
+                Span::raw(if selected { "> " } else { "  " }),
+                Span::styled(
+                    label,
+                    if selected {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!("  {}", details),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("j/k:Select | Enter:Switch | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_offloads_dialog(f: &mut Frame, app: &App) {
+    let Some(picker) = &app.offload_picker else {
+        return;
+    };
+
+    let area = centered_rect(60, 50, f.area());
+    let block = Block::default()
+        .title(format!(" Offloads on {} ", picker.interface))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = app
+        .offloads()
+        .iter()
+        .enumerate()
+        .map(|(i, offload)| {
+            let selected = i == picker.index;
+            let state = if offload.fixed {
+                format!("{} [fixed]", if offload.enabled { "on" } else { "off" })
+            } else if offload.enabled {
+                "on".to_string()
+            } else {
+                "off".to_string()
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(if selected { "> " } else { "  " }),
+                Span::styled(
+                    format!("{:<32}", offload.label()),
+                    if selected {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!(" {:<12}", state),
+                    if offload.enabled {
+                        Style::default().fg(Color::Green)
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    },
+                ),
+                Span::styled(offload.name.clone(), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("j/k:Select | Enter/Space:Toggle | Esc:Back")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_add_wireguard_peer_dialog(f: &mut Frame, app: &App) {
+    let Some(state) = &app.wireguard else {
+        return;
+    };
+
+    let area = centered_rect(70, 40, f.area());
+    let block = Block::default()
+        .title(format!(" New Peer on {} ", state.interface))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let field = |index: usize, text: &str, placeholder: &'static str| {
+        let style = if state.current_field == index {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        if text.is_empty() {
+            Span::styled(placeholder, style.fg(Color::DarkGray))
+        } else {
+            Span::styled(text.to_string(), style)
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            label("Public key: "),
+            field(
+                0,
+                &state.public_key_buffer,
+                "base64, from the peer's wg pubkey",
+            ),
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Allowed IPs: "),
+            field(
+                1,
+                &state.allowed_ips_buffer,
+                "e.g. 10.0.0.2/32, fd00::2/128",
+            ),
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Endpoint: "),
+            field(2, &state.endpoint_buffer, "optional, IP:port"),
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Keepalive (s): "),
+            field(3, &state.keepalive_buffer, "off"),
+        ])),
+    ];
+    items.extend(field_error(state.form_error.as_deref()));
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("Tab:Next field | Enter:Add | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_remove_wireguard_peer_dialog(f: &mut Frame, app: &App) {
+    let Some(state) = &app.wireguard else {
+        return;
+    };
+
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .title(format!(" Remove Peer from {} ", state.interface))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = app
+        .wireguard_peers()
+        .iter()
+        .enumerate()
+        .map(|(i, peer)| {
+            let selected = i == state.peer_index;
+            let allowed: Vec<String> = peer.allowed_ips.iter().map(|a| a.to_string()).collect();
+            ListItem::new(Line::from(vec![
+                Span::raw(if selected { "> " } else { "  " }),
+                Span::styled(
+                    peer.public_key.clone(),
+                    if selected {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    format!("  {}", allowed.join(", ")),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("j/k:Select | Enter:Remove | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_wireguard_keys_dialog(f: &mut Frame, app: &App) {
+    let Some((private, public)) = app.wireguard.as_ref().and_then(|s| s.keypair.as_ref()) else {
+        return;
+    };
+
+    let area = centered_rect(70, 40, f.area());
+    let block = Block::default()
+        .title(" New WireGuard Keypair ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let lines = vec![
+        Line::from(vec![label("Private key: "), Span::raw(private.as_str())]),
+        Line::from(vec![label("Public key:  "), Span::raw(public.as_str())]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Nothing has been changed. Keep the private key secret; it is forgotten when this closes.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+
+    let buttons = Paragraph::new("c:Copy public key | K:Generate another | Esc:Close")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_bridges_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(5),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let Some(state) = &app.bridges else {
+        return;
+    };
+
+    let title = Paragraph::new("Bridges")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let selected_style = Style::default()
+        .bg(Color::DarkGray)
+        .fg(Color::White)
+        .add_modifier(Modifier::BOLD);
+    // Keep the selection on screen; rows are one line each
+    let visible = chunks[1].height.saturating_sub(4).max(1) as usize;
+
+    match (&state.fdb, state.selected_bridge()) {
+        (Some(fdb), Some(bridge)) => {
+            let header = Row::new(
+                ["MAC", "Port", "VLAN", "Type", "Last seen", "Expires in"]
+                    .iter()
+                    .map(|h| Cell::from(*h).style(header_style)),
+            )
+            .height(1)
+            .bottom_margin(1);
+
+            let offset = (state.fdb_scroll + 1).saturating_sub(visible);
+            let rows = fdb.iter().enumerate().skip(offset).map(|(i, entry)| {
+                let expires = match (entry.kind, entry.updated, bridge.ageing_time) {
+                    (FdbKind::Dynamic, Some(updated), Some(ageing)) => {
+                        format!("{}s", ageing.saturating_sub(updated))
+                    }
+                    _ => "-".to_string(),
+                };
+                Row::new(vec![
+                    Cell::from(entry.mac.clone()),
+                    Cell::from(entry.port.clone()),
+                    Cell::from(entry.vlan.map(|v| v.to_string()).unwrap_or_default()),
+                    Cell::from(entry.kind.as_str()),
+                    Cell::from(
+                        entry
+                            .updated
+                            .map(|s| format!("{}s ago", s))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                    Cell::from(expires),
+                ])
+                .style(if i == state.fdb_scroll {
+                    selected_style
+                } else {
+                    Style::default()
+                })
+                .height(1)
+            });
+
+            let widths = [
+                Constraint::Length(19),
+                Constraint::Length(16),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Length(11),
+                Constraint::Min(10),
+            ];
+            let table = Table::new(rows, widths).header(header).block(
+                Block::default().borders(Borders::ALL).title(format!(
+                    " {} forwarding database: {} entries ",
+                    bridge.name,
+                    fdb.len()
+                )),
+            );
+            f.render_widget(table, chunks[1]);
+        }
+        _ => {
+            let header = Row::new(
+                ["Interface", "STP", "State", "Role", "Cost", "VLANs"]
+                    .iter()
+                    .map(|h| Cell::from(*h).style(header_style)),
+            )
+            .height(1)
+            .bottom_margin(1);
+
+            let offset = (state.selected + 1).saturating_sub(visible);
+            let rows = state
+                .rows()
+                .into_iter()
+                .enumerate()
+                .skip(offset)
+                .map(|(row, (i, port))| {
+                    let bridge = &state.bridges[i];
+                    let cells = match port.map(|j| &bridge.ports[j]) {
+                        None => vec![
+                            Cell::from(match bridge.managed_by {
+                                Some(tool) => format!("{} ({})", bridge.name, tool),
+                                None => bridge.name.clone(),
+                            })
+                            .style(Style::default().add_modifier(Modifier::BOLD)),
+                            Cell::from(if bridge.stp { "on" } else { "off" }),
+                            Cell::from(if bridge.up { "UP" } else { "DOWN" }).style(if bridge.up {
+                                Style::default().fg(Color::Green)
+                            } else {
+                                Style::default().fg(Color::Red)
+                            }),
+                            Cell::from(if bridge.stp && bridge.is_root() {
+                                "root bridge"
+                            } else {
+                                ""
+                            }),
+                            Cell::from(""),
+                            Cell::from(format!("{} port(s)", bridge.ports.len())),
+                        ],
+                        Some(port) => {
+                            let state_style = match port.state.as_deref() {
+                                Some("forwarding") => Style::default().fg(Color::Green),
+                                Some("blocking") | Some("disabled") => {
+                                    Style::default().fg(Color::Red)
+                                }
+                                _ => Style::default().fg(Color::Yellow),
+                            };
+                            let vlans: Vec<String> = port
+                                .vlans
+                                .iter()
+                                .map(|vlan| {
+                                    let mut text = vlan.id.to_string();
+                                    if vlan.pvid {
+                                        text.push_str(" PVID");
+                                    }
+                                    if vlan.untagged {
+                                        text.push_str(" untagged");
+                                    }
+                                    text
+                                })
+                                .collect();
+                            vec![
+                                Cell::from(format!("  └ {}", port.name)),
+                                Cell::from(""),
+                                Cell::from(port.state.clone().unwrap_or_default())
+                                    .style(state_style),
+                                Cell::from(port.role.unwrap_or("-")),
+                                Cell::from(
+                                    port.path_cost.map(|c| c.to_string()).unwrap_or_default(),
+                                ),
+                                Cell::from(vlans.join(", ")),
+                            ]
+                        }
+                    };
+                    Row::new(cells)
+                        .style(if row == state.selected {
+                            selected_style
+                        } else {
+                            Style::default()
+                        })
+                        .height(1)
+                });
+
+            let widths = [
+                Constraint::Length(26),
+                Constraint::Length(5),
+                Constraint::Length(11),
+                Constraint::Length(12),
+                Constraint::Length(8),
+                Constraint::Min(10),
+            ];
+            let table = Table::new(rows, widths).header(header).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} bridge(s) ", state.bridges.len())),
+            );
+            f.render_widget(table, chunks[1]);
+        }
+    }
+
+    // Settings of the bridge the selection is in
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let mut lines = Vec::new();
+    match state.selected_bridge() {
+        Some(bridge) => {
+            lines.push(Line::from(vec![
+                label("Bridge id: "),
+                Span::raw(bridge.bridge_id.as_deref().unwrap_or("-")),
+                label("   Root: "),
+                Span::raw(bridge.root_id.as_deref().unwrap_or("-")),
+            ]));
+            let filtering = match (bridge.vlan_filtering, bridge.default_pvid) {
+                (Some(true), Some(pvid)) => format!("on, default PVID {}", pvid),
+                (Some(true), None) => "on".to_string(),
+                (Some(false), _) => "off".to_string(),
+                (None, _) => "not supported".to_string(),
+            };
+            lines.push(Line::from(vec![
+                label("VLAN filtering: "),
+                Span::raw(filtering),
+                label("   Ageing time: "),
+                Span::raw(
+                    bridge
+                        .ageing_time
+                        .map(|s| format!("{}s", s))
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+            ]));
+            if let Some(tool) = bridge.managed_by {
+                lines.push(Line::from(Span::styled(
+                    format!("Managed by {}; it may revert changes made here", tool),
+                    Style::default().fg(Color::Magenta),
+                )));
+            }
+        }
+        None => lines.push(Line::from(Span::styled(
+            "No bridges. Press n to create one.",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+    let details = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(details, chunks[2]);
+
+    let help = match (&app.status_message, &state.fdb) {
+        (Some(message), _) => {
+            Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow))
+        }
+        (None, Some(_)) => Paragraph::new("j/k: Scroll | F: Ports | r: Refresh | Esc/q: Back")
+            .style(Style::default().fg(Color::DarkGray)),
+        (None, None) => Paragraph::new(
+            "n: New bridge | a: Add port | d: Remove port | s: Toggle STP | F: Forwarding DB | X: Delete | Esc/q: Back",
+        )
+        .style(Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(help.alignment(Alignment::Center), chunks[3]);
+}
+
+fn draw_create_bridge_dialog(f: &mut Frame, app: &App) {
+    let Some(state) = &app.bridges else {
+        return;
+    };
+
+    let area = centered_rect(50, 30, f.area());
+    let block = Block::default()
+        .title(" New Bridge ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let field_style = |field: usize| {
+        if state.current_field == field {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+    let default_name = validation::next_free_name("br", &app.interfaces);
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            label("Name: "),
+            if state.name_buffer.is_empty() {
+                Span::styled(default_name, field_style(0).fg(Color::DarkGray))
+            } else {
+                Span::styled(state.name_buffer.as_str(), field_style(0))
+            },
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Spanning tree: "),
+            Span::styled(
+                if state.stp { "[X] STP" } else { "[ ] STP" },
+                field_style(1),
+            ),
+        ])),
+    ];
+    items.extend(field_error(state.form_error.as_deref()));
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("Tab:Next field | Space:Toggle | Enter:Create | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_edit_mac_dialog(f: &mut Frame, app: &App) {
+    let state = &app.mac_edit_state;
+    let Some(iface) = app
+        .interfaces
+        .iter()
+        .find(|iface| iface.name == state.interface)
+    else {
+        return;
+    };
+
+    let area = centered_rect(50, 30, f.area());
+    let block = Block::default()
+        .title(format!(" MAC Address of {} ", iface.name))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+    let show = |mac: Option<MacAddr>| {
+        mac.map(|mac| mac.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            label("Current:   "),
+            Span::raw(show(iface.mac_address)),
+        ])),
+        ListItem::new(Line::from(vec![
+            label("Permanent: "),
+            Span::raw(show(iface.permanent_mac)),
+        ])),
+        ListItem::new(""),
+        ListItem::new(Line::from(vec![
+            label("New:       "),
+            Span::styled(state.buffer.as_str(), Style::default().bg(Color::DarkGray)),
+        ])),
+    ];
+    let new_mac = state.buffer.parse::<MacAddr>().ok();
+    if new_mac.is_some_and(|mac| mac.is_locally_administered() && !mac.is_multicast()) {
+        items.push(ListItem::new(Span::styled(
+            "           locally administered",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    items.extend(field_error(state.error.as_deref()));
+    f.render_widget(List::new(items), chunks[0]);
+
+    let buttons = Paragraph::new("Tab:Random | Ctrl+p:Permanent | Enter:Apply | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[1]);
+}
+
+fn draw_edit_ip_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Edit IP Configuration")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    if let Some(iface) = app.get_selected_interface() {
+        let check = app.ip_edit_state.check.as_ref();
+        let mode_str = format!(
+            "[{}] DHCP  [{}] Static",
+            if matches!(app.ip_edit_state.mode, IpConfigMode::DHCP) {
+                "X"
+            } else {
+                " "
+            },
+            if matches!(app.ip_edit_state.mode, IpConfigMode::Static) {
+                "X"
+            } else {
+                " "
+            }
+        );
+
+        let mut items = vec![
+            ListItem::new(Line::from(vec![
+                Span::styled("Interface: ", Style::default().fg(Color::Yellow)),
+                Span::raw(&iface.name),
+            ])),
+            ListItem::new(Line::from("")),
+            ListItem::new(Line::from(vec![
+                Span::styled("Configuration Mode: ", Style::default().fg(Color::Yellow)),
+                Span::raw(&mode_str),
+            ])),
+        ];
+
+        let state = &app.ip_edit_state;
+        let field_style = |field: usize| {
+            if state.current_field == field {
+                Style::default().bg(Color::DarkGray)
+            } else {
+                Style::default()
+            }
+        };
+
+        items.push(ListItem::new(Line::from("")));
+        items.push(ListItem::new(Line::from(Span::styled(
+            "Addresses:",
+            Style::default().fg(Color::Yellow),
+        ))));
+        if state.addresses.is_empty() {
+            items.push(ListItem::new(Line::from("  None")));
+        }
+        for (i, addr) in state.addresses.iter().enumerate() {
+            let selected = state.current_field == 1 && i == state.address_index;
+            items.push(ListItem::new(Line::from(vec![
+                Span::raw(if selected { "> " } else { "  " }),
+                Span::styled(
+                    addr.display(),
+                    if selected {
+                        Style::default().bg(Color::DarkGray)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Span::styled(
+                    if addr.secondary {
+                        "  secondary"
+                    } else {
+                        "  primary"
+                    },
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ])));
+        }
+
+        if matches!(state.mode, IpConfigMode::Static) {
+            items.push(ListItem::new(Line::from("")));
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(
+                    "New Address (IP or CIDR): ",
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::styled(&state.ip_buffer, field_style(2)),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.ip_error.as_deref())));
+
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled("Netmask: ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    if state.ip_buffer.contains('/') {
+                        "from CIDR"
+                    } else {
+                        &state.netmask_buffer
+                    },
+                    field_style(3),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.netmask_error.as_deref())));
+
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled("Label (optional): ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    if state.label_buffer.is_empty() {
+                        "None"
+                    } else {
+                        &state.label_buffer
+                    },
+                    field_style(4),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.label_error.as_deref())));
+
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled("Gateway (optional): ", Style::default().fg(Color::Yellow)),
+                Span::styled(
+                    if state.gateway_buffer.is_empty() {
+                        "None"
+                    } else {
+                        &state.gateway_buffer
+                    },
+                    field_style(5),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.gateway_error.as_deref())));
+            items.extend(form_warnings(check.map(|c| c.warnings.as_slice())));
+        }
+
+        let list = List::new(items).block(Block::default().borders(Borders::ALL));
+
+        f.render_widget(list, chunks[1]);
+    }
+
+    let help_text = if app.ip_edit_state.current_field == 1 {
+        "j/k:Select | d:Remove address | p:Make primary | Tab:Next field | Esc:Cancel"
+    } else {
+        "Tab:Next field | Space:Toggle mode | Enter:Apply | Esc:Cancel"
+    };
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_edit_dns_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Edit DNS Configuration")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let mut items = vec![ListItem::new(Line::from(vec![Span::styled(
+        "DNS Servers:",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )]))];
+
+    for (i, server) in app.dns_edit_state.dns_servers.iter().enumerate() {
+        let is_selected =
+            app.dns_edit_state.current_field == 0 && app.dns_edit_state.server_index == i;
+        items.push(ListItem::new(Line::from(vec![
+            Span::raw(format!("  {}. ", i + 1)),
+            Span::styled(
+                if server.is_empty() { "<empty>" } else { server },
+                if is_selected {
+                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                } else {
+                    Style::default()
+                },
+            ),
+        ])));
+    }
+
+    items.push(ListItem::new(Line::from("")));
+    items.push(ListItem::new(Line::from(vec![Span::styled(
+        "Search Domains:",
+        Style::default()
             .fg(Color::Cyan)
             .add_modifier(Modifier::BOLD),
     )])));
@@ -542,107 +2554,707 @@ fn draw_edit_dns_screen(f: &mut Frame, app: &App) {
         }
     }
 
-    let list = List::new(items).block(Block::default().borders(Borders::ALL));
+    let list = List::new(items).block(Block::default().borders(Borders::ALL));
+
+    f.render_widget(list, chunks[1]);
+
+    let help = Paragraph::new("↑↓:Navigate | Tab:Switch section | Enter:Edit | a:Add | x:Delete | Esc:Cancel | Ctrl+s:Save")
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_edit_ipv6_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Edit IPv6 Configuration")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    if let Some(iface) = app.get_selected_interface() {
+        let check = app.ipv6_edit_state.check.as_ref();
+        let enabled_str = format!(
+            "[{}] Enabled  [{}] Disabled",
+            if app.ipv6_edit_state.enabled {
+                "X"
+            } else {
+                " "
+            },
+            if !app.ipv6_edit_state.enabled {
+                "X"
+            } else {
+                " "
+            }
+        );
+
+        let mut items = vec![
+            ListItem::new(Line::from(vec![
+                Span::styled("Interface: ", Style::default().fg(Color::Yellow)),
+                Span::raw(&iface.name),
+            ])),
+            ListItem::new(Line::from("")),
+            ListItem::new(Line::from(vec![
+                Span::styled("IPv6 Status: ", Style::default().fg(Color::Yellow)),
+                Span::raw(&enabled_str),
+            ])),
+        ];
+
+        let state = &app.ipv6_edit_state;
+        let field_style = |field: usize| {
+            if state.current_field == field {
+                Style::default().bg(Color::DarkGray)
+            } else {
+                Style::default()
+            }
+        };
+        let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+        if state.enabled {
+            items.push(ListItem::new(Line::from("")));
+            items.push(ListItem::new(Line::from(label("Addresses:"))));
+            if state.addresses.is_empty() {
+                items.push(ListItem::new(Line::from("  None")));
+            }
+            for (i, addr) in state.addresses.iter().enumerate() {
+                let selected = state.current_field == 1 && i == state.address_index;
+                let flags = addr
+                    .ipv6_info
+                    .as_ref()
+                    .map(|info| info.flags.join(" "))
+                    .unwrap_or_default();
+                items.push(ListItem::new(Line::from(vec![
+                    Span::raw(if selected { "> " } else { "  " }),
+                    Span::styled(
+                        addr.display(),
+                        if selected {
+                            Style::default().bg(Color::DarkGray)
+                        } else {
+                            Style::default()
+                        },
+                    ),
+                    Span::styled(format!("  {}", flags), Style::default().fg(Color::DarkGray)),
+                ])));
+            }
+
+            items.push(ListItem::new(Line::from("")));
+            items.push(ListItem::new(Line::from(vec![
+                label("New Address (IP or CIDR): "),
+                Span::styled(
+                    if state.ip_buffer.is_empty() {
+                        "None"
+                    } else {
+                        &state.ip_buffer
+                    },
+                    field_style(2),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.ip_error.as_deref())));
+
+            items.push(ListItem::new(Line::from(vec![
+                label("Prefix Length: "),
+                Span::styled(
+                    if state.ip_buffer.contains('/') {
+                        "from CIDR"
+                    } else {
+                        &state.prefix_buffer
+                    },
+                    field_style(3),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.prefix_error.as_deref())));
+
+            items.push(ListItem::new(Line::from(vec![
+                label("Default Gateway (optional): "),
+                Span::styled(
+                    if state.gateway_buffer.is_empty() {
+                        "None"
+                    } else {
+                        &state.gateway_buffer
+                    },
+                    field_style(4),
+                ),
+            ])));
+            items.extend(field_error(check.and_then(|c| c.gateway_error.as_deref())));
+
+            if let Some(settings) = &state.settings {
+                items.push(ListItem::new(Line::from("")));
+                items.push(ListItem::new(Line::from(vec![
+                    label("Address Generation: "),
+                    Span::styled(settings.addr_gen_mode.as_str(), field_style(5)),
+                ])));
+                items.push(ListItem::new(Line::from(vec![
+                    label("Privacy Extensions: "),
+                    Span::styled(settings.use_tempaddr.as_str(), field_style(6)),
+                ])));
+                items.push(ListItem::new(Line::from(vec![
+                    label("Accept Router Advertisements: "),
+                    Span::styled(settings.accept_ra.as_str(), field_style(7)),
+                ])));
+            }
+
+            items.push(ListItem::new(Line::from(vec![
+                label("DHCPv6: "),
+                Span::styled(
+                    if state.dhcpv6 {
+                        "[X] Request an address"
+                    } else {
+                        "[ ] Request an address"
+                    },
+                    field_style(8),
+                ),
+            ])));
+            items.extend(form_warnings(check.map(|c| c.warnings.as_slice())));
+        }
+
+        let list = List::new(items).block(Block::default().borders(Borders::ALL));
+
+        f.render_widget(list, chunks[1]);
+    }
+
+    let help_text = if app.ipv6_edit_state.current_field == 1 {
+        "j/k:Select | d:Remove address | Tab:Next field | Esc:Cancel"
+    } else {
+        "Tab:Next field | Space:Toggle/Cycle | Enter:Apply | Esc:Cancel"
+    };
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_edit_vlan_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("VLAN Sub-interfaces")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    let state = &app.vlan_edit_state;
+    let check = state.check.as_ref();
+    let field_style = |field: usize| {
+        if state.current_field == field {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        }
+    };
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(Color::Yellow));
+
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            label("Parent: "),
+            Span::raw(&state.parent),
+        ])),
+        ListItem::new(Line::from("")),
+        ListItem::new(Line::from(label("VLANs:"))),
+    ];
+    if state.vlans.is_empty() {
+        items.push(ListItem::new(Line::from("  None")));
+    }
+    for (i, name) in state.vlans.iter().enumerate() {
+        let selected = state.current_field == 0 && i == state.vlan_index;
+        let iface = app.interfaces.iter().find(|iface| iface.name == *name);
+        let details = iface
+            .and_then(|iface| {
+                let vlan = iface.vlan.as_ref()?;
+                Some(format!(
+                    "  id {} {}  {}",
+                    vlan.id,
+                    vlan.protocol.as_str(),
+                    if iface.is_up { "UP" } else { "DOWN" }
+                ))
+            })
+            .unwrap_or_default();
+        items.push(ListItem::new(Line::from(vec![
+            Span::raw(if selected { "> " } else { "  " }),
+            Span::styled(
+                name.as_str(),
+                if selected {
+                    Style::default().bg(Color::DarkGray)
+                } else {
+                    Style::default()
+                },
+            ),
+            Span::styled(details, Style::default().fg(Color::DarkGray)),
+        ])));
+    }
+
+    let default_name = if state.id_buffer.is_empty() {
+        format!("{}.<id>", state.parent)
+    } else {
+        format!("{}.{}", state.parent, state.id_buffer)
+    };
+    items.push(ListItem::new(Line::from("")));
+    items.push(ListItem::new(Line::from(label("New VLAN:"))));
+    items.push(ListItem::new(Line::from(vec![
+        label("VLAN ID (1-4094): "),
+        Span::styled(&state.id_buffer, field_style(1)),
+    ])));
+    items.extend(field_error(check.and_then(|c| c.id_error.as_deref())));
+    items.push(ListItem::new(Line::from(vec![
+        label("Name: "),
+        if state.name_buffer.is_empty() {
+            Span::styled(default_name, field_style(2).fg(Color::DarkGray))
+        } else {
+            Span::styled(state.name_buffer.as_str(), field_style(2))
+        },
+    ])));
+    items.extend(field_error(check.and_then(|c| c.name_error.as_deref())));
+    items.push(ListItem::new(Line::from(vec![
+        label("Protocol: "),
+        Span::styled(state.protocol.as_str(), field_style(3)),
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        label("MTU (optional): "),
+        Span::styled(
+            if state.mtu_buffer.is_empty() {
+                "Parent's"
+            } else {
+                &state.mtu_buffer
+            },
+            field_style(4),
+        ),
+    ])));
+    items.extend(field_error(check.and_then(|c| c.mtu_error.as_deref())));
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL));
+    f.render_widget(list, chunks[1]);
+
+    let help_text = match state.current_field {
+        0 => "j/k:Select | d:Delete VLAN | Tab:Next field | Esc:Cancel",
+        3 => "Space:Toggle protocol | Tab:Next field | Enter:Create | Esc:Cancel",
+        _ => "Tab:Next field | Enter:Create | Esc:Cancel",
+    };
+    let help = Paragraph::new(help_text)
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+/// Link details below the SSID, with a bar for the signal strength
+fn wireless_lines(wireless: &WirelessInfo) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    if let Some(ref bssid) = wireless.bssid {
+        lines.push(Line::from(format!("  BSSID: {}", bssid)));
+    }
+    if let Some(frequency) = wireless.frequency {
+        let channel = wireless
+            .channel()
+            .map(|channel| format!("Channel {} ", channel))
+            .unwrap_or_default();
+        let band = wireless
+            .band()
+            .map(|band| format!(", {}", band))
+            .unwrap_or_default();
+        lines.push(Line::from(format!(
+            "  {}({} MHz{})",
+            channel, frequency, band
+        )));
+    }
+    if let (Some(dbm), Some(quality)) = (wireless.signal_dbm, wireless.signal_quality()) {
+        let mut spans = vec![Span::raw("  Signal: ")];
+        spans.extend(signal_bar(quality));
+        spans.push(Span::raw(format!(" {} dBm ({}%)", dbm, quality)));
+        lines.push(Line::from(spans));
+    }
+    if wireless.tx_bitrate.is_some() || wireless.rx_bitrate.is_some() {
+        let rate = |rate: Option<f32>| {
+            rate.map(|rate| format!("{:.1} Mbit/s", rate))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        lines.push(Line::from(format!(
+            "  Bitrate: tx {}, rx {}",
+            rate(wireless.tx_bitrate),
+            rate(wireless.rx_bitrate)
+        )));
+    }
+    if let Some(security) = wireless.security {
+        lines.push(Line::from(format!("  Security: {}", security.as_str())));
+    }
+    if let Some(seconds) = wireless.connected_time {
+        lines.push(Line::from(format!(
+            "  Connected for: {}",
+            format_lifetime(Some(seconds))
+        )));
+    }
+
+    lines
+}
+
+/// Negotiated link settings, driver and offloads, with links slower than a
+/// gigabit and offloads that are off picked out
+fn ethtool_lines(ethtool: &EthtoolInfo) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    let mut link = Vec::new();
+    if let Some(duplex) = ethtool.duplex {
+        link.push(format!("{} duplex", duplex.as_str()));
+    }
+    if let Some(autoneg) = ethtool.autoneg {
+        link.push(format!(
+            "autonegotiation {}",
+            if autoneg { "on" } else { "off" }
+        ));
+    }
+    if let Some(ref port) = ethtool.port {
+        link.push(port.clone());
+    }
+    if let Some(detected) = ethtool.link_detected {
+        link.push(if detected { "link detected" } else { "no link" }.to_string());
+    }
+    if ethtool.speed.is_some() || !link.is_empty() {
+        let mut spans = vec![Span::styled(
+            "Link: ",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )];
+        if let Some(speed) = ethtool.speed {
+            spans.push(Span::styled(format_speed(speed), speed_style(speed)));
+            if !link.is_empty() {
+                spans.push(Span::raw(", "));
+            }
+        }
+        spans.push(Span::raw(link.join(", ")));
+        lines.push(Line::from(spans));
+    }
+
+    if let Some(ref driver) = ethtool.driver {
+        let mut text = format!("  Driver: {}", driver.driver);
+        if let Some(ref version) = driver.version {
+            text.push_str(&format!(" {}", version));
+        }
+        if let Some(ref firmware) = driver.firmware {
+            text.push_str(&format!("  Firmware: {}", firmware));
+        }
+        if let Some(ref bus) = driver.bus_info {
+            text.push_str(&format!("  Bus: {}", bus));
+        }
+        lines.push(Line::from(text));
+    }
+
+    if !ethtool.offloads.is_empty() {
+        let mut spans = vec![Span::raw("  Offloads (o): ")];
+        for (i, offload) in ethtool.offloads.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(", "));
+            }
+            spans.push(Span::styled(
+                format!(
+                    "{} {}{}",
+                    offload.name,
+                    if offload.enabled { "on" } else { "off" },
+                    if offload.fixed { " [fixed]" } else { "" }
+                ),
+                if offload.enabled {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                },
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines
+}
+
+/// Bytes and packets each way, then errors and drops, which stand out once
+/// there are any
+fn counter_lines(counters: &LinkCounters) -> Vec<Line<'static>> {
+    let problem = |count: u64| {
+        if count > 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default()
+        }
+    };
+
+    vec![
+        Line::from(vec![
+            Span::styled(
+                "Traffic: ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "RX {} ({} packets)  TX {} ({} packets)",
+                format_bytes(counters.rx_bytes),
+                counters.rx_packets,
+                format_bytes(counters.tx_bytes),
+                counters.tx_packets
+            )),
+        ]),
+        Line::from(vec![
+            Span::raw("  Errors: "),
+            Span::styled(
+                format!("RX {}", counters.rx_errors),
+                problem(counters.rx_errors),
+            ),
+            Span::raw(" / "),
+            Span::styled(
+                format!("TX {}", counters.tx_errors),
+                problem(counters.tx_errors),
+            ),
+            Span::raw("  Dropped: "),
+            Span::styled(
+                format!("RX {}", counters.rx_dropped),
+                problem(counters.rx_dropped),
+            ),
+            Span::raw(" / "),
+            Span::styled(
+                format!("TX {}", counters.tx_dropped),
+                problem(counters.tx_dropped),
+            ),
+            Span::styled("  (S: all counters)", Style::default().fg(Color::DarkGray)),
+        ]),
+    ]
+}
+
+/// Anything below a gigabit usually means a bad cable or a forced setting
+fn speed_style(mbps: u32) -> Style {
+    if mbps < 1000 {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    }
+}
+
+/// Device settings, then each peer with its handshake age colored by
+/// whether the session is still alive
+fn wireguard_lines(wireguard: &WireGuardInfo, now: i64) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    lines.push(Line::from(format!(
+        "  Public key: {}",
+        wireguard
+            .public_key
+            .as_deref()
+            .unwrap_or("none (no private key set)")
+    )));
+    lines.push(Line::from(format!(
+        "  Listen port: {}  Fwmark: {}",
+        wireguard
+            .listen_port
+            .map(|port| port.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        if wireguard.fwmark == 0 {
+            "off".to_string()
+        } else {
+            format!("{:#x}", wireguard.fwmark)
+        }
+    )));
+    lines.push(Line::from(format!("  Peers: {}", wireguard.peers.len())));
 
-    f.render_widget(list, chunks[1]);
+    for (i, peer) in wireguard.peers.iter().enumerate() {
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {}. ", i + 1)),
+            Span::styled(peer.public_key.clone(), Style::default().fg(Color::Cyan)),
+            Span::raw(if peer.has_preshared_key {
+                " (preshared key)"
+            } else {
+                ""
+            }),
+        ]));
+        if let Some(endpoint) = peer.endpoint {
+            lines.push(Line::from(format!("     Endpoint: {}", endpoint)));
+        }
+        let allowed: Vec<String> = peer.allowed_ips.iter().map(|a| a.to_string()).collect();
+        lines.push(Line::from(format!(
+            "     Allowed IPs: {}",
+            if allowed.is_empty() {
+                "(none)".to_string()
+            } else {
+                allowed.join(", ")
+            }
+        )));
+        let handshake = match peer.handshake_age(now) {
+            Some(age) if age <= WIREGUARD_STALE_AFTER => Span::styled(
+                format!("{} ago", format_lifetime(Some(age as u32))),
+                Style::default().fg(Color::Green),
+            ),
+            Some(age) => Span::styled(
+                format!(
+                    "{} ago (stale)",
+                    format_lifetime(Some(age.min(u32::MAX as u64) as u32))
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+            None => Span::styled("never", Style::default().fg(Color::Red)),
+        };
+        lines.push(Line::from(vec![
+            Span::raw("     Latest handshake: "),
+            handshake,
+        ]));
+        lines.push(Line::from(format!(
+            "     Transfer: {} received, {} sent{}",
+            format_bytes(peer.rx_bytes),
+            format_bytes(peer.tx_bytes),
+            peer.persistent_keepalive
+                .map(|secs| format!(", keepalive every {}s", secs))
+                .unwrap_or_default()
+        )));
+    }
 
-    let help = Paragraph::new("↑↓:Navigate | Tab:Switch section | Enter:Edit | a:Add | x:Delete | Esc:Cancel | Ctrl+s:Save")
-        .style(Style::default().fg(Color::DarkGray))
-        .alignment(Alignment::Center);
-    f.render_widget(help, chunks[2]);
+    lines
 }
 
-fn draw_edit_ipv6_screen(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(0),
-            Constraint::Length(2),
-        ])
-        .split(f.area());
+/// Binary units, like `wg show`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
 
-    let title = Paragraph::new("Edit IPv6 Configuration")
-        .style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(title, chunks[0]);
+/// Ten cells, colored by quality (0-100)
+fn signal_bar(quality: u8) -> [Span<'static>; 2] {
+    let filled = (quality.min(100) as usize).div_ceil(10);
+    let color = match quality {
+        60.. => Color::Green,
+        30..=59 => Color::Yellow,
+        _ => Color::Red,
+    };
+    [
+        Span::styled("█".repeat(filled), Style::default().fg(color)),
+        Span::styled(
+            "░".repeat(10 - filled),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]
+}
 
-    if let Some(iface) = app.get_selected_interface() {
-        let enabled_str = format!(
-            "[{}] Enabled  [{}] Disabled",
-            if app.ipv6_edit_state.enabled {
-                "X"
+/// What the server handed out, one option per line
+fn lease_lines(lease: &DhcpLease) -> Vec<String> {
+    let join = |addresses: &[std::net::Ipv4Addr]| {
+        addresses
+            .iter()
+            .map(|addr| addr.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut lines = Vec::new();
+
+    if let Some(address) = lease.address {
+        let netmask = lease
+            .subnet_mask
+            .map(|mask| format!(" netmask {}", mask))
+            .unwrap_or_default();
+        lines.push(format!("  Address: {}{}", address, netmask));
+    }
+    if let Some(server) = lease.server {
+        lines.push(format!("  Server: {}", server));
+    }
+    match (lease.remaining(), lease.lease_time) {
+        (Some(remaining), lease_time) => lines.push(format!(
+            "  Remaining: {}{}",
+            if remaining.is_zero() {
+                "expired".to_string()
             } else {
-                " "
+                format_lifetime(Some(remaining.num_seconds() as u32))
             },
-            if !app.ipv6_edit_state.enabled {
-                "X"
-            } else {
-                " "
-            }
-        );
-
-        let mut items = vec![
-            ListItem::new(Line::from(vec![
-                Span::styled("Interface: ", Style::default().fg(Color::Yellow)),
-                Span::raw(&iface.name),
-            ])),
-            ListItem::new(Line::from("")),
-            ListItem::new(Line::from(vec![
-                Span::styled("IPv6 Status: ", Style::default().fg(Color::Yellow)),
-                Span::raw(&enabled_str),
-            ])),
-        ];
+            lease_time
+                .map(|secs| format!(" of {}", format_lifetime(Some(secs))))
+                .unwrap_or_default()
+        )),
+        (None, Some(lease_time)) => lines.push(format!(
+            "  Lease time: {}",
+            format_lifetime(Some(lease_time))
+        )),
+        (None, None) => {}
+    }
+    if !lease.routers.is_empty() {
+        lines.push(format!("  Router: {}", join(&lease.routers)));
+    }
+    if !lease.dns.is_empty() {
+        lines.push(format!("  DNS: {}", join(&lease.dns)));
+    }
+    if let Some(ref domain) = lease.domain {
+        lines.push(format!("  Domain: {}", domain));
+    }
+    if !lease.ntp.is_empty() {
+        lines.push(format!("  NTP: {}", join(&lease.ntp)));
+    }
+    if let Some(mtu) = lease.mtu {
+        lines.push(format!("  MTU: {}", mtu));
+    }
+    for route in &lease.classless_routes {
+        lines.push(format!("  Route: {}", route));
+    }
 
-        if app.ipv6_edit_state.enabled {
-            items.push(ListItem::new(Line::from("")));
-            items.push(ListItem::new(Line::from(vec![Span::styled(
-                "IPv6 Address (optional for static): ",
-                Style::default().fg(Color::Yellow),
-            )])));
-            items.push(ListItem::new(Line::from(vec![
-                Span::raw("  "),
-                Span::styled(
-                    if app.ipv6_edit_state.ip_buffer.is_empty() {
-                        "Auto (SLAAC)"
-                    } else {
-                        &app.ipv6_edit_state.ip_buffer
-                    },
-                    if app.ipv6_edit_state.current_field == 1 {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
-                    },
-                ),
-            ])));
+    lines
+}
 
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled("Prefix Length: ", Style::default().fg(Color::Yellow)),
-                Span::styled(
-                    &app.ipv6_edit_state.prefix_buffer,
-                    if app.ipv6_edit_state.current_field == 2 {
-                        Style::default().bg(Color::DarkGray)
-                    } else {
-                        Style::default()
-                    },
-                ),
-            ])));
-        }
+/// Remaining address lifetime, `None` meaning it never expires
+fn format_lifetime(seconds: Option<u32>) -> String {
+    match seconds {
+        None => "forever".to_string(),
+        Some(s) if s >= 3600 => format!("{}h{:02}m", s / 3600, s % 3600 / 60),
+        Some(s) if s >= 60 => format!("{}m{:02}s", s / 60, s % 60),
+        Some(s) => format!("{}s", s),
+    }
+}
 
-        let list = List::new(items).block(Block::default().borders(Borders::ALL));
+/// Inline error shown under a form field
+fn field_error(error: Option<&str>) -> Option<ListItem<'_>> {
+    error.map(|error| {
+        ListItem::new(Line::from(Span::styled(
+            format!("  ✗ {}", error),
+            Style::default().fg(Color::Red),
+        )))
+    })
+}
 
-        f.render_widget(list, chunks[1]);
+/// Non-blocking warnings listed at the bottom of a form
+fn form_warnings(warnings: Option<&[String]>) -> Vec<ListItem<'_>> {
+    let warnings = warnings.unwrap_or_default();
+    let mut items = Vec::new();
+    if !warnings.is_empty() {
+        items.push(ListItem::new(Line::from("")));
     }
-
-    let help = Paragraph::new("Tab:Next field | Space:Toggle | Enter:Apply | Esc:Cancel")
-        .style(Style::default().fg(Color::DarkGray))
-        .alignment(Alignment::Center);
-    f.render_widget(help, chunks[2]);
+    for warning in warnings {
+        items.push(ListItem::new(Line::from(Span::styled(
+            format!("  ! {}", warning),
+            Style::default().fg(Color::Yellow),
+        ))));
+    }
+    items
 }
 
 fn draw_confirm_dialog(f: &mut Frame, app: &App) {
@@ -675,6 +3287,113 @@ fn draw_confirm_dialog(f: &mut Frame, app: &App) {
     f.render_widget(buttons, chunks[1]);
 }
 
+fn draw_password_dialog(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 30, f.area());
+
+    let block = Block::default()
+        .title(" Authentication Required ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let prompt = Paragraph::new("sudo needs your password to apply this change")
+        .style(Style::default().fg(Color::Yellow))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(prompt, chunks[0]);
+
+    let masked = "*".repeat(app.password_state.buffer.chars().count());
+    let input = Paragraph::new(masked)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(" Password "));
+    f.render_widget(input, chunks[1]);
+
+    if let Some(ref error) = app.password_state.error {
+        let error = Paragraph::new(error.as_str())
+            .style(Style::default().fg(Color::Red))
+            .alignment(Alignment::Center);
+        f.render_widget(error, chunks[2]);
+    }
+
+    let buttons = Paragraph::new("Enter:Authenticate | Ctrl+u:Clear | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[3]);
+}
+
+fn draw_wifi_password_dialog(f: &mut Frame, app: &App) {
+    let Some(wifi) = &app.wifi else {
+        return;
+    };
+    let ssid = wifi
+        .selected_network()
+        .map(|network| network.ssid.as_str())
+        .unwrap_or_default();
+
+    let area = centered_rect(50, 30, f.area());
+
+    let block = Block::default()
+        .title(format!(" Password for '{}' ", ssid))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black));
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let security = wifi
+        .selected_network()
+        .map(|network| network.security.as_str())
+        .unwrap_or_default();
+    let prompt = Paragraph::new(format!(
+        "{} network; the password is saved with it",
+        security
+    ))
+    .style(Style::default().fg(Color::Yellow))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    f.render_widget(prompt, chunks[0]);
+
+    let masked = "*".repeat(wifi.password.chars().count());
+    let input = Paragraph::new(masked)
+        .style(Style::default().fg(Color::Green))
+        .block(Block::default().borders(Borders::ALL).title(" Password "));
+    f.render_widget(input, chunks[1]);
+
+    if let Some(ref error) = wifi.password_error {
+        let error = Paragraph::new(error.as_str())
+            .style(Style::default().fg(Color::Red))
+            .alignment(Alignment::Center);
+        f.render_widget(error, chunks[2]);
+    }
+
+    let buttons = Paragraph::new("Enter:Connect | Ctrl+u:Clear | Esc:Cancel")
+        .style(Style::default().fg(Color::Cyan))
+        .alignment(Alignment::Center);
+    f.render_widget(buttons, chunks[3]);
+}
+
 fn draw_help_screen(f: &mut Frame, _app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -716,20 +3435,40 @@ fn draw_help_screen(f: &mut Frame, _app: &App) {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from("  i           - Show detailed interface info"),
+        Line::from("  Space       - Expand/collapse all addresses of a row"),
         Line::from("  r           - Refresh data"),
         Line::from("  s/S         - Cycle sort column (forward/backward)"),
+        Line::from("  L           - Show/hide the Speed column"),
         Line::from("  /           - Search/filter"),
+        Line::from("  n           - Switch network namespace (host, ip netns, containers)"),
+        Line::from(
+            "  S           - All kernel and driver counters with their change (details view)",
+        ),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "Configuration (requires sudo):",
+            "Configuration (requires privileges):",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         )]),
-        Line::from("  e           - Edit IP (DHCP/Static)"),
+        Line::from("  e           - Edit IP (DHCP/Static, add/remove addresses)"),
         Line::from("  d           - Edit DNS servers"),
-        Line::from("  6           - Edit IPv6 settings"),
+        Line::from("  6           - Edit IPv6 (addresses, gateway, SLAAC, privacy, DHCPv6)"),
+        Line::from("  v           - VLAN sub-interfaces (create, delete)"),
+        Line::from("  b           - Bonds and teams (create, add/release slaves, fail over)"),
+        Line::from("  B           - Bridges (ports, STP, forwarding database)"),
         Line::from("  t           - Toggle interface up/down"),
+        Line::from("  M           - Change the MAC address (random, permanent or typed)"),
+        Line::from("  r/R         - Renew/release DHCP lease (details view)"),
+        Line::from("  p           - Probe for DHCP servers (details view)"),
+        Line::from("  w           - WiFi networks: scan, connect, disconnect (D), forget (f)"),
+        Line::from("  a/x         - Add/remove a WireGuard peer (details view)"),
+        Line::from("  K           - Generate a WireGuard keypair (details view)"),
+        Line::from("  o           - Turn offloads (TSO, GSO, GRO, checksum) on/off (details view)"),
+        Line::from(
+            "  S           - All kernel and driver counters with their change (details view)",
+        ),
+        Line::from("  u           - Undo the last change (repeat to go further back)"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Clipboard:",
@@ -750,7 +3489,10 @@ fn draw_help_screen(f: &mut Frame, _app: &App) {
         Line::from("  x           - Open terminal"),
         Line::from("                Execute commands (ping, traceroute, etc.)"),
         Line::from("                Use ↑↓ to scroll, Ctrl+l to clear"),
-        Line::from("  Ctrl+f      - Flush DNS cache (requires sudo)"),
+        Line::from("  Ctrl+f      - Flush DNS cache (requires privileges)"),
+        Line::from("  H           - Browse the audit log of configuration changes"),
+        Line::from("  C           - Subnet calculator: split, summarize, check for overlaps"),
+        Line::from("  O           - Overlapping subnets, duplicate addresses, shadowed routes"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Other:",
@@ -762,8 +3504,10 @@ fn draw_help_screen(f: &mut Frame, _app: &App) {
         Line::from("  q           - Quit"),
         Line::from(""),
         Line::from(vec![Span::styled("Note:", Style::default().fg(Color::Red))]),
-        Line::from("  Network configuration changes require sudo privileges."),
-        Line::from("  You'll be prompted for your password when needed."),
+        Line::from("  Network configuration changes require root privileges, obtained via"),
+        Line::from("  sudo, doas, pkexec or by running as root (see the details screen)."),
+        Line::from("  You'll be prompted for your sudo password when needed;"),
+        Line::from("  it is cached for sudo's timestamp window (5 minutes by default)."),
     ];
 
     let paragraph = Paragraph::new(help_text)
@@ -842,6 +3586,201 @@ fn draw_terminal_screen(f: &mut Frame, app: &App) {
     f.render_widget(help, chunks[3]);
 }
 
+fn draw_history_screen(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Percentage(40),
+            Constraint::Min(0),
+            Constraint::Length(2),
+        ])
+        .split(f.area());
+
+    let title = Paragraph::new("Configuration Change History")
+        .style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(title, chunks[0]);
+
+    // Entry list, scrolled so the selection stays visible
+    let visible = chunks[1].height.saturating_sub(2) as usize;
+    let skip = (app.history_index + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = app
+        .history
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, entry)| {
+            let style = if i == app.history_index {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{}  {}@{}  {:<10} ",
+                    entry
+                        .timestamp
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S"),
+                    entry.user,
+                    entry.host,
+                    entry.interface.as_deref().unwrap_or("-"),
+                )),
+                Span::styled(
+                    if entry.success { "OK     " } else { "FAILED " },
+                    if entry.success {
+                        Style::default().fg(Color::Green)
+                    } else {
+                        Style::default().fg(Color::Red)
+                    },
+                ),
+                Span::raw(entry.action.summary()),
+            ]))
+            .style(style)
+        })
+        .collect();
+
+    let list_title = format!(
+        " Entries ({}) - {} ",
+        app.history.len(),
+        app.audit_log_path.display()
+    );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(list_title));
+    f.render_widget(list, chunks[1]);
+
+    // Details of the selected entry
+    let mut lines = Vec::new();
+    if let Some(entry) = app.history.get(app.history_index) {
+        let heading = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+
+        if let Some(ref namespace) = entry.namespace {
+            lines.push(Line::from(vec![
+                Span::styled("Namespace: ", heading),
+                Span::raw(namespace.clone()),
+            ]));
+        }
+        if let Some(ref error) = entry.error {
+            lines.push(Line::from(vec![
+                Span::styled("Error: ", Style::default().fg(Color::Red)),
+                Span::raw(error.clone()),
+            ]));
+        }
+
+        lines.push(Line::from(Span::styled("Before:", heading)));
+        match entry.before {
+            Some(ref before) => lines.extend(snapshot_lines(before)),
+            None => lines.push(Line::from("  (not captured)")),
+        }
+        lines.push(Line::from(Span::styled("After:", heading)));
+        match entry.after {
+            Some(ref after) => lines.extend(snapshot_lines(after)),
+            None => lines.push(Line::from("  (not captured)")),
+        }
+
+        lines.push(Line::from(Span::styled("Commands:", heading)));
+        if entry.commands.is_empty() {
+            lines.push(Line::from("  (none)"));
+        }
+        for command in &entry.commands {
+            let status = command
+                .exit_status
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  [{}] ", status),
+                    if command.exit_status == Some(0) {
+                        Style::default().fg(Color::Green)
+                    } else {
+                        Style::default().fg(Color::Red)
+                    },
+                ),
+                Span::raw(command.command_line()),
+            ]));
+            if !command.stderr.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("      {}", command.stderr),
+                    Style::default().fg(Color::DarkGray),
+                )));
+            }
+        }
+    } else {
+        lines.push(Line::from("No configuration changes recorded yet"));
+    }
+
+    let details = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Details "))
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, chunks[2]);
+
+    let help = Paragraph::new("↑↓:Select entry | g/G:First/Last | Esc:Back")
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[3]);
+}
+
+fn snapshot_lines(snapshot: &StateSnapshot) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    if let Some(ref iface) = snapshot.interface {
+        let addresses = iface
+            .ip_addresses
+            .iter()
+            .map(|addr| match addr.network() {
+                Some(network) => network.to_string(),
+                None => addr.ip.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        lines.push(Line::from(format!(
+            "  Link: {}  IPv6: {}",
+            if iface.is_up { "UP" } else { "DOWN" },
+            if iface.ipv6_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        )));
+        lines.push(Line::from(format!(
+            "  Addresses: {}",
+            if addresses.is_empty() {
+                "none".to_string()
+            } else {
+                addresses
+            }
+        )));
+    }
+
+    let servers = snapshot
+        .dns
+        .nameservers
+        .iter()
+        .map(|ip| ip.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    lines.push(Line::from(format!(
+        "  DNS: [{}] search [{}]",
+        servers,
+        snapshot.dns.search_domains.join(", ")
+    )));
+
+    lines
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -0,0 +1,423 @@
+use crate::app::ConfirmAction;
+use crate::audit::StateSnapshot;
+use crate::helper::HelperRequest;
+use crate::models::{InterfaceAddress, InterfaceType};
+use crate::namespace::Namespace;
+use std::net::IpAddr;
+
+/// Helper requests that take the system from `current` back to `before`, or
+/// why that can't be done automatically.
+///
+/// Both snapshots must have been captured for the same interface. Kernel
+/// managed link-local addresses are left alone.
+pub fn plan(before: &StateSnapshot, current: &StateSnapshot) -> Result<Vec<HelperRequest>, String> {
+    let mut steps = Vec::new();
+
+    // A created VLAN, bond or bridge goes away again; a deleted one comes back
+    // with its slaves or ports, addresses and default routes
+    match (&before.interface, &current.interface) {
+        (None, Some(new)) if new.vlan.is_some() => {
+            steps.push(HelperRequest::DeleteVlan {
+                interface: new.name.clone(),
+            });
+        }
+        (None, Some(new)) if new.interface_type == InterfaceType::Bond => {
+            steps.push(HelperRequest::DeleteBond {
+                interface: new.name.clone(),
+            });
+        }
+        (None, Some(new)) if new.interface_type == InterfaceType::Bridge => {
+            steps.push(HelperRequest::DeleteBridge {
+                interface: new.name.clone(),
+            });
+        }
+        (Some(old), None) => {
+            let name = old.name.clone();
+            if let Some(ref vlan) = old.vlan {
+                steps.push(HelperRequest::CreateVlan {
+                    parent: vlan.parent.clone(),
+                    name: name.clone(),
+                    id: vlan.id,
+                    protocol: vlan.protocol,
+                    mtu: old.mtu,
+                });
+            } else if let Some(ref bond) = before.bond {
+                steps.push(HelperRequest::CreateBond {
+                    name: name.clone(),
+                    mode: bond.mode,
+                    miimon: bond.miimon,
+                });
+                for slave in &bond.slaves {
+                    steps.push(HelperRequest::EnslaveInterface {
+                        master: name.clone(),
+                        interface: slave.clone(),
+                    });
+                }
+            } else if let Some(ref bridge) = before.bridge {
+                steps.push(HelperRequest::CreateBridge {
+                    name: name.clone(),
+                    stp: bridge.stp,
+                });
+                for port in &bridge.ports {
+                    steps.push(HelperRequest::EnslaveInterface {
+                        master: name.clone(),
+                        interface: port.clone(),
+                    });
+                }
+            } else {
+                return Err(match old.interface_type {
+                    InterfaceType::Bond => {
+                        format!("{} was deleted and its bond settings are unknown", name)
+                    }
+                    InterfaceType::Bridge => {
+                        format!("{} was deleted and its bridge settings are unknown", name)
+                    }
+                    _ => format!("{} no longer exists and can't be recreated", name),
+                });
+            }
+
+            for (address, prefix, label) in managed_addresses(&old.ip_addresses) {
+                steps.push(HelperRequest::SetAddress {
+                    interface: name.clone(),
+                    address,
+                    prefix,
+                    label,
+                });
+            }
+            if old.is_up {
+                steps.push(HelperRequest::SetLink {
+                    interface: name.clone(),
+                    up: true,
+                });
+                for gateway in &before.gateways {
+                    steps.push(HelperRequest::SetRoute {
+                        interface: name.clone(),
+                        gateway: *gateway,
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+
+    if let (Some(old), Some(new)) = (&before.interface, &current.interface) {
+        let name = old.name.clone();
+
+        // Back into (or out of) its bond or bridge before anything else, as
+        // joining a bond takes the link down and drops its addresses
+        if old.master != new.master {
+            if let Some(ref master) = new.master {
+                steps.push(HelperRequest::ReleaseInterface {
+                    master: master.clone(),
+                    interface: name.clone(),
+                });
+            }
+            if let Some(ref master) = old.master {
+                steps.push(HelperRequest::EnslaveInterface {
+                    master: master.clone(),
+                    interface: name.clone(),
+                });
+            }
+        }
+
+        // Bring the link up first so addresses and routes can be restored
+        if old.is_up && !new.is_up {
+            steps.push(HelperRequest::SetLink {
+                interface: name.clone(),
+                up: true,
+            });
+        }
+
+        if old.ipv6_enabled != new.ipv6_enabled {
+            steps.push(HelperRequest::SetIpv6 {
+                interface: name.clone(),
+                enabled: old.ipv6_enabled,
+            });
+        }
+
+        if let (Some(old_settings), Some(new_settings)) = (old.ipv6_settings, new.ipv6_settings) {
+            for setting in old_settings.changes_from(&new_settings) {
+                steps.push(HelperRequest::SetIpv6Setting {
+                    interface: name.clone(),
+                    setting,
+                });
+            }
+        }
+
+        let old_addrs = managed_addresses(&old.ip_addresses);
+        let new_addrs = managed_addresses(&new.ip_addresses);
+
+        for (address, prefix, _) in new_addrs.iter().filter(|a| !old_addrs.contains(a)) {
+            steps.push(HelperRequest::DelAddress {
+                interface: name.clone(),
+                address: *address,
+                prefix: *prefix,
+            });
+        }
+        for (address, prefix, label) in old_addrs.iter().filter(|a| !new_addrs.contains(a)) {
+            steps.push(HelperRequest::SetAddress {
+                interface: name.clone(),
+                address: *address,
+                prefix: *prefix,
+                label: label.clone(),
+            });
+        }
+
+        for gateway in current
+            .gateways
+            .iter()
+            .filter(|gw| !before.gateways.contains(gw))
+        {
+            steps.push(HelperRequest::DelRoute {
+                interface: name.clone(),
+                gateway: *gateway,
+            });
+        }
+        for gateway in before
+            .gateways
+            .iter()
+            .filter(|gw| !current.gateways.contains(gw))
+        {
+            steps.push(HelperRequest::SetRoute {
+                interface: name.clone(),
+                gateway: *gateway,
+            });
+        }
+
+        // Peers are matched by key; their traffic counters always differ
+        if let (Some(old_wg), Some(new_wg)) = (&old.wireguard, &new.wireguard) {
+            for peer in new_wg
+                .peers
+                .iter()
+                .filter(|p| old_wg.peer(&p.public_key).is_none())
+            {
+                steps.push(HelperRequest::RemoveWireGuardPeer {
+                    interface: name.clone(),
+                    public_key: peer.public_key.clone(),
+                });
+            }
+            // A preshared key can't be read back, so it isn't restored
+            for peer in old_wg
+                .peers
+                .iter()
+                .filter(|p| new_wg.peer(&p.public_key).is_none())
+            {
+                steps.push(HelperRequest::AddWireGuardPeer {
+                    interface: name.clone(),
+                    peer: peer.config(),
+                });
+            }
+        }
+
+        if let (Some(old_mac), Some(new_mac)) = (old.mac_address, new.mac_address) {
+            if old_mac != new_mac {
+                steps.push(HelperRequest::SetMac {
+                    interface: name.clone(),
+                    mac: old_mac,
+                });
+            }
+        }
+
+        // Listed with dependencies first; turning one back on can bring
+        // others back by itself, leaving their steps nothing to do
+        if let (Some(old_ethtool), Some(new_ethtool)) = (&old.ethtool, &new.ethtool) {
+            for offload in old_ethtool.offloads.iter().filter(|o| !o.fixed) {
+                if new_ethtool
+                    .offload(&offload.name)
+                    .is_some_and(|now| now.enabled != offload.enabled)
+                {
+                    steps.push(HelperRequest::SetOffload {
+                        interface: name.clone(),
+                        feature: offload.name.clone(),
+                        enabled: offload.enabled,
+                    });
+                }
+            }
+        }
+
+        if !old.is_up && new.is_up {
+            steps.push(HelperRequest::SetLink {
+                interface: name,
+                up: false,
+            });
+        }
+    }
+
+    if before.dns.nameservers != current.dns.nameservers
+        || before.dns.search_domains != current.dns.search_domains
+    {
+        steps.push(HelperRequest::SetDns {
+            interface: String::new(),
+            servers: before.dns.nameservers.clone(),
+            search_domains: before.dns.search_domains.clone(),
+        });
+    }
+
+    Ok(steps)
+}
+
+fn managed_addresses(addresses: &[InterfaceAddress]) -> Vec<(IpAddr, u8, Option<String>)> {
+    addresses
+        .iter()
+        .filter(|addr| !addr.is_link_local())
+        .filter_map(|addr| {
+            addr.prefix
+                .map(|prefix| (addr.ip, prefix, addr.label.clone()))
+        })
+        .collect()
+}
+
+/// An executed action together with the state it replaced
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub action: ConfirmAction,
+    pub before: StateSnapshot,
+    /// Where the action ran; undoing it elsewhere would hit the wrong links
+    pub namespace: Option<Namespace>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{BondSnapshot, BridgeSnapshot};
+    use crate::models::{BondMode, DnsConfiguration, NetworkInterface};
+    use crate::network;
+
+    fn snapshot(interface: Option<NetworkInterface>) -> StateSnapshot {
+        StateSnapshot {
+            interface,
+            gateways: Vec::new(),
+            dns: DnsConfiguration {
+                nameservers: Vec::new(),
+                search_domains: Vec::new(),
+            },
+            bond: None,
+            bridge: None,
+        }
+    }
+
+    fn link(name: &str, interface_type: InterfaceType) -> NetworkInterface {
+        let mut iface = network::new_interface(name, false);
+        iface.interface_type = interface_type;
+        iface.ip_addresses.push(InterfaceAddress {
+            ip: "192.0.2.10".parse().unwrap(),
+            prefix: Some(24),
+            broadcast: None,
+            label: None,
+            secondary: false,
+            ipv6_info: None,
+        });
+        iface
+    }
+
+    fn describe(steps: &[HelperRequest]) -> Vec<String> {
+        steps.iter().map(|step| step.describe()).collect()
+    }
+
+    #[test]
+    fn recreates_deleted_bond() {
+        let mut before = snapshot(Some(link("bond0", InterfaceType::Bond)));
+        before.gateways = vec!["192.0.2.1".parse().unwrap()];
+        before.bond = Some(BondSnapshot {
+            mode: BondMode::Ieee8023ad,
+            miimon: Some(100),
+            slaves: vec!["eth1".to_string(), "eth2".to_string()],
+        });
+
+        let steps = plan(&before, &snapshot(None)).unwrap();
+        assert!(matches!(
+            &steps[0],
+            HelperRequest::CreateBond {
+                name,
+                mode: BondMode::Ieee8023ad,
+                miimon: Some(100),
+            } if name == "bond0"
+        ));
+        assert_eq!(
+            describe(&steps[1..]),
+            describe(&[
+                HelperRequest::EnslaveInterface {
+                    master: "bond0".to_string(),
+                    interface: "eth1".to_string(),
+                },
+                HelperRequest::EnslaveInterface {
+                    master: "bond0".to_string(),
+                    interface: "eth2".to_string(),
+                },
+                HelperRequest::SetAddress {
+                    interface: "bond0".to_string(),
+                    address: "192.0.2.10".parse().unwrap(),
+                    prefix: 24,
+                    label: None,
+                },
+                HelperRequest::SetLink {
+                    interface: "bond0".to_string(),
+                    up: true,
+                },
+                HelperRequest::SetRoute {
+                    interface: "bond0".to_string(),
+                    gateway: "192.0.2.1".parse().unwrap(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn recreates_deleted_bridge() {
+        let mut bridge = link("br0", InterfaceType::Bridge);
+        bridge.is_up = false;
+        let mut before = snapshot(Some(bridge));
+        before.bridge = Some(BridgeSnapshot {
+            stp: true,
+            ports: vec!["veth0".to_string()],
+        });
+
+        let steps = plan(&before, &snapshot(None)).unwrap();
+        assert!(matches!(
+            &steps[0],
+            HelperRequest::CreateBridge { name, stp: true } if name == "br0"
+        ));
+        assert!(matches!(
+            &steps[1],
+            HelperRequest::EnslaveInterface { master, interface }
+                if master == "br0" && interface == "veth0"
+        ));
+        assert!(matches!(&steps[2], HelperRequest::SetAddress { .. }));
+        // It was down, so it stays down
+        assert_eq!(steps.len(), 3);
+    }
+
+    #[test]
+    fn refuses_what_it_cannot_recreate() {
+        // A team device, whose settings live in teamd
+        let before = snapshot(Some(link("team0", InterfaceType::Bond)));
+        assert_eq!(
+            plan(&before, &snapshot(None)).unwrap_err(),
+            "team0 was deleted and its bond settings are unknown"
+        );
+
+        let before = snapshot(Some(link("eth9", InterfaceType::Ethernet)));
+        assert_eq!(
+            plan(&before, &snapshot(None)).unwrap_err(),
+            "eth9 no longer exists and can't be recreated"
+        );
+    }
+
+    #[test]
+    fn removes_created_bond() {
+        let created = snapshot(Some(link("bond1", InterfaceType::Bond)));
+        let steps = plan(&snapshot(None), &created).unwrap();
+        assert_eq!(
+            describe(&steps),
+            describe(&[HelperRequest::DeleteBond {
+                interface: "bond1".to_string(),
+            }])
+        );
+    }
+
+    #[test]
+    fn nothing_to_do_when_unchanged() {
+        let state = snapshot(Some(link("eth0", InterfaceType::Ethernet)));
+        assert!(plan(&state, &state).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1224 @@
+use crate::models::{
+    AllowedIp, IpNetwork, MacAddr, NetworkInterface, VlanProtocol, WifiSecurity, WireGuardInfo,
+    WireGuardPeerConfig,
+};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// Outcome of checking the static IPv4 form. Errors are per field and block
+/// the change; warnings are shown but can be confirmed anyway.
+#[derive(Debug, Clone, Default)]
+pub struct Ipv4FormCheck {
+    pub ip_error: Option<String>,
+    pub netmask_error: Option<String>,
+    pub label_error: Option<String>,
+    pub gateway_error: Option<String>,
+    pub warnings: Vec<String>,
+    /// Prefix length taken from CIDR notation or the netmask field
+    pub prefix: Option<u8>,
+}
+
+impl Ipv4FormCheck {
+    pub fn is_valid(&self) -> bool {
+        self.ip_error.is_none()
+            && self.netmask_error.is_none()
+            && self.label_error.is_none()
+            && self.gateway_error.is_none()
+    }
+}
+
+/// Outcome of checking the static IPv6 form
+#[derive(Debug, Clone, Default)]
+pub struct Ipv6FormCheck {
+    pub ip_error: Option<String>,
+    pub prefix_error: Option<String>,
+    pub gateway_error: Option<String>,
+    pub warnings: Vec<String>,
+    /// The new address, when one was entered and is valid
+    pub address: Option<(Ipv6Addr, u8)>,
+    pub gateway: Option<Ipv6Addr>,
+}
+
+impl Ipv6FormCheck {
+    pub fn is_valid(&self) -> bool {
+        self.ip_error.is_none() && self.prefix_error.is_none() && self.gateway_error.is_none()
+    }
+}
+
+/// Outcome of checking the VLAN form
+#[derive(Debug, Clone, Default)]
+pub struct VlanFormCheck {
+    pub id_error: Option<String>,
+    pub name_error: Option<String>,
+    pub mtu_error: Option<String>,
+    pub id: Option<u16>,
+    /// The entered name, or `<parent>.<id>` when left empty
+    pub name: Option<String>,
+    pub mtu: Option<u32>,
+}
+
+impl VlanFormCheck {
+    pub fn is_valid(&self) -> bool {
+        self.id_error.is_none() && self.name_error.is_none() && self.mtu_error.is_none()
+    }
+}
+
+pub fn parse_ipv4(input: &str) -> Result<Ipv4Addr, String> {
+    input
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not an IPv4 address", input.trim()))
+}
+
+/// Parse a dotted netmask, rejecting masks whose ones aren't contiguous
+pub fn netmask_to_prefix(input: &str) -> Result<u8, String> {
+    let mask = parse_ipv4(input)?;
+    if mask.is_unspecified() {
+        return Err("netmask must not be 0.0.0.0".to_string());
+    }
+
+    IpNetwork::from_netmask(IpAddr::V4(Ipv4Addr::UNSPECIFIED), IpAddr::V4(mask))
+        .map(|network| network.prefix())
+        .ok_or_else(|| format!("{} is not a contiguous netmask", input.trim()))
+}
+
+/// The dotted netmask for an IPv4 prefix length, `None` past /32
+pub fn prefix_to_netmask(prefix: u8) -> Option<IpAddr> {
+    IpNetwork::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), prefix)
+        .ok()
+        .map(|network| network.netmask())
+}
+
+fn check_unicast_v4(ip: Ipv4Addr) -> Result<(), String> {
+    if ip.is_unspecified() {
+        Err("0.0.0.0 cannot be assigned".to_string())
+    } else if ip.is_multicast() {
+        Err(format!("{} is a multicast address", ip))
+    } else if ip.is_broadcast() {
+        Err("255.255.255.255 cannot be assigned".to_string())
+    } else if ip.is_loopback() {
+        Err(format!("{} is a loopback address", ip))
+    } else {
+        Ok(())
+    }
+}
+
+/// Split `10.0.0.5/24` into the address and the prefix length
+pub fn split_cidr(input: &str) -> (&str, Option<&str>) {
+    match input.trim().split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (input.trim(), None),
+    }
+}
+
+/// An alias label must start with the interface name, e.g. `eth0:1`
+pub fn check_label(label: &str, interface: &str, address: &IpAddr) -> Result<(), String> {
+    if address.is_ipv6() {
+        return Err("labels only apply to IPv4 addresses".to_string());
+    }
+
+    let suffix = label
+        .strip_prefix(interface)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .ok_or_else(|| format!("label must look like {}:<name>", interface))?;
+
+    let valid_chars = suffix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if suffix.is_empty() || !valid_chars {
+        return Err(format!("'{}' is not a valid label", label));
+    }
+    if label.len() > 15 {
+        return Err("labels are limited to 15 characters".to_string());
+    }
+
+    Ok(())
+}
+
+/// What the kernel accepts as a link name: up to 15 characters, no slashes
+/// or whitespace. We're stricter and also keep out anything a shell or `ip`
+/// would read differently.
+pub fn is_valid_interface_name(name: &str) -> bool {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':' | '@'));
+
+    !name.is_empty() && name.len() <= 15 && valid_chars && !name.starts_with('-')
+}
+
+/// Check a new VLAN on `parent`: the id must be free on the parent for the
+/// chosen protocol, the name free on the system, and the MTU no larger than
+/// the parent's
+pub fn check_vlan_form(
+    id: &str,
+    name: &str,
+    protocol: VlanProtocol,
+    mtu: &str,
+    parent: &NetworkInterface,
+    interfaces: &[NetworkInterface],
+) -> VlanFormCheck {
+    let mut check = VlanFormCheck::default();
+
+    match id.trim().parse::<u16>() {
+        Ok(id) if (1..=4094).contains(&id) => {
+            let taken = interfaces.iter().find(|iface| {
+                iface.vlan.as_ref().is_some_and(|vlan| {
+                    vlan.parent == parent.name && vlan.id == id && vlan.protocol == protocol
+                })
+            });
+            match taken {
+                Some(iface) => {
+                    check.id_error = Some(format!("VLAN {} already exists as {}", id, iface.name))
+                }
+                None => check.id = Some(id),
+            }
+        }
+        _ => check.id_error = Some("VLAN id must be between 1 and 4094".to_string()),
+    }
+
+    let name = match (name.trim(), check.id) {
+        ("", Some(id)) => Some(format!("{}.{}", parent.name, id)),
+        ("", None) => None,
+        (name, _) => Some(name.to_string()),
+    };
+    if let Some(name) = name {
+        if !is_valid_interface_name(&name) {
+            check.name_error = Some(if name.len() > 15 {
+                format!("'{}' is longer than 15 characters; enter a name", name)
+            } else {
+                format!("'{}' is not a valid interface name", name)
+            });
+        } else if interfaces.iter().any(|iface| iface.name == name) {
+            check.name_error = Some(format!("{} already exists", name));
+        } else {
+            check.name = Some(name);
+        }
+    }
+
+    if !mtu.trim().is_empty() {
+        match (mtu.trim().parse::<u32>(), parent.mtu) {
+            (Ok(value), Some(max)) if value > max => {
+                check.mtu_error = Some(format!("MTU can't exceed {}'s MTU of {}", parent.name, max))
+            }
+            (Ok(value), _) if (68..=65535).contains(&value) => check.mtu = Some(value),
+            _ => check.mtu_error = Some("MTU must be between 68 and 65535".to_string()),
+        }
+    }
+
+    check
+}
+
+/// The first `<prefix>N` that doesn't exist yet, for an empty name field
+pub fn next_free_name(prefix: &str, interfaces: &[NetworkInterface]) -> String {
+    (0..)
+        .map(|n| format!("{}{}", prefix, n))
+        .find(|name| !interfaces.iter().any(|iface| iface.name == *name))
+        .unwrap_or_default()
+}
+
+/// Name for a new bond or bridge, `<prefix>N` when left empty
+pub fn check_new_link_name(
+    name: &str,
+    prefix: &str,
+    interfaces: &[NetworkInterface],
+) -> Result<String, String> {
+    let name = match name.trim() {
+        "" => next_free_name(prefix, interfaces),
+        name => name.to_string(),
+    };
+    if !is_valid_interface_name(&name) {
+        return Err(format!("'{}' is not a valid interface name", name));
+    }
+    if interfaces.iter().any(|iface| iface.name == name) {
+        return Err(format!("{} already exists", name));
+    }
+    Ok(name)
+}
+
+/// A new address for `interface`: a unicast MAC it doesn't already have.
+/// Another interface with the same address is reported as a warning.
+pub fn check_mac(
+    input: &str,
+    interface: &NetworkInterface,
+    interfaces: &[NetworkInterface],
+) -> Result<(MacAddr, Option<String>), String> {
+    let mac: MacAddr = input.parse()?;
+    if mac.is_zero() {
+        return Err("The all-zero address can't be used".to_string());
+    }
+    if mac.is_multicast() {
+        return Err(format!(
+            "{} is a multicast address; the first byte must be even",
+            mac
+        ));
+    }
+    if interface.mac_address == Some(mac) {
+        return Err(format!("{} already has this address", interface.name));
+    }
+
+    // Bond slaves, VLANs and macvlans share their parent's address on purpose
+    let warning = interfaces
+        .iter()
+        .find(|other| other.name != interface.name && other.mac_address == Some(mac))
+        .map(|other| format!("{} uses this address too", other.name));
+    Ok((mac, warning))
+}
+
+/// Name and link monitoring interval of a new bond; an empty interval means
+/// the driver default
+pub fn check_bond_form(
+    name: &str,
+    miimon: &str,
+    interfaces: &[NetworkInterface],
+) -> Result<(String, Option<u32>), String> {
+    let name = check_new_link_name(name, "bond", interfaces)?;
+
+    let miimon = match miimon.trim() {
+        "" => None,
+        value => match value.parse::<u32>() {
+            Ok(value) if value <= 60_000 => Some(value),
+            _ => return Err("MII polling interval must be 0-60000 ms".to_string()),
+        },
+    };
+
+    Ok((name, miimon))
+}
+
+/// WPA passphrases are 8-63 printable characters or a 64 digit hex key;
+/// SAE passwords have no fixed length
+pub fn check_wifi_password(password: &str, security: WifiSecurity) -> Result<(), String> {
+    if password.chars().any(|c| c.is_control()) {
+        return Err("password contains control characters".to_string());
+    }
+
+    match security {
+        WifiSecurity::Wpa | WifiSecurity::Wpa2Personal | WifiSecurity::Wpa2Wpa3Personal => {
+            let hex_key = password.len() == 64 && password.chars().all(|c| c.is_ascii_hexdigit());
+            if !hex_key && !(8..=63).contains(&password.chars().count()) {
+                return Err("WPA passwords must be 8 to 63 characters".to_string());
+            }
+        }
+        WifiSecurity::Wpa3Personal => {
+            if password.is_empty() || password.len() > 128 {
+                return Err("WPA3 passwords must be 1 to 128 characters".to_string());
+            }
+        }
+        WifiSecurity::Open | WifiSecurity::Owe => {
+            return Err(format!("{} networks take no password", security.as_str()))
+        }
+        _ => return Err(format!("{} networks are not supported", security.as_str())),
+    }
+
+    Ok(())
+}
+
+/// A WireGuard key: 32 bytes in base64, as `wg genkey` and `wg pubkey` print them
+pub fn decode_wireguard_key(key: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| "not a WireGuard key (44 characters of base64)".to_string())
+}
+
+/// Comma or space separated networks; a bare address means just that host.
+/// Host bits must be clear, as the kernel would silently drop them.
+pub fn parse_allowed_ips(input: &str) -> Result<Vec<AllowedIp>, String> {
+    let mut allowed_ips = Vec::new();
+
+    for entry in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+    {
+        let network: IpNetwork = entry.parse()?;
+        if !network.is_network() {
+            return Err(format!(
+                "'{}' has host bits set, use {}",
+                entry,
+                network.trunc()
+            ));
+        }
+
+        let allowed = AllowedIp {
+            address: network.ip(),
+            prefix: network.prefix(),
+        };
+        if !allowed_ips.contains(&allowed) {
+            allowed_ips.push(allowed);
+        }
+    }
+
+    Ok(allowed_ips)
+}
+
+/// Check the new peer form. Endpoints are IP:port (`[v6]:port` for IPv6), so
+/// the helper never resolves names; the keepalive is optional.
+pub fn check_wireguard_peer_form(
+    public_key: &str,
+    allowed_ips: &str,
+    endpoint: &str,
+    keepalive: &str,
+    device: &WireGuardInfo,
+) -> Result<WireGuardPeerConfig, String> {
+    decode_wireguard_key(public_key).map_err(|e| format!("Public key: {}", e))?;
+    let public_key = public_key.trim().to_string();
+    if device.public_key.as_deref() == Some(public_key.as_str()) {
+        return Err("Public key: that is this interface's own key".to_string());
+    }
+    if device.peer(&public_key).is_some() {
+        return Err("Public key: already a peer of this interface".to_string());
+    }
+
+    let allowed_ips = parse_allowed_ips(allowed_ips).map_err(|e| format!("Allowed IPs: {}", e))?;
+
+    let endpoint = match endpoint.trim() {
+        "" => None,
+        endpoint => match endpoint.parse::<SocketAddr>() {
+            Ok(addr) if addr.port() != 0 && !addr.ip().is_unspecified() => Some(addr),
+            _ => return Err("Endpoint: expected IP:port, e.g. 198.51.100.1:51820".to_string()),
+        },
+    };
+
+    let persistent_keepalive = match keepalive.trim() {
+        "" | "0" => None,
+        value => Some(
+            value
+                .parse::<u16>()
+                .map_err(|_| "Keepalive: expected seconds, 1-65535".to_string())?,
+        ),
+    };
+
+    Ok(WireGuardPeerConfig {
+        public_key,
+        endpoint,
+        allowed_ips,
+        persistent_keepalive,
+    })
+}
+
+/// Check the static IPv4 form for `interface` against the current addresses.
+/// The address may be given in CIDR notation, which overrides the netmask.
+pub fn check_ipv4_form(
+    ip: &str,
+    netmask: &str,
+    label: &str,
+    gateway: &str,
+    interface: &str,
+    interfaces: &[NetworkInterface],
+) -> Ipv4FormCheck {
+    let mut check = Ipv4FormCheck::default();
+    let (ip, cidr_prefix) = split_cidr(ip);
+
+    let ip = match parse_ipv4(ip).and_then(|ip| check_unicast_v4(ip).map(|_| ip)) {
+        Ok(ip) => Some(ip),
+        Err(e) => {
+            check.ip_error = Some(e);
+            None
+        }
+    };
+
+    let prefix = match cidr_prefix {
+        Some(prefix) => match prefix.parse::<u8>() {
+            Ok(prefix) if (1..=32).contains(&prefix) => Some(prefix),
+            _ => {
+                check
+                    .ip_error
+                    .get_or_insert_with(|| "prefix length must be between 1 and 32".to_string());
+                None
+            }
+        },
+        None => match netmask_to_prefix(netmask) {
+            Ok(prefix) => Some(prefix),
+            Err(e) => {
+                check.netmask_error = Some(e);
+                None
+            }
+        },
+    };
+    check.prefix = prefix;
+
+    if let (Some(ip), false) = (ip, label.trim().is_empty()) {
+        if let Err(e) = check_label(label.trim(), interface, &IpAddr::V4(ip)) {
+            check.label_error = Some(e);
+        }
+    }
+
+    let gateway = if gateway.trim().is_empty() {
+        None
+    } else {
+        match parse_ipv4(gateway).and_then(|gw| check_unicast_v4(gw).map(|_| gw)) {
+            Ok(gw) => Some(gw),
+            Err(e) => {
+                check.gateway_error = Some(e);
+                None
+            }
+        }
+    };
+
+    let (Some(ip), Some(prefix)) = (ip, prefix) else {
+        return check;
+    };
+
+    let subnet = IpNetwork::new(IpAddr::V4(ip), prefix).expect("prefix checked above");
+    let in_subnet = |other: Ipv4Addr| subnet.contains(IpAddr::V4(other));
+
+    let own_addresses: Vec<_> = interfaces
+        .iter()
+        .filter(|other| other.name == interface)
+        .flat_map(|iface| &iface.ip_addresses)
+        .collect();
+    if own_addresses.iter().any(|addr| addr.ip == IpAddr::V4(ip)) {
+        check.ip_error = Some(format!("{} is already assigned to {}", ip, interface));
+        return check;
+    }
+    let primary = own_addresses.iter().find_map(|addr| match addr.ip {
+        IpAddr::V4(other) if addr.prefix == Some(prefix) && in_subnet(other) => Some(other),
+        _ => None,
+    });
+    if let Some(primary) = primary {
+        check.warnings.push(format!(
+            "{} will be a secondary address next to {}",
+            ip, primary
+        ));
+    }
+
+    // /31 and /32 have no network or broadcast address (RFC 3021)
+    if prefix <= 30 {
+        if IpAddr::V4(ip) == subnet.network() {
+            check.warnings.push(format!(
+                "{} is the network address of {}",
+                ip,
+                subnet.trunc()
+            ));
+        } else if IpAddr::V4(ip) == subnet.last() {
+            check.warnings.push(format!(
+                "{} is the broadcast address of {}",
+                ip,
+                subnet.trunc()
+            ));
+        }
+    }
+
+    if let Some(gw) = gateway {
+        if gw == ip {
+            check.gateway_error = Some("gateway cannot be the interface's own address".to_string());
+        } else if !in_subnet(gw) {
+            check.gateway_error = Some(format!("{} is outside {}", gw, subnet.trunc()));
+        } else if prefix <= 30
+            && (IpAddr::V4(gw) == subnet.network() || IpAddr::V4(gw) == subnet.last())
+        {
+            check.gateway_error = Some(format!(
+                "{} is the network or broadcast address of the subnet",
+                gw
+            ));
+        }
+    }
+
+    check
+        .warnings
+        .extend(collisions(&subnet, interface, interfaces));
+    check
+}
+
+/// Subnets on other interfaces that overlap with `subnet`
+fn collisions(subnet: &IpNetwork, interface: &str, interfaces: &[NetworkInterface]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for other in interfaces.iter().filter(|other| other.name != interface) {
+        for addr in &other.ip_addresses {
+            let Some(other_subnet) = addr.network().filter(|network| network.is_ipv4()) else {
+                continue;
+            };
+            if addr.ip.is_loopback() {
+                continue;
+            }
+
+            if addr.ip == subnet.ip() {
+                warnings.push(format!("{} is already assigned to {}", addr.ip, other.name));
+                continue;
+            }
+
+            if subnet.overlaps(&other_subnet) {
+                warnings.push(format!(
+                    "{} overlaps {} on {}",
+                    subnet.trunc(),
+                    other_subnet.trunc(),
+                    other.name
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+fn check_unicast_v6(ip: Ipv6Addr) -> Result<(), String> {
+    if ip.is_unspecified() {
+        Err(":: cannot be assigned".to_string())
+    } else if ip.is_multicast() {
+        Err(format!("{} is a multicast address", ip))
+    } else if ip.is_loopback() {
+        Err("::1 is the loopback address".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_ipv6(input: &str) -> Result<Ipv6Addr, String> {
+    let ip = input
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not an IPv6 address", input.trim()))?;
+    check_unicast_v6(ip)?;
+    Ok(ip)
+}
+
+/// Check the IPv6 form. The new address is optional and may be given in CIDR
+/// notation, which overrides the prefix field; so is the default gateway.
+pub fn check_ipv6_form(
+    ip: &str,
+    prefix: &str,
+    gateway: &str,
+    interface: &str,
+    interfaces: &[NetworkInterface],
+) -> Ipv6FormCheck {
+    let mut check = Ipv6FormCheck::default();
+
+    if !gateway.trim().is_empty() {
+        match parse_ipv6(gateway) {
+            Ok(gw) => check.gateway = Some(gw),
+            Err(e) => check.gateway_error = Some(e),
+        }
+    }
+
+    if ip.trim().is_empty() {
+        return check;
+    }
+
+    let (ip, cidr_prefix) = split_cidr(ip);
+    let address = match parse_ipv6(ip) {
+        Ok(addr) => Some(addr),
+        Err(e) => {
+            check.ip_error = Some(e);
+            None
+        }
+    };
+
+    let prefix = match cidr_prefix.unwrap_or(prefix).trim().parse::<u8>() {
+        Ok(p) if (1..=128).contains(&p) => Some(p),
+        _ => {
+            let error = "prefix length must be between 1 and 128".to_string();
+            if cidr_prefix.is_some() {
+                check.ip_error.get_or_insert(error);
+            } else {
+                check.prefix_error = Some(error);
+            }
+            None
+        }
+    };
+
+    let (Some(address), Some(prefix)) = (address, prefix) else {
+        return check;
+    };
+
+    for iface in interfaces {
+        if iface
+            .ip_addresses
+            .iter()
+            .any(|a| a.ip == IpAddr::V6(address))
+        {
+            if iface.name == interface {
+                check.ip_error = Some(format!("{} is already assigned to {}", address, interface));
+                return check;
+            }
+            check
+                .warnings
+                .push(format!("{} is already assigned to {}", address, iface.name));
+        }
+    }
+
+    if prefix > 64 {
+        check
+            .warnings
+            .push(format!("/{} is longer than /64; SLAAC needs a /64", prefix));
+    }
+
+    check.address = Some((address, prefix));
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InterfaceAddress, VlanInfo};
+    use crate::network;
+
+    fn link(name: &str, mtu: u32) -> NetworkInterface {
+        let mut iface = network::new_interface(name, false);
+        iface.mtu = Some(mtu);
+        iface
+    }
+
+    fn vlan(name: &str, parent: &str, id: u16, protocol: VlanProtocol) -> NetworkInterface {
+        let mut iface = link(name, 1500);
+        iface.vlan = Some(VlanInfo {
+            parent: parent.to_string(),
+            id,
+            protocol,
+        });
+        iface
+    }
+
+    fn interfaces() -> Vec<NetworkInterface> {
+        vec![
+            link("eth0", 1500),
+            link("eth1", 9000),
+            vlan("eth0.10", "eth0", 10, VlanProtocol::Dot1Q),
+            vlan("guests", "eth0", 20, VlanProtocol::Dot1Q),
+            vlan("eth0.30", "eth0", 30, VlanProtocol::Dot1Ad),
+        ]
+    }
+
+    fn check(id: &str, name: &str, protocol: VlanProtocol, mtu: &str) -> VlanFormCheck {
+        let interfaces = interfaces();
+        check_vlan_form(id, name, protocol, mtu, &interfaces[0], &interfaces)
+    }
+
+    fn addressed(name: &str, addresses: &[&str]) -> NetworkInterface {
+        let mut iface = link(name, 1500);
+        iface.ip_addresses = addresses
+            .iter()
+            .map(|address| {
+                let network: IpNetwork = address.parse().unwrap();
+                InterfaceAddress {
+                    ip: network.ip(),
+                    prefix: Some(network.prefix()),
+                    broadcast: None,
+                    label: None,
+                    secondary: false,
+                    ipv6_info: None,
+                }
+            })
+            .collect();
+        iface
+    }
+
+    fn hosts() -> Vec<NetworkInterface> {
+        vec![
+            addressed("lo", &["127.0.0.1/8", "::1/128"]),
+            addressed("eth0", &["192.168.1.10/24", "2001:db8:1::10/64"]),
+            addressed("eth1", &["10.0.0.1/16", "2001:db8:2::1/64"]),
+        ]
+    }
+
+    fn ipv4(ip: &str, netmask: &str, gateway: &str) -> Ipv4FormCheck {
+        check_ipv4_form(ip, netmask, "", gateway, "eth0", &hosts())
+    }
+
+    #[test]
+    fn ipv4_valid_address() {
+        let check = ipv4("192.168.5.20", "255.255.255.0", "192.168.5.1");
+        assert!(check.is_valid(), "{:?}", check);
+        assert_eq!(check.prefix, Some(24));
+        assert!(check.warnings.is_empty(), "{:?}", check.warnings);
+    }
+
+    #[test]
+    fn ipv4_address_errors() {
+        let cases = [
+            ("", "'' is not an IPv4 address"),
+            ("192.168.5", "'192.168.5' is not an IPv4 address"),
+            ("2001:db8::1", "'2001:db8::1' is not an IPv4 address"),
+            ("0.0.0.0", "0.0.0.0 cannot be assigned"),
+            ("224.0.0.5", "224.0.0.5 is a multicast address"),
+            ("255.255.255.255", "255.255.255.255 cannot be assigned"),
+            ("127.0.0.2", "127.0.0.2 is a loopback address"),
+            ("192.168.1.10", "192.168.1.10 is already assigned to eth0"),
+        ];
+        for (ip, error) in cases {
+            let check = ipv4(ip, "255.255.255.0", "");
+            assert_eq!(check.ip_error.as_deref(), Some(error), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn ipv4_cidr_overrides_netmask() {
+        // The netmask field is ignored, even when it's invalid
+        let check = ipv4("192.168.5.20/26", "255.0.255.0", "");
+        assert!(check.is_valid(), "{:?}", check);
+        assert_eq!(check.prefix, Some(26));
+        assert_eq!(check.netmask_error, None);
+
+        let check = ipv4("192.168.5.20", "255.0.255.0", "");
+        assert_eq!(
+            check.netmask_error.as_deref(),
+            Some("255.0.255.0 is not a contiguous netmask")
+        );
+        assert_eq!(check.prefix, None);
+
+        for ip in ["192.168.5.20/0", "192.168.5.20/33", "192.168.5.20/x"] {
+            let check = ipv4(ip, "255.255.255.0", "");
+            assert_eq!(
+                check.ip_error.as_deref(),
+                Some("prefix length must be between 1 and 32"),
+                "{}",
+                ip
+            );
+            assert_eq!(check.prefix, None);
+        }
+    }
+
+    #[test]
+    fn ipv4_network_and_broadcast_addresses() {
+        let check = ipv4("192.168.5.0/24", "", "");
+        assert!(check.is_valid());
+        assert_eq!(
+            check.warnings,
+            ["192.168.5.0 is the network address of 192.168.5.0/24"]
+        );
+
+        let check = ipv4("192.168.5.63/26", "", "");
+        assert_eq!(
+            check.warnings,
+            ["192.168.5.63 is the broadcast address of 192.168.5.0/26"]
+        );
+
+        let check = ipv4("192.168.5.8/30", "", "");
+        assert_eq!(
+            check.warnings,
+            ["192.168.5.8 is the network address of 192.168.5.8/30"]
+        );
+    }
+
+    #[test]
+    fn ipv4_point_to_point_exceptions() {
+        // Both addresses of a /31 are usable (RFC 3021), as is a /32
+        for ip in ["192.168.5.0/31", "192.168.5.1/31", "192.168.5.7/32"] {
+            let check = ipv4(ip, "", "");
+            assert!(check.is_valid(), "{}", ip);
+            assert!(check.warnings.is_empty(), "{}: {:?}", ip, check.warnings);
+        }
+
+        let check = ipv4("192.168.5.0/31", "", "192.168.5.1");
+        assert!(check.is_valid(), "{:?}", check);
+    }
+
+    #[test]
+    fn ipv4_gateway() {
+        let cases = [
+            ("192.168.6.1", "192.168.6.1 is outside 192.168.5.0/24"),
+            ("10.0.0.1", "10.0.0.1 is outside 192.168.5.0/24"),
+            (
+                "192.168.5.20",
+                "gateway cannot be the interface's own address",
+            ),
+            (
+                "192.168.5.0",
+                "192.168.5.0 is the network or broadcast address of the subnet",
+            ),
+            (
+                "192.168.5.255",
+                "192.168.5.255 is the network or broadcast address of the subnet",
+            ),
+            ("gateway", "'gateway' is not an IPv4 address"),
+            ("224.0.0.1", "224.0.0.1 is a multicast address"),
+        ];
+        for (gateway, error) in cases {
+            let check = ipv4("192.168.5.20/24", "", gateway);
+            assert_eq!(check.gateway_error.as_deref(), Some(error), "{}", gateway);
+        }
+
+        // Only the /32 itself is inside a /32
+        let check = ipv4("192.168.5.20/32", "", "192.168.5.1");
+        assert_eq!(
+            check.gateway_error.as_deref(),
+            Some("192.168.5.1 is outside 192.168.5.20/32")
+        );
+    }
+
+    #[test]
+    fn ipv4_secondary_and_collisions() {
+        let check = ipv4("192.168.1.20/24", "", "");
+        assert!(check.is_valid());
+        assert_eq!(
+            check.warnings,
+            ["192.168.1.20 will be a secondary address next to 192.168.1.10"]
+        );
+
+        let check = ipv4("10.0.5.1/24", "", "");
+        assert!(check.is_valid());
+        assert_eq!(check.warnings, ["10.0.5.0/24 overlaps 10.0.0.0/16 on eth1"]);
+
+        let check = ipv4("10.0.0.1/16", "", "");
+        assert!(check.is_valid());
+        assert_eq!(check.warnings, ["10.0.0.1 is already assigned to eth1",]);
+
+        // The loopback range is refused outright rather than reported as a collision
+        let check = check_ipv4_form("127.1.0.1/8", "", "", "", "eth0", &hosts());
+        assert_eq!(
+            check.ip_error.as_deref(),
+            Some("127.1.0.1 is a loopback address")
+        );
+    }
+
+    #[test]
+    fn ipv4_label() {
+        let check = check_ipv4_form("192.168.5.20/24", "", "eth0:web", "", "eth0", &hosts());
+        assert!(check.is_valid());
+        let check = check_ipv4_form("192.168.5.20/24", "", "eth1:web", "", "eth0", &hosts());
+        assert_eq!(
+            check.label_error.as_deref(),
+            Some("label must look like eth0:<name>")
+        );
+    }
+
+    fn ipv6(ip: &str, prefix: &str, gateway: &str) -> Ipv6FormCheck {
+        check_ipv6_form(ip, prefix, gateway, "eth0", &hosts())
+    }
+
+    #[test]
+    fn ipv6_address_and_prefix() {
+        let check = ipv6("2001:db8:5::20", "64", "fe80::1");
+        assert!(check.is_valid(), "{:?}", check);
+        assert_eq!(check.address, Some(("2001:db8:5::20".parse().unwrap(), 64)));
+        assert_eq!(check.gateway, Some("fe80::1".parse().unwrap()));
+        assert!(check.warnings.is_empty());
+
+        // CIDR overrides the prefix field, even an invalid one
+        let check = ipv6("2001:db8:5::20/56", "nonsense", "");
+        assert!(check.is_valid(), "{:?}", check);
+        assert_eq!(check.address, Some(("2001:db8:5::20".parse().unwrap(), 56)));
+
+        let check = ipv6("2001:db8:5::20/129", "64", "");
+        assert_eq!(
+            check.ip_error.as_deref(),
+            Some("prefix length must be between 1 and 128")
+        );
+        let check = ipv6("2001:db8:5::20", "0", "");
+        assert_eq!(
+            check.prefix_error.as_deref(),
+            Some("prefix length must be between 1 and 128")
+        );
+        assert_eq!(check.address, None);
+
+        let check = ipv6("2001:db8:5::20/120", "", "");
+        assert!(check.is_valid());
+        assert_eq!(
+            check.warnings,
+            ["/120 is longer than /64; SLAAC needs a /64"]
+        );
+    }
+
+    #[test]
+    fn ipv6_errors() {
+        let cases = [
+            ("192.168.1.1", "'192.168.1.1' is not an IPv6 address"),
+            ("::", ":: cannot be assigned"),
+            ("::1", "::1 is the loopback address"),
+            ("ff02::1", "ff02::1 is a multicast address"),
+            (
+                "2001:db8:1::10",
+                "2001:db8:1::10 is already assigned to eth0",
+            ),
+        ];
+        for (ip, error) in cases {
+            let check = ipv6(ip, "64", "");
+            assert_eq!(check.ip_error.as_deref(), Some(error), "{}", ip);
+        }
+
+        let check = ipv6("", "", "ff02::2");
+        assert_eq!(
+            check.gateway_error.as_deref(),
+            Some("ff02::2 is a multicast address")
+        );
+        // Only a gateway is fine too
+        let check = ipv6("", "", "fe80::1");
+        assert!(check.is_valid());
+        assert_eq!(check.address, None);
+    }
+
+    #[test]
+    fn ipv6_collision_on_other_interface() {
+        let check = ipv6("2001:db8:2::1", "64", "");
+        assert!(check.is_valid());
+        assert_eq!(
+            check.warnings,
+            ["2001:db8:2::1 is already assigned to eth1"]
+        );
+    }
+
+    #[test]
+    fn netmask_prefix_round_trip() {
+        for prefix in 1..=32 {
+            let mask = prefix_to_netmask(prefix).unwrap();
+            assert_eq!(netmask_to_prefix(&mask.to_string()), Ok(prefix));
+        }
+        assert_eq!(prefix_to_netmask(0), Some("0.0.0.0".parse().unwrap()));
+        assert_eq!(prefix_to_netmask(33), None);
+
+        assert_eq!(netmask_to_prefix(" 255.255.252.0 "), Ok(22));
+        assert_eq!(
+            netmask_to_prefix("0.0.0.0"),
+            Err("netmask must not be 0.0.0.0".to_string())
+        );
+        assert_eq!(
+            netmask_to_prefix("255.0.255.0"),
+            Err("255.0.255.0 is not a contiguous netmask".to_string())
+        );
+        assert!(netmask_to_prefix("/24").is_err());
+    }
+
+    #[test]
+    fn vlan_id_range() {
+        for id in ["1", "100", " 4094 "] {
+            let result = check(id, "", VlanProtocol::Dot1Q, "");
+            assert!(result.is_valid(), "{}", id);
+            assert_eq!(result.id, Some(id.trim().parse().unwrap()));
+        }
+        for id in ["0", "4095", "65536", "-1", "", "ten"] {
+            let result = check(id, "", VlanProtocol::Dot1Q, "");
+            assert_eq!(
+                result.id_error.as_deref(),
+                Some("VLAN id must be between 1 and 4094"),
+                "{}",
+                id
+            );
+            assert_eq!(result.id, None);
+        }
+    }
+
+    #[test]
+    fn vlan_id_taken_on_parent() {
+        let result = check("10", "voice", VlanProtocol::Dot1Q, "");
+        assert_eq!(
+            result.id_error.as_deref(),
+            Some("VLAN 10 already exists as eth0.10")
+        );
+        // Found under its custom name too
+        let result = check("20", "", VlanProtocol::Dot1Q, "");
+        assert_eq!(
+            result.id_error.as_deref(),
+            Some("VLAN 20 already exists as guests")
+        );
+        assert_eq!(result.name, None);
+
+        // The same id is free with the other tag protocol...
+        let result = check("10", "eth0.10ad", VlanProtocol::Dot1Ad, "");
+        assert!(result.is_valid());
+        let result = check("30", "eth0.30q", VlanProtocol::Dot1Q, "");
+        assert!(result.is_valid());
+
+        // ...and on another parent
+        let interfaces = interfaces();
+        let result = check_vlan_form(
+            "10",
+            "",
+            VlanProtocol::Dot1Q,
+            "",
+            &interfaces[1],
+            &interfaces,
+        );
+        assert!(result.is_valid());
+        assert_eq!(result.name.as_deref(), Some("eth1.10"));
+    }
+
+    #[test]
+    fn vlan_name() {
+        assert_eq!(
+            check("40", "", VlanProtocol::Dot1Q, "").name.as_deref(),
+            Some("eth0.40")
+        );
+        assert_eq!(
+            check("40", " voice ", VlanProtocol::Dot1Q, "")
+                .name
+                .as_deref(),
+            Some("voice")
+        );
+
+        // 15 characters is the kernel's limit
+        let result = check("40", "abcdefghijklmno", VlanProtocol::Dot1Q, "");
+        assert!(result.is_valid());
+        let result = check("40", "abcdefghijklmnop", VlanProtocol::Dot1Q, "");
+        assert_eq!(
+            result.name_error.as_deref(),
+            Some("'abcdefghijklmnop' is longer than 15 characters; enter a name")
+        );
+
+        // A long parent makes the default name too long
+        let parent = link("enp0s31f6abcd", 1500);
+        let interfaces = vec![parent.clone()];
+        let result = check_vlan_form("4000", "", VlanProtocol::Dot1Q, "", &parent, &interfaces);
+        assert_eq!(
+            result.name_error.as_deref(),
+            Some("'enp0s31f6abcd.4000' is longer than 15 characters; enter a name")
+        );
+
+        let result = check("40", "my vlan", VlanProtocol::Dot1Q, "");
+        assert_eq!(
+            result.name_error.as_deref(),
+            Some("'my vlan' is not a valid interface name")
+        );
+        let result = check("40", "guests", VlanProtocol::Dot1Q, "");
+        assert_eq!(result.name_error.as_deref(), Some("guests already exists"));
+
+        // Without a valid id there is no default name to check
+        let result = check("0", "", VlanProtocol::Dot1Q, "");
+        assert_eq!(result.name, None);
+        assert_eq!(result.name_error, None);
+    }
+
+    #[test]
+    fn vlan_mtu() {
+        assert_eq!(check("40", "", VlanProtocol::Dot1Q, "").mtu, None);
+        assert_eq!(check("40", "", VlanProtocol::Dot1Q, "1400").mtu, Some(1400));
+        assert_eq!(
+            check("40", "", VlanProtocol::Dot1Q, "9000")
+                .mtu_error
+                .as_deref(),
+            Some("MTU can't exceed eth0's MTU of 1500")
+        );
+        assert_eq!(
+            check("40", "", VlanProtocol::Dot1Q, "67")
+                .mtu_error
+                .as_deref(),
+            Some("MTU must be between 68 and 65535")
+        );
+    }
+
+    /// Runs itself again inside `unshare -rn`, where it may create links
+    #[test]
+    #[ignore = "needs unprivileged user namespaces"]
+    fn vlan_on_dummy_link() {
+        use std::process::Command;
+
+        if std::env::var_os("NETUTIL_TEST_NETNS").is_none() {
+            let status = Command::new("unshare")
+                .args(["-rn"])
+                .arg(std::env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "validation::tests::vlan_on_dummy_link",
+                    "--ignored",
+                    "--nocapture",
+                ])
+                .env("NETUTIL_TEST_NETNS", "1")
+                .status()
+                .expect("run unshare");
+            assert!(status.success());
+            return;
+        }
+
+        let ip = |args: &[&str]| {
+            let status = Command::new("ip").args(args).status().expect("run ip");
+            assert!(status.success(), "ip {}", args.join(" "));
+        };
+        ip(&["link", "add", "dummy0", "mtu", "1500", "type", "dummy"]);
+
+        let find = |interfaces: &[NetworkInterface], name: &str| {
+            interfaces
+                .iter()
+                .find(|iface| iface.name == name)
+                .cloned()
+                .unwrap()
+        };
+        let interfaces = network::get_network_interfaces().unwrap();
+        let parent = find(&interfaces, "dummy0");
+        let form = check_vlan_form("100", "", VlanProtocol::Dot1Q, "1400", &parent, &interfaces);
+        assert!(form.is_valid());
+        let name = form.name.unwrap();
+        assert_eq!(name, "dummy0.100");
+
+        ip(&[
+            "link",
+            "add",
+            "link",
+            "dummy0",
+            "name",
+            &name,
+            "type",
+            "vlan",
+            "protocol",
+            VlanProtocol::Dot1Q.as_str(),
+            "id",
+            "100",
+            "mtu",
+            "1400",
+        ]);
+
+        let interfaces = network::get_network_interfaces().unwrap();
+        let created = find(&interfaces, &name);
+        assert_eq!(
+            created.vlan,
+            Some(VlanInfo {
+                parent: "dummy0".to_string(),
+                id: 100,
+                protocol: VlanProtocol::Dot1Q,
+            })
+        );
+        assert_eq!(created.mtu, Some(1400));
+
+        // Now the kernel knows about it, the form refuses a second one
+        let parent = find(&interfaces, "dummy0");
+        let form = check_vlan_form(
+            "100",
+            "other",
+            VlanProtocol::Dot1Q,
+            "",
+            &parent,
+            &interfaces,
+        );
+        assert_eq!(
+            form.id_error.as_deref(),
+            Some("VLAN 100 already exists as dummy0.100")
+        );
+        let form = check_vlan_form("100", "", VlanProtocol::Dot1Ad, "", &parent, &interfaces);
+        assert_eq!(
+            form.name_error.as_deref(),
+            Some("dummy0.100 already exists")
+        );
+    }
+
+    #[test]
+    fn mac_checks() {
+        let mac = |input: &str| input.parse::<MacAddr>().unwrap();
+        let mut interfaces = interfaces();
+        interfaces[0].mac_address = Some(mac("52:54:00:12:34:56"));
+        interfaces[1].mac_address = Some(mac("52:54:00:ab:cd:ef"));
+        let check = |input: &str| check_mac(input, &interfaces[0], &interfaces);
+
+        assert_eq!(
+            check("02:00:00:00:00:01"),
+            Ok((mac("02:00:00:00:00:01"), None))
+        );
+        assert_eq!(
+            check("52-54-00-AB-CD-EF"),
+            Ok((
+                mac("52:54:00:ab:cd:ef"),
+                Some("eth1 uses this address too".to_string())
+            ))
+        );
+
+        for (input, error) in [
+            ("00:00:00:00:00:00", "all-zero"),
+            ("01:00:5e:00:00:01", "multicast"),
+            ("ff:ff:ff:ff:ff:ff", "multicast"),
+            ("52:54:00:12:34:56", "already has"),
+            ("52:54:00:12:34", ""),
+        ] {
+            let result = check(input);
+            assert!(
+                result.as_ref().is_err_and(|e| e.contains(error)),
+                "{}: {:?}",
+                input,
+                result
+            );
+        }
+
+        // What the randomize key produces always passes
+        for _ in 0..100 {
+            let random = MacAddr::random_local().to_string();
+            assert!(check(&random).is_ok(), "{}", random);
+        }
+    }
+}
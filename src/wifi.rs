@@ -0,0 +1,919 @@
+//! Nearby WiFi networks and the daemon that manages the interface.
+//!
+//! NetworkManager is driven through `nmcli`; without it we talk to
+//! wpa_supplicant over its control socket. Both are asked for the same
+//! things - scan results and saved networks - and the parsers only see text,
+//! so they work without either daemon running.
+
+use crate::models::{self, WifiSecurity};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Where wpa_supplicant puts its per-interface control sockets
+const WPA_CTRL_DIRS: &[&str] = &["/run/wpa_supplicant", "/var/run/wpa_supplicant"];
+
+/// Our end of each control socket. wpa_supplicant answers any client that
+/// can reach it, so the directory is kept private to root.
+const WPA_CLIENT_DIR: &str = "/run/netutil";
+
+/// Scans take a few seconds; wpa_supplicant only answers SCAN with OK
+const WPA_SCAN_WAIT: Duration = Duration::from_millis(2500);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WifiNetwork {
+    /// Empty for hidden networks
+    pub ssid: String,
+    pub bssid: String,
+    /// MHz
+    pub frequency: Option<u32>,
+    /// 0-100
+    pub signal: u8,
+    pub security: WifiSecurity,
+    /// Currently associated
+    pub in_use: bool,
+    /// A saved profile exists, so connecting needs no password
+    pub saved: bool,
+}
+
+impl WifiNetwork {
+    pub fn channel(&self) -> Option<u32> {
+        models::wifi_channel(self.frequency?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WifiBackend {
+    NetworkManager,
+    /// Path of the interface's control socket
+    WpaSupplicant(PathBuf),
+}
+
+/// Find the daemon managing `interface`: NetworkManager unless it leaves the
+/// interface unmanaged, otherwise wpa_supplicant if it has a control socket
+pub fn backend(interface: &str) -> Result<WifiBackend> {
+    if let Ok(output) = Command::new("nmcli")
+        .args(["-t", "-f", "DEVICE,STATE", "device"])
+        .output()
+    {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let managed = stdout.lines().any(|line| {
+            let fields = split_terse(line);
+            fields.len() == 2 && fields[0] == interface && fields[1] != "unmanaged"
+        });
+        if output.status.success() && managed {
+            return Ok(WifiBackend::NetworkManager);
+        }
+    }
+
+    WPA_CTRL_DIRS
+        .iter()
+        .map(|dir| Path::new(dir).join(interface))
+        .find(|path| path.exists())
+        .map(WifiBackend::WpaSupplicant)
+        .with_context(|| {
+            format!(
+                "Neither NetworkManager nor wpa_supplicant manages {}",
+                interface
+            )
+        })
+}
+
+/// Scan and list nearby networks, strongest first, one entry per BSS
+pub fn scan(interface: &str) -> Result<Vec<WifiNetwork>> {
+    let mut networks = match backend(interface)? {
+        WifiBackend::NetworkManager => scan_networkmanager(interface)?,
+        WifiBackend::WpaSupplicant(path) => scan_wpa_supplicant(&path)?,
+    };
+    networks.sort_by_key(|network| std::cmp::Reverse(network.signal));
+    Ok(networks)
+}
+
+fn scan_networkmanager(interface: &str) -> Result<Vec<WifiNetwork>> {
+    let list = nmcli(&[
+        "-t",
+        "-f",
+        "IN-USE,SSID,BSSID,SIGNAL,FREQ,SECURITY",
+        "device",
+        "wifi",
+        "list",
+        "ifname",
+        interface,
+        "--rescan",
+        "auto",
+    ])?;
+    let saved = nmcli(&["-t", "-f", "NAME,TYPE", "connection", "show"])?;
+    let saved = parse_nmcli_connections(&saved);
+
+    let mut networks = parse_nmcli_list(&list);
+    for network in &mut networks {
+        network.saved = saved.contains(&network.ssid);
+    }
+    Ok(networks)
+}
+
+fn nmcli(args: &[&str]) -> Result<String> {
+    let output = Command::new("nmcli")
+        .args(args)
+        .output()
+        .context("Failed to run nmcli")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "nmcli failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn scan_wpa_supplicant(path: &Path) -> Result<Vec<WifiNetwork>> {
+    let ctrl = WpaCtrl::open(path)?;
+    // FAIL-BUSY means a scan is already running, whose results we'll get too
+    let reply = ctrl.request("SCAN")?;
+    if reply.trim() != "OK" && reply.trim() != "FAIL-BUSY" {
+        anyhow::bail!("wpa_supplicant refused to scan: {}", reply.trim());
+    }
+    std::thread::sleep(WPA_SCAN_WAIT);
+
+    let mut networks = parse_scan_results(&ctrl.request("SCAN_RESULTS")?);
+    let saved = parse_list_networks(&ctrl.request("LIST_NETWORKS")?);
+    let current = parse_status(&ctrl.request("STATUS")?, "bssid");
+
+    for network in &mut networks {
+        network.saved = saved.iter().any(|(_, ssid)| *ssid == network.ssid);
+        network.in_use = current.as_deref() == Some(network.bssid.as_str());
+    }
+    Ok(networks)
+}
+
+/// Client end of a wpa_supplicant control socket.
+///
+/// Replies are sent back to the address we bind to, so each client gets its
+/// own socket file in `WPA_CLIENT_DIR`, removed again on drop.
+pub struct WpaCtrl {
+    socket: UnixDatagram,
+    local: PathBuf,
+}
+
+impl WpaCtrl {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_in(path, Path::new(WPA_CLIENT_DIR))
+    }
+
+    fn open_in(path: &Path, dir: &Path) -> Result<Self> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        private_dir(dir)?;
+        let local = dir.join(format!(
+            "wpa-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        // Left behind by an earlier process with the same pid
+        match std::fs::remove_file(&local) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(e).with_context(|| format!("Failed to remove {}", local.display()))
+            }
+            _ => {}
+        }
+
+        let socket = UnixDatagram::bind(&local)
+            .with_context(|| format!("Failed to bind {}", local.display()))?;
+        let ctrl = Self { socket, local };
+        ctrl.socket
+            .connect(path)
+            .with_context(|| format!("Failed to connect to {}", path.display()))?;
+        ctrl.socket.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+        Ok(ctrl)
+    }
+
+    /// Send a command and return the reply, skipping unsolicited events
+    pub fn request(&self, command: &str) -> Result<String> {
+        self.socket
+            .send(command.as_bytes())
+            .with_context(|| format!("Failed to send {}", redact(command)))?;
+
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let len = self
+                .socket
+                .recv(&mut buf)
+                .with_context(|| format!("No reply from wpa_supplicant to {}", redact(command)))?;
+            // Events ("<3>CTRL-EVENT-...") only go to attached clients, but a
+            // stale client that used the same socket path may have attached
+            if buf.first() == Some(&b'<') {
+                continue;
+            }
+            return Ok(String::from_utf8_lossy(&buf[..len]).to_string());
+        }
+    }
+
+    /// Send a command that answers OK on success
+    pub fn command(&self, command: &str) -> Result<()> {
+        let reply = self.request(command)?;
+        if reply.trim() != "OK" {
+            anyhow::bail!(
+                "wpa_supplicant: {} returned {}",
+                redact(command),
+                reply.trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WpaCtrl {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.local);
+    }
+}
+
+/// Create `dir` as 0700, or make sure an existing one is ours and private
+fn private_dir(dir: &Path) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => {
+            return Err(e).with_context(|| format!("Failed to create {}", dir.display()))
+        }
+        _ => {}
+    }
+
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != nix::unistd::geteuid().as_raw() {
+        anyhow::bail!("{} is not a directory owned by us", dir.display());
+    }
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Commands with secrets in them, as they may appear in logs and errors
+pub fn redact(command: &str) -> String {
+    let mut words: Vec<&str> = command.splitn(4, ' ').collect();
+    if words.len() == 4 && matches!(words[0], "SET_NETWORK") && is_secret(words[2]) {
+        words[3] = "********";
+    }
+    words.join(" ")
+}
+
+fn is_secret(variable: &str) -> bool {
+    matches!(variable, "psk" | "sae_password" | "password" | "wep_key0")
+}
+
+/// `SCAN_RESULTS`: a header, then bssid, frequency, signal level, flags and
+/// SSID separated by tabs
+pub fn parse_scan_results(text: &str) -> Vec<WifiNetwork> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.splitn(5, '\t');
+            let bssid = fields.next()?.to_string();
+            let frequency = fields.next()?.parse().ok();
+            let dbm: i32 = fields.next()?.parse().ok()?;
+            let flags = fields.next()?;
+            let ssid = unescape_ssid(fields.next().unwrap_or_default());
+
+            Some(WifiNetwork {
+                ssid,
+                bssid,
+                frequency,
+                signal: models::signal_quality(dbm),
+                security: security_from_flags(flags),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Scan result flags such as `[WPA2-PSK-CCMP][SAE-CCMP][ESS]`
+pub fn security_from_flags(flags: &str) -> WifiSecurity {
+    let has = |needle: &str| flags.contains(needle);
+    let sae = has("SAE");
+    let psk = has("-PSK");
+
+    if sae && psk {
+        WifiSecurity::Wpa2Wpa3Personal
+    } else if sae {
+        WifiSecurity::Wpa3Personal
+    } else if has("EAP-SUITE-B") {
+        WifiSecurity::Wpa3Enterprise
+    } else if has("-EAP") {
+        WifiSecurity::Wpa2Enterprise
+    } else if has("OWE") {
+        WifiSecurity::Owe
+    } else if has("WPA2-") || has("RSN-") {
+        WifiSecurity::Wpa2Personal
+    } else if has("WPA-") {
+        WifiSecurity::Wpa
+    } else if has("WEP") {
+        WifiSecurity::Wep
+    } else {
+        WifiSecurity::Open
+    }
+}
+
+/// `LIST_NETWORKS`: a header, then id, SSID, BSSID and flags per saved network
+pub fn parse_list_networks(text: &str) -> Vec<(u32, String)> {
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let id = fields.next()?.parse().ok()?;
+            Some((id, unescape_ssid(fields.next()?)))
+        })
+        .collect()
+}
+
+/// One `key=value` line of a `STATUS` reply
+pub fn parse_status(text: &str, key: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(|value| value.to_string())
+}
+
+/// wpa_supplicant prints non-printable SSID bytes as `\xNN` and escapes
+/// backslashes and quotes
+pub fn unescape_ssid(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let [first, tail @ ..] = rest {
+        match (first, tail) {
+            (b'\\', [b'x', hi, lo, after @ ..]) => {
+                match u8::from_str_radix(&format!("{}{}", *hi as char, *lo as char), 16) {
+                    Ok(byte) => bytes.push(byte),
+                    Err(_) => bytes.extend_from_slice(&[b'\\', b'x', *hi, *lo]),
+                }
+                rest = after;
+            }
+            (b'\\', [escaped, after @ ..]) => {
+                bytes.push(*escaped);
+                rest = after;
+            }
+            _ => {
+                bytes.push(*first);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// SSIDs go to wpa_supplicant hex encoded, so any byte is safe
+pub fn hex_ssid(ssid: &str) -> String {
+    ssid.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Split a line of `nmcli -t` output on unescaped colons
+pub fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    fields.last_mut().unwrap().push(escaped);
+                }
+            }
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+/// `nmcli -t -f IN-USE,SSID,BSSID,SIGNAL,FREQ,SECURITY device wifi list`
+pub fn parse_nmcli_list(text: &str) -> Vec<WifiNetwork> {
+    text.lines()
+        .filter_map(|line| {
+            let fields = split_terse(line);
+            let [in_use, ssid, bssid, signal, freq, security] = fields.as_slice() else {
+                return None;
+            };
+
+            Some(WifiNetwork {
+                ssid: ssid.clone(),
+                bssid: bssid.to_lowercase(),
+                // "2437 MHz"
+                frequency: freq.split_whitespace().next().and_then(|f| f.parse().ok()),
+                signal: signal.parse().unwrap_or_default(),
+                security: security_from_nmcli(security),
+                in_use: in_use == "*",
+                saved: false,
+            })
+        })
+        .collect()
+}
+
+/// nmcli's SECURITY column, e.g. `WPA2 WPA3`, `WPA2 802.1X`, `OWE` or empty
+pub fn security_from_nmcli(security: &str) -> WifiSecurity {
+    let words: Vec<&str> = security.split_whitespace().collect();
+    let has = |word: &str| words.contains(&word);
+
+    if has("802.1X") {
+        if has("WPA3") {
+            WifiSecurity::Wpa3Enterprise
+        } else {
+            WifiSecurity::Wpa2Enterprise
+        }
+    } else if has("WPA3") && has("WPA2") {
+        WifiSecurity::Wpa2Wpa3Personal
+    } else if has("WPA3") {
+        WifiSecurity::Wpa3Personal
+    } else if has("WPA2") {
+        WifiSecurity::Wpa2Personal
+    } else if has("WPA1") {
+        WifiSecurity::Wpa
+    } else if has("WEP") {
+        WifiSecurity::Wep
+    } else if has("OWE") {
+        WifiSecurity::Owe
+    } else {
+        WifiSecurity::Open
+    }
+}
+
+/// Names of saved WiFi connections from `nmcli -t -f NAME,TYPE connection show`
+pub fn parse_nmcli_connections(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            let fields = split_terse(line);
+            match fields.as_slice() {
+                [name, kind] if kind == "802-11-wireless" => Some(name.clone()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Connect through wpa_supplicant, reusing a saved network for `ssid` and
+/// replacing its secret when one is given
+pub fn wpa_connect(
+    ctrl: &WpaCtrl,
+    ssid: &str,
+    security: WifiSecurity,
+    password: Option<&str>,
+) -> Result<()> {
+    let existing = parse_list_networks(&ctrl.request("LIST_NETWORKS")?)
+        .into_iter()
+        .find(|(_, saved)| saved == ssid)
+        .map(|(id, _)| id);
+
+    let id = match existing {
+        Some(id) if password.is_none() => id,
+        existing => {
+            let id = match existing {
+                Some(id) => id,
+                None => ctrl
+                    .request("ADD_NETWORK")?
+                    .trim()
+                    .parse::<u32>()
+                    .context("wpa_supplicant did not add a network")?,
+            };
+            for (variable, value) in network_settings(ssid, security, password)? {
+                ctrl.command(&format!("SET_NETWORK {} {} {}", id, variable, value))?;
+            }
+            id
+        }
+    };
+
+    ctrl.command(&format!("SELECT_NETWORK {}", id))?;
+    // Read-only configs (update_config=0) still connect, just aren't saved
+    let _ = ctrl.command("SAVE_CONFIG");
+    Ok(())
+}
+
+/// `SET_NETWORK` variables for a new network; strings are quoted
+fn network_settings(
+    ssid: &str,
+    security: WifiSecurity,
+    password: Option<&str>,
+) -> Result<Vec<(&'static str, String)>> {
+    let quoted = || {
+        password
+            .map(|password| format!("\"{}\"", password))
+            .context("a password is required for this network")
+    };
+    // A 64 digit hex psk is the key itself and goes unquoted
+    let psk = || match password {
+        Some(key) if key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(key.to_string())
+        }
+        _ => quoted(),
+    };
+
+    let mut settings = vec![("ssid", hex_ssid(ssid))];
+    match security {
+        WifiSecurity::Open => settings.push(("key_mgmt", "NONE".to_string())),
+        WifiSecurity::Owe => {
+            settings.push(("key_mgmt", "OWE".to_string()));
+            settings.push(("ieee80211w", "2".to_string()));
+        }
+        WifiSecurity::Wpa | WifiSecurity::Wpa2Personal => {
+            settings.push(("key_mgmt", "WPA-PSK".to_string()));
+            settings.push(("psk", psk()?));
+        }
+        WifiSecurity::Wpa2Wpa3Personal => {
+            settings.push(("key_mgmt", "WPA-PSK SAE".to_string()));
+            settings.push(("psk", psk()?));
+            settings.push(("sae_password", quoted()?));
+            settings.push(("ieee80211w", "1".to_string()));
+        }
+        WifiSecurity::Wpa3Personal => {
+            settings.push(("key_mgmt", "SAE".to_string()));
+            settings.push(("sae_password", quoted()?));
+            settings.push(("ieee80211w", "2".to_string()));
+        }
+        WifiSecurity::Wep | WifiSecurity::Wpa2Enterprise | WifiSecurity::Wpa3Enterprise => {
+            anyhow::bail!("{} networks are not supported", security.as_str())
+        }
+    }
+    Ok(settings)
+}
+
+pub fn wpa_forget(ctrl: &WpaCtrl, ssid: &str) -> Result<()> {
+    let ids: Vec<u32> = parse_list_networks(&ctrl.request("LIST_NETWORKS")?)
+        .into_iter()
+        .filter(|(_, saved)| saved == ssid)
+        .map(|(id, _)| id)
+        .collect();
+    if ids.is_empty() {
+        anyhow::bail!("'{}' is not a saved network", ssid);
+    }
+
+    for id in ids {
+        ctrl.command(&format!("REMOVE_NETWORK {}", id))?;
+    }
+    let _ = ctrl.command("SAVE_CONFIG");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// `wpa_cli scan_results` from a flat with a WPA3 transition AP
+    const SCAN_RESULTS: &str = "bssid / frequency / signal level / flags / ssid
+10:7b:44:12:34:56\t5180\t-52\t[WPA2-PSK-CCMP][SAE-CCMP][ESS]\thomenet
+a0:63:91:aa:bb:cc\t2437\t-71\t[WPA2-PSK-CCMP][ESS]\tCafe \\\"Guest\\\"
+02:11:22:33:44:55\t2412\t-80\t[ESS]\t
+f2:9f:c2:00:00:01\t2462\t-65\t[RSN-OWE-CCMP][ESS]\t\\xe2\\x98\\x95 Corner
+be:ef:00:00:00:01\t5500\tnoise\t[ESS]\tbroken
+";
+
+    const LIST_NETWORKS: &str = "network id / ssid / bssid / flags
+0\thomenet\tany\t[CURRENT]
+1\tCafe \\\"Guest\\\"\tany\t
+2\t\\xe2\\x98\\x95 Corner\tany\t[DISABLED]
+";
+
+    /// `nmcli -t -f IN-USE,SSID,BSSID,SIGNAL,FREQ,SECURITY device wifi list`
+    const NMCLI_LIST: &str = "*:homenet:10\\:7B\\:44\\:12\\:34\\:56:96:5180 MHz:WPA2 WPA3
+ :Lab\\:5G:A0\\:63\\:91\\:AA\\:BB\\:CC:58:5745 MHz:WPA2 802.1X
+ ::02\\:11\\:22\\:33\\:44\\:55:40:2412 MHz:
+ :Corner:F2\\:9F\\:C2\\:00\\:00\\:01:70:2462 MHz:OWE
+garbage line
+";
+
+    #[test]
+    fn parses_scan_results() {
+        let networks = parse_scan_results(SCAN_RESULTS);
+        let summary: Vec<(&str, &str, Option<u32>, u8, WifiSecurity)> = networks
+            .iter()
+            .map(|n| {
+                (
+                    n.ssid.as_str(),
+                    n.bssid.as_str(),
+                    n.frequency,
+                    n.signal,
+                    n.security,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "homenet",
+                    "10:7b:44:12:34:56",
+                    Some(5180),
+                    96,
+                    WifiSecurity::Wpa2Wpa3Personal
+                ),
+                (
+                    "Cafe \"Guest\"",
+                    "a0:63:91:aa:bb:cc",
+                    Some(2437),
+                    58,
+                    WifiSecurity::Wpa2Personal
+                ),
+                ("", "02:11:22:33:44:55", Some(2412), 40, WifiSecurity::Open),
+                (
+                    "☕ Corner",
+                    "f2:9f:c2:00:00:01",
+                    Some(2462),
+                    70,
+                    WifiSecurity::Owe
+                ),
+            ]
+        );
+        assert_eq!(networks[0].channel(), Some(36));
+        assert!(parse_scan_results("bssid / frequency / signal level / flags / ssid\n").is_empty());
+    }
+
+    #[test]
+    fn parses_list_networks() {
+        assert_eq!(
+            parse_list_networks(LIST_NETWORKS),
+            vec![
+                (0, "homenet".to_string()),
+                (1, "Cafe \"Guest\"".to_string()),
+                (2, "☕ Corner".to_string()),
+            ]
+        );
+        assert!(parse_list_networks("network id / ssid / bssid / flags\n").is_empty());
+    }
+
+    #[test]
+    fn unescapes_ssids() {
+        assert_eq!(unescape_ssid("plain"), "plain");
+        assert_eq!(unescape_ssid("back\\\\slash"), "back\\slash");
+        assert_eq!(unescape_ssid("\\\"quoted\\\""), "\"quoted\"");
+        assert_eq!(unescape_ssid("\\xe2\\x98\\x95"), "☕");
+        assert_eq!(unescape_ssid("tab\\x09"), "tab\t");
+        // Not valid hex, or cut short: kept as written
+        assert_eq!(unescape_ssid("\\xzz"), "\\xzz");
+        assert_eq!(unescape_ssid("end\\x4"), "endx4");
+        assert_eq!(unescape_ssid("end\\"), "end\\");
+        // Invalid UTF-8 is replaced, not dropped
+        assert_eq!(unescape_ssid("\\xff"), "\u{fffd}");
+    }
+
+    #[test]
+    fn splits_terse_lines() {
+        assert_eq!(
+            split_terse("*:homenet:10\\:7B\\:44\\:12\\:34\\:56:96"),
+            vec!["*", "homenet", "10:7B:44:12:34:56", "96"]
+        );
+        assert_eq!(split_terse("a\\\\:b"), vec!["a\\", "b"]);
+        assert_eq!(split_terse("::"), vec!["", "", ""]);
+        assert_eq!(split_terse(""), vec![""]);
+        assert_eq!(split_terse("trailing\\"), vec!["trailing"]);
+    }
+
+    #[test]
+    fn parses_nmcli_list() {
+        let networks = parse_nmcli_list(NMCLI_LIST);
+        assert_eq!(networks.len(), 4);
+
+        assert_eq!(networks[0].ssid, "homenet");
+        assert_eq!(networks[0].bssid, "10:7b:44:12:34:56");
+        assert_eq!(networks[0].frequency, Some(5180));
+        assert_eq!(networks[0].signal, 96);
+        assert_eq!(networks[0].security, WifiSecurity::Wpa2Wpa3Personal);
+        assert!(networks[0].in_use);
+
+        assert_eq!(networks[1].ssid, "Lab:5G");
+        assert_eq!(networks[1].security, WifiSecurity::Wpa2Enterprise);
+        assert!(!networks[1].in_use);
+        assert_eq!(networks[2].ssid, "");
+        assert_eq!(networks[2].security, WifiSecurity::Open);
+        assert_eq!(networks[3].security, WifiSecurity::Owe);
+        assert!(networks.iter().all(|network| !network.saved));
+    }
+
+    #[test]
+    fn parses_nmcli_connections() {
+        let saved = "homenet:802-11-wireless\nWired connection 1:802-3-ethernet\nLab\\:5G:802-11-wireless\n";
+        assert_eq!(parse_nmcli_connections(saved), vec!["homenet", "Lab:5G"]);
+    }
+
+    #[test]
+    fn redacts_secrets() {
+        assert_eq!(
+            redact("SET_NETWORK 3 psk \"hunter2 with spaces\""),
+            "SET_NETWORK 3 psk ********"
+        );
+        assert_eq!(
+            redact("SET_NETWORK 3 ssid 686f6d656e6574"),
+            "SET_NETWORK 3 ssid 686f6d656e6574"
+        );
+        assert_eq!(redact("SELECT_NETWORK 3"), "SELECT_NETWORK 3");
+    }
+
+    /// A wpa_supplicant control socket that keeps saved networks in memory
+    /// and records every command it gets
+    struct FakeWpa {
+        dir: PathBuf,
+        socket: PathBuf,
+        commands: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeWpa {
+        fn start(name: &str, saved: &[&str]) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "netutil-wifi-test-{}-{}",
+                std::process::id(),
+                name
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            let socket = dir.join("wlan0");
+            let server = UnixDatagram::bind(&socket).unwrap();
+
+            let commands = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&commands);
+            let mut networks: Vec<(u32, String)> = saved
+                .iter()
+                .enumerate()
+                .map(|(id, ssid)| (id as u32, ssid.to_string()))
+                .collect();
+            let mut next_id = networks.len() as u32;
+
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 4096];
+                while let Ok((len, client)) = server.recv_from(&mut buf) {
+                    let command = String::from_utf8_lossy(&buf[..len]).to_string();
+                    let words: Vec<&str> = command.splitn(4, ' ').collect();
+                    let reply = match words.as_slice() {
+                        ["LIST_NETWORKS"] => {
+                            let mut reply = "network id / ssid / bssid / flags\n".to_string();
+                            for (id, ssid) in &networks {
+                                reply.push_str(&format!("{}\t{}\tany\t\n", id, ssid));
+                            }
+                            reply
+                        }
+                        ["ADD_NETWORK"] => {
+                            networks.push((next_id, String::new()));
+                            next_id += 1;
+                            format!("{}\n", next_id - 1)
+                        }
+                        ["SET_NETWORK", id, "ssid", hex] => {
+                            let ssid: Vec<u8> = (0..hex.len())
+                                .step_by(2)
+                                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                                .collect();
+                            let id: u32 = id.parse().unwrap();
+                            for network in networks.iter_mut().filter(|(n, _)| *n == id) {
+                                network.1 = String::from_utf8(ssid.clone()).unwrap();
+                            }
+                            "OK\n".to_string()
+                        }
+                        ["REMOVE_NETWORK", id] => {
+                            let id: u32 = id.parse().unwrap();
+                            networks.retain(|(n, _)| *n != id);
+                            "OK\n".to_string()
+                        }
+                        ["SAVE_CONFIG"] => "FAIL\n".to_string(),
+                        _ => "OK\n".to_string(),
+                    };
+                    recorded.lock().unwrap().push(command);
+                    // Events that a previously attached client would see
+                    server
+                        .send_to(
+                            b"<3>CTRL-EVENT-SCAN-STARTED ",
+                            client.as_pathname().unwrap(),
+                        )
+                        .unwrap();
+                    server
+                        .send_to(reply.as_bytes(), client.as_pathname().unwrap())
+                        .unwrap();
+                }
+            });
+
+            Self {
+                dir,
+                socket,
+                commands,
+            }
+        }
+
+        fn open(&self) -> WpaCtrl {
+            WpaCtrl::open_in(&self.socket, &self.dir.join("client")).unwrap()
+        }
+
+        fn commands(&self) -> Vec<String> {
+            self.commands.lock().unwrap().clone()
+        }
+    }
+
+    impl Drop for FakeWpa {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn connect_adds_a_new_network() {
+        let wpa = FakeWpa::start("add", &["homenet"]);
+        let ctrl = wpa.open();
+
+        wpa_connect(
+            &ctrl,
+            "Corner",
+            WifiSecurity::Wpa2Wpa3Personal,
+            Some("hunter22"),
+        )
+        .unwrap();
+        assert_eq!(
+            wpa.commands(),
+            vec![
+                "LIST_NETWORKS",
+                "ADD_NETWORK",
+                "SET_NETWORK 1 ssid 436f726e6572",
+                "SET_NETWORK 1 key_mgmt WPA-PSK SAE",
+                "SET_NETWORK 1 psk \"hunter22\"",
+                "SET_NETWORK 1 sae_password \"hunter22\"",
+                "SET_NETWORK 1 ieee80211w 1",
+                "SELECT_NETWORK 1",
+                "SAVE_CONFIG",
+            ]
+        );
+    }
+
+    #[test]
+    fn connect_reuses_a_saved_network() {
+        let wpa = FakeWpa::start("reuse", &["homenet", "Corner"]);
+        let ctrl = wpa.open();
+
+        wpa_connect(&ctrl, "Corner", WifiSecurity::Wpa2Personal, None).unwrap();
+        assert_eq!(
+            wpa.commands(),
+            vec!["LIST_NETWORKS", "SELECT_NETWORK 1", "SAVE_CONFIG"]
+        );
+
+        // A new password replaces the saved one in place
+        let key = "a".repeat(64);
+        wpa_connect(&ctrl, "homenet", WifiSecurity::Wpa2Personal, Some(&key)).unwrap();
+        assert_eq!(
+            wpa.commands()[3..],
+            [
+                "LIST_NETWORKS".to_string(),
+                "SET_NETWORK 0 ssid 686f6d656e6574".to_string(),
+                "SET_NETWORK 0 key_mgmt WPA-PSK".to_string(),
+                format!("SET_NETWORK 0 psk {}", key),
+                "SELECT_NETWORK 0".to_string(),
+                "SAVE_CONFIG".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn connect_refuses_unsupported_networks() {
+        let wpa = FakeWpa::start("refuse", &[]);
+        let ctrl = wpa.open();
+
+        assert!(wpa_connect(&ctrl, "Corp", WifiSecurity::Wpa2Enterprise, Some("x")).is_err());
+        assert!(wpa_connect(&ctrl, "Home", WifiSecurity::Wpa3Personal, None).is_err());
+        assert!(!wpa.commands().iter().any(|c| c.starts_with("SELECT")));
+    }
+
+    #[test]
+    fn forget_removes_every_saved_copy() {
+        let wpa = FakeWpa::start("forget", &["homenet", "Corner", "homenet"]);
+        let ctrl = wpa.open();
+
+        wpa_forget(&ctrl, "homenet").unwrap();
+        assert_eq!(
+            wpa.commands(),
+            vec![
+                "LIST_NETWORKS",
+                "REMOVE_NETWORK 0",
+                "REMOVE_NETWORK 2",
+                "SAVE_CONFIG"
+            ]
+        );
+        assert!(wpa_forget(&ctrl, "homenet").is_err());
+    }
+
+    #[test]
+    fn client_socket_is_private_and_cleaned_up() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let wpa = FakeWpa::start("private", &[]);
+        let client_dir = wpa.dir.join("client");
+        std::fs::create_dir(&client_dir).unwrap();
+        std::fs::set_permissions(&client_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let ctrl = wpa.open();
+        let mode = std::fs::metadata(&client_dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(ctrl.local.starts_with(&client_dir));
+        assert!(ctrl.local.exists());
+
+        let local = ctrl.local.clone();
+        drop(ctrl);
+        assert!(!local.exists());
+    }
+}
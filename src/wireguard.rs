@@ -0,0 +1,555 @@
+//! WireGuard devices over generic netlink.
+//!
+//! The `wireguard` family hands out a device's keys, port and peers, and
+//! takes peers to add or remove, without needing the `wg` tool. Reading a
+//! device needs CAP_NET_ADMIN; without it the running helper is asked.
+//! Keypairs are generated here, so no private key ever leaves the process.
+
+use crate::models::WireGuardInfo;
+use anyhow::Result;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use zeroize::Zeroizing;
+
+pub use platform::{add_peer, remove_peer};
+
+/// A fresh (private, public) keypair in base64
+pub fn generate_keypair() -> (Zeroizing<String>, String) {
+    let secret = x25519_dalek::StaticSecret::random();
+    let public = x25519_dalek::PublicKey::from(&secret);
+    (
+        Zeroizing::new(BASE64.encode(secret.as_bytes())),
+        BASE64.encode(public.as_bytes()),
+    )
+}
+
+/// Enough of a key to tell peers apart in messages
+pub fn short_key(key: &str) -> String {
+    match key.get(..8) {
+        Some(start) => format!("{}…", start),
+        None => key.to_string(),
+    }
+}
+
+fn encode_key(key: &[u8]) -> String {
+    BASE64.encode(key)
+}
+
+/// Read `interface` from the kernel, or through the helper when we lack
+/// the privileges and it is already running
+pub fn get_wireguard_info(interface: &str) -> Result<WireGuardInfo> {
+    match platform::read_device(interface) {
+        #[cfg(target_os = "linux")]
+        Err(e)
+            if crate::netlink::is_errno(&e, nix::errno::Errno::EPERM)
+                && crate::helper::is_running() =>
+        {
+            crate::sudo::get_wireguard(interface)
+        }
+        result => result,
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::encode_key;
+    use crate::models::{AllowedIp, WireGuardInfo, WireGuardPeer, WireGuardPeerConfig};
+    use crate::netlink::{self, Netlink, NLA_F_NESTED, NLM_F_ACK, NLM_F_DUMP};
+    use anyhow::Result;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    const WG_CMD_GET_DEVICE: u8 = 0;
+    const WG_CMD_SET_DEVICE: u8 = 1;
+
+    const WGDEVICE_A_IFNAME: u16 = 2;
+    const WGDEVICE_A_PUBLIC_KEY: u16 = 4;
+    const WGDEVICE_A_LISTEN_PORT: u16 = 6;
+    const WGDEVICE_A_FWMARK: u16 = 7;
+    const WGDEVICE_A_PEERS: u16 = 8;
+
+    const WGPEER_A_PUBLIC_KEY: u16 = 1;
+    const WGPEER_A_PRESHARED_KEY: u16 = 2;
+    const WGPEER_A_FLAGS: u16 = 3;
+    const WGPEER_A_ENDPOINT: u16 = 4;
+    const WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL: u16 = 5;
+    const WGPEER_A_LAST_HANDSHAKE_TIME: u16 = 6;
+    const WGPEER_A_RX_BYTES: u16 = 7;
+    const WGPEER_A_TX_BYTES: u16 = 8;
+    const WGPEER_A_ALLOWEDIPS: u16 = 9;
+
+    const WGPEER_F_REMOVE_ME: u32 = 1;
+
+    const WGALLOWEDIP_A_FAMILY: u16 = 1;
+    const WGALLOWEDIP_A_IPADDR: u16 = 2;
+    const WGALLOWEDIP_A_CIDR_MASK: u16 = 3;
+
+    const AF_INET: u16 = 2;
+    const AF_INET6: u16 = 10;
+
+    pub fn read_device(interface: &str) -> Result<WireGuardInfo> {
+        let (mut socket, family) = open()?;
+        let mut request = Vec::new();
+        netlink::push_attr(&mut request, WGDEVICE_A_IFNAME, &name_attr(interface));
+
+        // Devices with many peers are split over several messages
+        let mut info = WireGuardInfo::default();
+        for reply in socket.genl_request(family, WG_CMD_GET_DEVICE, NLM_F_DUMP, &request)? {
+            parse_device(&reply, &mut info);
+        }
+        Ok(info)
+    }
+
+    fn open() -> Result<(Netlink, u16)> {
+        let mut socket = Netlink::generic()?;
+        let family = socket
+            .family_id("wireguard")?
+            .ok_or_else(|| anyhow::anyhow!("The kernel has no WireGuard support"))?;
+        Ok((socket, family))
+    }
+
+    fn name_attr(interface: &str) -> Vec<u8> {
+        let mut name = interface.as_bytes().to_vec();
+        name.push(0);
+        name
+    }
+
+    fn parse_device(payload: &[u8], info: &mut WireGuardInfo) {
+        for (kind, value) in netlink::attrs(payload) {
+            match kind {
+                WGDEVICE_A_PUBLIC_KEY if value.len() == 32 => {
+                    info.public_key = Some(encode_key(value))
+                }
+                WGDEVICE_A_LISTEN_PORT => info.listen_port = netlink::read_u16(value),
+                WGDEVICE_A_FWMARK => info.fwmark = netlink::read_u32(value).unwrap_or(0),
+                WGDEVICE_A_PEERS => {
+                    for (_, peer) in netlink::attrs(value) {
+                        let peer = parse_peer(peer);
+                        // A peer cut off by a message boundary continues in the next one
+                        match info
+                            .peers
+                            .iter_mut()
+                            .find(|known| known.public_key == peer.public_key)
+                        {
+                            Some(known) => known.allowed_ips.extend(peer.allowed_ips),
+                            None => info.peers.push(peer),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn parse_peer(payload: &[u8]) -> WireGuardPeer {
+        let mut peer = WireGuardPeer::default();
+
+        for (kind, value) in netlink::attrs(payload) {
+            match kind {
+                WGPEER_A_PUBLIC_KEY => peer.public_key = encode_key(value),
+                WGPEER_A_PRESHARED_KEY => peer.has_preshared_key = value.iter().any(|&b| b != 0),
+                WGPEER_A_ENDPOINT => peer.endpoint = parse_sockaddr(value),
+                WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL => {
+                    peer.persistent_keepalive = netlink::read_u16(value).filter(|&secs| secs != 0)
+                }
+                // struct __kernel_timespec; all zeros before the first handshake
+                WGPEER_A_LAST_HANDSHAKE_TIME => {
+                    peer.last_handshake = value
+                        .get(..8)
+                        .and_then(|secs| secs.try_into().ok())
+                        .map(i64::from_ne_bytes)
+                        .filter(|&secs| secs != 0)
+                }
+                WGPEER_A_RX_BYTES => peer.rx_bytes = netlink::read_u64(value).unwrap_or(0),
+                WGPEER_A_TX_BYTES => peer.tx_bytes = netlink::read_u64(value).unwrap_or(0),
+                WGPEER_A_ALLOWEDIPS => {
+                    peer.allowed_ips = netlink::attrs(value)
+                        .into_iter()
+                        .filter_map(|(_, allowed)| parse_allowed_ip(allowed))
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+
+        peer
+    }
+
+    fn parse_allowed_ip(payload: &[u8]) -> Option<AllowedIp> {
+        let attrs = netlink::attrs(payload);
+        let find = |wanted: u16| {
+            attrs
+                .iter()
+                .find(|(kind, _)| *kind == wanted)
+                .map(|(_, value)| *value)
+        };
+
+        let address = match (
+            find(WGALLOWEDIP_A_FAMILY).and_then(netlink::read_u16),
+            find(WGALLOWEDIP_A_IPADDR),
+        ) {
+            (Some(AF_INET), Some(addr)) => {
+                IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(addr.get(..4)?).ok()?))
+            }
+            (Some(AF_INET6), Some(addr)) => {
+                IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(addr.get(..16)?).ok()?))
+            }
+            _ => return None,
+        };
+        let prefix = *find(WGALLOWEDIP_A_CIDR_MASK)?.first()?;
+        Some(AllowedIp { address, prefix })
+    }
+
+    /// `struct sockaddr_in` or `sockaddr_in6`; the port is in network byte order
+    fn parse_sockaddr(value: &[u8]) -> Option<SocketAddr> {
+        let family = netlink::read_u16(value)?;
+        let port = u16::from_be_bytes(value.get(2..4)?.try_into().ok()?);
+        match family {
+            AF_INET => {
+                let addr = <[u8; 4]>::try_from(value.get(4..8)?).ok()?;
+                Some(SocketAddr::new(Ipv4Addr::from(addr).into(), port))
+            }
+            AF_INET6 => {
+                let addr = <[u8; 16]>::try_from(value.get(8..24)?).ok()?;
+                Some(SocketAddr::new(Ipv6Addr::from(addr).into(), port))
+            }
+            _ => None,
+        }
+    }
+
+    fn encode_sockaddr(endpoint: &SocketAddr) -> Vec<u8> {
+        let mut sockaddr = Vec::new();
+        match endpoint {
+            SocketAddr::V4(v4) => {
+                sockaddr.extend_from_slice(&AF_INET.to_ne_bytes());
+                sockaddr.extend_from_slice(&v4.port().to_be_bytes());
+                sockaddr.extend_from_slice(&v4.ip().octets());
+                sockaddr.extend_from_slice(&[0; 8]);
+            }
+            SocketAddr::V6(v6) => {
+                sockaddr.extend_from_slice(&AF_INET6.to_ne_bytes());
+                sockaddr.extend_from_slice(&v6.port().to_be_bytes());
+                sockaddr.extend_from_slice(&v6.flowinfo().to_be_bytes());
+                sockaddr.extend_from_slice(&v6.ip().octets());
+                sockaddr.extend_from_slice(&v6.scope_id().to_ne_bytes());
+            }
+        }
+        sockaddr
+    }
+
+    /// Add `peer` to `interface`, leaving the other peers alone
+    pub fn add_peer(interface: &str, peer: &WireGuardPeerConfig) -> Result<()> {
+        let public_key = decode_key(&peer.public_key)?;
+
+        let mut allowed_ips = Vec::new();
+        for allowed in &peer.allowed_ips {
+            let mut entry = Vec::new();
+            let (family, address) = match allowed.address {
+                IpAddr::V4(v4) => (AF_INET, v4.octets().to_vec()),
+                IpAddr::V6(v6) => (AF_INET6, v6.octets().to_vec()),
+            };
+            netlink::push_attr(&mut entry, WGALLOWEDIP_A_FAMILY, &family.to_ne_bytes());
+            netlink::push_attr(&mut entry, WGALLOWEDIP_A_IPADDR, &address);
+            netlink::push_attr(&mut entry, WGALLOWEDIP_A_CIDR_MASK, &[allowed.prefix]);
+            netlink::push_attr(&mut allowed_ips, NLA_F_NESTED, &entry);
+        }
+
+        let mut attrs = Vec::new();
+        netlink::push_attr(&mut attrs, WGPEER_A_PUBLIC_KEY, &public_key);
+        if let Some(ref endpoint) = peer.endpoint {
+            netlink::push_attr(&mut attrs, WGPEER_A_ENDPOINT, &encode_sockaddr(endpoint));
+        }
+        if let Some(keepalive) = peer.persistent_keepalive {
+            netlink::push_attr(
+                &mut attrs,
+                WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL,
+                &keepalive.to_ne_bytes(),
+            );
+        }
+        netlink::push_attr(&mut attrs, WGPEER_A_ALLOWEDIPS | NLA_F_NESTED, &allowed_ips);
+
+        set_peer(interface, &attrs)
+    }
+
+    pub fn remove_peer(interface: &str, public_key: &str) -> Result<()> {
+        let mut attrs = Vec::new();
+        netlink::push_attr(&mut attrs, WGPEER_A_PUBLIC_KEY, &decode_key(public_key)?);
+        netlink::push_attr(
+            &mut attrs,
+            WGPEER_A_FLAGS,
+            &WGPEER_F_REMOVE_ME.to_ne_bytes(),
+        );
+        set_peer(interface, &attrs)
+    }
+
+    /// One `WG_CMD_SET_DEVICE` carrying a single peer
+    fn set_peer(interface: &str, peer: &[u8]) -> Result<()> {
+        let (mut socket, family) = open()?;
+
+        let mut peers = Vec::new();
+        netlink::push_attr(&mut peers, NLA_F_NESTED, peer);
+        let mut request = Vec::new();
+        netlink::push_attr(&mut request, WGDEVICE_A_IFNAME, &name_attr(interface));
+        netlink::push_attr(&mut request, WGDEVICE_A_PEERS | NLA_F_NESTED, &peers);
+
+        socket.genl_request(family, WG_CMD_SET_DEVICE, NLM_F_ACK, &request)?;
+        Ok(())
+    }
+
+    fn decode_key(key: &str) -> Result<[u8; 32]> {
+        crate::validation::decode_wireguard_key(key).map_err(anyhow::Error::msg)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const KEY_A: [u8; 32] = [0xa1; 32];
+        const KEY_B: [u8; 32] = [0xb2; 32];
+        const KEY_C: [u8; 32] = [0xc3; 32];
+
+        /// `sockaddr_in` for 192.0.2.1:51820
+        fn sockaddr_v4() -> Vec<u8> {
+            [
+                &AF_INET.to_ne_bytes()[..],
+                &[0xca, 0x6c],
+                &[192, 0, 2, 1],
+                &[0; 8],
+            ]
+            .concat()
+        }
+
+        /// `sockaddr_in6` for [2001:db8::7]:443, with flow info and scope id
+        fn sockaddr_v6() -> Vec<u8> {
+            [
+                &AF_INET6.to_ne_bytes()[..],
+                &[0x01, 0xbb],
+                &[0, 0, 0, 0],
+                &[
+                    0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x07,
+                ],
+                &[0, 0, 0, 0],
+            ]
+            .concat()
+        }
+
+        fn allowed_ips(entries: &[(u16, &[u8], u8)]) -> Vec<u8> {
+            let mut list = Vec::new();
+            for (family, address, prefix) in entries {
+                let mut entry = Vec::new();
+                netlink::push_attr(&mut entry, WGALLOWEDIP_A_FAMILY, &family.to_ne_bytes());
+                netlink::push_attr(&mut entry, WGALLOWEDIP_A_IPADDR, address);
+                netlink::push_attr(&mut entry, WGALLOWEDIP_A_CIDR_MASK, &[*prefix]);
+                netlink::push_attr(&mut list, NLA_F_NESTED, &entry);
+            }
+            list
+        }
+
+        /// The nested `WGDEVICE_A_PEERS` attribute holding `peers`
+        fn peers(peers: &[Vec<u8>]) -> Vec<u8> {
+            let mut list = Vec::new();
+            for peer in peers {
+                netlink::push_attr(&mut list, NLA_F_NESTED, peer);
+            }
+            let mut attr = Vec::new();
+            netlink::push_attr(&mut attr, WGDEVICE_A_PEERS | NLA_F_NESTED, &list);
+            attr
+        }
+
+        fn peer_a() -> Vec<u8> {
+            let mut handshake = 1_700_000_000i64.to_ne_bytes().to_vec();
+            handshake.extend_from_slice(&123_456_789i64.to_ne_bytes());
+
+            let mut peer = Vec::new();
+            netlink::push_attr(&mut peer, WGPEER_A_PUBLIC_KEY, &KEY_A);
+            netlink::push_attr(&mut peer, WGPEER_A_PRESHARED_KEY, &[0x55; 32]);
+            netlink::push_attr(&mut peer, WGPEER_A_ENDPOINT, &sockaddr_v4());
+            netlink::push_attr(
+                &mut peer,
+                WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL,
+                &25u16.to_ne_bytes(),
+            );
+            netlink::push_attr(&mut peer, WGPEER_A_LAST_HANDSHAKE_TIME, &handshake);
+            netlink::push_attr(&mut peer, WGPEER_A_RX_BYTES, &4096u64.to_ne_bytes());
+            netlink::push_attr(&mut peer, WGPEER_A_TX_BYTES, &1024u64.to_ne_bytes());
+            netlink::push_attr(
+                &mut peer,
+                WGPEER_A_ALLOWEDIPS | NLA_F_NESTED,
+                &allowed_ips(&[(AF_INET, &[10, 8, 0, 2], 32)]),
+            );
+            peer
+        }
+
+        #[test]
+        fn sockaddrs() {
+            assert_eq!(
+                parse_sockaddr(&sockaddr_v4()),
+                Some("192.0.2.1:51820".parse().unwrap())
+            );
+            assert_eq!(
+                parse_sockaddr(&sockaddr_v6()),
+                Some("[2001:db8::7]:443".parse().unwrap())
+            );
+
+            // Short reads and unknown families
+            assert_eq!(parse_sockaddr(&sockaddr_v4()[..6]), None);
+            assert_eq!(parse_sockaddr(&sockaddr_v6()[..20]), None);
+            assert_eq!(parse_sockaddr(&[]), None);
+            let mut unix = sockaddr_v4();
+            unix[..2].copy_from_slice(&1u16.to_ne_bytes());
+            assert_eq!(parse_sockaddr(&unix), None);
+
+            for endpoint in ["192.0.2.1:51820", "[2001:db8::7]:443"] {
+                let endpoint: SocketAddr = endpoint.parse().unwrap();
+                assert_eq!(parse_sockaddr(&encode_sockaddr(&endpoint)), Some(endpoint));
+            }
+        }
+
+        #[test]
+        fn peer() {
+            assert_eq!(
+                parse_peer(&peer_a()),
+                WireGuardPeer {
+                    public_key: encode_key(&KEY_A),
+                    has_preshared_key: true,
+                    endpoint: Some("192.0.2.1:51820".parse().unwrap()),
+                    allowed_ips: vec![AllowedIp {
+                        address: "10.8.0.2".parse().unwrap(),
+                        prefix: 32,
+                    }],
+                    persistent_keepalive: Some(25),
+                    last_handshake: Some(1_700_000_000),
+                    rx_bytes: 4096,
+                    tx_bytes: 1024,
+                }
+            );
+        }
+
+        #[test]
+        fn peer_before_first_handshake() {
+            let mut peer = Vec::new();
+            netlink::push_attr(&mut peer, WGPEER_A_PUBLIC_KEY, &KEY_B);
+            netlink::push_attr(&mut peer, WGPEER_A_PRESHARED_KEY, &[0; 32]);
+            netlink::push_attr(
+                &mut peer,
+                WGPEER_A_PERSISTENT_KEEPALIVE_INTERVAL,
+                &0u16.to_ne_bytes(),
+            );
+            netlink::push_attr(&mut peer, WGPEER_A_LAST_HANDSHAKE_TIME, &[0; 16]);
+
+            assert_eq!(
+                parse_peer(&peer),
+                WireGuardPeer {
+                    public_key: encode_key(&KEY_B),
+                    ..Default::default()
+                }
+            );
+        }
+
+        #[test]
+        fn allowed_ips_skip_bad_entries() {
+            let list = allowed_ips(&[
+                (AF_INET, &[10, 8, 0, 0], 24),
+                (AF_INET6, &[0xfd; 16], 64),
+                // Address too short for its family
+                (AF_INET6, &[0xfd; 4], 64),
+                (7, &[1, 2, 3, 4], 8),
+            ]);
+            let parsed: Vec<_> = netlink::attrs(&list)
+                .into_iter()
+                .filter_map(|(_, entry)| parse_allowed_ip(entry))
+                .map(|allowed| allowed.to_string())
+                .collect();
+
+            assert_eq!(
+                parsed,
+                ["10.8.0.0/24", "fdfd:fdfd:fdfd:fdfd:fdfd:fdfd:fdfd:fdfd/64"]
+            );
+        }
+
+        #[test]
+        fn device_split_over_messages() {
+            let mut first = Vec::new();
+            netlink::push_attr(&mut first, WGDEVICE_A_IFNAME, &name_attr("wg0"));
+            netlink::push_attr(&mut first, WGDEVICE_A_PUBLIC_KEY, &[0x42; 32]);
+            netlink::push_attr(&mut first, WGDEVICE_A_LISTEN_PORT, &51820u16.to_ne_bytes());
+            netlink::push_attr(&mut first, WGDEVICE_A_FWMARK, &0x1234u32.to_ne_bytes());
+            let mut peer_b = Vec::new();
+            netlink::push_attr(&mut peer_b, WGPEER_A_PUBLIC_KEY, &KEY_B);
+            netlink::push_attr(&mut peer_b, WGPEER_A_ENDPOINT, &sockaddr_v6());
+            netlink::push_attr(
+                &mut peer_b,
+                WGPEER_A_ALLOWEDIPS | NLA_F_NESTED,
+                &allowed_ips(&[(AF_INET, &[10, 9, 0, 0], 16)]),
+            );
+            first.extend(peers(&[peer_a(), peer_b]));
+
+            // The kernel repeats the public key of a peer it had to cut off
+            let mut second = Vec::new();
+            netlink::push_attr(&mut second, WGDEVICE_A_IFNAME, &name_attr("wg0"));
+            let mut rest_of_b = Vec::new();
+            netlink::push_attr(&mut rest_of_b, WGPEER_A_PUBLIC_KEY, &KEY_B);
+            netlink::push_attr(
+                &mut rest_of_b,
+                WGPEER_A_ALLOWEDIPS | NLA_F_NESTED,
+                &allowed_ips(&[(
+                    AF_INET6,
+                    &[0x20, 0x01, 0x0d, 0xb8, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                    48,
+                )]),
+            );
+            let mut peer_c = Vec::new();
+            netlink::push_attr(&mut peer_c, WGPEER_A_PUBLIC_KEY, &KEY_C);
+            second.extend(peers(&[rest_of_b, peer_c]));
+
+            let mut info = WireGuardInfo::default();
+            parse_device(&first, &mut info);
+            parse_device(&second, &mut info);
+
+            assert_eq!(info.public_key, Some(encode_key(&[0x42; 32])));
+            assert_eq!(info.listen_port, Some(51820));
+            assert_eq!(info.fwmark, 0x1234);
+
+            let keys: Vec<_> = info
+                .peers
+                .iter()
+                .map(|peer| peer.public_key.clone())
+                .collect();
+            assert_eq!(
+                keys,
+                [encode_key(&KEY_A), encode_key(&KEY_B), encode_key(&KEY_C)]
+            );
+
+            let b = info.peer(&encode_key(&KEY_B)).unwrap();
+            assert_eq!(b.endpoint, Some("[2001:db8::7]:443".parse().unwrap()));
+            let allowed: Vec<_> = b.allowed_ips.iter().map(ToString::to_string).collect();
+            assert_eq!(allowed, ["10.9.0.0/16", "2001:db8:9::/48"]);
+
+            assert_eq!(info.peer(&encode_key(&KEY_A)).unwrap().allowed_ips.len(), 1);
+            assert!(info
+                .peer(&encode_key(&KEY_C))
+                .unwrap()
+                .allowed_ips
+                .is_empty());
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use crate::models::{WireGuardInfo, WireGuardPeerConfig};
+    use anyhow::Result;
+
+    /// WireGuard on macOS runs in userspace (`wireguard-go`), behind its own
+    /// control socket
+    pub fn read_device(_interface: &str) -> Result<WireGuardInfo> {
+        anyhow::bail!("Reading WireGuard devices is only supported on Linux")
+    }
+
+    pub fn add_peer(_interface: &str, _peer: &WireGuardPeerConfig) -> Result<()> {
+        anyhow::bail!("Managing WireGuard peers is only supported on Linux")
+    }
+
+    pub fn remove_peer(_interface: &str, _public_key: &str) -> Result<()> {
+        anyhow::bail!("Managing WireGuard peers is only supported on Linux")
+    }
+}
@@ -0,0 +1,488 @@
+//! Wireless link details from nl80211.
+//!
+//! The kernel is asked over generic netlink for the interface (SSID and
+//! frequency), the station we are associated with (signal, bitrates,
+//! connected time) and the scan entry of the current BSS (security). The
+//! parsers only see attribute payloads, so they work without a live socket.
+
+use crate::models::{WifiSecurity, WirelessInfo};
+use crate::netlink::{self, Netlink, NLM_F_DUMP};
+
+const NL80211_CMD_GET_INTERFACE: u8 = 5;
+const NL80211_CMD_GET_STATION: u8 = 17;
+const NL80211_CMD_GET_SCAN: u8 = 32;
+
+const NL80211_ATTR_IFINDEX: u16 = 3;
+const NL80211_ATTR_MAC: u16 = 6;
+const NL80211_ATTR_STA_INFO: u16 = 21;
+const NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+const NL80211_ATTR_BSS: u16 = 47;
+const NL80211_ATTR_SSID: u16 = 52;
+
+const NL80211_STA_INFO_SIGNAL: u16 = 7;
+const NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+const NL80211_STA_INFO_RX_BITRATE: u16 = 14;
+const NL80211_STA_INFO_CONNECTED_TIME: u16 = 16;
+
+const NL80211_RATE_INFO_BITRATE: u16 = 1;
+const NL80211_RATE_INFO_BITRATE32: u16 = 5;
+
+const NL80211_BSS_BSSID: u16 = 1;
+const NL80211_BSS_FREQUENCY: u16 = 2;
+const NL80211_BSS_CAPABILITY: u16 = 5;
+const NL80211_BSS_INFORMATION_ELEMENTS: u16 = 6;
+const NL80211_BSS_SIGNAL_MBM: u16 = 7;
+const NL80211_BSS_STATUS: u16 = 9;
+
+/// Capability bit set by networks that require encryption
+const CAPABILITY_PRIVACY: u16 = 0x10;
+
+/// Query nl80211 for `interface`. `None` when the kernel has no nl80211 or
+/// the interface isn't a wireless one.
+pub fn get_wireless_info(interface: &str) -> Option<WirelessInfo> {
+    let index = nix::net::if_::if_nametoindex(interface).ok()?;
+    let mut socket = Netlink::generic().ok()?;
+    let family = socket.family_id("nl80211").ok()??;
+
+    let mut request = Vec::new();
+    netlink::push_attr(&mut request, NL80211_ATTR_IFINDEX, &index.to_ne_bytes());
+
+    let mut info = WirelessInfo::default();
+    for reply in socket
+        .genl_request(family, NL80211_CMD_GET_INTERFACE, 0, &request)
+        .ok()?
+    {
+        parse_interface(&reply, &mut info);
+    }
+
+    // Both fail harmlessly while disconnected
+    if let Ok(replies) = socket.genl_request(family, NL80211_CMD_GET_STATION, NLM_F_DUMP, &request)
+    {
+        if let Some(reply) = replies.first() {
+            parse_station(reply, &mut info);
+        }
+    }
+    if let Ok(replies) = socket.genl_request(family, NL80211_CMD_GET_SCAN, NLM_F_DUMP, &request) {
+        for reply in replies {
+            parse_scan_entry(&reply, &mut info);
+        }
+    }
+
+    Some(info)
+}
+
+fn parse_interface(payload: &[u8], info: &mut WirelessInfo) {
+    for (kind, value) in netlink::attrs(payload) {
+        match kind {
+            NL80211_ATTR_SSID if !value.is_empty() => {
+                info.ssid = Some(String::from_utf8_lossy(value).to_string())
+            }
+            NL80211_ATTR_WIPHY_FREQ => info.frequency = netlink::read_u32(value),
+            _ => {}
+        }
+    }
+}
+
+/// The access point shows up as the one station of a managed interface
+fn parse_station(payload: &[u8], info: &mut WirelessInfo) {
+    for (kind, value) in netlink::attrs(payload) {
+        match kind {
+            NL80211_ATTR_MAC => info.bssid = format_mac(value),
+            NL80211_ATTR_STA_INFO => {
+                for (kind, value) in netlink::attrs(value) {
+                    match kind {
+                        NL80211_STA_INFO_SIGNAL => {
+                            info.signal_dbm = value.first().map(|&dbm| dbm as i8 as i32)
+                        }
+                        NL80211_STA_INFO_TX_BITRATE => info.tx_bitrate = parse_bitrate(value),
+                        NL80211_STA_INFO_RX_BITRATE => info.rx_bitrate = parse_bitrate(value),
+                        NL80211_STA_INFO_CONNECTED_TIME => {
+                            info.connected_time = netlink::read_u32(value)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Nested rate info in units of 100 kbit/s; the 32 bit form is needed past 6.5 Gbit/s
+fn parse_bitrate(rate_info: &[u8]) -> Option<f32> {
+    let attrs = netlink::attrs(rate_info);
+    let rate = attrs
+        .iter()
+        .find(|(kind, _)| *kind == NL80211_RATE_INFO_BITRATE32)
+        .and_then(|(_, value)| netlink::read_u32(value))
+        .or_else(|| {
+            attrs
+                .iter()
+                .find(|(kind, _)| *kind == NL80211_RATE_INFO_BITRATE)
+                .and_then(|(_, value)| netlink::read_u16(value))
+                .map(u32::from)
+        })?;
+
+    Some(rate as f32 / 10.0)
+}
+
+/// Only the BSS we're associated with tells us anything about the link
+fn parse_scan_entry(payload: &[u8], info: &mut WirelessInfo) {
+    let Some((_, bss)) = netlink::attrs(payload)
+        .into_iter()
+        .find(|(kind, _)| *kind == NL80211_ATTR_BSS)
+    else {
+        return;
+    };
+    let bss = netlink::attrs(bss);
+    let find = |wanted: u16| {
+        bss.iter()
+            .find(|(kind, _)| *kind == wanted)
+            .map(|(_, value)| *value)
+    };
+
+    // 0 = authenticated, 1 = associated, 2 = IBSS joined
+    if find(NL80211_BSS_STATUS).is_none() {
+        return;
+    }
+
+    let bssid = find(NL80211_BSS_BSSID).and_then(format_mac);
+    if info.bssid.is_some() && bssid != info.bssid {
+        return;
+    }
+    info.bssid = info.bssid.take().or(bssid);
+    info.frequency = info
+        .frequency
+        .or_else(|| find(NL80211_BSS_FREQUENCY).and_then(netlink::read_u32));
+    info.signal_dbm = info.signal_dbm.or_else(|| {
+        find(NL80211_BSS_SIGNAL_MBM)
+            .and_then(netlink::read_i32)
+            .map(|mbm| mbm / 100)
+    });
+
+    let privacy = find(NL80211_BSS_CAPABILITY)
+        .and_then(netlink::read_u16)
+        .is_some_and(|capability| capability & CAPABILITY_PRIVACY != 0);
+    let ies = find(NL80211_BSS_INFORMATION_ELEMENTS).unwrap_or_default();
+    info.security = Some(security_from_ies(ies, privacy));
+
+    if info.ssid.is_none() {
+        info.ssid = information_elements(ies)
+            .into_iter()
+            .find(|(id, _)| *id == 0)
+            .map(|(_, ssid)| String::from_utf8_lossy(ssid).to_string());
+    }
+}
+
+/// 802.11 information elements as (id, body) pairs
+fn information_elements(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut elements = Vec::new();
+
+    while let [id, len, rest @ ..] = data {
+        let Some(body) = rest.get(..*len as usize) else {
+            break;
+        };
+        elements.push((*id, body));
+        data = &rest[*len as usize..];
+    }
+
+    elements
+}
+
+/// Work out the security of a BSS from its RSN (WPA2/3) or vendor WPA element
+fn security_from_ies(ies: &[u8], privacy: bool) -> WifiSecurity {
+    let elements = information_elements(ies);
+
+    if let Some((_, rsn)) = elements.iter().find(|(id, _)| *id == 48) {
+        let akms = rsn_akm_suites(rsn);
+        let has = |suites: &[u8]| akms.iter().any(|akm| suites.contains(akm));
+
+        // 00-0F-AC suite types from IEEE 802.11 table 9-151
+        let sae = has(&[8, 9, 24, 25]);
+        let psk = has(&[2, 4, 6]);
+        return if sae && psk {
+            WifiSecurity::Wpa2Wpa3Personal
+        } else if sae {
+            WifiSecurity::Wpa3Personal
+        } else if has(&[11, 12, 13]) {
+            WifiSecurity::Wpa3Enterprise
+        } else if has(&[1, 3, 5]) {
+            WifiSecurity::Wpa2Enterprise
+        } else if has(&[18]) {
+            WifiSecurity::Owe
+        } else {
+            WifiSecurity::Wpa2Personal
+        };
+    }
+
+    let wpa = elements
+        .iter()
+        .any(|(id, body)| *id == 221 && body.starts_with(&[0x00, 0x50, 0xf2, 0x01]));
+    if wpa {
+        WifiSecurity::Wpa
+    } else if privacy {
+        WifiSecurity::Wep
+    } else {
+        WifiSecurity::Open
+    }
+}
+
+/// AKM suite types of the 00-0F-AC OUI listed in an RSN element
+fn rsn_akm_suites(rsn: &[u8]) -> Vec<u8> {
+    // Version (2), group cipher (4), then the pairwise cipher list
+    let Some(pairwise) = rsn.get(6..8) else {
+        return Vec::new();
+    };
+    let akm_start = 8 + u16::from_le_bytes([pairwise[0], pairwise[1]]) as usize * 4;
+    let Some(count) = rsn.get(akm_start..akm_start + 2) else {
+        return Vec::new();
+    };
+    let count = u16::from_le_bytes([count[0], count[1]]) as usize;
+
+    rsn.get(akm_start + 2..)
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .take(count)
+        .filter(|suite| suite[..3] == [0x00, 0x0f, 0xac])
+        .map(|suite| suite[3])
+        .collect()
+}
+
+fn format_mac(bytes: &[u8]) -> Option<String> {
+    if bytes.len() != 6 {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(":"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SSID "homenet" and the basic rate set
+    const SSID_AND_RATES: &[u8] = &[
+        0, 7, b'h', b'o', b'm', b'e', b'n', b'e', b't', //
+        1, 8, 0x82, 0x84, 0x8b, 0x96, 0x0c, 0x12, 0x18, 0x24,
+    ];
+
+    /// An RSN element with CCMP ciphers and the given AKM suite types
+    fn rsn(akms: &[u8]) -> Vec<u8> {
+        let mut body = vec![1, 0, 0x00, 0x0f, 0xac, 4, 1, 0, 0x00, 0x0f, 0xac, 4];
+        body.extend_from_slice(&(akms.len() as u16).to_le_bytes());
+        for akm in akms {
+            body.extend_from_slice(&[0x00, 0x0f, 0xac, *akm]);
+        }
+        body.extend_from_slice(&[0x80, 0]); // MFP capable
+        let mut element = vec![48, body.len() as u8];
+        element.extend_from_slice(&body);
+        element
+    }
+
+    fn ies(elements: &[&[u8]]) -> Vec<u8> {
+        let mut ies = SSID_AND_RATES.to_vec();
+        for element in elements {
+            ies.extend_from_slice(element);
+        }
+        ies
+    }
+
+    #[test]
+    fn rsn_akm_suites_reads_the_suite_list() {
+        // WPA2-PSK as sent by a typical home router
+        let psk = [
+            1, 0, 0x00, 0x0f, 0xac, 4, 1, 0, 0x00, 0x0f, 0xac, 4, 1, 0, 0x00, 0x0f, 0xac, 2, 0x0c,
+            0,
+        ];
+        assert_eq!(rsn_akm_suites(&psk), vec![2]);
+        assert_eq!(rsn_akm_suites(&rsn(&[2, 8])[2..]), vec![2, 8]);
+
+        // Two pairwise ciphers push the AKM list back
+        let two_ciphers = [
+            1, 0, 0x00, 0x0f, 0xac, 4, 2, 0, 0x00, 0x0f, 0xac, 4, 0x00, 0x0f, 0xac, 2, 1, 0, 0x00,
+            0x0f, 0xac, 1,
+        ];
+        assert_eq!(rsn_akm_suites(&two_ciphers), vec![1]);
+
+        // Vendor suites are not ours to interpret
+        let vendor = [
+            1, 0, 0x00, 0x0f, 0xac, 4, 1, 0, 0x00, 0x0f, 0xac, 4, 2, 0, 0x00, 0x50, 0xf2, 2, 0x00,
+            0x0f, 0xac, 2,
+        ];
+        assert_eq!(rsn_akm_suites(&vendor), vec![2]);
+    }
+
+    #[test]
+    fn rsn_akm_suites_survives_truncation() {
+        let full = rsn(&[2, 8]);
+        let body = &full[2..];
+        // Cut inside the pairwise list, inside the AKM count, inside a suite
+        assert!(rsn_akm_suites(&body[..4]).is_empty());
+        assert!(rsn_akm_suites(&body[..10]).is_empty());
+        assert!(rsn_akm_suites(&body[..13]).is_empty());
+        assert_eq!(rsn_akm_suites(&body[..19]), vec![2]);
+        // A count larger than the suites present
+        let mut overcount = body.to_vec();
+        overcount[12] = 9;
+        assert_eq!(rsn_akm_suites(&overcount), vec![2, 8]);
+    }
+
+    #[test]
+    fn security_from_ies_classifies() {
+        let cases: &[(&[u8], bool, WifiSecurity)] = &[
+            (&rsn(&[2]), true, WifiSecurity::Wpa2Personal),
+            (&rsn(&[6]), true, WifiSecurity::Wpa2Personal),
+            (&rsn(&[8]), true, WifiSecurity::Wpa3Personal),
+            (&rsn(&[2, 8]), true, WifiSecurity::Wpa2Wpa3Personal),
+            (&rsn(&[1]), true, WifiSecurity::Wpa2Enterprise),
+            (&rsn(&[12]), true, WifiSecurity::Wpa3Enterprise),
+            (&rsn(&[18]), false, WifiSecurity::Owe),
+            (
+                // WPA1 vendor element
+                &[
+                    221, 22, 0x00, 0x50, 0xf2, 1, 1, 0, 0x00, 0x50, 0xf2, 2, 1, 0, 0x00, 0x50,
+                    0xf2, 2, 1, 0, 0x00, 0x50, 0xf2, 2,
+                ],
+                true,
+                WifiSecurity::Wpa,
+            ),
+            // WMM is a vendor element too, but not WPA
+            (
+                &[221, 7, 0x00, 0x50, 0xf2, 2, 0, 1, 0],
+                false,
+                WifiSecurity::Open,
+            ),
+            (&[], true, WifiSecurity::Wep),
+            (&[], false, WifiSecurity::Open),
+        ];
+
+        for (element, privacy, expected) in cases {
+            assert_eq!(
+                security_from_ies(&ies(&[element]), *privacy),
+                *expected,
+                "{:?}",
+                element
+            );
+        }
+    }
+
+    #[test]
+    fn security_from_ies_handles_truncated_elements() {
+        // The RSN element claims more bytes than the frame holds, so it is
+        // never seen and only the privacy bit is left to go on
+        let mut truncated = rsn(&[8]);
+        truncated.truncate(10);
+        assert_eq!(
+            security_from_ies(&ies(&[&truncated]), true),
+            WifiSecurity::Wep
+        );
+        assert_eq!(information_elements(&ies(&[&truncated])).len(), 2);
+
+        // A complete but empty RSN element still means WPA2 or better
+        assert_eq!(
+            security_from_ies(&[48, 0], true),
+            WifiSecurity::Wpa2Personal
+        );
+        assert_eq!(security_from_ies(&[48], true), WifiSecurity::Wep);
+    }
+
+    fn attr(kind: u16, value: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        netlink::push_attr(&mut buf, kind, value);
+        buf
+    }
+
+    /// NL80211_CMD_NEW_STATION for an associated AP, as dumped by the kernel
+    fn station() -> Vec<u8> {
+        let mut tx = attr(NL80211_RATE_INFO_BITRATE32, &8667u32.to_ne_bytes());
+        tx.extend(attr(NL80211_RATE_INFO_BITRATE, &8667u16.to_ne_bytes()));
+        let rx = attr(NL80211_RATE_INFO_BITRATE, &540u16.to_ne_bytes());
+
+        let mut sta_info = attr(NL80211_STA_INFO_CONNECTED_TIME, &1234u32.to_ne_bytes());
+        sta_info.extend(attr(NL80211_STA_INFO_SIGNAL, &[(-52i8) as u8]));
+        sta_info.extend(attr(NL80211_STA_INFO_TX_BITRATE, &tx));
+        sta_info.extend(attr(NL80211_STA_INFO_RX_BITRATE, &rx));
+
+        let mut payload = attr(NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes());
+        payload.extend(attr(
+            NL80211_ATTR_MAC,
+            &[0x10, 0x7b, 0x44, 0x12, 0x34, 0x56],
+        ));
+        payload.extend(attr(NL80211_ATTR_STA_INFO, &sta_info));
+        payload
+    }
+
+    /// NL80211_CMD_NEW_SCAN_RESULTS for one BSS; `status` marks it as ours
+    fn scan_entry(bssid: [u8; 6], status: bool, ies: &[u8]) -> Vec<u8> {
+        let mut bss = attr(NL80211_BSS_BSSID, &bssid);
+        bss.extend(attr(NL80211_BSS_FREQUENCY, &5180u32.to_ne_bytes()));
+        bss.extend(attr(NL80211_BSS_CAPABILITY, &0x0411u16.to_ne_bytes()));
+        bss.extend(attr(NL80211_BSS_INFORMATION_ELEMENTS, ies));
+        bss.extend(attr(NL80211_BSS_SIGNAL_MBM, &(-6100i32).to_ne_bytes()));
+        if status {
+            bss.extend(attr(NL80211_BSS_STATUS, &1u32.to_ne_bytes()));
+        }
+
+        let mut payload = attr(NL80211_ATTR_IFINDEX, &3u32.to_ne_bytes());
+        payload.extend(attr(NL80211_ATTR_BSS, &bss));
+        payload
+    }
+
+    #[test]
+    fn parse_station_reads_link() {
+        let mut info = WirelessInfo::default();
+        parse_station(&station(), &mut info);
+
+        assert_eq!(info.bssid.as_deref(), Some("10:7b:44:12:34:56"));
+        assert_eq!(info.signal_dbm, Some(-52));
+        assert_eq!(info.tx_bitrate, Some(866.7));
+        assert_eq!(info.rx_bitrate, Some(54.0));
+        assert_eq!(info.connected_time, Some(1234));
+    }
+
+    #[test]
+    fn parse_station_ignores_truncated_payload() {
+        let payload = station();
+        let mut info = WirelessInfo::default();
+        // Cut inside the nested station info: the MAC is still read
+        parse_station(&payload[..payload.len() - 6], &mut info);
+        assert_eq!(info.bssid.as_deref(), Some("10:7b:44:12:34:56"));
+        assert_eq!(info.signal_dbm, None);
+    }
+
+    #[test]
+    fn parse_scan_entry_fills_in_the_associated_bss() {
+        let bssid = [0x10, 0x7b, 0x44, 0x12, 0x34, 0x56];
+        let mut info = WirelessInfo::default();
+        parse_station(&station(), &mut info);
+
+        // Neighbours and BSSes we aren't associated with are skipped
+        parse_scan_entry(&scan_entry([2; 6], true, &ies(&[&rsn(&[18])])), &mut info);
+        parse_scan_entry(&scan_entry(bssid, false, &ies(&[&rsn(&[18])])), &mut info);
+        assert_eq!(info.security, None);
+
+        parse_scan_entry(&scan_entry(bssid, true, &ies(&[&rsn(&[2, 8])])), &mut info);
+        assert_eq!(info.ssid.as_deref(), Some("homenet"));
+        assert_eq!(info.frequency, Some(5180));
+        assert_eq!(info.channel(), Some(36));
+        assert_eq!(info.security, Some(WifiSecurity::Wpa2Wpa3Personal));
+        // The station's own signal reading wins over the scan's
+        assert_eq!(info.signal_dbm, Some(-52));
+    }
+
+    #[test]
+    fn parse_scan_entry_without_station() {
+        let mut info = WirelessInfo::default();
+        parse_scan_entry(&scan_entry([2; 6], true, SSID_AND_RATES), &mut info);
+
+        assert_eq!(info.bssid.as_deref(), Some("02:02:02:02:02:02"));
+        assert_eq!(info.signal_dbm, Some(-61));
+        // Privacy bit without any WPA element
+        assert_eq!(info.security, Some(WifiSecurity::Wep));
+    }
+}
@@ -0,0 +1,127 @@
+//! `--probe-dhcp` against a responder on the far end of a veth pair.
+//!
+//! Needs root to create network namespaces: `cargo test -- --ignored`.
+
+use nix::sched::{setns, CloneFlags};
+use nix::sys::socket::{
+    bind, setsockopt, socket, sockopt, AddressFamily, SockFlag, SockType, SockaddrIn,
+};
+use std::fs::File;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::os::fd::AsRawFd;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+/// Deletes the namespaces, and the veth pair with them, when the test ends
+struct Namespaces(Vec<String>);
+
+impl Drop for Namespaces {
+    fn drop(&mut self) {
+        for name in &self.0 {
+            let _ = Command::new("ip").args(["netns", "del", name]).status();
+        }
+    }
+}
+
+fn ip(args: &[&str]) {
+    let status = Command::new("ip").args(args).status().expect("run ip");
+    assert!(status.success(), "ip {}", args.join(" "));
+}
+
+/// Answer one DHCPDISCOVER on `interface` with an offer of 10.213.0.50
+fn respond(interface: &str) -> std::io::Result<()> {
+    let fd = socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )?;
+    setsockopt(&fd, sockopt::Broadcast, &true)?;
+    setsockopt(&fd, sockopt::BindToDevice, &interface.into())?;
+    bind(fd.as_raw_fd(), &SockaddrIn::new(0, 0, 0, 0, 67))?;
+    let socket = UdpSocket::from(fd);
+    socket.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+    let mut buf = [0u8; 1500];
+    loop {
+        let (len, _) = socket.recv_from(&mut buf)?;
+        if len < 240 || buf[0] != 1 {
+            continue;
+        }
+
+        let mut offer = vec![0u8; 236];
+        offer[..3].copy_from_slice(&[2, 1, 6]);
+        offer[4..8].copy_from_slice(&buf[4..8]);
+        offer[10] = 0x80;
+        offer[16..20].copy_from_slice(&[10, 213, 0, 50]);
+        offer[28..44].copy_from_slice(&buf[28..44]);
+        offer[44..52].copy_from_slice(b"testsrv\0");
+        offer.extend_from_slice(&[99, 130, 83, 99]);
+        offer.extend_from_slice(&[53, 1, 2, 54, 4, 10, 213, 0, 1]);
+        offer.extend_from_slice(&[1, 4, 255, 255, 255, 0, 3, 4, 10, 213, 0, 1]);
+        offer.extend_from_slice(&[51, 4, 0, 0, 0x0e, 0x10, 255]);
+        socket.send_to(&offer, (Ipv4Addr::BROADCAST, 68))?;
+        return Ok(());
+    }
+}
+
+#[test]
+#[ignore = "needs root to create network namespaces"]
+fn probe_finds_server_across_veth() {
+    let id = std::process::id();
+    let server_ns = format!("netutil-dhcps-{}", id);
+    let client_ns = format!("netutil-dhcpc-{}", id);
+    let _cleanup = Namespaces(vec![server_ns.clone(), client_ns.clone()]);
+    ip(&["netns", "add", &server_ns]);
+    ip(&["netns", "add", &client_ns]);
+
+    ip(&[
+        "link", "add", "dhcp-srv", "netns", &server_ns, "type", "veth", "peer", "name", "dhcp-cli",
+        "netns", &client_ns,
+    ]);
+    ip(&[
+        "-n",
+        &server_ns,
+        "addr",
+        "add",
+        "10.213.0.1/24",
+        "dev",
+        "dhcp-srv",
+    ]);
+    ip(&["-n", &server_ns, "link", "set", "dhcp-srv", "up"]);
+    ip(&["-n", &client_ns, "link", "set", "dhcp-cli", "up"]);
+
+    // A network namespace belongs to the thread, so the responder can move
+    // into the server side without affecting the rest of the test
+    let namespace = File::open(format!("/run/netns/{}", server_ns)).expect("open netns");
+    let responder = thread::spawn(move || {
+        setns(namespace, CloneFlags::CLONE_NEWNET).expect("enter server netns");
+        respond("dhcp-srv")
+    });
+    // Give the responder time to bind before the DISCOVER goes out
+    thread::sleep(Duration::from_millis(200));
+
+    let output = Command::new("ip")
+        .args(["netns", "exec", &client_ns])
+        .arg(env!("CARGO_BIN_EXE_netutil-tui"))
+        .args(["--probe-dhcp", "dhcp-cli"])
+        .output()
+        .expect("run probe");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    responder.join().unwrap().expect("responder");
+
+    let offers: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let offers = offers.as_array().unwrap();
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0]["source"], "10.213.0.1");
+    assert_eq!(offers[0]["server_name"], "testsrv");
+    assert_eq!(offers[0]["lease"]["address"], "10.213.0.50");
+    assert_eq!(offers[0]["lease"]["subnet_mask"], "255.255.255.0");
+    assert_eq!(offers[0]["lease"]["routers"][0], "10.213.0.1");
+    assert_eq!(offers[0]["lease"]["lease_time"], 3600);
+}